@@ -0,0 +1,127 @@
+use collections::hashmap::HashMap;
+
+use std::io::File;
+
+use game::sprite;
+use game::units;
+use game::vfs;
+
+use game::error::{AnimationError, GameResult};
+
+/// Where the player's animations are defined. (A mod may override this
+/// file, along w/ the sheet it names, to give the player another skin.)
+pub static PLAYER_ANIMATIONS: &'static str = "assets/animations/MyChar.anim";
+
+type MotionTup = (sprite::Motion, sprite::Facing, sprite::Looking);
+
+/// How a single movement is drawn: `frames` tiles, left-to-right from
+/// `offset` on `sheet`, cycled `fps` times a second. (A single frame is
+/// drawn as a still sprite, & so may have an `fps` of 0.)
+#[deriving(Clone)]
+pub struct Animation {
+	sheet: ~str,
+	offset: (units::Tile, units::Tile),
+	frames: units::Frame,
+	fps: units::Fps
+}
+
+/// The animation of a character for each (motion, facing, looking) it
+/// may be in.
+///
+/// Each line of an animation file is either `sheet <path>`, naming the
+/// sheet of the lines which follow it, or `<motion> <facing> <looking>
+/// <column> <row> <frames> <fps>` (the offset being in tiles.) A facing or
+/// looking of `*` stands for every one; later lines override earlier ones,
+/// so a catch-all line may be refined by those after it. Lines starting
+/// w/ `#` are ignored.
+///
+/// Every movement must be given an animation, lest the file fail to load.
+#[deriving(Clone)]
+pub struct AnimationSet {
+	priv animations: HashMap<MotionTup, Animation>
+}
+
+impl AnimationSet {
+	pub fn load(path: &str) -> GameResult<AnimationSet> {
+		let error = |reason: ~str| AnimationError(path.to_owned(), reason);
+
+		let contents = match File::open(&vfs::resolve(path)).read_to_str() {
+			Ok(contents) => contents,
+			Err(msg) => return Err(error(format!("{}", msg)))
+		};
+
+		let mut animations = HashMap::new();
+		let mut sheet: Option<~str> = None;
+		for raw_line in contents.lines() {
+			let line = raw_line.trim();
+			if line.len() == 0 || line.starts_with("#") { continue; }
+
+			let words: ~[&str] = line.words().collect();
+			match words.as_slice() {
+				["sheet", sheet_path] => { sheet = Some(sheet_path.to_owned()); }
+				[motion, facing, looking, col, row, frames, fps] => {
+					let sheet = match sheet {
+						Some(ref sheet) => sheet.clone(),
+						None => return Err(error(format!("`{}` precedes any sheet", line)))
+					};
+					let motion = match sprite::Motion::from_name(motion) {
+						Some(motion) => motion,
+						None => return Err(error(format!("unknown motion `{}`", motion)))
+					};
+					let facings = match facing {
+						"*" => sprite::FACINGS.to_owned(),
+						name => match sprite::Facing::from_name(name) {
+							Some(facing) => ~[facing],
+							None => return Err(error(format!("unknown facing `{}`", name)))
+						}
+					};
+					let lookings = match looking {
+						"*" => sprite::LOOKINGS.to_owned(),
+						name => match sprite::Looking::from_name(name) {
+							Some(looking) => ~[looking],
+							None => return Err(error(format!("unknown looking `{}`", name)))
+						}
+					};
+					let animation = match (from_str::<uint>(col), from_str::<uint>(row),
+					                       from_str::<uint>(frames), from_str::<uint>(fps)) {
+						// (an animation of several frames must be cycled at some rate)
+						(Some(col), Some(row), Some(frames), Some(fps)) if frames > 0 && (frames == 1 || fps > 0) => Animation {
+							sheet: sheet,
+							offset: (units::Tile(col), units::Tile(row)),
+							frames: frames,
+							fps: fps
+						},
+						_ => return Err(error(format!("invalid frames in `{}`", line)))
+					};
+
+					for facing in facings.iter() {
+						for looking in lookings.iter() {
+							animations.insert((motion, *facing, *looking), animation.clone());
+						}
+					}
+				}
+				_ => return Err(error(format!("malformed line `{}`", line)))
+			}
+		}
+
+		for motion in sprite::MOTIONS.iter() {
+			for facing in sprite::FACINGS.iter() {
+				for looking in sprite::LOOKINGS.iter() {
+					if !animations.contains_key(&(*motion, *facing, *looking)) {
+						return Err(error(format!(
+							"nothing is defined for {} {} {}",
+							motion.name(), facing.name(), looking.name()
+						)));
+					}
+				}
+			}
+		}
+
+		Ok(AnimationSet { animations: animations })
+	}
+
+	/// The animation of `movement`. (Every movement has one, once loaded.)
+	pub fn get<'a>(&'a self, movement: MotionTup) -> &'a Animation {
+		self.animations.get(&movement)
+	}
+}
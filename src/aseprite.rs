@@ -0,0 +1,146 @@
+use serialize::json;
+use std::io::File;
+
+use sdl2::rect;
+
+use game::sprite;
+use game::units;
+use game::vfs;
+
+use game::error::{AnimationError, GameError, GameResult};
+
+/// The frames of a sheet exported by Aseprite (`File > Export Sprite
+/// Sheet`, w/ its JSON data), & the animations tagged among them.
+///
+/// Both the `Array` & `Hash` layouts of frames are understood; the frames
+/// of a `Hash` are ordered by the number their names end in (as Aseprite
+/// names them, e.g: `player 10.aseprite`), so `frame 10` follows `frame 9`.
+pub struct Sheet {
+	/// The sheet's image, found relative to its data.
+	image: ~str,
+
+	priv path: ~str,
+	// each frame's region of the image, & how long it is shown for
+	priv frames: ~[(rect::Rect, units::Millis)],
+	// each tag's name, the first & last frames it spans, & its direction
+	priv tags: ~[(~str, uint, uint, sprite::Playback)]
+}
+
+impl Sheet {
+	/// Reads the JSON data at `path`.
+	pub fn load(path: &str) -> GameResult<Sheet> {
+		let contents = match File::open(&vfs::resolve(path)).read_to_str() {
+			Ok(contents) => contents,
+			Err(msg) => return Err(error(path, format!("{}", msg)))
+		};
+		let data = match json::from_str(contents.as_slice()) {
+			Ok(data) => data,
+			Err(err) => return Err(error(path, format!("{}", err)))
+		};
+
+		let entries: ~[&json::Json] = match data.find(&~"frames") {
+			Some(&json::List(ref frames)) => frames.iter().collect(),
+			Some(&json::Object(ref frames)) => {
+				// (an object is ordered by its keys, which sort `10` before `2`)
+				let mut named: ~[(Option<uint>, &json::Json)] = frames.iter()
+					.map(|(name, frame)| (frame_number(name.as_slice()), frame))
+					.collect();
+				named.sort_by(|&(a, _), &(b, _)| a.cmp(&b));
+				named.move_iter().map(|(_, frame)| frame).collect()
+			}
+			_ => return Err(error(path, ~"no frames are listed"))
+		};
+
+		let mut frames = ~[];
+		for entry in entries.iter() {
+			let region = match entry.find(&~"frame") {
+				Some(region) => region,
+				None => return Err(error(path, ~"a frame lacks its region"))
+			};
+			match (number(region, "x"), number(region, "y"), number(region, "w"), number(region, "h"), number(*entry, "duration")) {
+				(Some(x), Some(y), Some(w), Some(h), Some(duration)) => {
+					frames.push((rect::Rect::new(x as i32, y as i32, w as i32, h as i32), units::Millis(duration as int)));
+				}
+				_ => return Err(error(path, ~"a frame's region or duration is malformed"))
+			}
+		}
+		if frames.is_empty() { return Err(error(path, ~"no frames are listed")); }
+
+		let meta = data.find(&~"meta");
+		let image = match meta.and_then(|meta| meta.find(&~"image")).and_then(|image| image.as_string()) {
+			Some(image) => match Path::new(path).dir_path().join(image).as_str() {
+				Some(image_path) => image_path.to_owned(),
+				None => return Err(error(path, ~"the image's path is not utf-8"))
+			},
+			None => return Err(error(path, ~"no image is named"))
+		};
+
+		let mut tags = ~[];
+		match meta.and_then(|meta| meta.find(&~"frameTags")).and_then(|tags| tags.as_list()) {
+			Some(list) => {
+				for tag in list.iter() {
+					let name = tag.find(&~"name").and_then(|name| name.as_string());
+					let playback = match tag.find(&~"direction").and_then(|direction| direction.as_string()) {
+						Some("reverse") 	=> sprite::Reverse,
+						Some("pingpong") 	=> sprite::PingPong,
+						_ 					=> sprite::Loop
+					};
+					match (name, number(tag, "from"), number(tag, "to")) {
+						(Some(name), Some(from), Some(to)) if from <= to && (to as uint) < frames.len() => {
+							tags.push((name.to_owned(), from as uint, to as uint, playback));
+						}
+						_ => return Err(error(path, ~"a frame tag is malformed"))
+					}
+				}
+			}
+			None => {}
+		}
+
+		Ok(Sheet { image: image, path: path.to_owned(), frames: frames, tags: tags })
+	}
+
+	/// The frames tagged `tag`, or every frame of the sheet w/o a tag.
+	pub fn frames(&self, tag: Option<&str>) -> GameResult<~[(rect::Rect, units::Millis)]> {
+		match tag {
+			Some(tag) => match self.find_tag(tag) {
+				Some(&(_, from, to, _)) => Ok(self.frames.slice(from, to + 1).to_owned()),
+				None => Err(error(self.path.as_slice(), format!("no frames are tagged `{}`", tag)))
+			},
+			None => Ok(self.frames.clone())
+		}
+	}
+
+	/// How the frames tagged `tag` are played: forwards (looping) unless
+	/// the tag's direction is `reverse` or `pingpong`.
+	pub fn playback(&self, tag: Option<&str>) -> sprite::Playback {
+		match tag.and_then(|tag| self.find_tag(tag)) {
+			Some(&(_, _, _, playback)) => playback,
+			None => sprite::Loop
+		}
+	}
+
+	fn find_tag<'a>(&'a self, tag: &str) -> Option<&'a (~str, uint, uint, sprite::Playback)> {
+		self.tags.iter().find(|&&(ref name, _, _, _)| name.as_slice() == tag)
+	}
+}
+
+fn error(path: &str, reason: ~str) -> GameError {
+	AnimationError(path.to_owned(), reason)
+}
+
+// the number `key` of a JSON object, if it has one
+fn number(object: &json::Json, key: &str) -> Option<f64> {
+	object.find(&key.to_owned()).and_then(|value| value.as_number())
+}
+
+// the last number in a frame's name, e.g: `10` of `player 10.aseprite`
+fn frame_number(name: &str) -> Option<uint> {
+	let end = match name.rfind(|c: char| c.is_digit()) {
+		Some(last) => last + 1,
+		None => return None
+	};
+	// (digits are a byte apiece)
+	let mut start = end;
+	while start > 0 && (name[start - 1] as char).is_digit() { start -= 1; }
+	from_str::<uint>(name.slice(start, end))
+}
@@ -0,0 +1,287 @@
+use collections::hashmap::HashMap;
+
+use std::io::File;
+use std::io::fs;
+use time;
+
+use game::graphics;
+use game::log;
+use game::vfs;
+
+/// Lists the assets loaded before the title screen is shown.
+pub static MANIFEST_PATH: &'static str = "assets/manifest.txt";
+
+// how often watched files are checked for changes (in ns)
+static WATCH_INTERVAL: u64 = 500000000;
+
+// a loaded asset, & how many times it is held
+struct Entry<T> {
+	asset: T,
+	refs: uint
+}
+
+/// Assets of a single kind (e.g: textures), each loaded once per path.
+///
+/// Every `acquire` (or `insert`) of a path counts one more holder of it,
+/// which should `release` it once done. An asset w/o holders stays
+/// loaded, in case it is soon wanted again, until `unload_unused`.
+pub struct Cache<T> {
+	priv entries: HashMap<~str, Entry<T>>
+}
+
+impl<T: Clone> Cache<T> {
+	pub fn new() -> Cache<T> {
+		Cache { entries: HashMap::new() }
+	}
+
+	/// The asset loaded from `path`, if any, counting one more holder of it.
+	pub fn acquire(&mut self, path: &str) -> Option<T> {
+		match self.entries.find_mut(&path.to_owned()) {
+			Some(entry) => { entry.refs += 1; Some(entry.asset.clone()) }
+			None => None
+		}
+	}
+
+	/// The asset loaded from `path`, if any, w/o counting a holder.
+	pub fn find(&self, path: &str) -> Option<T> {
+		self.entries.find(&path.to_owned()).map(|entry| entry.asset.clone())
+	}
+
+	/// The path (& the asset) of the first asset loaded which `predicate`
+	/// picks out, w/o counting a holder, e.g: to find a texture's path.
+	pub fn find_where(&self, predicate: |&T| -> bool) -> Option<(~str, T)> {
+		self.entries.iter()
+			.find(|&(_, entry)| predicate(&entry.asset))
+			.map(|(path, entry)| (path.clone(), entry.asset.clone()))
+	}
+
+	/// Swaps the asset loaded from `path` for `asset`, keeping its holders.
+	pub fn replace(&mut self, path: &str, asset: T) {
+		match self.entries.find_mut(&path.to_owned()) {
+			Some(entry) => { entry.asset = asset; }
+			None => {}
+		}
+	}
+
+	/// Stores `asset`, newly loaded from `path`, as held once by its loader.
+	pub fn insert(&mut self, path: ~str, asset: T) {
+		self.entries.insert(path, Entry { asset: asset, refs: 1 });
+	}
+
+	/// Counts one fewer holder of the asset loaded from `path`.
+	pub fn release(&mut self, path: &str) {
+		match self.entries.find_mut(&path.to_owned()) {
+			Some(entry) => if entry.refs > 0 { entry.refs -= 1; },
+			None => {}
+		}
+	}
+
+	/// Forgets every asset w/o holders, handing them (& their paths) back to be freed.
+	pub fn unload_unused(&mut self) -> ~[(~str, T)] {
+		let unused: ~[~str] = self.entries.iter()
+			.filter(|&(_, entry)| entry.refs == 0)
+			.map(|(path, _)| path.clone())
+			.collect();
+
+		let mut assets = ~[];
+		for path in unused.move_iter() {
+			log::debug("assets", format!("unloading {}", path));
+			match self.entries.pop(&path) {
+				Some(entry) => assets.push((path, entry.asset)),
+				None => {}
+			}
+		}
+		assets
+	}
+}
+
+/// The kinds of asset a manifest may list.
+#[deriving(Eq,Clone)]
+pub enum Kind {
+	/// A sprite sheet w/ black as its transparent color.
+	Image,
+	/// An image drawn w/o transparency, e.g: a backdrop.
+	OpaqueImage,
+	Music,
+	Map
+}
+
+impl Kind {
+	/// The name of this kind as it is written in a manifest.
+	pub fn name(&self) -> &'static str {
+		match *self {
+			Image 		=> "image",
+			OpaqueImage => "opaque_image",
+			Music 		=> "music",
+			Map 		=> "map"
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<Kind> {
+		[Image, OpaqueImage, Music, Map].iter().find(|kind| kind.name() == name).map(|kind| *kind)
+	}
+}
+
+/// The assets loaded up-front, so that they are ready before they are
+/// first needed; they stay loaded for as long as the game runs.
+///
+/// Each line of a manifest is `<kind> <path>`, where kind is `image`,
+/// `opaque_image`, `music` or `map`. Lines starting w/ `#` are ignored.
+pub struct Manifest {
+	priv entries: ~[(Kind, ~str)]
+}
+
+impl Manifest {
+	/// Reads the manifest at `path`; a missing manifest preloads nothing.
+	pub fn load(path: &str) -> Manifest {
+		let file_path = vfs::resolve(path);
+		if !file_path.exists() { return Manifest { entries: ~[] }; }
+
+		let contents = match File::open(&file_path).read_to_str() {
+			Ok(contents) => contents,
+			Err(msg) => {
+				log::warn("assets", format!("manifest could not be loaded: {}", msg));
+				return Manifest { entries: ~[] };
+			}
+		};
+
+		let mut entries = ~[];
+		for raw_line in contents.lines() {
+			let line = raw_line.trim();
+			if line.len() == 0 || line.starts_with("#") { continue; }
+
+			let words: ~[&str] = line.words().collect();
+			match words.as_slice() {
+				[name, asset] => match Kind::from_name(name) {
+					Some(kind) => entries.push((kind, asset.to_owned())),
+					None => log::warn("assets", format!("{}: ignoring unknown kind `{}`", path, name))
+				},
+				_ => log::warn("assets", format!("{}: ignoring malformed line `{}`", path, line))
+			}
+		}
+
+		Manifest { entries: entries }
+	}
+
+	/// The paths of every asset of `kind`, in the order they are listed.
+	pub fn paths_of(&self, kind: Kind) -> ~[~str] {
+		self.entries.iter()
+			.filter(|&&(entry_kind, _)| entry_kind == kind)
+			.map(|&(_, ref path)| path.clone())
+			.collect()
+	}
+}
+
+/// The assets loaded by the graphics subsystem: textures (w/ the options
+/// they were loaded w/), & the source of each map file. (Maps are built
+/// of sprites, so they are loaded alongside them; music is cached by the
+/// audio device itself.)
+///
+/// While watching, the files of loaded assets are checked for changes so
+/// that they may be reloaded in place, e.g: as an artist edits a sheet.
+pub struct AssetManager {
+	textures: Cache<(graphics::Texture, graphics::ImageOptions)>,
+	maps: Cache<~str>,
+
+	// each watched file's modification time as of when it was loaded;
+	// `None` unless watching (or for a file which was missing)
+	priv watched: Option<HashMap<~str, Option<u64>>>,
+	priv next_check: u64
+}
+
+impl AssetManager {
+	pub fn new() -> AssetManager {
+		AssetManager { textures: Cache::new(), maps: Cache::new(), watched: None, next_check: 0 }
+	}
+
+	/// Starts noting when each asset's file is loaded, so that
+	/// `changed_files()` may find those modified since.
+	pub fn watch(&mut self) {
+		if self.watched.is_none() { self.watched = Some(HashMap::new()); }
+	}
+
+	/// Notes that the file at `path` has just been (re)loaded.
+	pub fn record_load(&mut self, path: &str) {
+		match self.watched {
+			Some(ref mut watched) => { watched.insert(path.to_owned(), modified_time(path)); }
+			None => {}
+		}
+	}
+
+	/// The watched files modified since they were loaded, which are each
+	/// then noted as loaded anew. (Files are checked every `WATCH_INTERVAL`
+	/// at most; nothing is found in between.)
+	pub fn changed_files(&mut self) -> ~[~str] {
+		let now = time::precise_time_ns();
+		if now < self.next_check { return ~[]; }
+		self.next_check = now + WATCH_INTERVAL;
+
+		match self.watched {
+			Some(ref mut watched) => {
+				let changed: ~[~str] = watched.iter()
+					.filter(|&(path, modified)| modified_time(path.as_slice()) != *modified)
+					.map(|(path, _)| path.clone())
+					.collect();
+				for path in changed.iter() {
+					watched.insert(path.clone(), modified_time(path.as_slice()));
+				}
+				changed
+			}
+			None => ~[]
+		}
+	}
+
+	/// Forgets every texture & map source w/o holders (& stops watching
+	/// their files), handing back the textures to be freed.
+	pub fn unload_unused(&mut self) -> ~[graphics::Texture] {
+		let textures = self.textures.unload_unused();
+		let maps = self.maps.unload_unused();
+
+		match self.watched {
+			Some(ref mut watched) => {
+				for &(ref path, _) in textures.iter() { watched.pop(path); }
+				for &(ref path, _) in maps.iter() { watched.pop(path); }
+			}
+			None => {}
+		}
+		textures.move_iter().map(|(_, (texture, _))| texture).collect()
+	}
+
+	/// The contents of the map file at `path`, read (& cached) if need be.
+	/// Each map loaded should `release_map` its path once left.
+	pub fn load_map_source(&mut self, path: &str) -> Result<~str, ~str> {
+		match self.maps.acquire(path) {
+			Some(source) => return Ok(source),
+			None => {}
+		}
+
+		match File::open(&vfs::resolve(path)).read_to_str() {
+			Ok(source) => {
+				log::debug("assets", format!("read {}", path));
+				self.maps.insert(path.to_owned(), source.clone());
+				self.record_load(path);
+				Ok(source)
+			}
+			Err(msg) => Err(format!("{}", msg))
+		}
+	}
+
+	/// Replaces the cached source of the map at `path` w/ what is now on disk.
+	pub fn reread_map(&mut self, path: &str) -> Result<(), ~str> {
+		match File::open(&vfs::resolve(path)).read_to_str() {
+			Ok(source) => { self.maps.replace(path, source); Ok(()) }
+			Err(msg) => Err(format!("{}", msg))
+		}
+	}
+
+	pub fn release_map(&mut self, path: &str) { self.maps.release(path); }
+}
+
+// when the file an asset path resolves to was last modified, if it exists
+// (a file added to a mod counts as a change, as the path now resolves to it)
+fn modified_time(path: &str) -> Option<u64> {
+	match fs::stat(&vfs::resolve(path)) {
+		Ok(stat) => Some(stat.modified),
+		Err(_) => None
+	}
+}
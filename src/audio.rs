@@ -0,0 +1,682 @@
+use collections::hashmap::HashMap;
+use std::cmp;
+use std::libc::{c_char, c_int, c_void};
+use std::rand::Rng;
+use std::rc::Rc;
+use std::vec;
+
+use mixer = sdl2_mixer;
+
+use game::assets;
+use game::log;
+use game::organya;
+use game::rng;
+use game::units;
+use game::vfs;
+
+static FREQUENCY: int 		= 44100;
+static CHANNELS: int 		= 2; 	// stereo
+static CHUNK_SIZE: int 		= 1024;
+static MIXING_CHANNELS: int = 16;
+
+/// Music in this format is synthesized by `organya`, rather than decoded by the mixer.
+pub static ORGANYA_SUFFIX: &'static str = ".org";
+
+/// The loudest volume of each bus (master, music & sound effects.)
+pub static MAX_VOLUME: uint = 100;
+
+// the loudest volume accepted by `SDL_mixer`
+static MIXER_VOLUME: uint = 128;
+
+// how far to one side a sound at the edge of the screen (or beyond) is
+// panned, where `1.0` would leave the other ear silent
+static PAN_STRENGTH: f64 = 0.75;
+// sounds fade from full volume at the screen's edge to silence this many
+// screens' widths from the listener
+static FALLOFF_SCREENS: f64 = 1.5;
+
+// how many pitches an effect which varies is rendered at, spread evenly
+// across its range of pitch
+static PITCH_VARIANTS: uint = 5;
+
+/// A short sound effect which may be played at any time.
+#[deriving(Hash,Eq)]
+pub enum Sfx {
+	Jump,
+	Land,
+	Shoot,
+	BreakBlock
+}
+pub static SFXS: [Sfx, ..4] = [Jump, Land, Shoot, BreakBlock];
+
+impl Sfx {
+	fn path(&self) -> ~str {
+		match *self {
+			Jump 	=> ~"assets/base/Sound/jump.wav",
+			Land 	=> ~"assets/base/Sound/land.wav",
+			Shoot 	=> ~"assets/base/Sound/shoot.wav",
+			BreakBlock 	=> ~"assets/base/Sound/break_block.wav"
+		}
+	}
+
+	/// How much each playing of this effect differs from the last, so
+	/// that one heard over & over (e.g: landing, or gunfire) does not sound
+	/// mechanically identical; `None` if it is always played as recorded.
+	fn variation(&self) -> Option<Variation> {
+		match *self {
+			Jump 	=> None,
+			Land 	=> Some(Variation { pitch: 0.06, volume: 0.2 }),
+			Shoot 	=> Some(Variation { pitch: 0.04, volume: 0.1 }),
+			BreakBlock 	=> Some(Variation { pitch: 0.08, volume: 0.1 })
+		}
+	}
+}
+
+/// The range by which an effect varies each time it is played: its pitch
+/// is scaled by up to `pitch` either way, & it is up to `volume` quieter.
+struct Variation {
+	pitch: f64,
+	volume: f64
+}
+
+/// A piece of music made up of an intro, which plays once, followed by
+/// a loop section which repeats indefinitely.
+///
+/// Both sections live in the same file: the intro is `[0, loop_start)`
+/// and the loop is `[loop_start, loop_end)`. The track is decoded whole &
+/// fed to the mixer's own stream, which jumps from `loop_end` back to
+/// `loop_start` on the very sample, so the seam is never heard.
+/// (A track w/o an intro has a `loop_start` of 0.)
+#[deriving(Clone)]
+pub struct Track {
+	path: ~str,
+	loop_start: units::Millis,
+	loop_end: units::Millis
+}
+
+impl Track {
+	pub fn new(path: ~str, loop_start: units::Millis, loop_end: units::Millis) -> Track {
+		Track { path: path, loop_start: loop_start, loop_end: loop_end }
+	}
+}
+
+/// Acts as a handle to the underlying audio device.
+///
+/// Every sound effect is loaded up-front so that they may be played w/o
+/// mutable access; at most one music track is playing at a time.
+/// Music is cached by path, so returning to a track need not reload it.
+/// (Organya songs, `.org`, are synthesized as they play instead.)
+pub struct Audio {
+	priv music: Option<(~str, Rc<~mixer::Music>)>,
+	priv organya: Option<organya::Player>,
+	// a track playing in place of `music`, looping at its own points
+	priv looped: Option<Looped>,
+	priv music_cache: assets::Cache<Rc<~mixer::Music>>,
+	priv track: Option<Track>,
+	priv effects: HashMap<Sfx, ~mixer::Chunk>,
+	// the effects which vary, each rendered at a few pitches
+	priv pitched: HashMap<Sfx, Pitched>,
+	priv muted: bool,
+	// each bus's volume, `[0, MAX_VOLUME]`; the master scales the other two
+	priv master_volume: uint,
+	priv music_volume: uint,
+	priv sfx_volume: uint,
+	// where sounds are heard from, & the size of the screen around it
+	priv listener: Option<((units::Game, units::Game), (units::Game, units::Game))>,
+	// draws how each effect which varies is played
+	priv rng: rng::Rng,
+	// whether the audio device is open; w/o one nothing is ever played
+	priv device: bool
+}
+
+/// When the `Audio` device leaves scope the mixer is closed.
+impl Drop for Audio {
+	fn drop(&mut self) {
+		self.organya = None;
+		self.looped = None;
+		if !self.device { return; }
+		mixer::Music::halt();
+		mixer::close_audio();
+	}
+}
+
+impl Audio {
+	/// Opens the audio device and loads every sound effect; effects which
+	/// vary are varied by draws from `rng`.
+	pub fn new(rng: rng::Rng) -> Audio {
+		match mixer::open_audio(FREQUENCY, mixer::DEFAULT_FORMAT, CHANNELS, CHUNK_SIZE) {
+			Ok(_) => {}
+			Err(msg) => {fail!("audio device could not be opened: {}", msg)}
+		}
+		mixer::allocate_channels(MIXING_CHANNELS);
+
+		let mut effects = HashMap::<Sfx, ~mixer::Chunk>::new();
+		for sfx in SFXS.iter() {
+			match mixer::Chunk::from_file(&vfs::resolve(sfx.path())) {
+				Ok(chunk) => { effects.insert(*sfx, chunk); }
+				Err(msg) => {fail!("sound could not be loaded: {}", msg)}
+			}
+		}
+
+		let mut pitched = HashMap::<Sfx, Pitched>::new();
+		for sfx in SFXS.iter() {
+			match sfx.variation() {
+				Some(variation) => match Pitched::render(sfx.path(), variation.pitch) {
+					Ok(variants) => { pitched.insert(*sfx, variants); }
+					// the effect is simply played at its own pitch
+					Err(msg) => { log::warn("audio", format!("{} could not be varied: {}", sfx.path(), msg)); }
+				},
+				None => {}
+			}
+		}
+
+		Audio { 
+			music: None, 
+			organya: None,
+			looped: None,
+			music_cache: assets::Cache::new(),
+			track: None, 
+			effects: effects,
+			pitched: pitched,
+			muted: false,
+			master_volume: MAX_VOLUME,
+			music_volume: MAX_VOLUME,
+			sfx_volume: MAX_VOLUME,
+			listener: None,
+			rng: rng,
+			device: true
+		}
+	}
+
+	/// Audio which plays nothing, w/o opening the audio device, e.g: for a
+	/// dedicated server (see `Game::start_server`.) Volumes are still kept.
+	pub fn silent(rng: rng::Rng) -> Audio {
+		Audio {
+			music: None,
+			organya: None,
+			looped: None,
+			music_cache: assets::Cache::new(),
+			track: None,
+			effects: HashMap::new(),
+			pitched: HashMap::new(),
+			muted: false,
+			master_volume: MAX_VOLUME,
+			music_volume: MAX_VOLUME,
+			sfx_volume: MAX_VOLUME,
+			listener: None,
+			rng: rng,
+			device: false
+		}
+	}
+
+	/// Replaces the current music w/ the track at `path`.
+	/// The track repeats indefinitely if `looping` is set; should it fail
+	/// to load, the error is logged & no music plays.
+	///
+	/// An Organya song (`.org`) repeats from its own loop point, rather
+	/// than from the start.
+	pub fn play_music(&mut self, path: ~str, looping: bool) {
+		if !self.device { return; }
+		if path.ends_with(ORGANYA_SUFFIX) { return self.play_organya(path, looping); }
+
+		let music = self.load_music(path.clone());
+		mixer::Music::halt();
+		self.release_music();
+		self.organya = None;
+		self.looped = None;
+		self.track = None;
+		let music = match music {
+			Some(music) => music,
+			None => return
+		};
+
+		match music.borrow().play(if looping { -1 } else { 1 }) {
+			Ok(_) => {}
+			Err(msg) => { log::warn("audio", format!("music could not be played: {}", msg)); }
+		}
+
+		self.music = Some((path, music));
+	}
+
+	// replaces the current music w/ the Organya song at `path`, synthesized
+	// into the mixer's music stream in place of any decoded music
+	fn play_organya(&mut self, path: ~str, looping: bool) {
+		mixer::Music::halt();
+		self.release_music();
+		self.organya = None;
+		self.looped = None;
+		self.track = None;
+
+		let started = organya::Song::load(path.as_slice())
+			.and_then(|song| organya::Player::start(song, FREQUENCY as uint, looping));
+		match started {
+			Ok(player) => {
+				self.organya = Some(player);
+				self.apply_volumes();
+				if self.muted { self.organya.get_mut_ref().set_paused(true); }
+			}
+			Err(msg) => { log::warn("audio", format!("song {} could not be played: {}", path, msg)); }
+		}
+	}
+
+	/// Loads each track listed in `manifest`. (They are never released.)
+	pub fn preload(&mut self, manifest: &assets::Manifest) {
+		if !self.device { return; }
+		for path in manifest.paths_of(assets::Music).move_iter() {
+			// songs are synthesized as they play, so there is nothing to load ahead of time
+			if path.ends_with(ORGANYA_SUFFIX) { continue; }
+			self.load_music(path);
+		}
+	}
+
+	/// Frees any music which is no longer playing, nor preloaded.
+	pub fn unload_unused(&mut self) {
+		self.music_cache.unload_unused();
+	}
+
+	// the music at `path`, loaded if need be, held until `release_music()`;
+	// `None` (w/ the error logged) if it could not be loaded
+	fn load_music(&mut self, path: ~str) -> Option<Rc<~mixer::Music>> {
+		match self.music_cache.acquire(path.as_slice()) {
+			Some(music) => return Some(music),
+			None => {}
+		}
+
+		let music = match mixer::Music::from_file(&vfs::resolve(path.as_slice())) {
+			Ok(music) => Rc::new(music),
+			Err(msg) => {
+				log::error("audio", format!("music {} could not be loaded: {}", path, msg));
+				return None;
+			}
+		};
+		self.music_cache.insert(path, music.clone());
+		Some(music)
+	}
+
+	// lets go of the current music, if any
+	fn release_music(&mut self) {
+		match self.music.take() {
+			Some((path, _)) => self.music_cache.release(path.as_slice()),
+			None => {}
+		}
+	}
+
+	/// Replaces the current music w/ `track`, which plays its intro
+	/// once and then repeats its loop section.
+	///
+	/// If `track` is already playing it simply carries on. (Should it fail
+	/// to decode, it is played as music looping from its start instead.)
+	pub fn play_track(&mut self, track: Track) {
+		if !self.device { return; }
+		match self.track {
+			Some(ref current) if current.path == track.path => { return; }
+			_ => {}
+		}
+
+		mixer::Music::halt();
+		self.release_music();
+		self.organya = None;
+		self.looped = None;
+
+		match Looped::start(&track) {
+			Ok(looped) => {
+				self.looped = Some(looped);
+				self.apply_volumes();
+				if self.muted { self.looped.get_mut_ref().set_paused(true); }
+			}
+			Err(msg) => {
+				log::warn("audio", format!("track {} could not be looped: {}", track.path, msg));
+				self.play_music(track.path.clone(), true);
+			}
+		}
+		self.track = Some(track);
+	}
+
+	pub fn stop_music(&mut self) {
+		self.organya = None;
+		self.looped = None;
+		if !self.device { return; }
+		mixer::Music::halt();
+		self.release_music();
+		self.track = None;
+	}
+
+	/// Plays `sfx` on the first free mixing channel, equally loud in either ear.
+	/// Returns the channel the effect is playing on, if one was free.
+	pub fn play_sfx(&self, sfx: Sfx) -> Option<int> {
+		self.play_panned(sfx, (1.0, 1.0))
+	}
+
+	/// Plays `sfx` as though it came from `position` in the world: panned
+	/// towards the side of the listener it is on, & quieter the further off
+	/// screen it is. (Sounds too far away to be heard are not played.)
+	///
+	/// W/o a listener this is simply `play_sfx`.
+	pub fn play_sfx_at(&self, sfx: Sfx, position: (units::Game, units::Game)) -> Option<int> {
+		match self.listener {
+			Some((center, screen)) => {
+				let (left, right) = Audio::placement(position, center, screen);
+				if left <= 0.0 && right <= 0.0 { return None; }
+				self.play_panned(sfx, (left, right))
+			}
+			None => self.play_sfx(sfx)
+		}
+	}
+
+	/// Hears positioned sounds from `center` (e.g: of the camera), w/ a
+	/// screen of `screen` around it.
+	pub fn set_listener(&mut self, center: (units::Game, units::Game), screen: (units::Game, units::Game)) {
+		self.listener = Some((center, screen));
+	}
+
+	// plays `sfx` w/ the volume of each ear scaled by `gains`, each `[0, 1]`,
+	// varying its pitch & volume if it is an effect which varies
+	fn play_panned(&self, sfx: Sfx, gains: (f64, f64)) -> Option<int> {
+		if !self.device || self.muted { return None; }
+
+		let mut rng = self.rng.clone();
+		let (mut left, mut right) = gains;
+		let played = match (sfx.variation(), self.pitched.find(&sfx)) {
+			(Some(variation), Some(variants)) => {
+				let quieter = 1.0 - rng.gen_range(0.0, variation.volume);
+				left = left * quieter;
+				right = right * quieter;
+				variants.play(rng.gen_range(0, variants.len()))
+			}
+			_ => match mixer::Channel(-1).play(*self.effects.get(&sfx), 0) {
+				Ok(mixer::Channel(channel)) => Some(channel),
+				Err(_) => None
+			}
+		};
+
+		match played {
+			Some(channel) => {
+				// channels are reused, so even a centered effect resets the last one's panning
+				mixer::Channel(channel).set_panning((left * 255.0) as u8, (right * 255.0) as u8);
+				Some(channel)
+			}
+			None => None // all channels busy: drop the effect
+		}
+	}
+
+	// the gain of the left & right ears for a sound at `position`, heard
+	// from `center` w/ a `screen` sized view around it
+	fn placement(
+		position: (units::Game, units::Game),
+		center: (units::Game, units::Game),
+		screen: (units::Game, units::Game)
+	) -> (f64, f64) {
+		let ((units::Game(x), units::Game(y)), (units::Game(cx), units::Game(cy))) = (position, center);
+		let (units::Game(screen_w), units::Game(screen_h)) = screen;
+		let (dx, dy) = (x - cx, y - cy);
+
+		// anything on screen is heard at full volume
+		let beyond_x = (dx.abs() - (screen_w / 2.0)).max(0.0);
+		let beyond_y = (dy.abs() - (screen_h / 2.0)).max(0.0);
+		let beyond = ((beyond_x * beyond_x) + (beyond_y * beyond_y)).sqrt();
+		let loudness = (1.0 - (beyond / (screen_w * FALLOFF_SCREENS))).max(0.0);
+
+		let pan = (dx / (screen_w / 2.0)).max(-1.0).min(1.0) * PAN_STRENGTH;
+		(loudness * (1.0 - pan).min(1.0), loudness * (1.0 + pan).min(1.0))
+	}
+
+	/// Pauses the music & any playing effects, e.g: while the window is
+	/// out of focus. New effects are dropped until `unmute()` is called.
+	pub fn mute(&mut self) {
+		if !self.device || self.muted { return; }
+		mixer::Music::pause();
+		mixer::Channel(-1).pause();
+		match self.organya { Some(ref mut player) => player.set_paused(true), None => {} }
+		match self.looped { Some(ref mut looped) => looped.set_paused(true), None => {} }
+		self.muted = true;
+	}
+
+	/// Resumes whatever was playing when the audio was muted.
+	pub fn unmute(&mut self) {
+		if !self.muted { return; }
+		mixer::Music::resume();
+		mixer::Channel(-1).resume();
+		match self.organya { Some(ref mut player) => player.set_paused(false), None => {} }
+		match self.looped { Some(ref mut looped) => looped.set_paused(false), None => {} }
+		self.muted = false;
+	}
+
+	/// Sets the volume, `[0, MAX_VOLUME]`, of everything played: the
+	/// music & sound effects are each played at their own volume scaled by it.
+	pub fn set_master_volume(&mut self, volume: uint) {
+		self.master_volume = cmp::min(volume, MAX_VOLUME);
+		self.apply_volumes();
+	}
+
+	/// Sets the volume, `[0, MAX_VOLUME]`, of the music.
+	pub fn set_music_volume(&mut self, volume: uint) {
+		self.music_volume = cmp::min(volume, MAX_VOLUME);
+		self.apply_volumes();
+	}
+
+	/// Sets the volume, `[0, MAX_VOLUME]`, of every sound effect, including
+	/// those already playing.
+	pub fn set_sfx_volume(&mut self, volume: uint) {
+		self.sfx_volume = cmp::min(volume, MAX_VOLUME);
+		self.apply_volumes();
+	}
+
+	pub fn master_volume(&self) -> uint { self.master_volume }
+	pub fn music_volume(&self) -> uint { self.music_volume }
+	pub fn sfx_volume(&self) -> uint { self.sfx_volume }
+
+	// hands the mixer each bus's volume, scaled by the master volume
+	fn apply_volumes(&mut self) {
+		if !self.device { return; }
+		mixer::Music::set_volume(self.mixer_volume(self.music_volume));
+		let gain = (self.music_volume * self.master_volume) as f64 / (MAX_VOLUME * MAX_VOLUME) as f64;
+		match self.organya { Some(ref mut player) => player.set_gain(gain), None => {} }
+		match self.looped { Some(ref mut looped) => looped.set_gain(gain), None => {} }
+		// every channel, whether or not it is playing
+		mixer::Channel(-1).set_volume(self.mixer_volume(self.sfx_volume));
+	}
+
+	// `volume` of a bus as `SDL_mixer` measures it, once scaled by the master volume
+	fn mixer_volume(&self, volume: uint) -> int {
+		((volume * self.master_volume * MIXER_VOLUME) / (MAX_VOLUME * MAX_VOLUME)) as int
+	}
+}
+
+// the layout of `SDL_mixer`'s `Mix_Chunk`, whose samples `sdl2_mixer` keeps to itself
+struct RawChunk {
+	allocated: c_int,
+	samples: *mut u8,
+	length: u32, // in bytes
+	volume: u8
+}
+
+extern {
+	fn SDL_RWFromFile(file: *c_char, mode: *c_char) -> *mut c_void;
+	fn Mix_LoadWAV_RW(src: *mut c_void, free_src: c_int) -> *mut RawChunk;
+	fn Mix_QuickLoad_RAW(samples: *mut u8, length: u32) -> *mut RawChunk;
+	fn Mix_FreeChunk(chunk: *mut RawChunk);
+	fn Mix_PlayChannelTimed(channel: c_int, chunk: *mut RawChunk, loops: c_int, ticks: c_int) -> c_int;
+	fn Mix_HookMusic(mix_func: Option<extern "C" fn(*mut c_void, *mut u8, c_int)>, arg: *mut c_void);
+	fn SDL_LockAudio();
+	fn SDL_UnlockAudio();
+}
+
+/// An effect rendered at `PITCH_VARIANTS` pitches, from `1 - spread` to
+/// `1 + spread` times its own, by resampling it (so a higher pitch is shorter.)
+///
+/// The mixer must have been opened, as each variant is rendered in its format:
+/// signed 16-bit stereo.
+struct Pitched {
+	chunks: ~[*mut RawChunk],
+	// the samples of each chunk, which the mixer plays in place
+	buffers: ~[~[i16]]
+}
+
+impl Pitched {
+	fn render(path: ~str, spread: f64) -> Result<Pitched, ~str> {
+		let original = try!(Pitched::decode(path));
+		let mut pitched = Pitched { chunks: ~[], buffers: ~[] };
+		for index in range(0, PITCH_VARIANTS) {
+			let step = 1.0 - spread + ((2.0 * spread * index as f64) / (PITCH_VARIANTS - 1) as f64);
+			let mut buffer = Pitched::resample(original.as_slice(), step);
+			let chunk = unsafe {
+				Mix_QuickLoad_RAW(buffer.as_mut_ptr() as *mut u8, (buffer.len() * 2) as u32)
+			};
+			if chunk.is_null() { return Err(~"variant could not be loaded"); }
+			pitched.chunks.push(chunk);
+			pitched.buffers.push(buffer);
+		}
+		Ok(pitched)
+	}
+
+	fn len(&self) -> uint { self.chunks.len() }
+
+	// plays variant `index` on the first free channel, if there is one
+	fn play(&self, index: uint) -> Option<int> {
+		match unsafe { Mix_PlayChannelTimed(-1, self.chunks[index], 0, -1) } {
+			-1 => None,
+			channel => Some(channel as int)
+		}
+	}
+
+	// the samples of the sound at `path`, converted to the mixer's format
+	fn decode(path: ~str) -> Result<~[i16], ~str> {
+		let file_path = vfs::resolve(path.as_slice());
+		let chunk = unsafe {
+			let source = file_path.with_c_str(|file| "rb".with_c_str(|mode| SDL_RWFromFile(file, mode)));
+			if source.is_null() { return Err(~"file could not be opened"); }
+			Mix_LoadWAV_RW(source, 1)
+		};
+		if chunk.is_null() { return Err(~"sound could not be decoded"); }
+		if unsafe { (*chunk).length } < 4 {
+			unsafe { Mix_FreeChunk(chunk); }
+			return Err(~"sound is empty");
+		}
+
+		unsafe {
+			let samples = vec::raw::from_buf_raw((*chunk).samples as *i16, ((*chunk).length / 2) as uint);
+			Mix_FreeChunk(chunk);
+			Ok(samples)
+		}
+	}
+
+	// `samples` (interleaved left & right) played back `step` times as fast
+	fn resample(samples: &[i16], step: f64) -> ~[i16] {
+		let frames = samples.len() / 2;
+		let length = (frames as f64 / step) as uint;
+		let mut resampled = vec::with_capacity(length * 2);
+		for frame in range(0, length) {
+			// interpolate between the two frames the playhead falls between
+			let position = frame as f64 * step;
+			let (before, blend) = (position as uint, position - position.floor());
+			let after = cmp::min(before + 1, frames - 1);
+			for side in range(0, 2u) {
+				let (a, b) = (samples[(before * 2) + side] as f64, samples[(after * 2) + side] as f64);
+				resampled.push((a + ((b - a) * blend)) as i16);
+			}
+		}
+		resampled
+	}
+}
+
+/// The mixer must be done w/ a variant's samples before they are freed:
+/// chunks are freed (halting any channel playing them) before their buffers.
+impl Drop for Pitched {
+	fn drop(&mut self) {
+		for &chunk in self.chunks.iter() {
+			unsafe { Mix_FreeChunk(chunk); }
+		}
+	}
+}
+
+/// The samples of a track, decoded in the mixer's format (like a `Pitched`
+/// effect), & where the mixer has reached in them.
+struct LoopStream {
+	samples: ~[i16],
+	// in frames, i.e: pairs of samples
+	position: uint,
+	loop_start: uint,
+	loop_end: uint,
+	// the loudness of the track, `[0, 1]`
+	gain: f64,
+	paused: bool
+}
+
+impl LoopStream {
+	/// Fills `out` w/ interleaved stereo samples, going back to `loop_start`
+	/// as soon as `loop_end` is reached.
+	fn mix(&mut self, out: &mut [i16]) {
+		for frame in out.mut_chunks(2) {
+			if self.paused {
+				for sample in frame.mut_iter() { *sample = 0; }
+				continue;
+			}
+
+			if self.position >= self.loop_end { self.position = self.loop_start; }
+			for side in range(0, frame.len()) {
+				frame[side] = (self.samples[(self.position * 2) + side] as f64 * self.gain) as i16;
+			}
+			self.position += 1;
+		}
+	}
+}
+
+// `SDL_mixer` calls this for each buffer of music it needs, w/ the stream playing it
+extern "C" fn fill_loop(stream: *mut c_void, buffer: *mut u8, length: c_int) {
+	unsafe {
+		let stream = &mut *(stream as *mut LoopStream);
+		vec::raw::mut_buf_as_slice(buffer as *mut i16, (length as uint) / 2, |out| stream.mix(out));
+	}
+}
+
+/// Plays a `Track` in place of `SDL_mixer`'s music for as long as it lives,
+/// just as an `organya::Player` plays a song.
+struct Looped {
+	stream: ~LoopStream
+}
+
+impl Looped {
+	fn start(track: &Track) -> Result<Looped, ~str> {
+		let samples = try!(Pitched::decode(track.path.clone()));
+		let frames = samples.len() / 2;
+		let to_frames = |units::Millis(ms): units::Millis| (ms as uint * FREQUENCY as uint) / 1000;
+		let (loop_start, loop_end) = (to_frames(track.loop_start), cmp::min(to_frames(track.loop_end), frames));
+		if loop_start >= loop_end { return Err(~"loop section lies past the end of the track"); }
+
+		let mut looped = Looped { stream: ~LoopStream {
+			samples: samples, position: 0, loop_start: loop_start, loop_end: loop_end,
+			gain: 1.0, paused: false
+		}};
+		unsafe {
+			let stream: *mut LoopStream = &mut *looped.stream;
+			Mix_HookMusic(Some(fill_loop), stream as *mut c_void);
+		}
+		Ok(looped)
+	}
+
+	fn set_gain(&mut self, gain: f64) {
+		self.while_locked(|stream| stream.gain = gain);
+	}
+
+	fn set_paused(&mut self, paused: bool) {
+		self.while_locked(|stream| stream.paused = paused);
+	}
+
+	// changes the stream w/o the mixer reading it halfway through
+	fn while_locked(&mut self, change: |&mut LoopStream|) {
+		unsafe { SDL_LockAudio(); }
+		change(&mut *self.stream);
+		unsafe { SDL_UnlockAudio(); }
+	}
+}
+
+/// When the `Looped` leaves scope the mixer stops calling on its stream.
+impl Drop for Looped {
+	fn drop(&mut self) {
+		unsafe {
+			SDL_LockAudio();
+			Mix_HookMusic(None, 0 as *mut c_void);
+			SDL_UnlockAudio();
+		}
+	}
+}
@@ -1,41 +1,40 @@
-use sync::Arc;
-
 use sdl2::rect::Rect;
-use sdl2::render::Texture;
 
-use game;
 use game::graphics;
 use game::units;
+
+use game::error::GameResult;
 use game::units::{AsPixel};
 
 
 static BACKGROUND_SIZE: units::Tile = units::Tile(4);
 
 pub struct FixedBackdrop {
-	surface: Arc<~Texture>
+	surface: graphics::Texture
 }
 
 impl FixedBackdrop {
 	pub fn new(path: ~str, graphics: &mut graphics::Graphics) 
-		-> FixedBackdrop {
+		-> GameResult<FixedBackdrop> {
 
-		let asset = graphics.load_image(path, false);
-		FixedBackdrop { surface: asset }
+		let asset = try!(graphics.load_image(path, graphics::OPAQUE));
+		Ok(FixedBackdrop { surface: asset })
 	}
 
 	/// Repeatedly paints the asset across the entire screen.
 	/// Moving the destination rectangle `BACKGROUND_SIZE` pixels
 	/// in either direction as it progresses.
-	pub fn draw(&self, graphics: &graphics::Graphics) {
+	pub fn draw(&self, graphics: &graphics::Graphics, queue: &mut graphics::RenderQueue) {
 		let (mut x, mut y) = (0i32,0i32);
 		let units::Pixel(tile_size) = BACKGROUND_SIZE.to_pixel();	
+		let (screen_w, screen_h) = graphics.resolution().to_pixel();
 
-		while units::Pixel(x) < game::SCREEN_WIDTH.to_pixel() {
-			while units::Pixel(y) < game::SCREEN_HEIGHT.to_pixel() {
+		while units::Pixel(x) < screen_w {
+			while units::Pixel(y) < screen_h {
 				let src = Rect::new(0, 0, tile_size, tile_size);
 				let dest = Rect::new(x, y, tile_size, tile_size);
 
-				graphics.blit_surface(*(self.surface.get()), &src, &dest);
+				queue.submit(graphics::Backdrop, self.surface, &src, &dest, &graphics::PLAIN);
 				y+= tile_size;
 			}
 
@@ -44,3 +43,55 @@ impl FixedBackdrop {
 		}
 	}
 }
+
+/// A background image which tiles infinitely in both directions and
+/// scrolls at a fraction of the camera's speed, giving a sense of depth.
+///
+/// A `factor` of `(0.0, 0.0)` keeps the layer fixed in place, while
+/// `(1.0, 1.0)` scrolls it along w/ the map itself.
+pub struct ParallaxBackdrop {
+	priv surface: graphics::Texture,
+	priv size: (units::Game, units::Game),
+	priv factor: (f64, f64)
+}
+
+impl ParallaxBackdrop {
+	/// Loads the image at `path`, which is `size` large.
+	pub fn new(
+		path: ~str, size: (units::Game, units::Game), factor: (f64, f64),
+		graphics: &mut graphics::Graphics
+	) -> GameResult<ParallaxBackdrop> {
+		let asset = try!(graphics.load_image(path, graphics::OPAQUE));
+		Ok(ParallaxBackdrop { surface: asset, size: size, factor: factor })
+	}
+
+	/// Paints the image across the entire screen, shifted by
+	/// the camera's `offset` scaled by this layer's scroll factor.
+	pub fn draw(&self, graphics: &graphics::Graphics, queue: &mut graphics::RenderQueue, offset: (units::Game, units::Game)) {
+		let (units::Game(ox), units::Game(oy)) = offset;
+		let (w, h) = self.size;
+		let (factor_x, factor_y) = self.factor;
+
+		let (units::Pixel(wi), units::Pixel(hi)) = (w.to_pixel(), h.to_pixel());
+		let (units::Pixel(scroll_x), units::Pixel(scroll_y)) = 
+			(units::Game(ox * factor_x).to_pixel(), units::Game(oy * factor_y).to_pixel());
+		let (units::Pixel(screen_w), units::Pixel(screen_h)) = graphics.resolution().to_pixel();
+
+		// the first copy starts at or just before the screen's top-left corner
+		let start_x = -(((scroll_x % wi) + wi) % wi);
+		let start_y = -(((scroll_y % hi) + hi) % hi);
+		let src = Rect::new(0, 0, wi, hi);
+
+		let mut x = start_x;
+		while x < screen_w {
+			let mut y = start_y;
+			while y < screen_h {
+				let dest = Rect::new(x, y, wi, hi);
+				queue.submit(graphics::Backdrop, self.surface, &src, &dest, &graphics::PLAIN);
+				y += hi;
+			}
+
+			x += wi;
+		}
+	}
+}
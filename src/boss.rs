@@ -0,0 +1,368 @@
+use std::f64;
+use std::io::File;
+
+use game::graphics;
+use game::map;
+use game::sprite;
+use game::vfs;
+
+use game::collisions::Rectangle;
+use game::enemies;
+use game::enemies::Enemy;
+use game::entity;
+use game::entity::Entity;
+use game::error::{BossError, GameResult};
+use game::units;
+use game::units::{AsGame};
+
+/// How long a defeated boss takes to die, shaking & exploding all the while.
+pub static DEFEAT_TIME: units::Millis = units::Millis(2000);
+
+// while invulnerable, a boss blinks every so often
+static FLASH_INTERVAL: units::Millis = units::Millis(50);
+// how far a dying boss shakes from side to side
+static DEFEAT_SHAKE: units::Game = units::Game(2.0);
+
+static LOOT: enemies::LootTable = &[];
+
+/// How a boss moves about during one of its phases.
+#[deriving(Clone)]
+pub enum Pattern {
+	/// Holds still at its spawn point.
+	Still,
+	/// Traces a figure-eight about its spawn point, reaching as far
+	/// as `(x, y)` from it, once every `period`.
+	Sway(units::Game, units::Game, units::Millis),
+	/// Flies level towards the player, at a fixed speed.
+	Chase(units::Velocity)
+}
+
+/// One stage of a fight: it begins once the boss is worn down to
+/// `threshold` hit points, & lasts until the next phase begins.
+#[deriving(Clone)]
+pub struct Phase {
+	threshold: units::HitPoint,
+	pattern: Pattern,
+	/// The damage dealt to the player when they touch the boss.
+	contact_damage: units::HitPoint,
+	/// How long the boss shrugs off every hit, once the phase begins.
+	invulnerable: units::Millis
+}
+
+/// What has become of a boss since it was last asked.
+#[deriving(Eq,Clone)]
+pub enum Defeat {
+	/// It has been worn down to nothing, & begun to die.
+	Begun,
+	/// It has finished dying: its flag should now be set.
+	Ended
+}
+
+/// A large enemy fought in phases, each w/ its own pattern of movement.
+///
+/// A boss is read from a file of lines such as:
+///
+/// * `name <name>` is shown while debugging.
+/// * `sheet <path>` is the sprite sheet the boss is cut from.
+/// * `sprite <col> <row> <width> <height> <frames> <fps>` is where (& how
+///   large) its animation is on the sheet, in tiles.
+/// * `hit_points <hp>` is how much damage it takes to defeat.
+/// * `flag <id>` is the event flag set once it is defeated; a boss whose
+///   flag is already set does not appear.
+/// * `event <id>` (optional) is the map's event run once it is defeated.
+/// * `phase <hp> <damage> <invulnerable ms> <pattern..>` adds a phase
+///   beginning once the boss is down to `hp`, where the pattern is one of
+///   `still`, `sway <x> <y> <period ms>` (`x` & `y` in tiles), or
+///   `chase <speed>`. Phases are listed strongest first; the first begins
+///   the fight & so must start at full health.
+///
+/// Lines starting w/ `#` are ignored.
+#[deriving(Clone)]
+pub struct Boss {
+	priv name: ~str,
+	priv home: (units::Game, units::Game),
+	priv x: units::Game,
+	priv y: units::Game,
+	// where the boss stood before its last update
+	priv previous: (units::Game, units::Game),
+	priv size: (units::Game, units::Game),
+
+	priv hit_points: units::HitPoint,
+	priv max_hit_points: units::HitPoint,
+	priv phases: ~[Phase],
+	priv phase: uint,
+	priv phase_time: units::Millis,
+	priv invulnerable_time: units::Millis,
+
+	priv flag: uint,
+	priv event: Option<uint>,
+	// how long the boss has been dying, once it has been defeated
+	priv defeat_time: Option<units::Millis>,
+	priv defeat: Option<Defeat>,
+
+	priv sprite: ~sprite::Updatable
+}
+
+impl Boss {
+	/// Reads the boss at `path`, placing its top-left corner at `position`.
+	pub fn load(
+		graphics: &mut graphics::Graphics,
+		path: &str,
+		position: (units::Game, units::Game)
+	) -> GameResult<Boss> {
+		let error = |reason: ~str| BossError(path.to_owned(), reason);
+		let contents = match File::open(&vfs::resolve(path)).read_to_str() {
+			Ok(contents) => contents,
+			Err(msg) => return Err(error(format!("{}", msg)))
+		};
+
+		let mut name = ~"boss";
+		let mut sheet = None;
+		let mut region = None;
+		let mut hit_points = None;
+		let mut flag = None;
+		let mut event = None;
+		let mut phases: ~[Phase] = ~[];
+
+		for raw_line in contents.lines() {
+			let line = raw_line.trim();
+			if line.len() == 0 || line.starts_with("#") { continue; }
+
+			let words: ~[&str] = line.words().collect();
+			match words.as_slice() {
+				["name", boss_name] => { name = boss_name.to_owned(); }
+				["sheet", sheet_path] => { sheet = Some(sheet_path.to_owned()); }
+				["sprite", col, row, w, h, frames, fps] => {
+					region = match (from_str::<uint>(col), from_str::<uint>(row), from_str::<uint>(w),
+					                from_str::<uint>(h), from_str::<uint>(frames), from_str::<uint>(fps)) {
+						(Some(col), Some(row), Some(w), Some(h), Some(frames), Some(fps)) if frames > 0 => {
+							Some(((units::Tile(col), units::Tile(row)), (units::Tile(w), units::Tile(h)), frames, fps))
+						}
+						_ => return Err(error(format!("invalid sprite `{}`", line)))
+					};
+				}
+				["hit_points", hp] => {
+					hit_points = match from_str::<units::HitPoint>(hp) {
+						Some(hp) if hp > 0 => Some(hp),
+						_ => return Err(error(format!("invalid hit points `{}`", line)))
+					};
+				}
+				["flag", id] => { flag = from_str::<uint>(id); }
+				["event", id] => { event = from_str::<uint>(id); }
+				["phase", threshold, damage, invulnerable, ..pattern] => {
+					let pattern = match pattern {
+						["still"] => Still,
+						["sway", x, y, period] => match (from_str::<uint>(x), from_str::<uint>(y), from_str::<uint>(period)) {
+							(Some(x), Some(y), Some(period)) if period > 0 => {
+								Sway(units::Tile(x).to_game(), units::Tile(y).to_game(), units::Millis(period as int))
+							}
+							_ => return Err(error(format!("invalid sway `{}`", line)))
+						},
+						["chase", speed] => match from_str::<f64>(speed) {
+							Some(speed) => Chase(units::Velocity(speed)),
+							None => return Err(error(format!("invalid chase `{}`", line)))
+						},
+						_ => return Err(error(format!("unknown pattern in `{}`", line)))
+					};
+					match (from_str::<units::HitPoint>(threshold), from_str::<units::HitPoint>(damage), from_str::<uint>(invulnerable)) {
+						(Some(threshold), Some(damage), Some(invulnerable)) => phases.push(Phase {
+							threshold: threshold,
+							pattern: pattern,
+							contact_damage: damage,
+							invulnerable: units::Millis(invulnerable as int)
+						}),
+						_ => return Err(error(format!("invalid phase `{}`", line)))
+					}
+				}
+				_ => return Err(error(format!("malformed line `{}`", line)))
+			}
+		}
+
+		let (sheet, region, hit_points, flag) = match (sheet, region, hit_points, flag) {
+			(Some(sheet), Some(region), Some(hit_points), Some(flag)) => (sheet, region, hit_points, flag),
+			_ => return Err(error(~"a boss needs a sheet, sprite, hit_points & flag"))
+		};
+		if phases.is_empty() || phases[0].threshold < hit_points {
+			return Err(error(~"the first phase must begin at full health"));
+		}
+		if phases.windows(2).any(|pair| pair[1].threshold >= pair[0].threshold) {
+			return Err(error(~"phases must be listed strongest first"));
+		}
+
+		let (offset, (w, h), frames, fps) = region;
+		let sprite = if frames > 1 {
+			~try!(sprite::AnimatedSprite::new(graphics, sheet, offset, (w, h), frames, fps)) as ~sprite::Updatable
+		} else {
+			~try!(sprite::Sprite::new(graphics, position, offset, (w, h), sheet)) as ~sprite::Updatable
+		};
+
+		let (x, y) = position;
+		let invulnerable = phases[0].invulnerable;
+		Ok(Boss {
+			name: name,
+			home: position,
+			x: x, y: y,
+			previous: position,
+			size: (w.to_game(), h.to_game()),
+
+			hit_points: hit_points,
+			max_hit_points: hit_points,
+			phases: phases,
+			phase: 0,
+			phase_time: units::Millis(0),
+			invulnerable_time: invulnerable,
+
+			flag: flag,
+			event: event,
+			defeat_time: None,
+			defeat: None,
+
+			sprite: sprite
+		})
+	}
+
+	pub fn name<'a>(&'a self) -> &'a str { self.name.as_slice() }
+	pub fn hit_points(&self) -> units::HitPoint { self.hit_points }
+	pub fn max_hit_points(&self) -> units::HitPoint { self.max_hit_points }
+
+	/// The event flag set once this boss is defeated.
+	pub fn flag(&self) -> uint { self.flag }
+	/// The map's event run once this boss is defeated, if any.
+	pub fn event(&self) -> Option<uint> { self.event }
+
+	/// Responds true from the moment the boss is defeated until it has finished dying.
+	pub fn is_dying(&self) -> bool { self.defeat_time.is_some() && self.is_alive() }
+
+	/// Responds true while the boss shrugs off every hit.
+	pub fn is_invulnerable(&self) -> bool { self.invulnerable_time > units::Millis(0) }
+
+	/// Responds w/ how the boss's defeat has progressed since this was last called, if at all.
+	pub fn take_defeat(&mut self) -> Option<Defeat> { self.defeat.take() }
+
+	fn current_phase<'a>(&'a self) -> &'a Phase { &self.phases[self.phase] }
+
+	// moves to the weakest phase the boss has been worn down to
+	fn advance_phase(&mut self) {
+		let mut phase = self.phase;
+		while phase + 1 < self.phases.len() && self.hit_points <= self.phases[phase + 1].threshold {
+			phase += 1;
+		}
+		if phase != self.phase {
+			self.phase = phase;
+			self.phase_time = units::Millis(0);
+			self.invulnerable_time = self.phases[phase].invulnerable;
+		}
+	}
+}
+
+impl Entity for Boss {
+	/// Moves the boss along its current phase's pattern; once defeated it
+	/// shakes in place until it has finished dying.
+	#[allow(unused_variable)]
+	fn update(
+		&mut self,
+		elapsed_time: units::Millis,
+		player: (units::Game, units::Game),
+		map: &map::Map
+	) {
+		self.previous = (self.x, self.y);
+		self.sprite.update(elapsed_time);
+
+		match self.defeat_time {
+			Some(time) => {
+				let now = time + elapsed_time;
+				self.defeat_time = Some(now);
+				if time < DEFEAT_TIME && now >= DEFEAT_TIME { self.defeat = Some(Ended); }
+
+				let (home_x, _) = self.home;
+				let (units::Millis(t), units::Millis(flash)) = (now, FLASH_INTERVAL);
+				self.x = if (t / flash) % 2 == 0 { home_x - DEFEAT_SHAKE } else { home_x + DEFEAT_SHAKE };
+				self.sprite.set_position((self.x, self.y));
+				return;
+			}
+			None => {}
+		}
+
+		self.phase_time = self.phase_time + elapsed_time;
+		if self.invulnerable_time > units::Millis(0) {
+			self.invulnerable_time = self.invulnerable_time - elapsed_time;
+		}
+
+		let (home_x, home_y) = self.home;
+		match self.current_phase().pattern {
+			Still => {
+				self.x = home_x;
+				self.y = home_y;
+			}
+			Sway(reach_x, reach_y, units::Millis(period)) => {
+				let units::Millis(t) = self.phase_time;
+				let angle = (t as f64 / period as f64) * 2.0 * f64::consts::PI;
+				self.x = home_x + reach_x * units::Game(f64::sin(angle));
+				self.y = home_y + reach_y * units::Game(f64::sin(angle * 2.0));
+			}
+			Chase(speed) => {
+				let (player_x, _) = player;
+				let (w, _) = self.size;
+				let center_x = self.x + (w / units::Game(2.0));
+				let step = speed * elapsed_time;
+				self.x = if player_x > center_x + step { self.x + step }
+					else if player_x < center_x - step { self.x - step }
+					else { self.x };
+			}
+		}
+		self.sprite.set_position((self.x, self.y));
+	}
+
+	/// The boss blinks while it is invulnerable.
+	fn draw(&self, queue: &mut graphics::RenderQueue, offset: (units::Game, units::Game), alpha: f64) {
+		if self.is_invulnerable() {
+			let (units::Millis(t), units::Millis(flash)) = (self.invulnerable_time, FLASH_INTERVAL);
+			if (t / flash) % 2 == 0 { return; }
+		}
+
+		self.sprite.draw_queued(queue, graphics::Entities, entity::blend(offset, self.previous, (self.x, self.y), alpha));
+	}
+
+	fn hitbox(&self) -> Rectangle {
+		let (w, h) = self.size;
+		Rectangle { x: self.x, y: self.y, width: w, height: h }
+	}
+
+	fn kind(&self) -> entity::Kind { entity::Enemy }
+
+	/// A boss lives on until it has finished dying.
+	fn is_alive(&self) -> bool {
+		match self.defeat_time {
+			Some(time) => time < DEFEAT_TIME,
+			None => true
+		}
+	}
+}
+
+impl Enemy for Boss {
+	/// A dying boss is harmless.
+	fn contact_damage(&self) -> units::HitPoint {
+		if self.defeat_time.is_some() { 0 } else { self.current_phase().contact_damage }
+	}
+
+	/// Hits are shrugged off while invulnerable (or dying); otherwise the
+	/// boss is worn down, moving on to later phases as it weakens.
+	fn on_hit(&mut self, damage: units::HitPoint) {
+		if self.is_invulnerable() || self.defeat_time.is_some() { return; }
+
+		self.hit_points = if self.hit_points > damage { self.hit_points - damage } else { 0 };
+		if self.hit_points == 0 {
+			self.defeat_time = Some(units::Millis(0));
+			self.defeat = Some(Begun);
+		} else {
+			self.advance_phase();
+		}
+	}
+
+	/// Defeating a boss is its own reward; its flag marks the victory.
+	fn experience(&self) -> uint { 0 }
+
+	fn loot(&self) -> enemies::LootTable { LOOT }
+
+	fn clone_enemy(&self) -> ~Enemy { ~self.clone() as ~Enemy }
+}
@@ -0,0 +1,183 @@
+use std::cmp;
+use std::rand::Rng;
+
+use game::rng;
+use game::units;
+
+// fraction of the distance to its target the camera covers each millisecond
+static SMOOTHING: f64 = 0.008;
+
+// furthest the view strays from the camera's position while shaking
+static SHAKE_DISTANCE: f64 = 3.0;
+
+#[deriving(Clone)]
+struct Pan {
+	from: (units::Game, units::Game),
+	to: (units::Game, units::Game),
+	duration: units::Millis,
+	elapsed: units::Millis
+}
+
+/// A viewport into the world which trails behind some target.
+///
+/// The camera is positioned by its top-left corner, expressed in `Game` units,
+/// and is kept within the bounds of the current map so the area outside of it
+/// is never shown.
+#[deriving(Clone)]
+pub struct Camera {
+	priv x: units::Game,
+	priv y: units::Game,
+
+	// position before the most recent update
+	priv prev_x: units::Game,
+	priv prev_y: units::Game,
+
+	// while a script directs the camera: where it pans from & to, how long
+	// the pan takes & how far through it the camera is
+	priv pan: Option<Pan>,
+
+	// the view is jittered until the shake is over
+	priv shake_time: units::Millis,
+	priv jitter: (units::Game, units::Game),
+
+	// the size of the view
+	priv screen: (units::Game, units::Game),
+	priv rng: rng::Rng
+}
+
+impl Camera {
+	/// Creates a camera which is immediately centered on `target`,
+	/// viewing a screen of `resolution`.
+	pub fn new(
+		target: (units::Game, units::Game),
+		bounds: (units::Game, units::Game),
+		resolution: units::Resolution,
+		rng: rng::Rng
+	) -> Camera {
+		let screen = resolution.to_game();
+		let (x, y) = Camera::goal_for(target, bounds, screen);
+		Camera {
+			x: x, y: y, prev_x: x, prev_y: y,
+			pan: None,
+			shake_time: units::Millis(0),
+			jitter: (units::Game(0.0), units::Game(0.0)),
+			screen: screen,
+			rng: rng
+		}
+	}
+
+	/// Eases the camera towards `target` (the center of whatever it follows.)
+	/// `bounds` is the width & height of the map the camera is clamped to.
+	///
+	/// While panning the camera ignores `target`, following its pan instead.
+	pub fn update(
+		&mut self, 
+		elapsed_time: units::Millis,
+		target: (units::Game, units::Game), 
+		bounds: (units::Game, units::Game)
+	) {
+		let units::Millis(dt) = elapsed_time;
+		self.prev_x = self.x;
+		self.prev_y = self.y;
+
+		match self.pan {
+			Some(ref mut pan) => {
+				pan.elapsed = cmp::min(pan.elapsed + elapsed_time, pan.duration);
+				let ((from_x, from_y), (to_x, to_y)) = (pan.from, pan.to);
+				let (units::Millis(elapsed), units::Millis(duration)) = (pan.elapsed, pan.duration);
+				let progress = if duration > 0 { units::Game(elapsed as f64 / duration as f64) } else { units::Game(1.0) };
+
+				self.x = from_x + ((to_x - from_x) * progress);
+				self.y = from_y + ((to_y - from_y) * progress);
+			}
+			None => {
+				let (goal_x, goal_y) = Camera::goal_for(target, bounds, self.screen);
+				let factor = cmp::min(units::Game(SMOOTHING * dt as f64), units::Game(1.0));
+
+				self.x = self.x + ((goal_x - self.x) * factor);
+				self.y = self.y + ((goal_y - self.y) * factor);
+			}
+		}
+
+		self.jitter = if self.shake_time > units::Millis(0) {
+			self.shake_time = self.shake_time - elapsed_time;
+			let rng = &mut self.rng;
+			(units::Game(rng.gen_range(-SHAKE_DISTANCE, SHAKE_DISTANCE)),
+			 units::Game(rng.gen_range(-SHAKE_DISTANCE, SHAKE_DISTANCE)))
+		} else {
+			(units::Game(0.0), units::Game(0.0))
+		};
+	}
+
+	/// Pans the camera steadily over to center on `target`, arriving after
+	/// `duration`, & holds it there until `follow()` is called.
+	pub fn pan_to(
+		&mut self,
+		target: (units::Game, units::Game),
+		bounds: (units::Game, units::Game),
+		duration: units::Millis
+	) {
+		self.pan = Some(Pan {
+			from: (self.x, self.y),
+			to: Camera::goal_for(target, bounds, self.screen),
+			duration: duration,
+			elapsed: units::Millis(0)
+		});
+	}
+
+	/// Responds true while the camera has yet to finish its pan.
+	pub fn is_panning(&self) -> bool {
+		self.pan.as_ref().map_or(false, |pan| pan.elapsed < pan.duration)
+	}
+
+	/// Goes back to trailing behind its target after a pan.
+	pub fn follow(&mut self) {
+		self.pan = None;
+	}
+
+	/// Shakes the view for `duration`, e.g: for an earthquake.
+	pub fn shake(&mut self, duration: units::Millis) {
+		self.shake_time = cmp::max(self.shake_time, duration);
+	}
+
+	/// The point of the world at the middle of the view.
+	pub fn center(&self) -> (units::Game, units::Game) {
+		let (screen_w, screen_h) = self.screen;
+		(self.x + (screen_w / units::Game(2.0)), self.y + (screen_h / units::Game(2.0)))
+	}
+
+	/// The width & height of the view.
+	pub fn screen(&self) -> (units::Game, units::Game) { self.screen }
+
+	/// The distance the world should be shifted (up & to the left)
+	/// when it is drawn to the screen.
+	///
+	/// `alpha` blends between the camera's previous & current positions.
+	/// Any shaking is added on top.
+	pub fn offset(&self, alpha: f64) -> (units::Game, units::Game) {
+		let (jitter_x, jitter_y) = self.jitter;
+		(self.prev_x + ((self.x - self.prev_x) * units::Game(alpha)) + jitter_x,
+		 self.prev_y + ((self.y - self.prev_y) * units::Game(alpha)) + jitter_y)
+	}
+
+	/// Computes the top-left corner which would center `target` on a
+	/// `screen` sized view, without revealing anything outside of `bounds`.
+	fn goal_for(
+		target: (units::Game, units::Game), 
+		bounds: (units::Game, units::Game),
+		screen: (units::Game, units::Game)
+	) -> (units::Game, units::Game) {
+		let (target_x, target_y) = target;
+		let (bounds_w, bounds_h) = bounds;
+		let (screen_w, screen_h) = screen;
+
+		(Camera::clamp(target_x - (screen_w / units::Game(2.0)), bounds_w - screen_w),
+		 Camera::clamp(target_y - (screen_h / units::Game(2.0)), bounds_h - screen_h))
+	}
+
+	/// Restricts `position` to `[0, max]`; maps smaller than the screen are
+	/// pinned to their top-left corner.
+	fn clamp(position: units::Game, max: units::Game) -> units::Game {
+		cmp::max(units::Game(0.0), cmp::min(position, max))
+	}
+}
@@ -1,4 +1,9 @@
+use std::f64;
+
+use collections::hashmap::HashMap;
+
 use game::units;
+use game::units::{AsGame};
 
 pub struct Info {
 	collided: bool,
@@ -6,6 +11,31 @@ pub struct Info {
 	col: units::Tile 
 }
 
+/// A side of an entity's collision box.
+pub enum Side {
+	Left,
+	Right,
+	Top,
+	Bottom
+}
+
+/// Of every tile in `collisions`, the one closest to the `side` of the
+/// box which overlapped them; e.g: the lowest tile for the top side.
+pub fn nearest(collisions: &[Info], side: Side) -> Info {
+	let mut nearest = Info { collided: false, row: units::Tile(0), col: units::Tile(0) };
+	for info in collisions.iter() {
+		let closer = !nearest.collided || match side {
+			Left 	=> info.col > nearest.col,
+			Right 	=> info.col < nearest.col,
+			Top 	=> info.row > nearest.row,
+			Bottom 	=> info.row < nearest.row
+		};
+		if closer { nearest = *info; }
+	}
+
+	nearest
+}
+
 pub struct Rectangle {
 	x: units::Game, 
 	y: units::Game,
@@ -28,4 +58,111 @@ impl Rectangle {
 
 	pub fn width(&self) 	-> units::Game { self.width }
 	pub fn height(&self) 	-> units::Game { self.height }
+
+	/// Responds true if the two rectangles overlap, including touching edges.
+	pub fn collides_with(&self, other: &Rectangle) -> bool {
+		self.right() 	>= other.left() &&
+		self.left() 	<= other.right() &&
+		self.top() 		<= other.bottom() &&
+		self.bottom() 	>= other.top()
+	}
+}
+
+/// The fraction (from `0` to `1`) of the movement `delta` which `moving`
+/// can make before it first touches `obstacle`, or `None` if it never does.
+///
+/// Unlike testing the rectangle at its destination, this can not step
+/// over a thin obstacle when `delta` is large.
+pub fn sweep(moving: &Rectangle, delta: (units::Game, units::Game), obstacle: &Rectangle) -> Option<f64> {
+	let (dx, dy) = delta;
+	let (entry_x, exit_x) = sweep_axis((moving.left(), moving.right()), dx, (obstacle.left(), obstacle.right()));
+	let (entry_y, exit_y) = sweep_axis((moving.top(), moving.bottom()), dy, (obstacle.top(), obstacle.bottom()));
+
+	let (entry, exit) = (entry_x.max(entry_y), exit_x.min(exit_y));
+	if entry < exit && entry <= 1.0 && exit > 0.0 {
+		Some(entry.max(0.0))
+	} else {
+		None
+	}
+}
+
+// the times at which a span moving by `delta` starts & stops overlapping
+// `obstacle`, as fractions of `delta`. (a span which is not moving
+// overlaps either forever, or never.)
+fn sweep_axis(
+	span: (units::Game, units::Game),
+	delta: units::Game,
+	obstacle: (units::Game, units::Game)
+) -> (f64, f64) {
+	let (units::Game(low), units::Game(high)) = span;
+	let (units::Game(obstacle_low), units::Game(obstacle_high)) = obstacle;
+	let units::Game(d) = delta;
+
+	if d > 0.0 {
+		((obstacle_low - high) / d, (obstacle_high - low) / d)
+	} else if d < 0.0 {
+		((obstacle_high - low) / d, (obstacle_low - high) / d)
+	} else if low < obstacle_high && high > obstacle_low {
+		(f64::NEG_INFINITY, f64::INFINITY)
+	} else {
+		(f64::INFINITY, f64::NEG_INFINITY)
+	}
+}
+
+/// A broad-phase for collisions between entities.
+///
+/// Each frame entities insert their hitboxes under some id; a rectangle
+/// then need only be tested against the ids which share a tile w/ it,
+/// rather than against every entity in the world.
+pub struct SpatialGrid {
+	priv cells: HashMap<(int, int), ~[uint]>
+}
+
+impl SpatialGrid {
+	pub fn new() -> SpatialGrid {
+		SpatialGrid { cells: HashMap::new() }
+	}
+
+	/// Forgets every hitbox inserted so far, e.g: before entities move.
+	pub fn clear(&mut self) {
+		self.cells.clear();
+	}
+
+	/// Records that the entity `id` occupies `rectangle`.
+	pub fn insert(&mut self, id: uint, rectangle: &Rectangle) {
+		for cell in SpatialGrid::cells_of(rectangle).move_iter() {
+			self.cells.find_or_insert_with(cell, |_| ~[]).push(id);
+		}
+	}
+
+	/// The ids of every entity which may overlap `rectangle`, in ascending
+	/// order; each must still be tested w/ `Rectangle::collides_with`.
+	pub fn query(&self, rectangle: &Rectangle) -> ~[uint] {
+		let mut ids = ~[];
+		for cell in SpatialGrid::cells_of(rectangle).iter() {
+			match self.cells.find(cell) {
+				Some(occupants) => ids.push_all(occupants.as_slice()),
+				None => {}
+			}
+		}
+
+		ids.sort();
+		ids.dedup();
+		ids
+	}
+
+	// the `(col, row)` of each tile touched by `rectangle`, which may lie
+	// partly off the map (i.e: at negative coordinates.)
+	fn cells_of(rectangle: &Rectangle) -> ~[(int, int)] {
+		let units::Game(tile_size) = units::Tile(1).to_game();
+		let cell = |units::Game(position): units::Game| (position / tile_size).floor() as int;
+
+		let mut cells = ~[];
+		for row in range(cell(rectangle.top()), cell(rectangle.bottom()) + 1) {
+			for col in range(cell(rectangle.left()), cell(rectangle.right()) + 1) {
+				cells.push((col, row));
+			}
+		}
+		cells
+	}
 }
@@ -0,0 +1,543 @@
+use std::io;
+use std::io::File;
+use std::io::fs;
+
+use collections::hashmap::HashMap;
+
+use sdl2::controller;
+use sdl2::keycode;
+
+use game;
+use game::audio;
+use game::i18n;
+use game::input;
+use game::log;
+use game::units;
+
+/// The largest window scale accepted from the settings file.
+pub static MAX_SCALE: uint = 8;
+// the fewest tiles across (or down) the screen may be
+static MIN_RESOLUTION: uint = 8;
+
+// names used for keys in the bindings file
+static KEY_NAMES: [(&'static str, keycode::KeyCode), ..49] = [
+	("left", keycode::LeftKey), ("right", keycode::RightKey),
+	("up", keycode::UpKey), ("down", keycode::DownKey),
+	("space", keycode::SpaceKey), ("return", keycode::ReturnKey),
+	("escape", keycode::EscapeKey), ("tab", keycode::TabKey),
+	("lshift", keycode::LShiftKey), ("rshift", keycode::RShiftKey),
+	("lctrl", keycode::LCtrlKey), ("rctrl", keycode::RCtrlKey),
+	("lalt", keycode::LAltKey), ("ralt", keycode::RAltKey),
+	("backspace", keycode::BackspaceKey), ("f1", keycode::F1Key),
+	("f2", keycode::F2Key), ("f3", keycode::F3Key), ("f4", keycode::F4Key),
+	("f5", keycode::F5Key), ("f9", keycode::F9Key), ("f11", keycode::F11Key),
+	("f12", keycode::F12Key),
+	("a", keycode::AKey), ("b", keycode::BKey), ("c", keycode::CKey),
+	("d", keycode::DKey), ("e", keycode::EKey), ("f", keycode::FKey),
+	("g", keycode::GKey), ("h", keycode::HKey), ("i", keycode::IKey),
+	("j", keycode::JKey), ("k", keycode::KKey), ("l", keycode::LKey),
+	("m", keycode::MKey), ("n", keycode::NKey), ("o", keycode::OKey),
+	("p", keycode::PKey), ("q", keycode::QKey), ("r", keycode::RKey),
+	("s", keycode::SKey), ("t", keycode::TKey), ("u", keycode::UKey),
+	("v", keycode::VKey), ("w", keycode::WKey), ("x", keycode::XKey),
+	("y", keycode::YKey), ("z", keycode::ZKey)
+];
+
+// names used for controller buttons in the bindings file
+static BUTTON_NAMES: [(&'static str, controller::ControllerButton), ..15] = [
+	("a", controller::ButtonA), ("b", controller::ButtonB),
+	("x", controller::ButtonX), ("y", controller::ButtonY),
+	("back", controller::ButtonBack), ("guide", controller::ButtonGuide),
+	("start", controller::ButtonStart),
+	("leftstick", controller::ButtonLeftStick), ("rightstick", controller::ButtonRightStick),
+	("leftshoulder", controller::ButtonLeftShoulder), ("rightshoulder", controller::ButtonRightShoulder),
+	("dpad_up", controller::ButtonDPadUp), ("dpad_down", controller::ButtonDPadDown),
+	("dpad_left", controller::ButtonDPadLeft), ("dpad_right", controller::ButtonDPadRight)
+];
+
+/// Maps physical keys & controller buttons to the actions they trigger.
+///
+/// Keys & buttons are stored by their SDL codes, as `u32`s. The keys
+/// of a second player sharing the keyboard are kept apart, in `second_keys`.
+#[deriving(Clone)]
+pub struct Bindings {
+	keys: HashMap<u32, input::Action>,
+	second_keys: HashMap<u32, input::Action>,
+	buttons: HashMap<u32, input::Action>
+}
+
+impl Bindings {
+	/// The bindings used when no bindings file exists.
+	pub fn default() -> Bindings {
+		let mut bindings = Bindings { keys: HashMap::new(), second_keys: HashMap::new(), buttons: HashMap::new() };
+
+		bindings.keys.insert(keycode::LeftKey as u32, 		input::MoveLeft);
+		bindings.keys.insert(keycode::RightKey as u32, 		input::MoveRight);
+		bindings.keys.insert(keycode::UpKey as u32, 		input::LookUp);
+		bindings.keys.insert(keycode::DownKey as u32, 		input::LookDown);
+		bindings.keys.insert(keycode::ZKey as u32, 			input::Jump);
+		bindings.keys.insert(keycode::XKey as u32, 			input::Fire);
+		bindings.keys.insert(keycode::SKey as u32, 			input::NextWeapon);
+		bindings.keys.insert(keycode::AKey as u32, 			input::PrevWeapon);
+		bindings.keys.insert(keycode::EscapeKey as u32, 	input::Pause);
+		bindings.keys.insert(keycode::QKey as u32, 			input::Quit);
+		bindings.keys.insert(keycode::F11Key as u32, 		input::ToggleFullscreen);
+		bindings.keys.insert(keycode::F12Key as u32, 		input::Screenshot);
+		bindings.keys.insert(keycode::F3Key as u32, 		input::ToggleDebug);
+		bindings.keys.insert(keycode::F2Key as u32, 		input::ToggleHitboxes);
+		bindings.keys.insert(keycode::F4Key as u32, 		input::ToggleInputs);
+		bindings.keys.insert(keycode::ReturnKey as u32, 	input::Skip);
+		bindings.keys.insert(keycode::MKey as u32, 			input::ToggleMap);
+		bindings.keys.insert(keycode::F5Key as u32, 		input::SaveState);
+		bindings.keys.insert(keycode::F9Key as u32, 		input::LoadState);
+
+		bindings.second_keys.insert(keycode::JKey as u32, 	input::MoveLeft);
+		bindings.second_keys.insert(keycode::LKey as u32, 	input::MoveRight);
+		bindings.second_keys.insert(keycode::IKey as u32, 	input::LookUp);
+		bindings.second_keys.insert(keycode::KKey as u32, 	input::LookDown);
+		bindings.second_keys.insert(keycode::NKey as u32, 	input::Jump);
+		bindings.second_keys.insert(keycode::BKey as u32, 	input::Fire);
+		bindings.second_keys.insert(keycode::OKey as u32, 	input::NextWeapon);
+		bindings.second_keys.insert(keycode::UKey as u32, 	input::PrevWeapon);
+
+		bindings.buttons.insert(controller::ButtonDPadLeft as u32, 	input::MoveLeft);
+		bindings.buttons.insert(controller::ButtonDPadRight as u32, input::MoveRight);
+		bindings.buttons.insert(controller::ButtonDPadUp as u32, 	input::LookUp);
+		bindings.buttons.insert(controller::ButtonDPadDown as u32, 	input::LookDown);
+		bindings.buttons.insert(controller::ButtonA as u32, 		input::Jump);
+		bindings.buttons.insert(controller::ButtonX as u32, 		input::Fire);
+		bindings.buttons.insert(controller::ButtonRightShoulder as u32, input::NextWeapon);
+		bindings.buttons.insert(controller::ButtonLeftShoulder as u32, 	input::PrevWeapon);
+		bindings.buttons.insert(controller::ButtonStart as u32, 	input::Pause);
+		bindings.buttons.insert(controller::ButtonBack as u32, 		input::Quit);
+		bindings.buttons.insert(controller::ButtonY as u32, 		input::Skip);
+		bindings.buttons.insert(controller::ButtonB as u32, 		input::ToggleMap);
+
+		bindings
+	}
+
+	/// Reads the bindings file at `path`.
+	///
+	/// If no such file exists the default bindings are written to `path`
+	/// (so that they may be edited) and then returned. Any action the file
+	/// leaves unbound keeps its default key & button, where they are free.
+	///
+	/// Each line of the file is either `key <name> <action>`,
+	/// `key2 <name> <action>` (for the second player) or
+	/// `button <name> <action>`; lines starting w/ `#` are ignored.
+	pub fn load(path: &str) -> Bindings {
+		let file_path = Path::new(path);
+		if !file_path.exists() {
+			let defaults = Bindings::default();
+			defaults.save(path);
+			return defaults;
+		}
+
+		let contents = match File::open(&file_path).read_to_str() {
+			Ok(contents) => contents,
+			Err(msg) => {fail!("bindings could not be loaded: {}", msg)}
+		};
+
+		let mut bindings = Bindings { keys: HashMap::new(), second_keys: HashMap::new(), buttons: HashMap::new() };
+		for raw_line in contents.lines() {
+			let line = raw_line.trim();
+			if line.len() == 0 || line.starts_with("#") { continue; }
+
+			let words: ~[&str] = line.words().collect();
+			match words.as_slice() {
+				["key", name, action] => {
+					match (key_from_name(name), input::Action::from_name(action)) {
+						(Some(key), Some(action)) => { bindings.keys.insert(key as u32, action); }
+						_ => { log::warn("config", format!("{}: ignoring unknown binding `{}`", path, line)); }
+					}
+				}
+				["key2", name, action] => {
+					match (key_from_name(name), input::Action::from_name(action)) {
+						(Some(key), Some(action)) => { bindings.second_keys.insert(key as u32, action); }
+						_ => { log::warn("config", format!("{}: ignoring unknown binding `{}`", path, line)); }
+					}
+				}
+				["button", name, action] => {
+					match (button_from_name(name), input::Action::from_name(action)) {
+						(Some(button), Some(action)) => { bindings.buttons.insert(button as u32, action); }
+						_ => { log::warn("config", format!("{}: ignoring unknown binding `{}`", path, line)); }
+					}
+				}
+				_ => { log::warn("config", format!("{}: ignoring malformed line `{}`", path, line)); }
+			}
+		}
+
+		// files written before an action existed (or whose key then had no
+		// name, & so was dropped) leave it unbound: it keeps its default key,
+		// or button, if that is free
+		let defaults = Bindings::default();
+		for (key, action) in defaults.keys.iter() {
+			if !bindings.keys.values().any(|bound| bound == action)
+				&& !bindings.keys.contains_key(key) && !bindings.second_keys.contains_key(key) {
+				log::info("config", format!("{}: {} is unbound, so keeps its default key", path, action.name()));
+				bindings.keys.insert(*key, *action);
+			}
+		}
+		for (button, action) in defaults.buttons.iter() {
+			if !bindings.buttons.values().any(|bound| bound == action) && !bindings.buttons.contains_key(button) {
+				bindings.buttons.insert(*button, *action);
+			}
+		}
+
+		// files written before the second player had keys of their own bind none
+		if bindings.second_keys.is_empty() {
+			for (key, action) in defaults.second_keys.move_iter() {
+				if !bindings.keys.contains_key(&key) { bindings.second_keys.insert(key, action); }
+			}
+		}
+
+		bindings
+	}
+
+	/// Writes these bindings to `path`, creating its directory if needed.
+	pub fn save(&self, path: &str) {
+		let file_path = Path::new(path);
+		match fs::mkdir_recursive(&file_path.dir_path(), io::UserDir) {
+			Ok(_) => {}
+			Err(msg) => { log::warn("config", format!("could not create config directory: {}", msg)); return; }
+		}
+
+		let mut contents = ~"# key <name> <action>\n# key2 <name> <action>\n# button <name> <action>\n";
+		for &(name, key) in KEY_NAMES.iter() {
+			match self.keys.find(&(key as u32)) {
+				Some(action) => { contents.push_str(format!("key {} {}\n", name, action.name())); }
+				None => {}
+			}
+		}
+		for &(name, key) in KEY_NAMES.iter() {
+			match self.second_keys.find(&(key as u32)) {
+				Some(action) => { contents.push_str(format!("key2 {} {}\n", name, action.name())); }
+				None => {}
+			}
+		}
+		for &(name, button) in BUTTON_NAMES.iter() {
+			match self.buttons.find(&(button as u32)) {
+				Some(action) => { contents.push_str(format!("button {} {}\n", name, action.name())); }
+				None => {}
+			}
+		}
+
+		match File::create(&file_path).write_str(contents) {
+			Ok(_) => {}
+			Err(msg) => { log::warn("config", format!("bindings could not be saved: {}", msg)); }
+		}
+	}
+
+	/// Binds the key w/ SDL code `key` to `action`, in place of every key
+	/// bound to it before (& of whatever `key` was bound to itself.)
+	///
+	/// Responds false, binding nothing, if `key` has no name to be saved
+	/// under in the bindings file.
+	pub fn bind_key(&mut self, key: u32, action: input::Action) -> bool {
+		if !KEY_NAMES.iter().any(|&(_, named)| named as u32 == key) { return false; }
+
+		let stale: ~[u32] = self.keys.iter()
+			.filter(|&(_, bound)| *bound == action)
+			.map(|(bound_key, _)| *bound_key)
+			.collect();
+		for stale_key in stale.iter() { self.keys.remove(stale_key); }
+		self.keys.insert(key, action);
+		// first come first served: the second player may not keep the key
+		self.second_keys.remove(&key);
+		true
+	}
+
+	/// The names of the keys bound to `action`, as written in the bindings file.
+	pub fn key_names(&self, action: input::Action) -> ~[&'static str] {
+		KEY_NAMES.iter()
+			.filter(|&&(_, key)| self.keys.find(&(key as u32)) == Some(&action))
+			.map(|&(name, _)| name)
+			.collect()
+	}
+
+	/// The action bound to the key w/ SDL code `key`, if any.
+	pub fn key_action(&self, key: u32) -> Option<input::Action> {
+		self.keys.find_copy(&key)
+	}
+
+	/// The action bound to the key w/ SDL code `key` for the second player, if any.
+	pub fn second_key_action(&self, key: u32) -> Option<input::Action> {
+		self.second_keys.find_copy(&key)
+	}
+
+	/// The action bound to the controller button w/ SDL code `button`, if any.
+	pub fn button_action(&self, button: u32) -> Option<input::Action> {
+		self.buttons.find_copy(&button)
+	}
+}
+
+/// How the game keeps from running faster than its target framerate.
+#[deriving(Eq,Clone)]
+pub enum FrameLimit {
+	/// Sleeps away whatever is left of each frame.
+	Sleep,
+	/// Waits on the display's vertical sync as each frame is presented.
+	VSync,
+	/// Draws frames as fast as possible, e.g: for benchmarking.
+	Uncapped
+}
+
+impl FrameLimit {
+	/// The name of this limit as it is written in the settings file.
+	pub fn name(&self) -> &'static str {
+		match *self {
+			Sleep 		=> "sleep",
+			VSync 		=> "vsync",
+			Uncapped 	=> "uncapped"
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<FrameLimit> {
+		[Sleep, VSync, Uncapped].iter().find(|limit| limit.name() == name).map(|limit| *limit)
+	}
+}
+
+/// How the game's window is presented.
+#[deriving(Clone)]
+pub struct Settings {
+	/// How many times larger than the game itself the window is drawn,
+	/// when not fullscreen.
+	scale: uint,
+	fullscreen: bool,
+	/// The size of the screen the game itself is drawn at, before scaling.
+	resolution: units::Resolution,
+	frame_limit: FrameLimit,
+	log: log::Options,
+	/// Whether images & maps are reloaded as their files change on disk,
+	/// e.g: while editing them. (Only worth the checking during development.)
+	hot_reload: bool,
+	/// The language all of the game's text is shown in, named after its
+	/// string table, e.g: `en` for `assets/lang/en.lang`.
+	language: ~str,
+	/// The TrueType font (& its point size) text is drawn in, in place of
+	/// the bitmap font, e.g: for languages written in scripts it lacks.
+	font: Option<(~str, uint)>,
+	/// The volume of everything, of the music & of sound effects, each `[0, 100]`.
+	master_volume: uint,
+	music_volume: uint,
+	sfx_volume: uint,
+	/// The seed of the game's randomness, e.g: to reproduce a bug; w/o it
+	/// each run is seeded differently.
+	seed: Option<u64>,
+	/// Whether a speedrun timer is shown, & whether it stops while maps load.
+	speedrun_timer: bool,
+	speedrun_pause_loads: bool,
+	/// Whether the world may be saved & restored in memory (w/ F5 & F9),
+	/// e.g: to practise a difficult room over & over.
+	practice: bool,
+	/// Whether the ghost of the best run through each map is shown, to race against.
+	ghosts: bool,
+	/// The port games played are hosted on (see `net::Server`), so that a
+	/// player on another machine may join in; w/o it games are not hosted.
+	host: Option<u16>
+}
+
+impl Settings {
+	/// The settings used when no settings file exists.
+	pub fn default() -> Settings {
+		Settings {
+			scale: 2,
+			fullscreen: false,
+			resolution: game::DEFAULT_RESOLUTION,
+			frame_limit: Sleep,
+			log: log::Options::default(),
+			hot_reload: false,
+			language: i18n::DEFAULT_LANGUAGE.to_owned(),
+			font: None,
+			master_volume: audio::MAX_VOLUME,
+			music_volume: audio::MAX_VOLUME,
+			sfx_volume: audio::MAX_VOLUME,
+			seed: None,
+			speedrun_timer: false,
+			speedrun_pause_loads: false,
+			practice: false,
+			ghosts: false,
+			host: None
+		}
+	}
+
+	/// Reads the settings file at `path`.
+	///
+	/// If no such file exists the default settings are written to `path`
+	/// (so that they may be edited) and then returned.
+	///
+	/// The file holds the lines `scale <1-8>`, `fullscreen <on|off>`,
+	/// `resolution <columns> <rows>` (the screen's size in tiles),
+	/// `frame_limit <sleep|vsync|uncapped>`, `log_level <level>`,
+	/// `log_module <module> <level>` (repeated for each module logged at
+	/// a level of its own), `log_file <path>`, `hot_reload <on|off>`,
+	/// `language <name>`, `font <path> <point size>`, `master_volume <0-100>`,
+	/// `music_volume <0-100>`, `sfx_volume <0-100>`, `seed <number>`,
+	/// `speedrun_timer <on|off>`, `speedrun_pause_loads <on|off>`, `practice <on|off>`,
+	/// `ghosts <on|off>` & `host <port>`; any may be left out, & lines
+	/// starting w/ `#` are ignored.
+	pub fn load(path: &str) -> Settings {
+		let file_path = Path::new(path);
+		if !file_path.exists() {
+			let defaults = Settings::default();
+			defaults.save(path);
+			return defaults;
+		}
+
+		let contents = match File::open(&file_path).read_to_str() {
+			Ok(contents) => contents,
+			Err(msg) => {fail!("settings could not be loaded: {}", msg)}
+		};
+
+		let mut settings = Settings::default();
+		for raw_line in contents.lines() {
+			let line = raw_line.trim();
+			if line.len() == 0 || line.starts_with("#") { continue; }
+
+			let words: ~[&str] = line.words().collect();
+			match words.as_slice() {
+				["scale", scale] => {
+					match from_str::<uint>(scale) {
+						Some(scale) if scale >= 1 && scale <= MAX_SCALE => { settings.scale = scale; }
+						_ => { log::warn("config", format!("{}: ignoring invalid scale `{}`", path, scale)); }
+					}
+				}
+				["resolution", cols, rows] => {
+					match (from_str::<uint>(cols), from_str::<uint>(rows)) {
+						(Some(cols), Some(rows)) if cols >= MIN_RESOLUTION && rows >= MIN_RESOLUTION => {
+							settings.resolution = units::Resolution { width: units::Tile(cols), height: units::Tile(rows) };
+						}
+						_ => { log::warn("config", format!("{}: ignoring invalid resolution `{} {}`", path, cols, rows)); }
+					}
+				}
+				["frame_limit", name] => {
+					match FrameLimit::from_name(name) {
+						Some(limit) => { settings.frame_limit = limit; }
+						None => { log::warn("config", format!("{}: ignoring unknown frame limit `{}`", path, name)); }
+					}
+				}
+				["log_level", name] => {
+					match log::Level::from_name(name) {
+						Some(level) => { settings.log.level = level; }
+						None => { log::warn("config", format!("{}: ignoring unknown log level `{}`", path, name)); }
+					}
+				}
+				["log_module", module, name] => {
+					match log::Level::from_name(name) {
+						Some(level) => { settings.log.modules.push((module.to_owned(), level)); }
+						None => { log::warn("config", format!("{}: ignoring unknown log level `{}`", path, name)); }
+					}
+				}
+				["log_file", log_path] 	=> { settings.log.file = Some(log_path.to_owned()); }
+				["fullscreen", "on"] 	=> { settings.fullscreen = true; }
+				["fullscreen", "off"] 	=> { settings.fullscreen = false; }
+				["hot_reload", "on"] 	=> { settings.hot_reload = true; }
+				["hot_reload", "off"] 	=> { settings.hot_reload = false; }
+				["speedrun_timer", "on"] 	=> { settings.speedrun_timer = true; }
+				["speedrun_timer", "off"] 	=> { settings.speedrun_timer = false; }
+				["speedrun_pause_loads", "on"] 	=> { settings.speedrun_pause_loads = true; }
+				["speedrun_pause_loads", "off"] => { settings.speedrun_pause_loads = false; }
+				["practice", "on"] 		=> { settings.practice = true; }
+				["practice", "off"] 	=> { settings.practice = false; }
+				["ghosts", "on"] 		=> { settings.ghosts = true; }
+				["ghosts", "off"] 		=> { settings.ghosts = false; }
+				["language", name] 		=> { settings.language = name.to_owned(); }
+				[bus, volume] if bus.ends_with("_volume") => {
+					let level = match from_str::<uint>(volume) {
+						Some(level) if level <= audio::MAX_VOLUME => level,
+						_ => { log::warn("config", format!("{}: ignoring invalid volume `{}`", path, volume)); continue; }
+					};
+					match bus {
+						"master_volume" => { settings.master_volume = level; }
+						"music_volume" 	=> { settings.music_volume = level; }
+						"sfx_volume" 	=> { settings.sfx_volume = level; }
+						_ => { log::warn("config", format!("{}: ignoring malformed line `{}`", path, line)); }
+					}
+				}
+				["seed", seed] => {
+					match from_str::<u64>(seed) {
+						Some(seed) => { settings.seed = Some(seed); }
+						None => { log::warn("config", format!("{}: ignoring invalid seed `{}`", path, seed)); }
+					}
+				}
+				["host", port] => {
+					match from_str::<u16>(port) {
+						Some(port) if port > 0 => { settings.host = Some(port); }
+						_ => { log::warn("config", format!("{}: ignoring invalid port `{}`", path, port)); }
+					}
+				}
+				["font", font_path, size] => {
+					match from_str::<uint>(size) {
+						Some(size) if size > 0 => { settings.font = Some((font_path.to_owned(), size)); }
+						_ => { log::warn("config", format!("{}: ignoring invalid font size `{}`", path, size)); }
+					}
+				}
+				_ => { log::warn("config", format!("{}: ignoring malformed line `{}`", path, line)); }
+			}
+		}
+
+		settings
+	}
+
+	/// Sets the volume of each of `audio`'s buses to these settings'.
+	pub fn apply_volumes(&self, audio: &mut audio::Audio) {
+		audio.set_master_volume(self.master_volume);
+		audio.set_music_volume(self.music_volume);
+		audio.set_sfx_volume(self.sfx_volume);
+	}
+
+	/// Writes these settings to `path`, creating its directory if needed.
+	pub fn save(&self, path: &str) {
+		let file_path = Path::new(path);
+		match fs::mkdir_recursive(&file_path.dir_path(), io::UserDir) {
+			Ok(_) => {}
+			Err(msg) => { log::warn("config", format!("could not create config directory: {}", msg)); return; }
+		}
+
+		let units::Resolution { width: units::Tile(cols), height: units::Tile(rows) } = self.resolution;
+		let mut contents = format!(
+			"# scale <1-{}>\n# fullscreen <on|off>\n# resolution <columns> <rows>\n# frame_limit <sleep|vsync|uncapped>\n\
+			 # log_level <debug|info|warn|error>\n# log_module <module> <level>\n# log_file <path>\n# hot_reload <on|off>\n# language <name>\n# font <path> <point size>\n\
+			 # master_volume <0-{}>\n# music_volume <0-{}>\n# sfx_volume <0-{}>\n# seed <number>\n# speedrun_timer <on|off>\n# speedrun_pause_loads <on|off>\n# practice <on|off>\n# ghosts <on|off>\n# host <port>\n\
+			 scale {}\nfullscreen {}\nresolution {} {}\nframe_limit {}\nlog_level {}\nhot_reload {}\nlanguage {}\n\
+			 master_volume {}\nmusic_volume {}\nsfx_volume {}\nspeedrun_timer {}\nspeedrun_pause_loads {}\npractice {}\nghosts {}\n",
+			MAX_SCALE, audio::MAX_VOLUME, audio::MAX_VOLUME, audio::MAX_VOLUME, self.scale, if self.fullscreen { "on" } else { "off" }, cols, rows,
+			self.frame_limit.name(), self.log.level.name(), if self.hot_reload { "on" } else { "off" },
+			self.language, self.master_volume, self.music_volume, self.sfx_volume,
+			if self.speedrun_timer { "on" } else { "off" }, if self.speedrun_pause_loads { "on" } else { "off" },
+			if self.practice { "on" } else { "off" }, if self.ghosts { "on" } else { "off" }
+		);
+		for &(ref module, level) in self.log.modules.iter() {
+			contents.push_str(format!("log_module {} {}\n", *module, level.name()));
+		}
+		match self.log.file {
+			Some(ref log_path) => contents.push_str(format!("log_file {}\n", *log_path)),
+			None => {}
+		}
+		match self.font {
+			Some((ref font_path, size)) => contents.push_str(format!("font {} {}\n", *font_path, size)),
+			None => {}
+		}
+		match self.seed {
+			Some(seed) => contents.push_str(format!("seed {}\n", seed)),
+			None => {}
+		}
+		match self.host {
+			Some(port) => contents.push_str(format!("host {}\n", port)),
+			None => {}
+		}
+
+		match File::create(&file_path).write_str(contents) {
+			Ok(_) => {}
+			Err(msg) => { log::warn("config", format!("settings could not be saved: {}", msg)); }
+		}
+	}
+}
+
+fn key_from_name(name: &str) -> Option<keycode::KeyCode> {
+	KEY_NAMES.iter().find(|&&(key_name, _)| key_name == name).map(|&(_, key)| key)
+}
+
+fn button_from_name(name: &str) -> Option<controller::ControllerButton> {
+	BUTTON_NAMES.iter().find(|&&(button_name, _)| button_name == name).map(|&(_, button)| button)
+}
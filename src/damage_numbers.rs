@@ -0,0 +1,123 @@
+use game;
+use game::graphics;
+
+use game::error::GameResult;
+use game::units;
+use game::units::{AsGame};
+
+// how long a number floats, fading out over the end of it
+static LIFETIME: units::Millis 	= units::Millis(800);
+static FADE_TIME: units::Millis = units::Millis(300);
+static RISE_SPEED: units::Velocity = units::Velocity(0.04);
+
+// hits landing this close together, & this soon after one another, are
+// counted up in a single number
+static AGGREGATE_TIME: units::Millis 	= units::Millis(400);
+static AGGREGATE_RANGE: units::Tile 	= units::Tile(1);
+
+// numbers over enemies are white, those over the player red
+static HIT_TINT: (u8, u8, u8) 	= (255, 255, 255);
+static HURT_TINT: (u8, u8, u8) 	= (255, 64, 64);
+
+// a single number, floating up from where it was struck
+struct Number {
+	x: units::Game,
+	y: units::Game,
+	rise: units::Game,
+	amount: units::HitPoint,
+	tint: (u8, u8, u8),
+	// since the number appeared, & since it was last added to
+	age: units::Millis,
+	since_hit: units::Millis
+}
+
+/// The damage dealt in combat, shown as numbers which drift up from
+/// whatever was struck & fade away.
+///
+/// A flurry of hits on the same spot (e.g: a spread shot landing all at
+/// once) is totted up in one number rather than piling up a dozen.
+pub struct DamageNumbers {
+	priv font: graphics::Font,
+	priv numbers: ~[Number]
+}
+
+impl DamageNumbers {
+	pub fn new(display: &mut graphics::Graphics) -> GameResult<DamageNumbers> {
+		Ok(DamageNumbers {
+			font: try!(graphics::Font::new(display, game::FONT_PATH.to_owned(), game::FONT_SIZE)),
+			numbers: ~[]
+		})
+	}
+
+	/// Shows an enemy being struck for `damage` at `position`.
+	pub fn hit(&mut self, position: (units::Game, units::Game), damage: units::HitPoint) {
+		self.add(position, damage, HIT_TINT);
+	}
+
+	/// Shows the player losing `damage` hit points at `position`.
+	pub fn hurt(&mut self, position: (units::Game, units::Game), damage: units::HitPoint) {
+		self.add(position, damage, HURT_TINT);
+	}
+
+	/// Removes every number, e.g: when the player leaves the map.
+	pub fn clear(&mut self) {
+		self.numbers = ~[];
+	}
+
+	pub fn update(&mut self, elapsed_time: units::Millis) {
+		for number in self.numbers.mut_iter() {
+			number.age = number.age + elapsed_time;
+			number.since_hit = number.since_hit + elapsed_time;
+			number.rise = number.rise + (RISE_SPEED * elapsed_time);
+		}
+		self.numbers.retain(|number| number.age < LIFETIME);
+	}
+
+	/// Queues every number, centered over where it was struck.
+	pub fn draw(&self, queue: &mut graphics::RenderQueue, offset: (units::Game, units::Game)) {
+		let (offset_x, offset_y) = offset;
+		for number in self.numbers.iter() {
+			let text = format!("-{}", number.amount);
+			let x = number.x - (self.font.text_width(text.as_slice()) / units::Game(2.0)) - offset_x;
+			let y = number.y - number.rise - (self.font.line_height() / units::Game(2.0)) - offset_y;
+
+			let (units::Millis(age), units::Millis(lifetime), units::Millis(fade)) = (number.age, LIFETIME, FADE_TIME);
+			let alpha = if age > lifetime - fade {
+				(255 * (lifetime - age) / fade) as u8
+			} else {
+				255
+			};
+
+			let mut params = graphics::PLAIN;
+			params.tint = number.tint;
+			params.alpha = alpha;
+			self.font.queue_text(queue, graphics::Overlay, x, y, text.as_slice(), &params);
+		}
+	}
+
+	// adds to a recent number of the same tint near `position`, if there is one
+	fn add(&mut self, position: (units::Game, units::Game), amount: units::HitPoint, tint: (u8, u8, u8)) {
+		let (x, y) = position;
+		let units::Game(range) = AGGREGATE_RANGE.to_game();
+
+		for number in self.numbers.mut_iter() {
+			let (units::Game(dx), units::Game(dy)) = (number.x - x, number.y - y);
+			if number.tint == tint && number.since_hit < AGGREGATE_TIME
+			&& dx.abs() < range && dy.abs() < range {
+				number.amount += amount;
+				number.age = units::Millis(0);
+				number.since_hit = units::Millis(0);
+				return;
+			}
+		}
+
+		self.numbers.push(Number {
+			x: x, y: y,
+			rise: units::Game(0.0),
+			amount: amount,
+			tint: tint,
+			age: units::Millis(0),
+			since_hit: units::Millis(0)
+		});
+	}
+}
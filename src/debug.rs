@@ -0,0 +1,128 @@
+use std::cmp;
+
+use sdl2::pixels;
+use sdl2::rect;
+
+use game;
+use game::graphics;
+
+use game::error::GameResult;
+use game::units;
+use game::units::{AsPixel};
+
+// how many recent frames the frame-time graph shows
+static HISTORY: uint = 120;
+
+// the graph sits in the bottom-left corner, one pixel column per frame
+static GRAPH_HEIGHT: i32 = 48;
+static GRAPH_MARGIN: i32 = 8;
+// milliseconds of frame time per pixel of graph height
+static GRAPH_SCALE: f64 = 0.5;
+
+// text is drawn in the top-right corner, right-aligned
+static TEXT_MARGIN: units::Game = units::Game(8.0);
+
+/// Timings & counts shown over the game while debugging (toggled w/ F3.)
+///
+/// The game loop reports how long each frame spent updating & drawing;
+/// the topmost state supplies any further lines, e.g: the player's position.
+pub struct DebugOverlay {
+	priv font: graphics::Font,
+	priv visible: bool,
+
+	// the most recent frames' lengths in ms, oldest first
+	priv frame_times: ~[f64],
+	priv update_time: f64,
+	priv draw_time: f64,
+
+	// frames counted since `fps` was last refreshed (once a second)
+	priv frames: uint,
+	priv counted_for: f64,
+	priv fps: uint
+}
+
+impl DebugOverlay {
+	pub fn new(display: &mut graphics::Graphics) -> GameResult<DebugOverlay> {
+		Ok(DebugOverlay {
+			font: try!(graphics::Font::new(display, game::FONT_PATH.to_owned(), game::FONT_SIZE)),
+			visible: false,
+			frame_times: ~[],
+			update_time: 0.0,
+			draw_time: 0.0,
+			frames: 0,
+			counted_for: 0.0,
+			fps: 0
+		})
+	}
+
+	pub fn toggle(&mut self) { self.visible = !self.visible; }
+	pub fn is_visible(&self) -> bool { self.visible }
+
+	/// Records a finished frame: `frame_time` long in all, of which
+	/// `update_time` was spent updating & `draw_time` drawing (each in ms.)
+	pub fn record_frame(&mut self, frame_time: f64, update_time: f64, draw_time: f64) {
+		self.frame_times.push(frame_time);
+		if self.frame_times.len() > HISTORY { self.frame_times.shift(); }
+		self.update_time = update_time;
+		self.draw_time = draw_time;
+
+		self.frames += 1;
+		self.counted_for += frame_time;
+		if self.counted_for >= 1000.0 {
+			self.fps = self.frames;
+			self.frames = 0;
+			self.counted_for = 0.0;
+		}
+	}
+
+	/// Draws the overlay, followed by the `extra` lines of text.
+	pub fn draw(&self, display: &graphics::Graphics, extra: &[~str]) {
+		let mut lines = ~[
+			format!("fps {}", self.fps),
+			format!("update {:.2f}ms", self.update_time),
+			format!("draw {:.2f}ms", self.draw_time),
+			format!("culled {}", display.culled())
+		];
+		for line in extra.iter() { lines.push(line.clone()); }
+
+		let (screen_w, screen_h) = display.resolution().to_game();
+		let mut y = TEXT_MARGIN;
+		for line in lines.iter() {
+			let x = screen_w - TEXT_MARGIN - self.font.text_width(line.as_slice());
+			self.font.draw_text(display, x, y, line.as_slice());
+			y = y + self.font.line_height();
+		}
+
+		self.draw_graph(display, screen_h);
+	}
+
+	// plots each recent frame's length as a bar, w/ a line marking the
+	// time one frame should take.
+	fn draw_graph(&self, display: &graphics::Graphics, screen_h: units::Game) {
+		let budget = 1000.0 / game::TARGET_FRAMERATE as f64;
+		let units::Pixel(screen_bottom) = screen_h.to_pixel();
+		let bottom = screen_bottom - GRAPH_MARGIN;
+		let top = bottom - GRAPH_HEIGHT;
+
+		display.fill_rect(
+			&rect::Rect::new(GRAPH_MARGIN, top, HISTORY as i32, GRAPH_HEIGHT),
+			pixels::RGBA(0, 0, 0, 160)
+		);
+
+		for (i, &frame_time) in self.frame_times.iter().enumerate() {
+			let height = cmp::min((frame_time / GRAPH_SCALE) as i32, GRAPH_HEIGHT);
+			let color = if frame_time > budget {
+				pixels::RGBA(224, 64, 64, 255)
+			} else {
+				pixels::RGBA(64, 224, 64, 255)
+			};
+			display.fill_rect(&rect::Rect::new(GRAPH_MARGIN + i as i32, bottom - height, 1, height), color);
+		}
+
+		let budget_height = (budget / GRAPH_SCALE) as i32;
+		display.fill_rect(
+			&rect::Rect::new(GRAPH_MARGIN, bottom - budget_height, HISTORY as i32, 1),
+			pixels::RGBA(255, 255, 255, 192)
+		);
+	}
+}
@@ -4,6 +4,14 @@ use collections::hashmap::HashMap;
 
 use game::sprite;
 use game::graphics;
+use game::map;
+
+use game::collisions::Rectangle;
+use game::enemies;
+use game::enemies::Enemy;
+use game::entity;
+use game::entity::Entity;
+use game::error::GameResult;
 
 use game::units;
 use game::units::{AsGame};
@@ -22,12 +30,27 @@ static EAST_OFFSET: units::Tile = units::Tile(1);
 static SPRITE_FRAMES: units::Frame	= 3;
 static SPRITE_FPS: units::Fps 		= 15;
 
+static CONTACT_DAMAGE: units::HitPoint 	= 1;
+static EXPERIENCE: uint 				= 1;
+static HIT_POINTS: units::HitPoint 	= 1;
+
+static LOOT: enemies::LootTable = &[
+	(3, enemies::Energy),
+	(1, enemies::Heart(2)),
+	(1, enemies::Missiles(1)),
+	(1, enemies::Nothing)
+];
+
+#[deriving(Clone)]
 pub struct CaveBat {
 	x: units::Game, 
 	y: units::Game,
+	// where the bat was drawn before its last update
+	previous: (units::Game, units::Game),
 
 	flight_angle: units::Degrees,
 	facing: sprite::Facing,
+	hit_points: units::HitPoint,
 	sprites: HashMap<sprite::Facing, ~sprite::Updatable>,
 }
 
@@ -35,53 +58,54 @@ impl CaveBat {
 	pub fn new(
 		display: &mut graphics::Graphics, 
 		x: units::Game, y: units::Game
-	) -> CaveBat {
+	) -> GameResult<CaveBat> {
 		let sprite_map = HashMap::<sprite::Facing, ~sprite::Updatable>::new();
 
 		let mut new_bat = CaveBat { 
 			x: x, y: y, 
+			previous: (x, y),
 			facing: sprite::West,
 			flight_angle: units::Degrees(0.0), 
+			hit_points: HIT_POINTS,
 
 			sprites: sprite_map
 		};
 
 		for facing in sprite::FACINGS.iter() {
-			new_bat.load_sprite(display, *facing);
+			try!(new_bat.load_sprite(display, *facing));
 		}
 
-		new_bat
+		Ok(new_bat)
 	}
 
 	fn load_sprite(&mut self, 
 				   display: &mut graphics::Graphics, 
-				   facing: sprite::Facing) {
-		
-		self.sprites.find_or_insert_with(facing, 
-			|key| -> ~sprite::Updatable {
-				let asset_path = ~"assets/base/Npc/NpcCemet.bmp";
-				let sprite_x = X_OFFSET;
-				let sprite_y = match *key {
-					sprite::West => Y_OFFSET + WEST_OFFSET,
-					sprite::East => Y_OFFSET + EAST_OFFSET,
-				};
-
-				~sprite::AnimatedSprite::new(
-						display, asset_path, 
-						(sprite_x, sprite_y), 
-						(units::Tile(1), units::Tile(1)),
-						SPRITE_FRAMES, SPRITE_FPS
-					).unwrap() as ~sprite::Updatable
-			}
-		);
+				   facing: sprite::Facing) -> GameResult<()> {
+		if self.sprites.contains_key(&facing) { return Ok(()); }
+
+		let asset_path = ~"assets/base/Npc/NpcCemet.bmp";
+		let sprite_x = X_OFFSET;
+		let sprite_y = match facing {
+			sprite::West => Y_OFFSET + WEST_OFFSET,
+			sprite::East => Y_OFFSET + EAST_OFFSET,
+		};
+
+		let sprite = try!(sprite::AnimatedSprite::new(
+			display, asset_path, 
+			(sprite_x, sprite_y), 
+			(units::Tile(1), units::Tile(1)),
+			SPRITE_FRAMES, SPRITE_FPS
+		));
+		self.sprites.insert(facing, ~sprite as ~sprite::Updatable);
+		Ok(())
 	}
 
 	fn center_x(&self) -> units::Game {
 		self.x + (units::Tile(1).to_game() / units::Game(2.0))
 	}
 
-	pub fn update(&mut self, elapsed_time: units::Millis, player_x: units::Game) {
-		let av: units::Degrees = ANGULAR_VELOCITY * elapsed_time;
+	/// The bat's current height along its sine wave
+	fn flight_y(&self) -> units::Game {
 		let amp: units::Game = // peak height of the wave in game units
 			units::Tile(5).to_game() / units::Game(2.0);
 		
@@ -90,7 +114,23 @@ impl CaveBat {
 				f64::sin(self.flight_angle.to_radians())
 			);
 
-		let y1 = self.y + (amp * wave);
+		self.y + (amp * wave)
+	}
+}
+
+impl Entity for CaveBat {
+	#[allow(unused_variable)]
+	fn update(
+		&mut self, 
+		elapsed_time: units::Millis, 
+		player: (units::Game, units::Game), 
+		map: &map::Map
+	) {
+		let (player_x, _) = player;
+		let av: units::Degrees = ANGULAR_VELOCITY * elapsed_time;
+
+		let y1 = self.flight_y();
+		self.previous = (self.x, y1);
 		self.flight_angle = self.flight_angle + av;
 		self.facing = if self.center_x() > player_x {
 			sprite::West
@@ -104,7 +144,35 @@ impl CaveBat {
 		sprite_ref.set_position((self.x, y1));
 	}
 
-	pub fn draw(&self, display: &graphics::Graphics) {
-		self.sprites.get(&self.facing).draw(display);
+	fn draw(&self, queue: &mut graphics::RenderQueue, offset: (units::Game, units::Game), alpha: f64) {
+		let offset = entity::blend(offset, self.previous, (self.x, self.flight_y()), alpha);
+		self.sprites.get(&self.facing).draw_queued(queue, graphics::Entities, offset);
 	}
+
+	fn hitbox(&self) -> Rectangle {
+		Rectangle {
+			x: self.x + units::Game(8.0), y: self.flight_y() + units::Game(8.0),
+			width: units::Game(16.0), height: units::Game(16.0)
+		}
+	}
+
+	fn kind(&self) -> entity::Kind { entity::Enemy }
+
+	fn is_alive(&self) -> bool {
+		self.hit_points > 0
+	}
+}
+
+impl Enemy for CaveBat {
+	fn contact_damage(&self) -> units::HitPoint { CONTACT_DAMAGE }
+
+	fn on_hit(&mut self, damage: units::HitPoint) {
+		self.hit_points -= damage;
+	}
+
+	fn experience(&self) -> uint { EXPERIENCE }
+
+	fn loot(&self) -> enemies::LootTable { LOOT }
+
+	fn clone_enemy(&self) -> ~Enemy { ~self.clone() as ~Enemy }
 }
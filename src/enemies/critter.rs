@@ -0,0 +1,247 @@
+use std::cmp;
+
+use collections::hashmap::HashMap;
+
+use game::sprite;
+use game::graphics;
+use game::map;
+
+use game::collisions::Rectangle;
+use game::enemies;
+use game::enemies::Enemy;
+use game::entity;
+use game::entity::Entity;
+use game::error::GameResult;
+use game::units;
+use game::units::{AsGame};
+
+// physics
+static GRAVITY: units::Acceleration 	= units::Acceleration(0.00078125);
+static MAX_VELOCITY_Y: units::Velocity 	= units::Velocity(0.2998046875);
+static HOP_SPEED_X: units::Velocity 	= units::Velocity(0.1);
+static HOP_SPEED_Y: units::Velocity 	= units::Velocity(0.3);
+
+// behavior
+static HOP_DELAY: units::Millis 		= units::Millis(800);
+static CROUCH_TIME: units::Millis 		= units::Millis(200);
+static HOP_RANGE: units::Tile 			= units::Tile(6);
+
+static CONTACT_DAMAGE: units::HitPoint 	= 2;
+static EXPERIENCE: uint 				= 3;
+static HIT_POINTS: units::HitPoint 		= 3;
+
+static LOOT: enemies::LootTable = &[
+	(4, enemies::Energy),
+	(1, enemies::Heart(3)),
+	(2, enemies::Missiles(2)),
+	(1, enemies::Nothing)
+];
+
+// location of the critter on its sprite sheet
+static IDLE_FRAME: units::Tile 		= units::Tile(0);
+static CROUCH_FRAME: units::Tile 	= units::Tile(1);
+static AIRBORNE_FRAME: units::Tile 	= units::Tile(2);
+
+// y-offsets for different horizontal facings.
+static WEST_OFFSET: units::Tile = units::Tile(0);
+static EAST_OFFSET: units::Tile = units::Tile(1);
+
+// collision box (expressed as `units::Game`)
+static COLLISION_BOX: Rectangle = Rectangle {
+	x: units::Game(4.0), y: units::Game(8.0),
+	width: units::Game(24.0), height: units::Game(24.0)
+};
+
+#[deriving(Hash,Eq,Clone)]
+enum Pose {
+	Idle,
+	Crouching,
+	Airborne
+}
+static POSES: [Pose, ..3] = [Idle, Crouching, Airborne];
+
+/// A small critter which sits still until the player wanders close,
+/// at which point it hops towards them.
+#[deriving(Clone)]
+pub struct Critter {
+	x: units::Game,
+	y: units::Game,
+	// where the critter was before its last update
+	previous: (units::Game, units::Game),
+
+	velocity_x: units::Velocity,
+	velocity_y: units::Velocity,
+	on_ground: bool,
+	ground_time: units::Millis,
+
+	facing: sprite::Facing,
+	hit_points: units::HitPoint,
+	sprites: HashMap<(sprite::Facing, Pose), ~sprite::Updatable>
+}
+
+impl Critter {
+	pub fn new(
+		display: &mut graphics::Graphics,
+		x: units::Game, y: units::Game
+	) -> GameResult<Critter> {
+		let mut new_critter = Critter {
+			x: x, y: y,
+			previous: (x, y),
+			velocity_x: units::Velocity(0.0),
+			velocity_y: units::Velocity(0.0),
+			on_ground: false,
+			ground_time: units::Millis(0),
+
+			facing: sprite::West,
+			hit_points: HIT_POINTS,
+			sprites: HashMap::<(sprite::Facing, Pose), ~sprite::Updatable>::new()
+		};
+
+		for facing in sprite::FACINGS.iter() {
+			for pose in POSES.iter() {
+				try!(new_critter.load_sprite(display, (*facing, *pose)));
+			}
+		}
+
+		Ok(new_critter)
+	}
+
+	fn load_sprite(&mut self,
+				   display: &mut graphics::Graphics,
+				   key: (sprite::Facing, Pose)) -> GameResult<()> {
+		if self.sprites.contains_key(&key) { return Ok(()); }
+
+		let (facing, pose) = key;
+		let asset_path = ~"assets/base/Npc/NpcCemet.bmp";
+		let sprite_x = match pose {
+			Idle 		=> IDLE_FRAME,
+			Crouching 	=> CROUCH_FRAME,
+			Airborne 	=> AIRBORNE_FRAME
+		};
+		let sprite_y = match facing {
+			sprite::West => WEST_OFFSET,
+			sprite::East => EAST_OFFSET
+		};
+
+		let sprite = try!(sprite::Sprite::new(
+			display,
+			(units::Game(0.0), units::Game(0.0)),
+			(sprite_x, sprite_y),
+			(units::Tile(1), units::Tile(1)),
+			asset_path
+		));
+		self.sprites.insert(key, ~sprite as ~sprite::Updatable);
+		Ok(())
+	}
+
+	fn center_x(&self) -> units::Game {
+		self.x + (units::Tile(1).to_game() / units::Game(2.0))
+	}
+
+	fn pose(&self) -> Pose {
+		if !self.on_ground {
+			Airborne
+		} else if self.ground_time > HOP_DELAY - CROUCH_TIME {
+			Crouching
+		} else {
+			Idle
+		}
+	}
+
+	/// Moves the critter by `(dx, dy)`, stopping it against any walls.
+	fn move_by(&mut self, map: &map::Map, dx: units::Game, dy: units::Game) {
+		let mut hitbox = self.hitbox_at(self.x + dx, self.y);
+		if map.is_colliding(&hitbox) {
+			self.velocity_x = units::Velocity(0.0);
+		} else {
+			self.x = self.x + dx;
+		}
+
+		hitbox = self.hitbox_at(self.x, self.y + dy);
+		if map.is_colliding(&hitbox) {
+			if dy > units::Game(0.0) { self.on_ground = true; }
+			self.velocity_y = units::Velocity(0.0);
+		} else {
+			self.on_ground = false;
+			self.y = self.y + dy;
+		}
+	}
+
+	fn hitbox_at(&self, x: units::Game, y: units::Game) -> Rectangle {
+		Rectangle {
+			x: x + COLLISION_BOX.left(), y: y + COLLISION_BOX.top(),
+			width: COLLISION_BOX.width(), height: COLLISION_BOX.height()
+		}
+	}
+}
+
+impl Entity for Critter {
+	fn update(
+		&mut self,
+		elapsed_time: units::Millis,
+		player: (units::Game, units::Game),
+		map: &map::Map
+	) {
+		self.previous = (self.x, self.y);
+		let (player_x, _) = player;
+		self.facing = if self.center_x() > player_x { sprite::West } else { sprite::East };
+
+		// wind up & hop towards the player once they are in range
+		if self.on_ground {
+			self.velocity_x = units::Velocity(0.0);
+			self.ground_time = self.ground_time + elapsed_time;
+
+			let distance = cmp::max(self.center_x() - player_x, player_x - self.center_x());
+			if self.ground_time > HOP_DELAY && distance < HOP_RANGE.to_game() {
+				self.ground_time = units::Millis(0);
+				self.velocity_y = -HOP_SPEED_Y;
+				self.velocity_x = match self.facing {
+					sprite::West => -HOP_SPEED_X,
+					sprite::East =>  HOP_SPEED_X
+				};
+			}
+		}
+
+		self.velocity_y = cmp::min(
+			self.velocity_y + (GRAVITY * elapsed_time),
+			MAX_VELOCITY_Y
+		);
+
+		let (dx, dy) = (self.velocity_x * elapsed_time, self.velocity_y * elapsed_time);
+		self.move_by(map, dx, dy);
+
+		let key = (self.facing, self.pose());
+		let sprite_ref = self.sprites.get_mut(&key);
+		sprite_ref.update(elapsed_time);
+		sprite_ref.set_position((self.x, self.y));
+	}
+
+	fn draw(&self, queue: &mut graphics::RenderQueue, offset: (units::Game, units::Game), alpha: f64) {
+		let offset = entity::blend(offset, self.previous, (self.x, self.y), alpha);
+		self.sprites.get(&(self.facing, self.pose())).draw_queued(queue, graphics::Entities, offset);
+	}
+
+	fn hitbox(&self) -> Rectangle {
+		self.hitbox_at(self.x, self.y)
+	}
+
+	fn kind(&self) -> entity::Kind { entity::Enemy }
+
+	fn is_alive(&self) -> bool {
+		self.hit_points > 0
+	}
+}
+
+impl Enemy for Critter {
+	fn contact_damage(&self) -> units::HitPoint { CONTACT_DAMAGE }
+
+	fn on_hit(&mut self, damage: units::HitPoint) {
+		self.hit_points -= damage;
+	}
+
+	fn experience(&self) -> uint { EXPERIENCE }
+
+	fn loot(&self) -> enemies::LootTable { LOOT }
+
+	fn clone_enemy(&self) -> ~Enemy { ~self.clone() as ~Enemy }
+}
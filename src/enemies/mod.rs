@@ -1,5 +1,84 @@
+use std::rand::Rng;
+
+use game::graphics;
+use game::rng;
+use game::units;
+use game::entity::Entity;
+use game::error::{EnemyError, GameResult};
+
 // Bring enemies into this crate's namespace
 pub use game::enemies::bat::CaveBat;
+pub use game::enemies::critter::Critter;
 
 // Load enemy modules
 pub mod bat;
+pub mod critter;
+
+/// The name of each kind of enemy a map may place.
+pub static KINDS: &'static [&'static str] = &["bat", "critter"];
+
+/// What a defeated enemy may leave behind.
+pub enum Loot {
+	Nothing,
+	/// The enemy's experience, as a crystal.
+	Energy,
+	/// A heart restoring so many hit points.
+	Heart(units::HitPoint),
+	/// So many missiles' worth of ammo.
+	Missiles(uint)
+}
+
+/// An enemy's possible loot, each w/ its weight: an entry of weight 2 is
+/// rolled twice as often as one of weight 1.
+pub type LootTable = &'static [(uint, Loot)];
+
+/// Any hostile actor which lives on the map alongside the player.
+///
+/// An enemy's hitbox is the area in which it will hurt the player, and
+/// in which it may itself be hit.
+pub trait Enemy : Entity {
+	/// The damage dealt to the player when they touch this enemy.
+	fn contact_damage(&self) -> units::HitPoint;
+
+	/// Responds to being struck for `damage` hit points.
+	fn on_hit(&mut self, damage: units::HitPoint);
+
+	/// The weapon energy this enemy leaves behind once it has been defeated.
+	fn experience(&self) -> uint;
+
+	/// What this enemy leaves behind once it has been defeated.
+	fn loot(&self) -> LootTable;
+
+	/// A copy of this enemy, as it is now, e.g: for a snapshot of the world.
+	fn clone_enemy(&self) -> ~Enemy;
+}
+
+impl Clone for ~Enemy {
+	fn clone(&self) -> ~Enemy { self.clone_enemy() }
+}
+
+/// Picks an entry of `table` at random (drawn from `rng`), by weight.
+pub fn roll(table: LootTable, rng: &mut rng::Rng) -> Loot {
+	let total = table.iter().fold(0u, |total, &(weight, _)| total + weight);
+	if total == 0 { return Nothing; }
+
+	let mut pick = rng.gen_range(0, total);
+	for &(weight, loot) in table.iter() {
+		if pick < weight { return loot; }
+		pick -= weight;
+	}
+	Nothing
+}
+
+/// Creates an enemy of `kind` (one of `KINDS`) at `x`, `y`.
+pub fn create(
+	display: &mut graphics::Graphics,
+	kind: &str,
+	x: units::Game, y: units::Game
+) -> GameResult<~Enemy> {
+	match kind {
+		"bat" 		=> Ok(~try!(CaveBat::new(display, x, y)) as ~Enemy),
+		"critter" 	=> Ok(~try!(Critter::new(display, x, y)) as ~Enemy),
+		_ 			=> Err(EnemyError(kind.to_owned(), ~"there is no such kind of enemy"))
+	}
+}
@@ -0,0 +1,52 @@
+use game::graphics;
+use game::map;
+use game::units;
+use game::collisions::Rectangle;
+
+/// The sorts of entities which live in a `world::World`.
+#[deriving(Eq)]
+pub enum Kind {
+	Enemy,
+	Projectile,
+	Pickup,
+	/// A friendly character, which the player may talk to but not hurt.
+	Npc
+}
+
+/// Anything (besides the player) which lives on the map & moves about it.
+pub trait Entity {
+	/// Advances the entity by `elapsed_time`.
+	/// `player` is the center of the player, which many entities react to.
+	fn update(
+		&mut self,
+		elapsed_time: units::Millis,
+		player: (units::Game, units::Game),
+		map: &map::Map
+	);
+
+	/// Queues the entity's current state to be drawn, shifted by the camera's `offset`,
+	/// `alpha` of the way from where it stood before its last update to where it stands now.
+	fn draw(&self, queue: &mut graphics::RenderQueue, offset: (units::Game, units::Game), alpha: f64);
+
+	/// The area in which this entity touches others.
+	fn hitbox(&self) -> Rectangle;
+
+	fn kind(&self) -> Kind;
+
+	/// Responds false once this entity should be removed from the world.
+	fn is_alive(&self) -> bool;
+}
+
+/// The camera's `offset`, shifted so that a sprite placed where an entity
+/// stands now (`current`) is drawn `alpha` of the way there from where it
+/// stood before (`previous`), just as `player::Player::draw` blends.
+pub fn blend(
+	offset: (units::Game, units::Game),
+	previous: (units::Game, units::Game),
+	current: (units::Game, units::Game),
+	alpha: f64
+) -> (units::Game, units::Game) {
+	let ((ox, oy), (px, py), (cx, cy)) = (offset, previous, current);
+	let behind = units::Game(1.0 - alpha);
+	(ox + ((cx - px) * behind), oy + ((cy - py) * behind))
+}
@@ -0,0 +1,135 @@
+use game::units;
+
+/// Scales the player's physics constants while an item is worn.
+///
+/// Each factor multiplies the constant it names, so `1.0` leaves it as it
+/// is; wearing several items multiplies their factors together, which
+/// means they stack the same way whatever order they were put on in.
+#[deriving(Eq,Clone)]
+pub struct Modifiers {
+	jump_speed: f64,
+	air_acceleration: f64,
+	max_fall_speed: f64
+}
+
+/// Leaves every constant as it is.
+pub static UNMODIFIED: Modifiers = Modifiers {
+	jump_speed: 1.0, air_acceleration: 1.0, max_fall_speed: 1.0
+};
+
+impl Modifiers {
+	/// The effect of wearing both `self` & `other`.
+	pub fn stack(&self, other: &Modifiers) -> Modifiers {
+		Modifiers {
+			jump_speed: self.jump_speed * other.jump_speed,
+			air_acceleration: self.air_acceleration * other.air_acceleration,
+			max_fall_speed: self.max_fall_speed * other.max_fall_speed
+		}
+	}
+
+	pub fn jump_speed(&self, base: units::Velocity) -> units::Velocity {
+		let units::Velocity(speed) = base;
+		units::Velocity(speed * self.jump_speed)
+	}
+
+	pub fn air_acceleration(&self, base: units::Acceleration) -> units::Acceleration {
+		let units::Acceleration(accel) = base;
+		units::Acceleration(accel * self.air_acceleration)
+	}
+
+	pub fn max_fall_speed(&self, base: units::Velocity) -> units::Velocity {
+		let units::Velocity(speed) = base;
+		units::Velocity(speed * self.max_fall_speed)
+	}
+}
+
+/// An item the player may wear, given & taken away by scripts
+/// w/ `<EQ+ id` & `<EQ- id`.
+#[deriving(Eq,Clone)]
+pub enum Item {
+	/// Springy boots: higher jumps, & more control in the air.
+	Boots,
+	/// A jetpack: a slower descent, & a booster fired by holding jump in midair.
+	Jetpack,
+	/// A feather: the gentlest of descents.
+	Feather
+}
+
+pub static ITEMS: [Item, ..3] = [Boots, Jetpack, Feather];
+
+impl Item {
+	/// The item numbered `id` in scripts & saves, if there is one.
+	pub fn from_id(id: uint) -> Option<Item> {
+		ITEMS.iter().find(|item| item.id() == id).map(|item| *item)
+	}
+
+	pub fn id(&self) -> uint {
+		match *self {
+			Boots 	=> 1,
+			Jetpack => 2,
+			Feather => 3
+		}
+	}
+
+	pub fn modifiers(&self) -> Modifiers {
+		match *self {
+			Boots 	=> Modifiers { jump_speed: 1.15, air_acceleration: 1.25, max_fall_speed: 1.0 },
+			Jetpack => Modifiers { jump_speed: 1.0, air_acceleration: 1.0, max_fall_speed: 0.7 },
+			Feather => Modifiers { jump_speed: 1.0, air_acceleration: 1.0, max_fall_speed: 0.5 }
+		}
+	}
+
+	/// Responds true if wearing this item spares the player from fall damage.
+	pub fn breaks_falls(&self) -> bool {
+		match *self {
+			Jetpack | Feather 	=> true,
+			Boots 				=> false
+		}
+	}
+}
+
+/// The items the player is wearing.
+#[deriving(Clone)]
+pub struct Equipment {
+	priv worn: ~[Item]
+}
+
+impl Equipment {
+	/// Creates a set w/ nothing worn.
+	pub fn new() -> Equipment {
+		Equipment { worn: ~[] }
+	}
+
+	/// Puts on `item`, unless it is already worn.
+	pub fn equip(&mut self, item: Item) {
+		if !self.is_equipped(item) { self.worn.push(item); }
+	}
+
+	pub fn unequip(&mut self, item: Item) {
+		self.worn.retain(|worn| *worn != item);
+	}
+
+	pub fn is_equipped(&self, item: Item) -> bool {
+		self.worn.contains(&item)
+	}
+
+	/// The ids of every item worn, in the order they were put on.
+	pub fn ids(&self) -> ~[uint] {
+		self.worn.iter().map(|item| item.id()).collect()
+	}
+
+	/// Wears exactly the items numbered `ids`; unknown ids are skipped.
+	pub fn restore(&mut self, ids: &[uint]) {
+		self.worn = ids.iter().filter_map(|id| Item::from_id(*id)).collect();
+	}
+
+	/// Responds true if anything worn spares the player from fall damage.
+	pub fn breaks_falls(&self) -> bool {
+		self.worn.iter().any(|item| item.breaks_falls())
+	}
+
+	/// The effect of everything worn, stacked together.
+	pub fn modifiers(&self) -> Modifiers {
+		self.worn.iter().fold(UNMODIFIED, |total, item| total.stack(&item.modifiers()))
+	}
+}
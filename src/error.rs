@@ -0,0 +1,50 @@
+use std::fmt;
+
+/// Why some asset of the game could not be loaded.
+///
+/// Loaders return these rather than failing, so that their callers may
+/// decide whether to fall back (e.g: stay on the current map) or give up.
+#[deriving(Clone)]
+pub enum GameError {
+	/// The image at a path could not be loaded, w/ the reason why.
+	ImageError(~str, ~str),
+	/// The map file at a path could not be read or is malformed.
+	MapError(~str, ~str),
+	/// The animation file at a path could not be read, is malformed, or
+	/// leaves some movement w/o an animation.
+	AnimationError(~str, ~str),
+	/// The boss file at a path could not be read or is malformed.
+	BossError(~str, ~str),
+	/// The TrueType font at a path could not be opened.
+	FontError(~str, ~str),
+	/// The script file at a path could not be read or is malformed.
+	ScriptError(~str, ~str),
+	/// The atlas at a path could not be read, is malformed, or lacks a region.
+	AtlasError(~str, ~str),
+	/// An enemy of a kind could not be created, w/ the reason why.
+	EnemyError(~str, ~str)
+}
+
+pub type GameResult<T> = Result<T, GameError>;
+
+impl GameError {
+	/// A description of the error, naming the file at fault.
+	pub fn message(&self) -> ~str {
+		match *self {
+			ImageError(ref path, ref reason) 		=> format!("image {} could not be loaded: {}", *path, *reason),
+			MapError(ref path, ref reason) 			=> format!("map {} could not be loaded: {}", *path, *reason),
+			AnimationError(ref path, ref reason) 	=> format!("animations {} could not be loaded: {}", *path, *reason),
+			BossError(ref path, ref reason) 		=> format!("boss {} could not be loaded: {}", *path, *reason),
+			FontError(ref path, ref reason) 		=> format!("font {} could not be loaded: {}", *path, *reason),
+			ScriptError(ref path, ref reason) 		=> format!("script {} could not be loaded: {}", *path, *reason),
+			AtlasError(ref path, ref reason) 		=> format!("atlas {} could not be loaded: {}", *path, *reason),
+			EnemyError(ref kind, ref reason) 		=> format!("enemy `{}` could not be created: {}", *kind, *reason)
+		}
+	}
+}
+
+impl fmt::Show for GameError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f.buf, "{}", self.message())
+	}
+}
@@ -0,0 +1,75 @@
+use std::cmp;
+
+use collections::hashmap::{HashMap, HashSet};
+
+use game::units;
+use game::units::{AsGame, AsTile};
+
+/// The width & height (in tiles) of a single cell of the map screen.
+pub static CELL_SIZE: uint = 4;
+
+// how far around the player the map is revealed
+static REVEAL_RANGE: units::Tile = units::Tile(6);
+
+/// The parts of each map the player has seen, kept in square cells of
+/// `CELL_SIZE` tiles (so that a save need not list every tile.)
+#[deriving(Clone)]
+pub struct Exploration {
+	priv maps: HashMap<~str, HashSet<(uint, uint)>>
+}
+
+impl Exploration {
+	/// Creates a record in which nothing has been seen.
+	pub fn new() -> Exploration {
+		Exploration { maps: HashMap::new() }
+	}
+
+	/// Reveals the cells of the map at `path` (of size `bounds`) within
+	/// `REVEAL_RANGE` of `center`, e.g: of the player.
+	pub fn explore(&mut self, path: &str, center: (units::Game, units::Game), bounds: (units::Game, units::Game)) {
+		let (x, y) = center;
+		let (width, height) = bounds;
+		let reach = REVEAL_RANGE.to_game();
+		let edge = units::Game(1.0);
+
+		let units::Tile(left) 	= cmp::max(x - reach, units::Game(0.0)).to_tile();
+		let units::Tile(right) 	= cmp::max(cmp::min(x + reach, width - edge), units::Game(0.0)).to_tile();
+		let units::Tile(top) 	= cmp::max(y - reach, units::Game(0.0)).to_tile();
+		let units::Tile(bottom) = cmp::max(cmp::min(y + reach, height - edge), units::Game(0.0)).to_tile();
+
+		let cells = self.maps.find_or_insert_with(path.to_owned(), |_| HashSet::new());
+		for row in range(top / CELL_SIZE, (bottom / CELL_SIZE) + 1) {
+			for col in range(left / CELL_SIZE, (right / CELL_SIZE) + 1) {
+				cells.insert((col, row));
+			}
+		}
+	}
+
+	/// Responds true if the player has seen the tile at `(col, row)` of the map at `path`.
+	pub fn is_explored(&self, path: &str, tile: (units::Tile, units::Tile)) -> bool {
+		let (units::Tile(col), units::Tile(row)) = tile;
+		match self.maps.find_equiv(&path) {
+			Some(cells) => cells.contains(&(col / CELL_SIZE, row / CELL_SIZE)),
+			None => false
+		}
+	}
+
+	/// The `(col, row)` of every cell seen, by the path of its map, in order.
+	pub fn entries(&self) -> ~[(~str, ~[(uint, uint)])] {
+		let mut entries: ~[(~str, ~[(uint, uint)])] = self.maps.iter().map(|(path, cells)| {
+			let mut cells: ~[(uint, uint)] = cells.iter().map(|cell| *cell).collect();
+			cells.sort();
+			(path.clone(), cells)
+		}).collect();
+		entries.sort_by(|&(ref a, _), &(ref b, _)| a.cmp(b));
+		entries
+	}
+
+	/// Has seen exactly the cells listed in `entries`.
+	pub fn restore(&mut self, entries: &[(~str, ~[(uint, uint)])]) {
+		self.maps = HashMap::new();
+		for &(ref path, ref cells) in entries.iter() {
+			self.maps.insert(path.clone(), cells.iter().map(|cell| *cell).collect());
+		}
+	}
+}
@@ -0,0 +1,63 @@
+use std::vec;
+
+// one bit per flag, packed into words of this many bits
+static WORD_BITS: uint = 64;
+
+/// A set of numbered event flags, used to remember what has happened in
+/// the world (e.g: a door being unlocked, or a boss being defeated.)
+///
+/// Every flag starts out clear. Flags are stored as a bitset, so ids
+/// should be kept reasonably small.
+#[deriving(Clone)]
+pub struct FlagSet {
+	priv words: ~[u64]
+}
+
+impl FlagSet {
+	/// Creates a set w/ every flag clear.
+	pub fn new() -> FlagSet {
+		FlagSet { words: ~[] }
+	}
+
+	/// Creates a set w/ exactly the flags in `ids` set.
+	pub fn from_ids(ids: &[uint]) -> FlagSet {
+		let mut flags = FlagSet::new();
+		for id in ids.iter() {
+			flags.set(*id);
+		}
+		flags
+	}
+
+	/// Responds true if flag `id` has been set.
+	pub fn is_set(&self, id: uint) -> bool {
+		let (word, bit) = (id / WORD_BITS, id % WORD_BITS);
+		word < self.words.len() && (self.words[word] & (1u64 << bit)) != 0
+	}
+
+	pub fn set(&mut self, id: uint) {
+		let (word, bit) = (id / WORD_BITS, id % WORD_BITS);
+		if word >= self.words.len() {
+			let missing = word + 1 - self.words.len();
+			self.words.push_all_move(vec::from_elem(missing, 0u64));
+		}
+		self.words[word] |= 1u64 << bit;
+	}
+
+	pub fn clear(&mut self, id: uint) {
+		let (word, bit) = (id / WORD_BITS, id % WORD_BITS);
+		if word < self.words.len() {
+			self.words[word] &= !(1u64 << bit);
+		}
+	}
+
+	/// The ids of every flag which is set, in ascending order.
+	pub fn ids(&self) -> ~[uint] {
+		let mut ids = ~[];
+		for (word, bits) in self.words.iter().enumerate() {
+			for bit in range(0, WORD_BITS) {
+				if (*bits & (1u64 << bit)) != 0 { ids.push((word * WORD_BITS) + bit); }
+			}
+		}
+		ids
+	}
+}
@@ -1,72 +1,208 @@
 use std::cmp;
+use std::io;
 use std::io::Timer;
-
-use game::units::{AsGame};
+use std::io::fs;
+use time;
 
 use sdl2::sdl;
-use sdl2::event;
-use sdl2::keycode;
 
+use game::states::GameState;
+
+pub mod animation;
+pub mod aseprite;
+pub mod assets;
+pub mod audio;
 pub mod backdrop;
+pub mod boss;
+pub mod camera;
 pub mod collisions;
+pub mod config;
+pub mod damage_numbers;
+pub mod debug;
+pub mod equipment;
+pub mod exploration;
+pub mod flags;
+pub mod ghost;
 pub mod graphics;
+pub mod hud;
+pub mod i18n;
 pub mod input;
+pub mod input_display;
+pub mod log;
 pub mod map;
+pub mod net;
+pub mod npc;
+pub mod organya;
+pub mod pack;
+pub mod particles;
+pub mod pickups;
 pub mod player;
+pub mod profile;
+pub mod projectiles;
+pub mod rng;
 pub mod enemies;
+pub mod entity;
+pub mod error;
+pub mod save;
+pub mod script;
+pub mod speedrun;
 pub mod sprite;
+pub mod states;
+pub mod stats;
+pub mod textbox;
 pub mod units;
+pub mod vfs;
+pub mod weapons;
+pub mod weather;
+pub mod world;
 
-static TARGET_FRAMERATE: units::Fps 	= 60;
+pub static TARGET_FRAMERATE: units::Fps = 60;
 static MAX_FRAME_TIME: units::Millis 	= units::Millis(5 * (1000 / TARGET_FRAMERATE) as int);
+static UPDATE_STEP: units::Millis 		= units::Millis((1000 / TARGET_FRAMERATE) as int);
+
+/// The screen's size unless the settings file chooses another.
+pub static DEFAULT_RESOLUTION: units::Resolution = units::Resolution {
+	width: units::Tile(20), height: units::Tile(15)
+};
 
-pub static SCREEN_WIDTH: 	units::Tile 	= units::Tile(20);
-pub static SCREEN_HEIGHT:	units::Tile  	= units::Tile(15);
+/// Where screenshots are saved, each named for when it was taken.
+pub static SCREENSHOT_DIR: &'static str = "screenshots";
+
+/// The font used for all text drawn by the game.
+pub static FONT_PATH: &'static str = "assets/base/Font.bmp";
+pub static FONT_SIZE: (units::Game, units::Game) = (units::Game(16.0), units::Game(16.0));
 
 /// An instance of the `rust-story` game with its own event loop.
+///
+/// The game owns the subsystems shared by every screen, and a stack of
+/// `states::GameState`s which take turns using them.
 pub struct Game {
-	priv quote: player::Player,
-	priv yatty: enemies::CaveBat,
-	priv map: 	map::Map,
+	priv states: 		~[~states::GameState],
 
 	priv display: 		graphics::Graphics,
-	priv controller: 	input::Input 
+	priv audio: 		audio::Audio,
+	priv controller: 	input::Input,
+	// either overlay is left out should it fail to load
+	priv debug: 		Option<debug::DebugOverlay>,
+	priv inputs: 		Option<input_display::InputDisplay>,
+	priv rng: 			rng::Rng,
+	priv speedrun: 		Option<speedrun::Overlay>,
+
+	priv frame_limit: 	config::FrameLimit
 }
 
 /// When the `Game` leaves scope SDL is instructed to `quit`.
 impl Drop for Game {
 	fn drop(&mut self) {
-		println!("quitting sdl ...");
+		log::info("game", ~"quitting sdl ...");
 		sdl::quit();
 	}
 }
 
 impl Game {
-	/// Starts running this games event loop, note that this will block indefinitely.
-	/// This function will return to the caller when the escape key is pressed.
-	pub fn new() -> Game {
-		println!("initalizing sdl ...");
-		
+	/// Sets up every subsystem & the first screen, to be run by `start()`.
+	/// Responds `None` (w/ the reason logged) if the first screen can not be loaded.
+	pub fn new() -> Option<Game> {
+		let settings = Game::init_subsystems();
+		log::info("game", ~"initalizing sdl ...");
+
 		// initialize all major subsystems
 		// hide the mouse cursor in our drawing context
 		sdl::init([sdl::InitEverything]);
-		let mut display = graphics::Graphics::new();
-		let controller =  input::Input::new();		
-
-		Game {
-			map: 	map::Map::create_test_map(&mut display),
-			quote: 	player::Player::new(
-					&mut display, 
-					(SCREEN_WIDTH / units::Tile(2)).to_game(),
-					(SCREEN_HEIGHT / units::Tile(2)).to_game()
-				),
-			yatty:	enemies::CaveBat::new(
-					&mut display,
-					(SCREEN_WIDTH / units::Tile(3)).to_game(),
-					(units::Tile(10)).to_game()	
-				),
+		let mut display = graphics::Graphics::new(&settings);
+		let controller =  input::Input::new(config::Bindings::load(profile::bindings_path().as_slice()));
+
+		let rng = Game::seed_rng(&settings);
+		let mut audio = audio::Audio::new(rng.clone());
+		settings.apply_volumes(&mut audio);
+
+		let manifest = assets::Manifest::load(assets::MANIFEST_PATH);
+		display.preload(&manifest);
+		audio.preload(&manifest);
+
+		// w/o its first screen the game can not go on, though it may w/o its overlays
+		// the timer is drawn only if it is enabled, though every run reports to it regardless
+		let timer = speedrun::Timer::new(settings.speedrun_timer, settings.speedrun_pause_loads);
+		let speedrun = if timer.is_enabled() {
+			match speedrun::Overlay::new(&mut display, timer.clone()) {
+				Ok(overlay) => Some(overlay),
+				Err(err) => { log::error("game", err.message()); None }
+			}
+		} else {
+			None
+		};
+
+		let profiles = match states::ProfileSelect::new(&mut display, rng.clone(), timer) {
+			Ok(profiles) => ~profiles as ~states::GameState,
+			Err(err) => { log::error("game", err.message()); sdl::quit(); return None; }
+		};
+		let debug = match debug::DebugOverlay::new(&mut display) {
+			Ok(debug) => Some(debug),
+			Err(err) => { log::error("game", format!("the debug overlay is disabled: {}", err.message())); None }
+		};
+		let inputs = match input_display::InputDisplay::new(&mut display) {
+			Ok(inputs) => Some(inputs),
+			Err(err) => { log::error("game", format!("the input display is disabled: {}", err.message())); None }
+		};
+
+		Some(Game {
+			states: ~[profiles],
 			display: display,
-			controller: controller
+			audio: audio,
+			controller: controller,
+			debug: debug,
+			inputs: inputs,
+			rng: rng,
+			speedrun: speedrun,
+			frame_limit: settings.frame_limit
+		})
+	}
+
+	// loads the settings of the profile chosen last (until another is chosen),
+	// then sets up logging first, so that every subsystem may log, & those
+	// subsystems which need no SDL
+	fn init_subsystems() -> config::Settings {
+		profile::init();
+		let settings = config::Settings::load(profile::settings_path().as_slice());
+		log::init(&settings.log);
+		vfs::init(vfs::MODS_DIR, pack::ARCHIVE_PATH);
+		i18n::init(settings.language.as_slice());
+		settings
+	}
+
+	// every system draws from one sequence, whose seed is logged so a session may be reproduced
+	fn seed_rng(settings: &config::Settings) -> rng::Rng {
+		let rng = match settings.seed {
+			Some(seed) => rng::Rng::new(seed),
+			None => rng::Rng::unseeded()
+		};
+		log::info("game", format!("random seed: {}", rng.seed()));
+		rng
+	}
+
+	// a timer to sleep out each frame w/; w/o one the game can not sleep, and so runs uncapped
+	fn frame_timer() -> Option<Timer> {
+		match Timer::new() {
+			Ok(timer) => Some(timer),
+			Err(msg) => { log::error("game", format!("timer could not be created: {}", msg)); None }
+		}
+	}
+
+	// sleeps out what is left of the frame begun at `start_time` (in SDL ticks)
+	fn sleep_out_frame(timer: &mut Option<Timer>, start_time: units::Millis) {
+		let frame_delay = units::Millis(1000 / TARGET_FRAMERATE as int);
+		let iter_time = units::Millis(sdl::get_ticks() as int) - start_time;
+		let next_frame_time: u64 = if frame_delay > iter_time {	// if we did not miss our deadline: adjust delay accordingly
+			let (units::Millis(fd), units::Millis(it)) = (frame_delay, iter_time);
+			(fd - it) as u64
+		} else {												// otherwise missed frame-deadline, skip waiting period
+			let (units::Millis(it), units::Millis(fd)) = (iter_time, frame_delay);
+			log::debug("game", format!("missed frame deadline by {}ms", it - fd));
+			0 as u64
+		};
+		match *timer {
+			Some(ref mut timer) => timer.sleep(next_frame_time),
+			None => {}
 		}
 	}
 
@@ -74,117 +210,296 @@ impl Game {
 		self.event_loop();
 	}
 
+	/// Joins the game hosted at `address` (e.g: `192.168.0.2:7777`) as its
+	/// second player, in place of the first screen. Responds false (w/ the
+	/// reason logged) if it can not be joined.
+	pub fn join(&mut self, address: &str) -> bool {
+		let client = match net::Client::connect(address) {
+			Ok(client) => client,
+			Err(msg) => { log::error("game", msg); return false; }
+		};
+		match states::RemoteGame::new(&mut self.display, client, self.rng.clone()) {
+			Ok(remote) => { self.states = ~[~remote as ~states::GameState]; true }
+			Err(err) => { log::error("game", err.message()); false }
+		}
+	}
 
-	/// Polls current input events & dispatches them to the engine.
+	/// Runs a dedicated server: a game hosted on `port` (see `net::Server`),
+	/// simulated w/o a window or an audio device (only SDL's timer is started),
+	/// so that it may be hosted on a machine w/o a display.
 	///
-	/// Then renders a snapshot of the world-state and then waits
-	/// until its next frame deadline.
+	/// No one plays at the server itself: its first player stands at the
+	/// start, while the second joins in from another machine. Nothing is
+	/// drawn but the snapshots sent to them. Should the game end (e.g: the
+	/// first player is defeated) a new one is started, hosted on the same port.
+	/// This blocks until the process is stopped, or responds false (w/ the
+	/// reason logged) should the game fail to be hosted.
+	pub fn start_server(port: u16) -> bool {
+		let mut settings = Game::init_subsystems();
+		log::info("game", ~"initalizing sdl (timer only) ...");
+		sdl::init([sdl::InitTimer]);
+
+		// there is no window to fill, nor assets to watch; bitmap fonts need no SDL_ttf
+		settings.fullscreen = false;
+		settings.hot_reload = false;
+		settings.font = None;
+		let mut display = graphics::Graphics::with_backend(
+			~graphics::NullBackend::new(settings.resolution) as ~graphics::Backend,
+			&settings
+		);
+		let mut controller = input::Input::new(config::Bindings::default());
+
+		let rng = Game::seed_rng(&settings);
+		let mut audio = audio::Audio::silent(rng.clone());
+		let timer = speedrun::Timer::new(false, false);
+
+		let mut server = match net::Server::host(port) {
+			Ok(server) => server,
+			Err(msg) => { log::error("game", format!("could not host on port {}: {}", port, msg)); sdl::quit(); return false; }
+		};
+		let mut sleeper = Game::frame_timer();
+
+		loop {
+			let mut playing = match states::Playing::hosted(&mut display, &mut audio, server, rng.clone(), timer.clone()) {
+				Ok(playing) => playing,
+				Err(err) => { log::error("game", err.message()); sdl::quit(); return false; }
+			};
+
+			// as `event_loop`, less the drawing: only the game's end breaks the loop
+			let mut stepper = Stepper::new();
+			let mut outcome = states::Stay;
+			while match outcome { states::Stay => true, _ => false } {
+				let start_time_ms = units::Millis(sdl::get_ticks() as int);
+				controller.begin_new_frame();
+				outcome = playing.handle_input(&controller, &mut display, &mut audio);
+
+				stepper.bank();
+				while match outcome { states::Stay => true, _ => false } {
+					match stepper.step(&mut playing as &mut GameState, &mut display, &mut audio) {
+						Some(transition) => { outcome = transition; }
+						None => { break; }
+					}
+				}
+
+				Game::sleep_out_frame(&mut sleeper, start_time_ms);
+			}
+
+			match outcome {
+				states::Quit => { sdl::quit(); return true; }
+				_ => log::info("game", ~"the game has ended; starting a new one")
+			}
+			server = match playing.take_host() {
+				Some(server) => server,
+				None => { log::error("game", ~"the server was lost along w/ the game"); sdl::quit(); return false; }
+			};
+		}
+	}
+
+
+	/// Polls current input events & dispatches them to the current state.
+	///
+	/// The simulation is then advanced in fixed `UPDATE_STEP`s to catch up
+	/// w/ the wall-clock; any time left over is carried into the next frame.
+	/// Then renders a snapshot of the world-state, interpolated between the
+	/// last two steps, and then waits until its next frame deadline.
+	/// (Unless the frame limit is vsync, where presenting the frame waits
+	/// on the display, or uncapped, where nothing waits at all.)
 	fn event_loop(&mut self) {
 		// event loop control
-		let frame_delay = units::Millis(1000 / TARGET_FRAMERATE as int);
-		let mut last_update_time = units::Millis(sdl::get_ticks() as int);
+		let mut stepper = Stepper::new();
 		let mut running = true;
-		let mut timer = Timer::new().unwrap();
+		let mut timer = Game::frame_timer();
 		
 		while running {
 			let start_time_ms = units::Millis(sdl::get_ticks() as int);
+			let frame_start = time::precise_time_ns();
 			self.controller.begin_new_frame();
 
-			// drain event queue once per frame
-			// ideally should do in separate task
-			match event::poll_event() {
-				event::KeyDownEvent(_,_,key_cap,_,_) => {
-					self.controller.key_down_event(key_cap);
-				}
-				event::KeyUpEvent(_,_,key_cap,_,_) => {
-					self.controller.key_up_event(key_cap);
-				}
-				_ => {}
-			}
+			self.controller.poll_events();
 
-			// Handle exit game
-			if self.controller.was_key_released(keycode::EscapeKey) {
-				running = false;
+			// window events
+			if self.controller.was_quit_requested() { break; }
+			if self.controller.was_resized() { self.display.fit_to_window(); }
+			if self.controller.lost_focus() {
+				self.audio.mute();
+				let top = self.states.len() - 1;
+				let transition = self.states[top].on_focus_lost(&mut self.display);
+				if !self.apply(transition) { break; }
+			} else if self.controller.gained_focus() {
+				self.audio.unmute();
 			}
-
-			// Handle player movement
-			if self.controller.is_key_held(keycode::LeftKey)
-				&& self.controller.is_key_held(keycode::RightKey) {
-
-				self.quote.stop_moving();
-			} else if self.controller.is_key_held(keycode::LeftKey) {
-				self.quote.start_moving_left();
-			} else if self.controller.is_key_held(keycode::RightKey) {
-				self.quote.start_moving_right();
-			} else {
-				self.quote.stop_moving();
+			if self.controller.was_key_pressed(input::ToggleFullscreen) {
+				self.display.toggle_fullscreen();
 			}
-
-			// Handle player looking
-			if self.controller.is_key_held(keycode::UpKey)
-				&& self.controller.is_key_held(keycode::DownKey) {
-
-				self.quote.look_horizontal();
-			} else if self.controller.is_key_held(keycode::UpKey) {
-				self.quote.look_up();
-			} else if self.controller.is_key_held(keycode::DownKey) {
-				self.quote.look_down();
-			} else {
-				self.quote.look_horizontal();
+			if self.controller.was_key_pressed(input::ToggleDebug) {
+				match self.debug { Some(ref mut debug) => debug.toggle(), None => {} }
+			}
+			if self.controller.was_key_pressed(input::ToggleInputs) {
+				match self.inputs { Some(ref mut inputs) => inputs.toggle(), None => {} }
 			}
 
-			// Handle player jump
-			if self.controller.was_key_pressed(keycode::ZKey) {
-				self.quote.start_jump();
-			} else if self.controller.was_key_released(keycode::ZKey) {
-				self.quote.stop_jump();
+			// assets edited on disk (only checked w/ `hot_reload` on)
+			let changed_maps = self.display.reload_changed();
+			if !changed_maps.is_empty() {
+				for state in self.states.mut_iter() {
+					state.on_maps_changed(changed_maps.as_slice(), &mut self.display);
+				}
 			}
 
+			// input
+			let top = self.states.len() - 1;
+			let transition = self.states[top].handle_input(
+				&self.controller, &mut self.display, &mut self.audio
+			);
+			running = self.apply(transition);
+
 			// update
-			let current_time_ms = units::Millis(sdl::get_ticks() as int);
-			let elapsed_time = current_time_ms - last_update_time;
-			self.update(cmp::min(elapsed_time, MAX_FRAME_TIME));
-			last_update_time = current_time_ms;
+			let update_start = time::precise_time_ns();
+			stepper.bank();
+			while running {
+				let top = self.states.len() - 1;
+				match stepper.step(&mut *self.states[top], &mut self.display, &mut self.audio) {
+					Some(transition) => { running = self.apply(transition); }
+					None => { break; }
+				}
+			}
+			let update_time = time::precise_time_ns() - update_start;
+
+			if !running { break; }
 
 			// draw
+			let draw_start = time::precise_time_ns();
 			self.display.clear_buffer(); // clear back-buffer
-			self.draw();
+			self.draw(stepper.alpha());
+			let draw_time = time::precise_time_ns() - draw_start;
+			if self.controller.was_key_pressed(input::Screenshot) {
+				self.display.capture_screenshot(&Game::screenshot_path());
+			}
 			self.display.switch_buffers();
 
 			// throttle event-loop
-			let iter_time = units::Millis(sdl::get_ticks() as int) - start_time_ms;
-			let next_frame_time: u64 = if frame_delay > iter_time {	// if we did not miss our deadline: adjust delay accordingly
-				let (units::Millis(fd), units::Millis(it)) = (frame_delay, iter_time);
-				(fd - it) as u64
-			} else { 0 as u64 };									// otherwise missed frame-deadline, skip waiting period
-			timer.sleep(next_frame_time);
-
-			
-			/* Print current FPS to stdout
-			let units::Millis(start_time) = start_time_ms;
-			let seconds_per_frame =  (sdl::get_ticks() as int - start_time) as f64 / 1000.0;
-			let fps = 1.0 / (seconds_per_frame);
-
-			println!("fps: {}", fps);
-			*/
-			
+			if self.frame_limit == config::Sleep {
+				Game::sleep_out_frame(&mut timer, start_time_ms);
+			}
+
+			let frame_time = time::precise_time_ns() - frame_start;
+			match self.debug {
+				Some(ref mut debug) => debug.record_frame(Game::to_ms(frame_time), Game::to_ms(update_time), Game::to_ms(draw_time)),
+				None => {}
+			}
 		}
 
 	}
 
-	/// Instructs our actors to draw their current state to the screen. 
-	fn draw(&self) {
-		self.map.draw_background(&self.display);
-		self.map.draw_sprites(&self.display);
-		self.quote.draw(&self.display);
-		self.yatty.draw(&self.display);
-		self.map.draw(&self.display);
+	/// A new path in `SCREENSHOT_DIR` named for the current time,
+	/// creating the directory if needed.
+	fn screenshot_path() -> Path {
+		let dir = Path::new(SCREENSHOT_DIR);
+		match fs::mkdir_recursive(&dir, io::UserDir) {
+			Ok(_) => {}
+			Err(msg) => { log::warn("game", format!("could not create screenshot directory: {}", msg)); }
+		}
+
+		// several shots may be taken within the same second
+		let stamp = time::now().strftime("%Y-%m-%d_%H-%M-%S");
+		let mut path = dir.join(format!("{}.png", stamp));
+		let mut count = 1;
+		while path.exists() {
+			path = dir.join(format!("{}_{}.png", stamp, count));
+			count += 1;
+		}
+		path
+	}
+
+	/// Applies `transition` to the stack of states.
+	/// Responds false once the game should stop running.
+	fn apply(&mut self, transition: states::Transition) -> bool {
+		match transition {
+			states::Stay 			=> {}
+			states::Push(state) 	=> { self.states.push(state); }
+			states::Pop 			=> { self.states.pop(); }
+			states::Replace(state) 	=> { self.states = ~[state]; }
+			states::Quit 			=> { return false; }
+		}
+
+		!self.states.is_empty()
+	}
+
+	/// Draws the topmost state, preceded by any states it overlays.
+	/// `alpha` is how far, `[0, 1)`, we are between the last update step & the next.
+	fn draw(&self, alpha: f64) {
+		let mut bottom = self.states.len() - 1;
+		while bottom > 0 && self.states[bottom].is_overlay() {
+			bottom -= 1;
+		}
+
+		for state in self.states.slice_from(bottom).iter() {
+			state.draw(&self.display, alpha);
+		}
+
+		// the speedrun timer & input display are atop every state, beneath only the debug overlay
+		match self.speedrun {
+			Some(ref overlay) => overlay.draw(&self.display),
+			None => {}
+		}
+		match self.inputs {
+			Some(ref inputs) if inputs.is_visible() => inputs.draw(&self.display, &self.controller),
+			_ => {}
+		}
+
+		match self.debug {
+			Some(ref debug) if debug.is_visible() => {
+				let top = self.states.len() - 1;
+				let mut info = ~[format!("seed: {}", self.rng.seed())];
+				info.push_all_move(self.states[top].debug_info());
+				debug.draw(&self.display, info.as_slice());
+			}
+			_ => {}
+		}
+	}
+
+	/// Converts a duration in nanoseconds to (fractional) milliseconds.
+	fn to_ms(nanoseconds: u64) -> f64 {
+		nanoseconds as f64 / 1000000.0
+	}
+}
+
+/// The fixed timestep shared by `Game::event_loop` & `Game::start_server`:
+/// the wall-clock time which passes is banked, then spent in `UPDATE_STEP`s.
+struct Stepper {
+	last_update_time: units::Millis,
+	accumulator: units::Millis
+}
+
+impl Stepper {
+	fn new() -> Stepper {
+		Stepper { last_update_time: units::Millis(sdl::get_ticks() as int), accumulator: units::Millis(0) }
+	}
+
+	/// Banks the time passed since it was last banked, at most `MAX_FRAME_TIME`
+	/// of it (so that a long stall is not caught up on all at once.)
+	fn bank(&mut self) {
+		let current_time_ms = units::Millis(sdl::get_ticks() as int);
+		self.accumulator = self.accumulator + cmp::min(current_time_ms - self.last_update_time, MAX_FRAME_TIME);
+		self.last_update_time = current_time_ms;
+	}
+
+	/// Advances `state` by an `UPDATE_STEP` of the banked time, responding w/
+	/// its transition; `None` once less than a step is left.
+	fn step(
+		&mut self,
+		state: &mut GameState,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Option<states::Transition> {
+		if self.accumulator < UPDATE_STEP { return None; }
+		self.accumulator = self.accumulator - UPDATE_STEP;
+		Some(state.update(UPDATE_STEP, display, audio))
 	}
 
-	/// Passes the current time in milliseconds to our underlying actors.	
-	fn update(&mut self, elapsed_time: units::Millis) {
-		self.map.update(elapsed_time);
-		self.quote.update(elapsed_time, &self.map);
-		self.yatty.update(elapsed_time, self.quote.center_x());
+	/// How far, `[0, 1)`, the time left banked is between the last step & the next.
+	fn alpha(&self) -> f64 {
+		let (units::Millis(remainder), units::Millis(step)) = (self.accumulator, UPDATE_STEP);
+		remainder as f64 / step as f64
 	}
 }
@@ -0,0 +1,217 @@
+use collections::hashmap::HashMap;
+
+use std::io;
+use std::io::File;
+use std::io::fs;
+
+use game::graphics;
+use game::log;
+use game::player;
+use game::sprite;
+
+use game::units;
+
+/// Where the ghost of each map is kept, named for the map's file.
+pub static GHOSTS_DIR: &'static str = "ghosts";
+
+// every ghost file starts w/ these bytes, then the seed of the session it
+// was recorded in (a little-endian `u64`), then its count of frames
+static MAGIC: &'static [u8] = bytes!("GHS2");
+// ghosts saved before seeds were recorded start w/ these, then their count of frames
+static UNSEEDED_MAGIC: &'static [u8] = bytes!("GHST");
+
+// how the ghost is drawn: faded & tinted blue
+static GHOST_ALPHA: u8 = 112;
+static GHOST_TINT: (u8, u8, u8) = (160, 192, 255);
+
+/// Where the player stood, & how they moved, during one update.
+///
+/// Frames are saved as 5 bytes each: `x` & `y` (whole `Game` units, as
+/// little-endian `i16`s), then the index of the player's motion, facing
+/// & looking in `sprite::MOTIONS`, `FACINGS` & `LOOKINGS`, packed together.
+#[deriving(Clone)]
+struct Frame {
+	x: i16,
+	y: i16,
+	movement: u8
+}
+
+impl Frame {
+	fn of(quote: &player::Player) -> Frame {
+		let (units::Game(x), units::Game(y)) = quote.position();
+		let (motion, facing, looking) = quote.movement();
+		let motion = sprite::MOTIONS.iter().position(|m| *m == motion).unwrap_or(0) as u8;
+		let facing = sprite::FACINGS.iter().position(|f| *f == facing).unwrap_or(0) as u8;
+		let looking = sprite::LOOKINGS.iter().position(|l| *l == looking).unwrap_or(0) as u8;
+		Frame {
+			x: x.round() as i16,
+			y: y.round() as i16,
+			movement: (motion * 6) + (facing * 3) + looking
+		}
+	}
+
+	fn position(&self) -> (units::Game, units::Game) {
+		(units::Game(self.x as f64), units::Game(self.y as f64))
+	}
+
+	/// The movement packed into this frame, or `None` if it is out of range.
+	fn movement(&self) -> Option<player::MotionTup> {
+		let index = self.movement as uint;
+		match (index / 6, (index / 3) % 2, index % 3) {
+			(motion, facing, looking) if motion < sprite::MOTIONS.len() =>
+				Some((sprite::MOTIONS[motion], sprite::FACINGS[facing], sprite::LOOKINGS[looking])),
+			_ => None
+		}
+	}
+}
+
+/// The file holding the ghost of the map at `map_path`.
+fn ghost_path(map_path: &str) -> Path {
+	let name = Path::new(map_path).filestem_str().unwrap_or(map_path).to_owned();
+	Path::new(format!("{}/{}.ghost", GHOSTS_DIR, name))
+}
+
+/// Reads the seed & frames of the ghost of the map at `map_path`, if there
+/// is one; the seed is `None` for a ghost saved w/o one. A ghost which can not
+/// be read is ignored (& the error logged.)
+fn read_frames(map_path: &str) -> Option<(Option<u64>, ~[Frame])> {
+	let path = ghost_path(map_path);
+	if !path.exists() { return None; }
+
+	let bytes = match File::open(&path).read_to_end() {
+		Ok(bytes) => bytes,
+		Err(msg) => { log::warn("ghost", format!("{} could not be read: {}", path.display(), msg)); return None; }
+	};
+	let seeded = bytes.len() >= MAGIC.len() && bytes.slice_to(MAGIC.len()) == MAGIC;
+	let unseeded = bytes.len() >= UNSEEDED_MAGIC.len() && bytes.slice_to(UNSEEDED_MAGIC.len()) == UNSEEDED_MAGIC;
+	let seed_len = if seeded { 8 } else { 0 };
+	let header = MAGIC.len() + seed_len + 4;
+	if !(seeded || unseeded) || bytes.len() < header {
+		log::warn("ghost", format!("{} is not a ghost", path.display()));
+		return None;
+	}
+	let little_endian = |field: &[u8]| field.iter().enumerate()
+		.fold(0u64, |value, (i, &byte)| value | (byte as u64 << (8 * i)));
+	let seed = if seeded { Some(little_endian(bytes.slice(MAGIC.len(), MAGIC.len() + 8))) } else { None };
+	let count = little_endian(bytes.slice(header - 4, header)) as uint;
+	if bytes.len() < header + (count * 5) {
+		log::warn("ghost", format!("{} is too short", path.display()));
+		return None;
+	}
+
+	Some((seed, bytes.slice(header, header + (count * 5)).chunks(5).map(|frame| Frame {
+		x: ((frame[0] as u16) | (frame[1] as u16 << 8)) as i16,
+		y: ((frame[2] as u16) | (frame[3] as u16 << 8)) as i16,
+		movement: frame[4]
+	}).collect()))
+}
+
+/// Records where the player goes on a map, one frame each update, so
+/// that it may be raced against as a `Ghost` on later visits. The seed of
+/// the session's `rng::Rng` is saved along w/ it, so that the run may be
+/// played out again.
+pub struct Recorder {
+	priv map_path: ~str,
+	priv seed: u64,
+	priv frames: ~[Frame],
+	// a run interrupted (e.g: by restoring a savestate) is not kept
+	priv discarded: bool
+}
+
+impl Recorder {
+	/// Starts recording on the map at `map_path`, in a session seeded w/ `seed`.
+	pub fn new(map_path: ~str, seed: u64) -> Recorder {
+		Recorder { map_path: map_path, seed: seed, frames: ~[], discarded: false }
+	}
+
+	pub fn record(&mut self, quote: &player::Player) {
+		if !self.discarded { self.frames.push(Frame::of(quote)); }
+	}
+
+	/// Throws away this run, so that it is never saved.
+	pub fn discard(&mut self) {
+		self.discarded = true;
+		self.frames = ~[];
+	}
+
+	/// Saves the run as the map's ghost, should it have left the map
+	/// faster than the ghost saved before (or should there be none.)
+	pub fn save(&self) {
+		if self.discarded || self.frames.is_empty() { return; }
+		match read_frames(self.map_path.as_slice()) {
+			Some((_, ref best)) if best.len() <= self.frames.len() => return,
+			_ => {}
+		}
+
+		let path = ghost_path(self.map_path.as_slice());
+		match fs::mkdir_recursive(&path.dir_path(), io::UserDir) {
+			Ok(_) => {}
+			Err(msg) => { log::warn("ghost", format!("could not create ghosts directory: {}", msg)); return; }
+		}
+
+		let count = self.frames.len();
+		let mut bytes = MAGIC.to_owned();
+		for i in range(0, 8u) { bytes.push((self.seed >> (8 * i)) as u8); }
+		bytes.push_all([count as u8, (count >> 8) as u8, (count >> 16) as u8, (count >> 24) as u8]);
+		for frame in self.frames.iter() {
+			let (x, y) = (frame.x as u16, frame.y as u16);
+			bytes.push_all([x as u8, (x >> 8) as u8, y as u8, (y >> 8) as u8, frame.movement]);
+		}
+		match File::create(&path).write(bytes) {
+			Ok(_) => log::info("ghost", format!("new best of {} frames saved to {}", count, path.display())),
+			Err(msg) => log::warn("ghost", format!("ghost could not be saved: {}", msg))
+		}
+	}
+}
+
+/// A translucent copy of the player, replaying the best run saved for a
+/// map alongside them; it starts as the player enters the map, & vanishes
+/// once it has left.
+pub struct Ghost {
+	priv seed: Option<u64>,
+	priv frames: ~[Frame],
+	priv frame: uint,
+	priv sprites: HashMap<player::MotionTup, ~sprite::Updatable>
+}
+
+impl Ghost {
+	/// The ghost of the map at `map_path`, drawn w/ `quote`'s sprites, if it has one.
+	pub fn load(map_path: &str, quote: &player::Player) -> Option<Ghost> {
+		read_frames(map_path).map(|(seed, frames)| Ghost { seed: seed, frames: frames, frame: 0, sprites: quote.sprites() })
+	}
+
+	/// The seed of the session the ghost was recorded in, if it was saved w/ one.
+	pub fn seed(&self) -> Option<u64> { self.seed }
+
+	/// Steps the ghost on to its next frame.
+	pub fn update(&mut self, elapsed_time: units::Millis) {
+		if self.frame >= self.frames.len() { return; }
+		let frame = self.frames[self.frame].clone();
+		self.frame += 1;
+
+		match frame.movement() {
+			Some(movement) => match self.sprites.find_mut(&movement) {
+				Some(sprite) => {
+					sprite.set_position(frame.position());
+					sprite.update(elapsed_time);
+				}
+				None => {}
+			},
+			None => {}
+		}
+	}
+
+	/// Queues the ghost as it stands now, shifted by the camera's `offset`.
+	pub fn draw(&self, queue: &mut graphics::RenderQueue, offset: (units::Game, units::Game)) {
+		if self.frame == 0 || self.frame >= self.frames.len() { return; }
+		let movement = match self.frames[self.frame - 1].movement() {
+			Some(movement) => movement,
+			None => return
+		};
+		let params = graphics::DrawParams { tint: GHOST_TINT, alpha: GHOST_ALPHA, .. graphics::PLAIN };
+		match self.sprites.find(&movement) {
+			Some(sprite) => sprite.draw_queued_with(queue, graphics::Entities, offset, &params),
+			None => {}
+		}
+	}
+}
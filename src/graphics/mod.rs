@@ -0,0 +1,1258 @@
+use sdl2::pixels;
+use sdl2::rect;
+use sdl2::sdl;
+use sdl2::surface;
+use sdl2::surface::ll;
+use sdl2::render;
+use sdl2::mouse;
+use sdl2::video;
+use image = sdl2_image;
+use sdl2_image::{LoadSurface, SaveSurface};
+use sdl2_ttf;
+
+use std::cmp;
+use std::i32;
+use std::io::File;
+use std::mem;
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use collections::hashmap::HashMap;
+
+use game;
+use game::assets;
+use game::config;
+use game::i18n;
+use game::log;
+use game::sprite;
+use game::vfs;
+
+use game::error::{GameResult, AtlasError, ImageError, FontError};
+
+use game::collisions::Rectangle;
+use game::sprite::Drawable;
+use game::units;
+use game::units::{AsGame, AsPixel};
+
+// Full-screen effects drawn over everything else
+pub mod transitions;
+// Text rendered from TrueType fonts
+pub mod truetype;
+
+// darkness is drawn as horizontal strips of this many pixels
+static DARKNESS_STRIP: i32 = 2;
+
+// the placeholder for missing images: a square large enough to cover any
+// region of a sheet, checkered in squares of `PLACEHOLDER_CHECK` pixels
+static PLACEHOLDER_SIZE: int = 512;
+static PLACEHOLDER_CHECK: int = 8;
+
+// glyph sheets hold printable ascii, left-to-right, top-to-bottom
+static GLYPHS_PER_ROW: uint = 16;
+static FIRST_GLYPH: char 	= ' ';
+static LAST_GLYPH: char 	= '~';
+
+/// How the pixels of an image are treated once it has been loaded.
+#[deriving(Clone)]
+pub struct ImageOptions {
+	/// Pixels of this `(r, g, b)` color are made transparent.
+	color_key: Option<(u8, u8, u8)>
+}
+
+/// Every pixel of the image is drawn, e.g: for backdrops.
+pub static OPAQUE: ImageOptions = ImageOptions { color_key: None };
+/// Black pixels are transparent, as on most of the game's sprite sheets.
+pub static BLACK_KEYED: ImageOptions = ImageOptions { color_key: Some((0, 0, 0)) };
+
+/// A circle of light which cuts through darkness, centered on (`x`, `y`).
+pub struct Light {
+	x: units::Game,
+	y: units::Game,
+	radius: units::Game
+}
+
+/// How a copy's pixels are combined w/ what is already on the screen.
+#[deriving(Eq,Clone)]
+pub enum Blend {
+	/// Drawn over the screen, blended by the pixels' alpha.
+	Normal,
+	/// Added to the screen, brightening it, e.g: for glowing shots.
+	Additive
+}
+
+/// How a copy of a texture is flipped, turned & colored as it is drawn.
+///
+/// Flipping lets one sheet face both ways; rotation (clockwise, about
+/// the center of the copy) lets e.g: shots spin as they fly. Tinting &
+/// fading need no extra art for e.g: damage flashes & dying enemies.
+#[deriving(Eq,Clone)]
+pub struct DrawParams {
+	flip_horizontal: bool,
+	flip_vertical: bool,
+	angle: units::Degrees,
+	/// Multiplies each pixel's `(r, g, b)`; white leaves colors as they are.
+	tint: (u8, u8, u8),
+	/// Multiplies each pixel's opacity; `255` leaves it as it is.
+	alpha: u8,
+	blend: Blend
+}
+
+/// Drawn as it is on the sheet.
+pub static PLAIN: DrawParams = DrawParams {
+	flip_horizontal: false, flip_vertical: false, angle: units::Degrees(0.0),
+	tint: (255, 255, 255), alpha: 255, blend: Normal
+};
+
+/// A handle to an image loaded by the graphics subsystem's `Backend`.
+#[deriving(Eq,Clone)]
+pub struct Texture(uint);
+
+/// Whatever actually puts pixels on the screen.
+///
+/// `SdlBackend` draws through an SDL2 renderer; `NullBackend` merely
+/// records what it is asked to draw, so the game can run w/o a display.
+pub trait Backend {
+	/// Loads the image at `path`, treating its pixels as `options` describe.
+	fn load_texture(&mut self, path: &Path, options: ImageOptions) -> Result<Texture, ~str>;
+	/// Makes a texture `width` x `height` large from `pixels`, 4 bytes (ARGB) apiece.
+	fn create_texture(&mut self, width: int, height: int, pixels: ~[u8]) -> Result<Texture, ~str>;
+	/// Makes a texture of an image already in memory, e.g: rendered text.
+	fn create_texture_from_surface(&mut self, image: ~surface::Surface) -> Result<Texture, ~str>;
+	/// Replaces the image of `texture` w/ the one now at `path`, so that
+	/// everything drawn from it draws the new image.
+	fn reload_texture(&mut self, texture: Texture, path: &Path, options: ImageOptions) -> Result<(), ~str>;
+	/// Frees a texture; any later copies of it are ignored.
+	fn free_texture(&mut self, texture: Texture);
+
+	/// Copies `src_rect` of `texture` to `dest_rect` on the screen.
+	fn copy(&self, texture: Texture, src_rect: &rect::Rect, dest_rect: &rect::Rect);
+	/// Copies as `copy` does, flipped, rotated & colored by `params`.
+	fn copy_with(&self, texture: Texture, src_rect: &rect::Rect, dest_rect: &rect::Rect, params: &DrawParams);
+	/// Fills `dest_rect` w/ a solid `color`, blended by the color's alpha.
+	fn fill_rect(&self, dest_rect: &rect::Rect, color: pixels::Color);
+	fn clear(&self);
+	fn present(&self);
+
+	/// The size of the window, in (real) pixels.
+	fn output_size(&self) -> Result<(int, int), ~str>;
+	/// Scales everything drawn by `scale`, & draws it within `viewport`
+	/// (which is measured in unscaled pixels.)
+	fn set_view(&self, scale: int, viewport: &rect::Rect);
+	fn set_fullscreen(&self, fullscreen: bool) -> Result<(), ~str>;
+	/// Resizes the window (when not fullscreen) to `width` x `height` (real) pixels.
+	fn set_window_size(&self, width: int, height: int) -> Result<(), ~str>;
+
+	/// Saves whatever is on the screen to an image at `path`.
+	fn save_screenshot(&self, path: &Path) -> Result<(), ~str>;
+}
+
+/// Draws to a window through an SDL2 renderer.
+pub struct SdlBackend {
+	priv screen: ~render::Renderer,
+	// indexed by texture; freed textures leave a `None` behind
+	priv textures: ~[Option<~render::Texture>]
+}
+
+impl SdlBackend {
+	pub fn new(screen: ~render::Renderer) -> SdlBackend {
+		// .bmp sheets are loaded by SDL itself; everything else by SDL_image
+		image::init([image::InitPng]);
+		SdlBackend { screen: screen, textures: ~[] }
+	}
+}
+
+impl Backend for SdlBackend {
+	/// The format is chosen by the file's extension: `.bmp` files are read
+	/// directly, others (e.g: `.png` sheets w/ an alpha channel) through SDL_image.
+	fn load_texture(&mut self, path: &Path, options: ImageOptions) -> Result<Texture, ~str> {
+		let is_bitmap = match path.extension_str() {
+			Some(extension) => extension.eq_ignore_ascii_case("bmp"),
+			None => true
+		};
+		let sprite_window = if is_bitmap {
+			surface::Surface::from_bmp(path)
+		} else {
+			LoadSurface::from_file(path)
+		};
+
+		let sprite = match sprite_window {
+			Ok(sprite) => sprite,
+			Err(msg) => return Err(format!("sprite could not be loaded: {}", msg))
+		};
+		match options.color_key {
+			Some((r, g, b)) => unsafe {
+				let key = pixels::ll::SDL_MapRGB((*sprite.raw).format, r, g, b);
+				ll::SDL_SetColorKey(sprite.raw, 1, key);
+			},
+			None => {}
+		}
+
+		// wrap surface in texture and store it
+		match self.screen.create_texture_from_surface(sprite) {
+			Ok(texture) => {
+				self.textures.push(Some(texture));
+				Ok(Texture(self.textures.len() - 1))
+			}
+			Err(msg) => Err(format!("sprite could not be rendered: {}", msg))
+		}
+	}
+
+	fn create_texture(&mut self, width: int, height: int, pixels: ~[u8]) -> Result<Texture, ~str> {
+		let image = match surface::Surface::from_data(
+			pixels, width, height, 32, width * 4,
+			0x00ff0000, 0x0000ff00, 0x000000ff, 0xff000000
+		) {
+			Ok(image) => image,
+			Err(msg) => return Err(msg)
+		};
+		self.create_texture_from_surface(image)
+	}
+
+	fn create_texture_from_surface(&mut self, image: ~surface::Surface) -> Result<Texture, ~str> {
+		match self.screen.create_texture_from_surface(image) {
+			Ok(texture) => {
+				self.textures.push(Some(texture));
+				Ok(Texture(self.textures.len() - 1))
+			}
+			Err(msg) => Err(format!("texture could not be created: {}", msg))
+		}
+	}
+
+	fn reload_texture(&mut self, texture: Texture, path: &Path, options: ImageOptions) -> Result<(), ~str> {
+		let Texture(id) = texture;
+		if id >= self.textures.len() { return Err(format!("no texture {}", id)); }
+
+		// the fresh texture takes the old one's slot, & the old is dropped
+		let Texture(fresh) = try!(self.load_texture(path, options));
+		self.textures.swap(id, fresh);
+		self.textures.pop();
+		Ok(())
+	}
+
+	fn free_texture(&mut self, texture: Texture) {
+		let Texture(id) = texture;
+		if id < self.textures.len() { self.textures[id] = None; }
+	}
+
+	fn copy(&self, texture: Texture, src_rect: &rect::Rect, dest_rect: &rect::Rect) {
+		let Texture(id) = texture;
+		if id >= self.textures.len() { return; }
+		match self.textures[id] {
+			Some(ref texture) => { self.screen.copy(*texture, Some(*src_rect), Some(*dest_rect)); }
+			None => {}
+		}
+	}
+
+	fn copy_with(&self, texture: Texture, src_rect: &rect::Rect, dest_rect: &rect::Rect, params: &DrawParams) {
+		let Texture(id) = texture;
+		if id >= self.textures.len() { return; }
+
+		// SDL flips one way at a time; flipping both ways is a half turn
+		let units::Degrees(angle) = params.angle;
+		let (flip, angle) = match (params.flip_horizontal, params.flip_vertical) {
+			(false, false) 	=> (render::FlipNone, angle),
+			(true, false) 	=> (render::FlipHorizontal, angle),
+			(false, true) 	=> (render::FlipVertical, angle),
+			(true, true) 	=> (render::FlipNone, angle + 180.0)
+		};
+		let (r, g, b) = params.tint;
+		let blend = match params.blend {
+			Normal 		=> render::BlendBlend,
+			Additive 	=> render::BlendAdd
+		};
+
+		match self.textures[id] {
+			Some(ref texture) => {
+				texture.set_color_mod(r, g, b);
+				texture.set_alpha_mod(params.alpha);
+				texture.set_blend_mode(blend);
+				self.screen.copy_ex(*texture, Some(*src_rect), Some(*dest_rect), angle, None, flip);
+
+				// the texture is shared by every sprite cut from it
+				texture.set_color_mod(255, 255, 255);
+				texture.set_alpha_mod(255);
+				texture.set_blend_mode(render::BlendBlend);
+			}
+			None => {}
+		}
+	}
+
+	fn fill_rect(&self, dest_rect: &rect::Rect, color: pixels::Color) {
+		self.screen.set_blend_mode(render::BlendBlend);
+		self.screen.set_draw_color(color);
+		self.screen.fill_rect(dest_rect);
+		self.screen.set_draw_color(pixels::RGBA(0, 0, 0, 255));
+	}
+
+	fn clear(&self) { self.screen.clear(); }
+	fn present(&self) { self.screen.present(); }
+
+	fn output_size(&self) -> Result<(int, int), ~str> {
+		self.screen.get_output_size()
+	}
+
+	fn set_view(&self, scale: int, viewport: &rect::Rect) {
+		self.screen.set_scale(scale as f64, scale as f64);
+		self.screen.set_viewport(Some(*viewport));
+	}
+
+	fn set_fullscreen(&self, fullscreen: bool) -> Result<(), ~str> {
+		let mode = if fullscreen { video::FTDesktop } else { video::FTOff };
+		match *self.screen.get_parent() {
+			render::Window(ref window) => {
+				if window.set_fullscreen(mode) { Ok(()) } else { Err(sdl::get_error()) }
+			}
+			_ => Err(~"the renderer does not draw to a window")
+		}
+	}
+
+	fn set_window_size(&self, width: int, height: int) -> Result<(), ~str> {
+		match *self.screen.get_parent() {
+			render::Window(ref window) => { window.set_size(width, height); Ok(()) }
+			_ => Err(~"the renderer does not draw to a window")
+		}
+	}
+
+	/// The format is chosen by the file's extension, as w/ `load_texture`.
+	fn save_screenshot(&self, path: &Path) -> Result<(), ~str> {
+		let (w, h) = match self.output_size() {
+			Ok(size) => size,
+			Err(msg) => return Err(msg)
+		};
+		let data = match self.screen.read_pixels(None, pixels::ARGB8888) {
+			Ok(data) => data,
+			Err(msg) => return Err(format!("the screen could not be read: {}", msg))
+		};
+		let shot = match surface::Surface::from_data(
+			data, w, h, 32, w * 4,
+			0x00ff0000, 0x0000ff00, 0x000000ff, 0xff000000
+		) {
+			Ok(shot) => shot,
+			Err(msg) => return Err(msg)
+		};
+
+		let is_bitmap = match path.extension_str() {
+			Some(extension) => extension.eq_ignore_ascii_case("bmp"),
+			None => true
+		};
+		if is_bitmap { shot.save_bmp(path) } else { shot.save(path) }
+	}
+}
+
+/// A single call made to a `NullBackend`.
+#[deriving(Eq,Clone)]
+pub enum DrawCall {
+	/// A texture, its source rectangle, & where it was copied to.
+	CopyTexture(Texture, rect::Rect, rect::Rect),
+	/// As `CopyTexture`, for a copy which was flipped or rotated.
+	CopyWith(Texture, rect::Rect, rect::Rect, DrawParams),
+	FillRect(rect::Rect, pixels::Color),
+	Clear,
+	Present
+}
+
+/// A backend w/o a display, for running the game headless (e.g: in CI.)
+///
+/// Images are never read: every texture is simply handed a new id.
+/// Each call is appended to `calls()`, a log shared w/ whoever created
+/// the backend, so that what was drawn may be inspected.
+pub struct NullBackend {
+	priv calls: Rc<RefCell<~[DrawCall]>>,
+	priv textures: uint,
+	// the (unscaled) size reported for the missing window
+	priv size: (int, int)
+}
+
+impl NullBackend {
+	/// A backend pretending to draw to a window of `resolution`.
+	pub fn new(resolution: units::Resolution) -> NullBackend {
+		let (units::Pixel(w), units::Pixel(h)) = resolution.to_pixel();
+		NullBackend { calls: Rc::new(RefCell::new(~[])), textures: 0, size: (w as int, h as int) }
+	}
+
+	/// The log of every call made to this backend so far.
+	pub fn calls(&self) -> Rc<RefCell<~[DrawCall]>> { self.calls.clone() }
+
+	fn record(&self, call: DrawCall) {
+		self.calls.borrow().borrow_mut().get().push(call);
+	}
+}
+
+#[allow(unused_variable)]
+impl Backend for NullBackend {
+	fn load_texture(&mut self, path: &Path, options: ImageOptions) -> Result<Texture, ~str> {
+		self.textures += 1;
+		Ok(Texture(self.textures - 1))
+	}
+
+	fn create_texture(&mut self, width: int, height: int, pixels: ~[u8]) -> Result<Texture, ~str> {
+		self.textures += 1;
+		Ok(Texture(self.textures - 1))
+	}
+
+	fn create_texture_from_surface(&mut self, image: ~surface::Surface) -> Result<Texture, ~str> {
+		self.textures += 1;
+		Ok(Texture(self.textures - 1))
+	}
+
+	fn reload_texture(&mut self, texture: Texture, path: &Path, options: ImageOptions) -> Result<(), ~str> { Ok(()) }
+
+	fn free_texture(&mut self, texture: Texture) {}
+
+	fn copy(&self, texture: Texture, src_rect: &rect::Rect, dest_rect: &rect::Rect) {
+		self.record(CopyTexture(texture, *src_rect, *dest_rect));
+	}
+
+	fn copy_with(&self, texture: Texture, src_rect: &rect::Rect, dest_rect: &rect::Rect, params: &DrawParams) {
+		self.record(CopyWith(texture, *src_rect, *dest_rect, *params));
+	}
+
+	fn fill_rect(&self, dest_rect: &rect::Rect, color: pixels::Color) {
+		self.record(FillRect(*dest_rect, color));
+	}
+
+	fn clear(&self) { self.record(Clear); }
+	fn present(&self) { self.record(Present); }
+
+	fn output_size(&self) -> Result<(int, int), ~str> { Ok(self.size) }
+	fn set_view(&self, scale: int, viewport: &rect::Rect) {}
+	fn set_fullscreen(&self, fullscreen: bool) -> Result<(), ~str> { Ok(()) }
+	fn set_window_size(&self, width: int, height: int) -> Result<(), ~str> { Ok(()) }
+	fn save_screenshot(&self, path: &Path) -> Result<(), ~str> { Ok(()) }
+}
+
+/// Acts as a buffer to the underlying display
+///
+/// The game is always drawn at its own resolution, then scaled up by the
+/// largest whole number which fits the window (so pixels stay square &
+/// sharp) and centered between black bars.
+pub struct Graphics {
+	priv backend: ~Backend,
+	priv assets: assets::AssetManager,
+	// drawn in place of any image which could not be loaded
+	priv placeholder: Option<Texture>,
+	priv fullscreen: bool,
+	priv resolution: units::Resolution,
+	// draw calls culled (for lying off-screen) since the buffer was last cleared
+	priv culled: Cell<uint>,
+	// the TrueType font (& point size) named by the settings, if any
+	priv font: Option<(~str, uint)>,
+	// each TrueType font opened, by `<path>@<point size>`
+	priv typefaces: HashMap<~str, truetype::Typeface>
+}
+
+impl Graphics {
+	/// Prepare the display for rendering, w/ a window as described by `settings`
+	pub fn new(settings: &config::Settings) -> Graphics {
+		let (units::Pixel(w), units::Pixel(h)) = settings.resolution.to_pixel();
+		let scale = settings.scale as int;
+		
+		let current_mode = ~video::Window::new(
+			"rust-story v0.0",							// title
+			video::PosCentered, video::PosCentered,		// position (x,y)
+			w as int * scale, h as int * scale,	
+			[video::InputGrabbed, video::Resizable]
+		);
+
+		// only a hardware renderer can wait on the display's vsync
+		let renderer_flags = match settings.frame_limit {
+			config::VSync => ~[render::Accelerated, render::PresentVSync],
+			config::Sleep | config::Uncapped => ~[render::Software]
+		};
+		let render_context = render::Renderer::from_window(
+			current_mode.unwrap(),
+			render::DriverAuto,
+			renderer_flags
+		);
+
+		let graphics = match render_context {
+			Ok(renderer) => Graphics::with_backend(~SdlBackend::new(renderer) as ~Backend, settings),
+			Err(_) => {fail!("Could not create a renderer using SDL2.");}
+		};
+
+		mouse::show_cursor(false);
+		return graphics;
+	}
+
+	/// Prepare `backend` for rendering, e.g: a `NullBackend` for running headless.
+	pub fn with_backend(backend: ~Backend, settings: &config::Settings) -> Graphics {
+		let mut graphics = Graphics {
+			backend: backend,
+			assets: assets::AssetManager::new(),
+			placeholder: None,
+			fullscreen: false,
+			resolution: settings.resolution,
+			culled: Cell::new(0),
+			font: settings.font.clone(),
+			typefaces: HashMap::new()
+		};
+
+		if settings.hot_reload { graphics.assets.watch(); }
+		if graphics.font.is_some() && !sdl2_ttf::init() {
+			log::warn("graphics", format!("SDL_ttf could not be started: {}", sdl::get_error()));
+			graphics.font = None;
+		}
+
+		if settings.fullscreen {
+			graphics.toggle_fullscreen();
+		} else {
+			graphics.fit_to_window();
+		}
+		graphics
+	}
+
+	/// Switches between the window & (borderless) fullscreen on the desktop.
+	pub fn toggle_fullscreen(&mut self) {
+		self.fullscreen = !self.fullscreen;
+		match self.backend.set_fullscreen(self.fullscreen) {
+			Ok(_) => {}
+			Err(msg) => { log::warn("graphics", format!("could not change fullscreen mode: {}", msg)); }
+		}
+		self.fit_to_window();
+	}
+
+	pub fn is_fullscreen(&self) -> bool { self.fullscreen }
+
+	/// Resizes the window to `scale` times the game's resolution, e.g:
+	/// once the window scale has been changed in the options menu.
+	pub fn set_scale(&self, scale: uint) {
+		let (units::Pixel(w), units::Pixel(h)) = self.resolution.to_pixel();
+		match self.backend.set_window_size(w as int * scale as int, h as int * scale as int) {
+			Ok(_) => {}
+			Err(msg) => { log::warn("graphics", format!("could not resize the window: {}", msg)); }
+		}
+		self.fit_to_window();
+	}
+
+	/// The size of the screen the game is drawn at, before any scaling.
+	pub fn resolution(&self) -> units::Resolution { self.resolution }
+
+	/// Scales the game by the largest whole number which fits the window,
+	/// and centers it; the rest of the window is left as black bars.
+	///
+	/// Called whenever the window changes size.
+	pub fn fit_to_window(&self) {
+		let (units::Pixel(w), units::Pixel(h)) = self.resolution.to_pixel();
+		let (window_w, window_h) = match self.backend.output_size() {
+			Ok(size) => size,
+			Err(msg) => { log::warn("graphics", format!("could not read the window's size: {}", msg)); return; }
+		};
+
+		let scale = cmp::max(1, cmp::min(window_w / w as int, window_h / h as int));
+
+		// the viewport is measured in game pixels, i.e: before scaling
+		let margin_x = ((window_w - w as int * scale) / 2) / scale;
+		let margin_y = ((window_h - h as int * scale) / 2) / scale;
+		self.backend.set_view(scale, &rect::Rect::new(margin_x as i32, margin_y as i32, w, h));
+	}
+
+	/// Loads an image which resides at `file_path` and returns a handle
+	/// This handle can safely be used in any of the graphics subsystem's rendering
+	/// contexts.
+	///
+	/// Images are cached by path, so `options` only take effect the first
+	/// time a given image is loaded. Each load is counted as a holder of
+	/// the image, and should be matched by a `release_image`.
+	///
+	/// An image which can not be loaded is replaced by a placeholder, &
+	/// a warning logged; an error is only returned if that fails as well.
+	pub fn load_image(&mut self, file_path: ~str, options: ImageOptions) -> GameResult<Texture> {
+		// Retrieve a handle or generate a new one if it exists already.
+		match self.assets.textures.acquire(file_path.as_slice()) {
+			Some((texture, _)) => return Ok(texture),
+			None => {}
+		}
+
+		self.assets.record_load(file_path.as_slice());
+		match self.backend.load_texture(&vfs::resolve(file_path.as_slice()), options) {
+			Ok(texture) => {
+				log::debug("graphics", format!("loaded image {}", file_path));
+				self.assets.textures.insert(file_path, (texture, options));
+				Ok(texture)
+			}
+			Err(msg) => {
+				// a missing image is drawn as a checkerboard, rather than ending the game
+				let error = ImageError(file_path.clone(), msg);
+				let placeholder = match self.placeholder_texture() {
+					Some(texture) => texture,
+					None => return Err(error)
+				};
+				log::warn("graphics", format!("{}; drawing a placeholder instead", error.message()));
+				self.assets.textures.insert(file_path, (placeholder, options));
+				Ok(placeholder)
+			}
+		}
+	}
+
+	/// The magenta & black checkerboard drawn in place of missing images,
+	/// created the first time it is needed.
+	fn placeholder_texture(&mut self) -> Option<Texture> {
+		if self.placeholder.is_some() { return self.placeholder; }
+
+		let mut pixels: ~[u8] = ~[];
+		for y in range(0, PLACEHOLDER_SIZE) {
+			for x in range(0, PLACEHOLDER_SIZE) {
+				let magenta = ((x / PLACEHOLDER_CHECK) + (y / PLACEHOLDER_CHECK)) % 2 == 0;
+				// (blue, green, red, alpha): ARGB in little-endian order
+				if magenta {
+					pixels.push_all([255, 0, 255, 255]);
+				} else {
+					pixels.push_all([0, 0, 0, 255]);
+				}
+			}
+		}
+
+		match self.backend.create_texture(PLACEHOLDER_SIZE, PLACEHOLDER_SIZE, pixels) {
+			Ok(texture) => { self.placeholder = Some(texture); }
+			Err(msg) => { log::error("graphics", format!("placeholder could not be created: {}", msg)); }
+		}
+		self.placeholder
+	}
+
+	/// The path of the image `texture` was loaded from (& the options it
+	/// was loaded w/), e.g: to name it to another machine. Textures made
+	/// in memory, & the placeholder, have none.
+	pub fn image_source(&self, texture: Texture) -> Option<(~str, ImageOptions)> {
+		if Some(texture) == self.placeholder { return None; }
+		self.assets.textures.find_where(|&(loaded, _)| loaded == texture)
+			.map(|(path, (_, options))| (path, options))
+	}
+
+	/// Counts one fewer holder of the image at `file_path`; once it has
+	/// none it is freed by the next `unload_unused()`.
+	pub fn release_image(&mut self, file_path: &str) {
+		self.assets.textures.release(file_path);
+	}
+
+	/// Frees every image (& map source) which nothing holds any longer,
+	/// e.g: those of a map which has just been left.
+	pub fn unload_unused(&mut self) {
+		for &texture in self.assets.unload_unused().iter() {
+			// the placeholder is shared by every missing image
+			if Some(texture) != self.placeholder { self.backend.free_texture(texture); }
+		}
+	}
+
+	/// Reloads, in place, each image modified on disk since it was loaded,
+	/// so every sprite cut from it draws the new image at once. Modified
+	/// maps are re-read as well, & their paths returned: only whoever
+	/// holds a map can rebuild it.
+	///
+	/// Does nothing unless `hot_reload` is on in the settings.
+	pub fn reload_changed(&mut self) -> ~[~str] {
+		let mut maps = ~[];
+		for path in self.assets.changed_files().move_iter() {
+			match self.assets.textures.find(path.as_slice()) {
+				// sprites of a missing image hold the shared placeholder, which can't be swapped out
+				Some((texture, _)) if Some(texture) == self.placeholder => {
+					log::info("graphics", format!("{} has appeared; restart to draw it", path));
+				}
+				Some((texture, options)) => {
+					match self.backend.reload_texture(texture, &vfs::resolve(path.as_slice()), options) {
+						Ok(_) => log::info("graphics", format!("reloaded image {}", path)),
+						Err(msg) => log::warn("graphics", format!("image {} could not be reloaded: {}", path, msg))
+					}
+				}
+				None => match self.assets.reread_map(path.as_slice()) {
+					Ok(_) => { log::info("graphics", format!("reloaded map {}", path)); maps.push(path); }
+					Err(msg) => log::warn("graphics", format!("map {} could not be reloaded: {}", path, msg))
+				}
+			}
+		}
+		maps
+	}
+
+	/// Loads each image & map listed in `manifest`. (They are never released.)
+	pub fn preload(&mut self, manifest: &assets::Manifest) {
+		let images = manifest.paths_of(assets::Image).move_iter().map(|path| (path, BLACK_KEYED));
+		let opaque = manifest.paths_of(assets::OpaqueImage).move_iter().map(|path| (path, OPAQUE));
+		for (path, options) in images.chain(opaque) {
+			match self.load_image(path, options) {
+				Ok(_) => {}
+				Err(err) => log::warn("graphics", err.message())
+			}
+		}
+
+		for path in manifest.paths_of(assets::Map).iter() {
+			match self.assets.load_map_source(path.as_slice()) {
+				Ok(_) => {}
+				Err(msg) => log::warn("graphics", format!("map {} could not be preloaded: {}", *path, msg))
+			}
+		}
+	}
+
+	/// The TrueType font (& its point size) the game's text is drawn in,
+	/// if the settings name one; otherwise text is drawn from `FONT_PATH`.
+	pub fn truetype_font(&self) -> Option<(~str, uint)> { self.font.clone() }
+
+	/// The glyphs of the TrueType font at `file_path` at `point_size`, &
+	/// the height of its lines (in pixels.)
+	///
+	/// Each font is opened once, & its glyphs shared by everything drawn
+	/// in it; those of printable ascii & of the current language's text
+	/// are rendered straight away, any others by `prepare_glyphs`.
+	pub fn load_typeface(&mut self, file_path: &str, point_size: uint) -> GameResult<(truetype::GlyphTable, units::Pixel)> {
+		let key = format!("{}@{}", file_path, point_size);
+		if !self.typefaces.contains_key(&key) {
+			let typeface = match truetype::Typeface::open(&vfs::resolve(file_path), point_size) {
+				Ok(typeface) => typeface,
+				Err(msg) => return Err(FontError(file_path.to_owned(), msg))
+			};
+			log::debug("graphics", format!("loaded font {} at {}pt", file_path, point_size));
+			self.typefaces.insert(key.clone(), typeface);
+
+			let mut chars: ~[char] = range(FIRST_GLYPH as u8, LAST_GLYPH as u8 + 1).map(|c| c as char).collect();
+			chars.push_all(i18n::characters());
+			self.prepare_glyphs(chars);
+		}
+
+		let typeface = self.typefaces.get(&key);
+		Ok((typeface.glyphs(), typeface.line_height()))
+	}
+
+	/// Renders the glyphs of `chars` in every TrueType font opened so far,
+	/// e.g: once the language has been switched, or before showing a
+	/// message which was written w/o a key.
+	pub fn prepare_glyphs(&mut self, chars: &[char]) {
+		for (key, typeface) in self.typefaces.mut_iter() {
+			for &(c, ref msg) in typeface.render(&mut *self.backend, chars).iter() {
+				log::warn("graphics", format!("{}: `{}` could not be rendered: {}", *key, c, *msg));
+			}
+		}
+	}
+
+	/// The caches of everything loaded through the graphics subsystem.
+	pub fn assets_mut<'a>(&'a mut self) -> &'a mut assets::AssetManager { &mut self.assets }
+	
+
+	pub fn blit_surface(
+		&self, 
+		src: Texture, 
+		src_rect: &rect::Rect, 
+		dest_rect: &rect::Rect
+	) {
+		self.backend.copy(src, src_rect, dest_rect);
+	}
+
+	/// Blits as `blit_surface` does, flipped, rotated & colored by `params`.
+	pub fn blit_with(
+		&self, 
+		src: Texture, 
+		src_rect: &rect::Rect, 
+		dest_rect: &rect::Rect,
+		params: &DrawParams
+	) {
+		if *params == PLAIN {
+			self.backend.copy(src, src_rect, dest_rect);
+		} else {
+			self.backend.copy_with(src, src_rect, dest_rect, params);
+		}
+	}
+
+	/// Fills `dest_rect` w/ a solid `color`, blended by the color's alpha.
+	pub fn fill_rect(&self, dest_rect: &rect::Rect, color: pixels::Color) {
+		self.backend.fill_rect(dest_rect, color);
+	}
+
+	/// Draws the edges of `rectangle` in `color`, shifted by the camera's `offset`.
+	pub fn draw_outline(&self, rectangle: &Rectangle, offset: (units::Game, units::Game), color: pixels::Color) {
+		let (offset_x, offset_y) = offset;
+		let (units::Pixel(x), units::Pixel(y)) = ((rectangle.left() - offset_x).to_pixel(), (rectangle.top() - offset_y).to_pixel());
+		let (units::Pixel(w), units::Pixel(h)) = (rectangle.width().to_pixel(), rectangle.height().to_pixel());
+
+		self.fill_rect(&rect::Rect::new(x, y, w, 1), color);
+		self.fill_rect(&rect::Rect::new(x, y + h - 1, w, 1), color);
+		self.fill_rect(&rect::Rect::new(x, y, 1, h), color);
+		self.fill_rect(&rect::Rect::new(x + w - 1, y, 1, h), color);
+	}
+
+	/// Darkens everything drawn so far by blending black over the screen.
+	pub fn dim_screen(&self, alpha: u8) {
+		let (units::Pixel(w), units::Pixel(h)) = self.resolution.to_pixel();
+		self.fill_rect(&rect::Rect::new(0, 0, w, h), pixels::RGBA(0, 0, 0, alpha));
+	}
+
+	/// Covers the screen in black (blended by `alpha`) except within `lights`,
+	/// whose positions are given in screen space.
+	///
+	/// The screen is darkened one thin strip at a time, each strip skipping
+	/// the spans which fall inside a light.
+	pub fn draw_darkness(&self, alpha: u8, lights: &[Light]) {
+		let (units::Pixel(w), units::Pixel(h)) = self.resolution.to_pixel();
+		let color = pixels::RGBA(0, 0, 0, alpha);
+
+		// (x, y, radius) of each light, in pixels
+		let circles: ~[(f64, f64, f64)] = lights.iter().map(|light| {
+			let (units::Pixel(x), units::Pixel(y)) = (light.x.to_pixel(), light.y.to_pixel());
+			let units::Pixel(radius) = light.radius.to_pixel();
+			(x as f64, y as f64, radius as f64)
+		}).collect();
+
+		let mut strip_y = 0;
+		while strip_y < h {
+			let middle = (strip_y + (DARKNESS_STRIP / 2)) as f64;
+
+			// the lit span of each light crossing this strip, left to right
+			let mut lit: ~[(i32, i32)] = ~[];
+			for &(x, y, radius) in circles.iter() {
+				let distance = middle - y;
+				if distance * distance >= radius * radius { continue; }
+
+				let reach = (radius * radius - distance * distance).sqrt();
+				lit.push(((x - reach) as i32, (x + reach) as i32));
+			}
+			lit.sort();
+
+			let mut dark_from = 0;
+			for &(left, right) in lit.iter() {
+				if left > dark_from {
+					self.fill_rect(&rect::Rect::new(dark_from, strip_y, left - dark_from, DARKNESS_STRIP), color);
+				}
+				if right > dark_from { dark_from = right; }
+			}
+			if dark_from < w {
+				self.fill_rect(&rect::Rect::new(dark_from, strip_y, w - dark_from, DARKNESS_STRIP), color);
+			}
+
+			strip_y += DARKNESS_STRIP;
+		}
+	}
+
+	/// Saves the back-buffer, as drawn so far, to `path` (a `.bmp` or `.png`.)
+	///
+	/// Call this before `switch_buffers()`: afterwards the back-buffer's
+	/// contents are undefined.
+	pub fn capture_screenshot(&self, path: &Path) {
+		match self.backend.save_screenshot(path) {
+			Ok(_) => { log::info("graphics", format!("saved screenshot to {}", path.display())); }
+			Err(msg) => { log::warn("graphics", format!("screenshot could not be saved: {}", msg)); }
+		}
+	}
+
+	pub fn switch_buffers(&self) -> bool {
+		self.backend.present();
+		true
+	}
+
+	pub fn clear_buffer(&self) {
+		self.backend.clear();
+		self.culled.set(0);
+	}
+
+	/// Records that `count` draw calls were skipped for lying off-screen.
+	pub fn record_culled(&self, count: uint) {
+		self.culled.set(self.culled.get() + count);
+	}
+
+	/// How many draw calls have been culled since the buffer was last cleared.
+	pub fn culled(&self) -> uint { self.culled.get() }
+}
+
+/// Where a draw call falls in a frame: every call of one layer is drawn
+/// before any call of the next.
+#[deriving(Eq,Clone)]
+pub enum Layer {
+	/// Fixed & parallax backdrops.
+	Backdrop,
+	/// The tiles behind the player.
+	BackTiles,
+	/// The player, enemies, NPCs, shots & pickups.
+	Entities,
+	Particles,
+	/// The tiles in front of everything.
+	ForeTiles,
+	/// Anything drawn in screen space, atop the map, e.g: the HUD.
+	Overlay
+}
+
+impl Layer {
+	/// Responds true if calls of this layer are drawn from the top of the
+	/// screen down, so that whatever stands lower overlaps what is behind it.
+	/// (Calls of other layers are drawn in the order they were queued.)
+	pub fn is_y_sorted(&self) -> bool {
+		match *self {
+			Entities | Particles => true,
+			_ => false
+		}
+	}
+}
+
+/// A single queued call: a blit of part of a texture (from its source
+/// rectangle, to its destination, drawn as its params say), or a filled rectangle.
+pub enum Call {
+	Copy(Texture, rect::Rect, rect::Rect, DrawParams),
+	Fill(rect::Rect, pixels::Color)
+}
+
+struct Queued {
+	layer: Layer,
+	// how far down the screen the call reaches, for layers which are y-sorted
+	key: i32,
+	call: Call
+}
+
+impl Queued {
+	// sorted by layer, then key, then texture (fills before any texture)
+	fn order(&self) -> (uint, i32, uint) {
+		let texture = match self.call {
+			Copy(Texture(id), _, _, _) => id + 1,
+			Fill(_, _) => 0
+		};
+		(self.layer as uint, self.key, texture)
+	}
+}
+
+/// The draw calls of a frame, collected & then issued in a well-defined
+/// order: by layer, then (within y-sorted layers) by how far down the
+/// screen each reaches. So e.g: entities overlap one another the same
+/// way however the world happens to order them.
+///
+/// Calls which would otherwise be drawn together are grouped by the
+/// texture they are cut from, so that drawing a map tile-by-tile binds
+/// each tileset just once. Calls to the same texture keep the order
+/// they were queued in.
+///
+/// Calls which fall wholly outside the screen are dropped as they are
+/// queued, & counted towards `Graphics::culled` once the queue is flushed.
+pub struct RenderQueue {
+	priv calls: ~[Queued],
+	// the screen, in pixels
+	priv viewport: rect::Rect,
+	priv culled: uint
+}
+
+impl RenderQueue {
+	/// An empty queue, culling whatever falls outside the screen of `display`.
+	pub fn new(display: &Graphics) -> RenderQueue {
+		let (units::Pixel(w), units::Pixel(h)) = display.resolution().to_pixel();
+		RenderQueue { calls: ~[], viewport: rect::Rect::new(0, 0, w, h), culled: 0 }
+	}
+
+	/// An empty queue which culls nothing, e.g: for calls to be drawn on
+	/// another screen (see `take_calls`.)
+	pub fn unculled() -> RenderQueue {
+		let (min, max) = (i32::MIN / 2, i32::MAX);
+		RenderQueue { calls: ~[], viewport: rect::Rect::new(min, min, max, max), culled: 0 }
+	}
+
+	/// Queues a blit of `src_rect` on `texture` to `dest_rect`, on `layer`.
+	pub fn submit(&mut self, layer: Layer, texture: Texture, src_rect: &rect::Rect, dest_rect: &rect::Rect, params: &DrawParams) {
+		// a rotated blit may reach past its rectangle, by as much as half its diagonal
+		let bounds = if params.angle == units::Degrees(0.0) {
+			*dest_rect
+		} else {
+			let reach = cmp::max(dest_rect.w, dest_rect.h) / 2 + 1;
+			rect::Rect::new(dest_rect.x - reach, dest_rect.y - reach, dest_rect.w + reach * 2, dest_rect.h + reach * 2)
+		};
+		if !self.is_visible(&bounds) { self.culled += 1; return; }
+
+		let key = if layer.is_y_sorted() { dest_rect.y + dest_rect.h } else { 0 };
+		self.calls.push(Queued { layer: layer, key: key, call: Copy(texture, *src_rect, *dest_rect, *params) });
+	}
+
+	/// Queues a fill of `dest_rect` w/ `color`, on `layer`.
+	pub fn fill(&mut self, layer: Layer, dest_rect: &rect::Rect, color: pixels::Color) {
+		if !self.is_visible(dest_rect) { self.culled += 1; return; }
+
+		let key = if layer.is_y_sorted() { dest_rect.y + dest_rect.h } else { 0 };
+		self.calls.push(Queued { layer: layer, key: key, call: Fill(*dest_rect, color) });
+	}
+
+	// responds true if any of `bounds` lies on the screen
+	fn is_visible(&self, bounds: &rect::Rect) -> bool {
+		let screen = &self.viewport;
+		bounds.x < screen.x + screen.w && bounds.x + bounds.w > screen.x &&
+		bounds.y < screen.y + screen.h && bounds.y + bounds.h > screen.y
+	}
+
+	/// Issues every queued call to `display`, in order, and empties the queue.
+	pub fn flush(&mut self, display: &Graphics) {
+		display.record_culled(self.culled);
+		self.culled = 0;
+
+		self.calls.sort_by(|a, b| a.order().cmp(&b.order()));
+
+		for queued in self.calls.iter() {
+			match queued.call {
+				Copy(texture, ref src_rect, ref dest_rect, ref params) => display.blit_with(texture, src_rect, dest_rect, params),
+				Fill(ref dest_rect, color) => display.fill_rect(dest_rect, color)
+			}
+		}
+		self.calls = ~[];
+	}
+
+	/// Empties the queue w/o drawing anything, handing back each call (&
+	/// its layer) in the order it would have been drawn.
+	pub fn take_calls(&mut self) -> ~[(Layer, Call)] {
+		self.calls.sort_by(|a, b| a.order().cmp(&b.order()));
+		mem::replace(&mut self.calls, ~[]).move_iter().map(|queued| (queued.layer, queued.call)).collect()
+	}
+}
+
+// where a font's glyphs are drawn from
+enum Glyphs {
+	// cut from a sheet, w/ the index of each character beyond ascii
+	Sheet(~[sprite::Sprite], HashMap<char, uint>),
+	// rendered from a TrueType font
+	Rendered(truetype::GlyphTable)
+}
+
+/// A font for drawing text on screen: either a fixed-width bitmap font,
+/// loaded from a sheet of glyphs, or a TrueType font.
+///
+/// The sheet holds one `glyph_size` cell per printable ascii character,
+/// starting from ` ` (space). Any other (utf-8) characters the font has
+/// glyphs for are listed, in order, in a file beside the sheet w/ the
+/// extension `.chars` (e.g: `Font.chars` for `Font.bmp`); their glyphs
+/// follow `~` on the sheet.
+///
+/// A TrueType font needs no sheet: each character's glyph is rendered
+/// into a texture the first time it is needed (see `Graphics::load_typeface`),
+/// so text in any script can be drawn. Its glyphs are as wide as they are.
+///
+/// Either way, characters w/o a glyph draw as `?`.
+pub struct Font {
+	priv glyphs: Glyphs,
+	// the size of a cell of the sheet; only the height applies to rendered glyphs
+	priv glyph_size: (units::Game, units::Game)
+}
+
+impl Font {
+	/// The font the game's text is drawn in: the TrueType font named by
+	/// the settings, if there is one & it loads, else the bitmap `FONT_PATH`.
+	pub fn load(graphics: &mut Graphics) -> GameResult<Font> {
+		match graphics.truetype_font() {
+			Some((path, point_size)) => match Font::from_truetype(graphics, path.as_slice(), point_size) {
+				Ok(font) => return Ok(font),
+				Err(err) => { log::warn("graphics", format!("{}; using the bitmap font instead", err.message())); }
+			},
+			None => {}
+		}
+		Font::new(graphics, game::FONT_PATH.to_owned(), game::FONT_SIZE)
+	}
+
+	pub fn new(graphics: &mut Graphics, file_path: ~str, glyph_size: (units::Game, units::Game)) -> GameResult<Font> {
+		let (glyph_w, glyph_h) = glyph_size;
+		let num_ascii = (LAST_GLYPH as uint) - (FIRST_GLYPH as uint) + 1;
+
+		let mut extra = HashMap::new();
+		for c in Font::load_extra_chars(file_path.as_slice()).move_iter() {
+			if c > LAST_GLYPH && !extra.contains_key(&c) {
+				let index = num_ascii + extra.len();
+				extra.insert(c, index);
+			}
+		}
+
+		let mut glyphs: ~[sprite::Sprite] = ~[];
+		for glyph in range(0, num_ascii + extra.len()) {
+			glyphs.push(try!(sprite::Sprite::from_region(
+				graphics, (units::Game(0.0), units::Game(0.0)),
+				(glyph_w * units::Game((glyph % GLYPHS_PER_ROW) as f64),
+				 glyph_h * units::Game((glyph / GLYPHS_PER_ROW) as f64)),
+				glyph_size,
+				file_path.clone()
+			)));
+		}
+
+		Ok(Font { glyphs: Sheet(glyphs, extra), glyph_size: glyph_size })
+	}
+
+	/// The TrueType (or OpenType) font at `file_path`, at `point_size`.
+	pub fn from_truetype(graphics: &mut Graphics, file_path: &str, point_size: uint) -> GameResult<Font> {
+		let (table, line_height) = try!(graphics.load_typeface(file_path, point_size));
+		Ok(Font { glyphs: Rendered(table), glyph_size: (units::Game(0.0), line_height.to_game()) })
+	}
+
+	// the characters listed in the `.chars` file beside the sheet at `file_path`, if any
+	fn load_extra_chars(file_path: &str) -> ~[char] {
+		let chars_path = Path::new(file_path).with_extension("chars");
+		let resolved = match chars_path.as_str() {
+			Some(path) => vfs::resolve(path),
+			None => return ~[]
+		};
+		if !resolved.exists() { return ~[]; }
+
+		match File::open(&resolved).read_to_str() {
+			Ok(contents) => contents.chars().filter(|c| !c.is_whitespace()).collect(),
+			Err(msg) => {
+				log::warn("graphics", format!("{} could not be loaded: {}", chars_path.display(), msg));
+				~[]
+			}
+		}
+	}
+
+	/// Draws `text` in screen space on a single line, w/ its top-left at `(x, y)`.
+	pub fn draw_text(&self, display: &Graphics, x: units::Game, y: units::Game, text: &str) {
+		match self.glyphs {
+			Sheet(ref glyphs, ref extra) => {
+				let (glyph_w, _) = self.glyph_size;
+				let mut glyph_x = x;
+
+				for c in text.chars() {
+					// glyphs are positioned at the origin; shift them into place.
+					Font::sheet_glyph(glyphs.as_slice(), extra, c)
+						.draw(display, (units::Game(0.0) - glyph_x, units::Game(0.0) - y));
+					glyph_x = glyph_x + glyph_w;
+				}
+			}
+			Rendered(ref table) => {
+				for &(texture, ref src_rect, ref dest_rect) in Font::rendered_copies(table, x, y, text).iter() {
+					display.blit_surface(texture, src_rect, dest_rect);
+				}
+			}
+		}
+	}
+
+	/// Queues `text` on `layer` of `queue`, as `draw_text` w/ `params`.
+	pub fn queue_text(
+		&self,
+		queue: &mut RenderQueue, layer: Layer,
+		x: units::Game, y: units::Game,
+		text: &str, params: &DrawParams
+	) {
+		match self.glyphs {
+			Sheet(ref glyphs, ref extra) => {
+				let (glyph_w, _) = self.glyph_size;
+				let mut glyph_x = x;
+
+				for c in text.chars() {
+					Font::sheet_glyph(glyphs.as_slice(), extra, c)
+						.draw_queued_with(queue, layer, (units::Game(0.0) - glyph_x, units::Game(0.0) - y), params);
+					glyph_x = glyph_x + glyph_w;
+				}
+			}
+			Rendered(ref table) => {
+				for &(texture, ref src_rect, ref dest_rect) in Font::rendered_copies(table, x, y, text).iter() {
+					queue.submit(layer, texture, src_rect, dest_rect, params);
+				}
+			}
+		}
+	}
+
+	/// The width `text` occupies when drawn.
+	pub fn text_width(&self, text: &str) -> units::Game {
+		match self.glyphs {
+			Sheet(_, _) => {
+				let (glyph_w, _) = self.glyph_size;
+				glyph_w * units::Game(text.char_len() as f64)
+			}
+			Rendered(ref table) => {
+				let table = table.borrow().borrow();
+				text.chars().fold(units::Game(0.0), |width, c| {
+					match Font::rendered_glyph(table.get(), c) {
+						Some(glyph) => { let (w, _) = glyph.size; width + w.to_game() }
+						None => width
+					}
+				})
+			}
+		}
+	}
+
+	pub fn line_height(&self) -> units::Game {
+		let (_, glyph_h) = self.glyph_size;
+		glyph_h
+	}
+
+	fn sheet_glyph<'a>(glyphs: &'a [sprite::Sprite], extra: &HashMap<char, uint>, c: char) -> &'a sprite::Sprite {
+		if c > LAST_GLYPH {
+			match extra.find(&c) {
+				Some(&index) => return &glyphs[index],
+				None => {}
+			}
+		}
+		let c = if c < FIRST_GLYPH || c > LAST_GLYPH { '?' } else { c };
+		&glyphs[(c as uint) - (FIRST_GLYPH as uint)]
+	}
+
+	fn rendered_glyph<'a>(table: &'a HashMap<char, truetype::Glyph>, c: char) -> Option<&'a truetype::Glyph> {
+		table.find(&c).or_else(|| table.find(&'?'))
+	}
+
+	// the texture, source & (screen-space) destination of each character
+	// of `text`, laid out from `(x, y)` by each glyph's width
+	fn rendered_copies(
+		table: &truetype::GlyphTable,
+		x: units::Game, y: units::Game,
+		text: &str
+	) -> ~[(Texture, rect::Rect, rect::Rect)] {
+		let table = table.borrow().borrow();
+		let (units::Pixel(mut glyph_x), units::Pixel(glyph_y)) = (x.to_pixel(), y.to_pixel());
+
+		let mut copies = ~[];
+		for c in text.chars() {
+			match Font::rendered_glyph(table.get(), c) {
+				Some(glyph) => {
+					let (units::Pixel(w), units::Pixel(h)) = glyph.size;
+					copies.push((glyph.texture, rect::Rect::new(0, 0, w, h), rect::Rect::new(glyph_x, glyph_y, w, h)));
+					glyph_x += w;
+				}
+				None => {}
+			}
+		}
+		copies
+	}
+}
+
+/// A single sprite sheet whose regions are looked up by name, rather
+/// than by hard-coded offsets into the sheet.
+pub struct Atlas {
+	priv path: ~str,
+	priv sheet_path: ~str,
+	priv regions: HashMap<~str, ((units::Game, units::Game), (units::Game, units::Game))>
+}
+
+impl Atlas {
+	/// Loads the region definitions at `path`.
+	///
+	/// The file names its sheet w/ a `sheet <path>` line, followed by one
+	/// `<name> <x> <y> <width> <height>` line (in game units) per region.
+	/// Lines starting with `#` are ignored.
+	pub fn load(path: ~str) -> GameResult<Atlas> {
+		let contents = match File::open(&vfs::resolve(path.as_slice())).read_to_str() {
+			Ok(contents) => contents,
+			Err(msg) => { return Err(AtlasError(path, format!("{}", msg))); }
+		};
+
+		let mut sheet_path = None;
+		let mut regions = HashMap::new();
+		for raw_line in contents.lines() {
+			let line = raw_line.trim();
+			if line.len() == 0 || line.starts_with("#") { continue; }
+
+			let words: ~[&str] = line.words().collect();
+			match words.as_slice() {
+				["sheet", asset] => { sheet_path = Some(asset.to_owned()); }
+				[name, x, y, w, h] => {
+					regions.insert(name.to_owned(), (
+						(try!(Atlas::parse_game(x, &path)), try!(Atlas::parse_game(y, &path))),
+						(try!(Atlas::parse_game(w, &path)), try!(Atlas::parse_game(h, &path)))
+					));
+				}
+				_ => { return Err(AtlasError(path.clone(), format!("unrecognized line `{}`", line))); }
+			}
+		}
+
+		match sheet_path {
+			Some(sheet_path) => Ok(Atlas { path: path, sheet_path: sheet_path, regions: regions }),
+			None => Err(AtlasError(path, ~"no sheet is named"))
+		}
+	}
+
+	pub fn sheet_path(&self) -> ~str { self.sheet_path.clone() }
+
+	/// The `(offset, size)` of the region `name` on the sheet.
+	pub fn region(&self, name: &str) -> GameResult<((units::Game, units::Game), (units::Game, units::Game))> {
+		match self.regions.find_equiv(&name) {
+			Some(&region) => Ok(region),
+			None => Err(AtlasError(self.path.clone(), format!("no region is named `{}`", name)))
+		}
+	}
+
+	fn parse_game(word: &str, path: &~str) -> GameResult<units::Game> {
+		match from_str::<f64>(word) {
+			Some(value) => Ok(units::Game(value)),
+			None => Err(AtlasError(path.clone(), format!("expected a number, found `{}`", word)))
+		}
+	}
+}
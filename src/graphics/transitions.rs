@@ -0,0 +1,109 @@
+use sdl2::pixels;
+use sdl2::rect;
+use std::cmp;
+
+use game::graphics;
+use game::units;
+use game::units::{AsPixel};
+
+/// How a transition covers (or reveals) the screen.
+#[deriving(Eq,Clone)]
+pub enum Style {
+	/// Blending the whole screen to black.
+	Fade,
+	/// Sweeping black across the screen from left to right.
+	Wipe,
+	/// Closing (or opening) a circle of view around some point.
+	Iris
+}
+
+impl Style {
+	/// The style written as `name` in scripts, if there is one.
+	pub fn from_name(name: &str) -> Option<Style> {
+		match name {
+			"fade" 	=> Some(Fade),
+			"wipe" 	=> Some(Wipe),
+			"iris" 	=> Some(Iris),
+			_ 		=> None
+		}
+	}
+}
+
+/// Whether a transition is covering the screen, or revealing it again.
+#[deriving(Eq,Clone)]
+pub enum Direction {
+	Out,
+	In
+}
+
+/// A full-screen transition, played over `duration` & drawn atop
+/// everything else on the screen.
+///
+/// Once it has finished, a transition going `Out` leaves the screen black
+/// (until it is replaced by one going `In`), while one going `In` draws nothing.
+pub struct Effect {
+	priv style: Style,
+	priv direction: Direction,
+	priv duration: units::Millis,
+	priv elapsed: units::Millis
+}
+
+impl Effect {
+	pub fn new(style: Style, direction: Direction, duration: units::Millis) -> Effect {
+		Effect {
+			style: style, direction: direction,
+			duration: duration, elapsed: units::Millis(0)
+		}
+	}
+
+	pub fn update(&mut self, elapsed_time: units::Millis) {
+		self.elapsed = cmp::min(self.elapsed + elapsed_time, self.duration);
+	}
+
+	pub fn is_finished(&self) -> bool { self.elapsed >= self.duration }
+
+	pub fn direction(&self) -> Direction { self.direction }
+
+	/// How much of the screen is covered, from `0.0` (none of it) to `1.0`.
+	pub fn coverage(&self) -> f64 {
+		let (units::Millis(elapsed), units::Millis(duration)) = (self.elapsed, self.duration);
+		let progress = if duration > 0 { elapsed as f64 / duration as f64 } else { 1.0 };
+		match self.direction {
+			Out => progress,
+			In 	=> 1.0 - progress
+		}
+	}
+
+	/// Draws the transition over the screen; an iris closes around `focus`
+	/// (in screen space), e.g: the player.
+	pub fn draw(&self, display: &graphics::Graphics, focus: (units::Game, units::Game)) {
+		let coverage = self.coverage();
+		if coverage <= 0.0 { return; }
+
+		let (width, height) = display.resolution().to_game();
+		match self.style {
+			Fade => display.dim_screen((coverage * 255.0) as u8),
+			// covering from the left going out, & uncovering from the left coming in
+			Wipe => {
+				let covered = width * units::Game(coverage);
+				let left = match self.direction { Out => units::Game(0.0), In => width - covered };
+				display.fill_rect(&rect_of(left, covered, height), pixels::RGBA(0, 0, 0, 255));
+			}
+			Iris => {
+				let (x, y) = focus;
+				// wide enough to show every corner of the screen when fully open
+				let units::Game(diagonal) = width + height;
+				display.draw_darkness(255, &[graphics::Light {
+					x: x, y: y,
+					radius: units::Game(diagonal * (1.0 - coverage))
+				}]);
+			}
+		}
+	}
+}
+
+// a rectangle the full height of the screen, in pixels
+fn rect_of(left: units::Game, width: units::Game, height: units::Game) -> rect::Rect {
+	let (units::Pixel(x), units::Pixel(w), units::Pixel(h)) = (left.to_pixel(), width.to_pixel(), height.to_pixel());
+	rect::Rect::new(x, 0, w, h)
+}
@@ -0,0 +1,75 @@
+use sdl2::pixels;
+use sdl2_ttf;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::str;
+
+use collections::hashmap::HashMap;
+
+use game::graphics::{Backend, Texture};
+use game::units;
+
+/// The glyph of a single character, rendered into a texture of its own.
+#[deriving(Clone)]
+pub struct Glyph {
+	texture: Texture,
+	/// The size of the texture, in pixels; each character is followed by
+	/// the next one its width along.
+	size: (units::Pixel, units::Pixel)
+}
+
+/// The glyphs of a typeface rendered so far, by character. The table is
+/// shared by every `Font` drawn in the typeface, so that a glyph rendered
+/// once may be drawn by all of them.
+pub type GlyphTable = Rc<RefCell<HashMap<char, Glyph>>>;
+
+/// A TrueType (or OpenType) font opened at one point size, which renders
+/// its glyphs (white, so they may be tinted) as they are first needed.
+pub struct Typeface {
+	priv font: ~sdl2_ttf::Font,
+	priv glyphs: GlyphTable
+}
+
+impl Typeface {
+	pub fn open(path: &Path, point_size: uint) -> Result<Typeface, ~str> {
+		match sdl2_ttf::Font::from_file(path, point_size as int) {
+			Ok(font) => Ok(Typeface { font: font, glyphs: Rc::new(RefCell::new(HashMap::new())) }),
+			Err(msg) => Err(msg)
+		}
+	}
+
+	/// The height of a line of text, in pixels.
+	pub fn line_height(&self) -> units::Pixel {
+		units::Pixel(self.font.height() as i32)
+	}
+
+	pub fn glyphs(&self) -> GlyphTable { self.glyphs.clone() }
+
+	/// Renders the glyph of each of `chars` not rendered yet through `backend`.
+	///
+	/// Responds w/ each character which could not be rendered (& so is
+	/// drawn as `?`), & why.
+	pub fn render(&mut self, backend: &mut Backend, chars: &[char]) -> ~[(char, ~str)] {
+		let mut failures = ~[];
+		let mut glyphs = self.glyphs.borrow().borrow_mut();
+		for &c in chars.iter() {
+			if c.is_whitespace() && c != ' ' { continue; }
+			if glyphs.get().contains_key(&c) { continue; }
+
+			let text = str::from_char(c);
+			let rendered = self.font.render_str_blended(text.as_slice(), pixels::RGBA(255, 255, 255, 255))
+				.and_then(|surface| {
+					let (w, h) = (surface.get_width() as i32, surface.get_height() as i32);
+					backend.create_texture_from_surface(surface).map(|texture| {
+						Glyph { texture: texture, size: (units::Pixel(w), units::Pixel(h)) }
+					})
+				});
+			match rendered {
+				Ok(glyph) => { glyphs.get().insert(c, glyph); }
+				Err(msg) => failures.push((c, msg))
+			}
+		}
+		failures
+	}
+}
@@ -0,0 +1,293 @@
+use sdl2::pixels;
+use sdl2::rect;
+
+use game::graphics;
+use game::sprite;
+use game::sprite::Drawable;
+
+use game::error::GameResult;
+use game::units;
+use game::units::{AsPixel};
+use game::weapons;
+
+static TEXT_BOX_PATH: &'static str = "assets/base/TextBox.bmp";
+
+// (expressed as `units::Game`; the HUD is laid out in half-tiles)
+static HALF_TILE: units::Game = units::Game(16.0);
+
+// health bar
+static HEALTH_BAR_X: units::Game 		= units::Game(32.0);
+static HEALTH_BAR_Y: units::Game 		= units::Game(64.0);
+static HEALTH_BAR_SOURCE: (units::Game, units::Game) 	= (units::Game(0.0), units::Game(80.0));
+static HEALTH_BAR_SIZE: (units::Game, units::Game) 		= (units::Game(128.0), units::Game(16.0));
+
+static HEALTH_FILL_X: units::Game 		= units::Game(80.0);
+static HEALTH_FILL_SOURCE: (units::Game, units::Game) 	= (units::Game(0.0), units::Game(48.0));
+static HEALTH_FILL_MAX_WIDTH: units::Game 				= units::Game(78.0);
+
+static HEALTH_NUMBER_X: units::Game 	= units::Game(48.0);
+
+// weapon level
+static LEVEL_Y: units::Game 			= units::Game(48.0);
+static LEVEL_LABEL_SOURCE: (units::Game, units::Game) 	= (units::Game(80.0), units::Game(160.0));
+static LEVEL_NUMBER_X: units::Game 		= units::Game(64.0);
+
+// experience bar
+static EXP_BAR_X: units::Game 			= units::Game(80.0);
+static EXP_BAR_SOURCE: (units::Game, units::Game) 	= (units::Game(0.0), units::Game(144.0));
+static EXP_FILL_SOURCE: (units::Game, units::Game) 	= (units::Game(0.0), units::Game(160.0));
+static EXP_MAX_SOURCE: (units::Game, units::Game) 	= (units::Game(0.0), units::Game(176.0));
+static EXP_BAR_SIZE: (units::Game, units::Game) 	= (units::Game(80.0), units::Game(16.0));
+static EXP_FILL_MAX_WIDTH: units::Game 				= units::Game(78.0);
+
+// ammo
+static AMMO_X: units::Game 				= units::Game(96.0);
+static AMMO_Y: units::Game 				= units::Game(32.0);
+static AMMO_SLASH_SOURCE: (units::Game, units::Game) 	= (units::Game(80.0), units::Game(48.0));
+static AMMO_NONE_SOURCE: (units::Game, units::Game) 	= (units::Game(96.0), units::Game(48.0));
+
+// the boss's health bar, centered along the bottom of the screen
+// (expressed as `units::Game`)
+static BOSS_BAR_WIDTH: units::Game 		= units::Game(320.0);
+static BOSS_BAR_HEIGHT: units::Game 	= units::Game(12.0);
+static BOSS_BAR_MARGIN: units::Game 	= units::Game(32.0);
+static BOSS_BAR_BORDER: units::Game 	= units::Game(2.0);
+
+// the booster's fuel gauge, beneath the health bar
+// (expressed as `units::Game`)
+static FUEL_X: units::Game 			= units::Game(32.0);
+static FUEL_Y: units::Game 			= units::Game(84.0);
+static FUEL_WIDTH: units::Game 		= units::Game(128.0);
+static FUEL_HEIGHT: units::Game 	= units::Game(6.0);
+
+// white digits, 0-9, laid out one half-tile apart
+static DIGITS_Y: units::Game = units::Game(112.0);
+
+/// The heads-up display: drawn in screen space, on top of the world.
+///
+/// Shows the player's health, the current weapon's level & experience,
+/// and that weapon's remaining ammunition. (if it uses any.) The HUD of a
+/// second player is drawn as a panel against the right edge of the screen.
+pub struct Hud {
+	priv health_bar: sprite::Sprite,
+	priv health_fill: sprite::Sprite,
+	priv level_label: sprite::Sprite,
+	priv exp_bar: sprite::Sprite,
+	priv exp_fill: sprite::Sprite,
+	priv exp_max: sprite::Sprite,
+	priv ammo_slash: sprite::Sprite,
+	priv ammo_none: sprite::Sprite,
+	priv digits: ~[sprite::Sprite],
+
+	priv hit_points: units::HitPoint,
+	priv weapon_level: uint,
+	priv weapon_max: bool,
+	priv ammo: Option<(uint, uint)>,
+	// the booster's `(remaining, full)` fuel, while there is one
+	priv fuel: Option<(units::Millis, units::Millis)>,
+	// the boss's `(hit points, max hit points)`, while one is being fought
+	priv boss: Option<(units::HitPoint, units::HitPoint)>,
+	// whether this is the second player's panel, on the right
+	priv right: bool
+}
+
+impl Hud {
+	pub fn new(graphics: &mut graphics::Graphics) -> GameResult<Hud> {
+		let origin = (units::Game(0.0), units::Game(0.0));
+		let half = (HALF_TILE, HALF_TILE);
+
+		let mut digits: ~[sprite::Sprite] = ~[];
+		for digit in range(0, 10) {
+			digits.push(try!(sprite::Sprite::from_region(
+				graphics, origin,
+				(HALF_TILE * units::Game(digit as f64), DIGITS_Y), half,
+				TEXT_BOX_PATH.to_owned()
+			)));
+		}
+
+		Ok(Hud {
+			health_bar: try!(sprite::Sprite::from_region(
+				graphics, (HEALTH_BAR_X, HEALTH_BAR_Y),
+				HEALTH_BAR_SOURCE, HEALTH_BAR_SIZE, TEXT_BOX_PATH.to_owned()
+			)),
+			health_fill: try!(sprite::Sprite::from_region(
+				graphics, (HEALTH_FILL_X, HEALTH_BAR_Y),
+				HEALTH_FILL_SOURCE, (HEALTH_FILL_MAX_WIDTH, HALF_TILE), TEXT_BOX_PATH.to_owned()
+			)),
+			level_label: try!(sprite::Sprite::from_region(
+				graphics, (HEALTH_BAR_X, LEVEL_Y),
+				LEVEL_LABEL_SOURCE, half, TEXT_BOX_PATH.to_owned()
+			)),
+			exp_bar: try!(sprite::Sprite::from_region(
+				graphics, (EXP_BAR_X, LEVEL_Y),
+				EXP_BAR_SOURCE, EXP_BAR_SIZE, TEXT_BOX_PATH.to_owned()
+			)),
+			exp_fill: try!(sprite::Sprite::from_region(
+				graphics, (EXP_BAR_X, LEVEL_Y),
+				EXP_FILL_SOURCE, (EXP_FILL_MAX_WIDTH, HALF_TILE), TEXT_BOX_PATH.to_owned()
+			)),
+			exp_max: try!(sprite::Sprite::from_region(
+				graphics, (EXP_BAR_X, LEVEL_Y),
+				EXP_MAX_SOURCE, EXP_BAR_SIZE, TEXT_BOX_PATH.to_owned()
+			)),
+			ammo_slash: try!(sprite::Sprite::from_region(
+				graphics, (AMMO_X, AMMO_Y + HALF_TILE),
+				AMMO_SLASH_SOURCE, half, TEXT_BOX_PATH.to_owned()
+			)),
+			ammo_none: try!(sprite::Sprite::from_region(
+				graphics, (AMMO_X, AMMO_Y),
+				AMMO_NONE_SOURCE, half, TEXT_BOX_PATH.to_owned()
+			)),
+			digits: digits,
+
+			hit_points: 0,
+			weapon_level: 1,
+			weapon_max: false,
+			ammo: None,
+			fuel: None,
+			boss: None,
+			right: false
+		})
+	}
+
+	/// The HUD of the second player, mirroring the first across the screen.
+	pub fn for_second_player(graphics: &mut graphics::Graphics) -> GameResult<Hud> {
+		let mut hud = try!(Hud::new(graphics));
+		hud.right = true;
+		Ok(hud)
+	}
+
+	// how far right of the first player's panel this one is drawn
+	fn shift(&self, display: &graphics::Graphics) -> units::Game {
+		if !self.right { return units::Game(0.0); }
+		let (screen_w, _) = display.resolution().to_game();
+		let (bar_w, _) = HEALTH_BAR_SIZE;
+		screen_w - (HEALTH_BAR_X * units::Game(2.0)) - bar_w
+	}
+
+	/// Records the values the HUD should display this frame.
+	/// `ammo` is `(current, max)`, or `None` for weapons w/ infinite ammo.
+	pub fn update(
+		&mut self, 
+		hit_points: units::HitPoint, max_hit_points: units::HitPoint,
+		experience: &weapons::Experience, ammo: Option<(uint, uint)>
+	) {
+		self.hit_points = hit_points;
+		self.weapon_level = experience.level();
+		self.weapon_max = experience.is_max();
+		self.ammo = ammo;
+
+		let units::Game(max_width) = HEALTH_FILL_MAX_WIDTH;
+		let fraction = if max_hit_points > 0 { hit_points as f64 / max_hit_points as f64 } else { 0.0 };
+		self.health_fill.set_width(units::Game(max_width * fraction));
+
+		let units::Game(max_width) = EXP_FILL_MAX_WIDTH;
+		let fraction = experience.energy() as f64 / experience.max_energy() as f64;
+		self.exp_fill.set_width(units::Game(max_width * fraction));
+	}
+
+	/// Records the boss's `(hit points, max hit points)`, or `None` once
+	/// no boss is being fought (hiding its health bar.)
+	pub fn update_boss(&mut self, boss: Option<(units::HitPoint, units::HitPoint)>) {
+		self.boss = boss;
+	}
+
+	/// Records the booster's `(remaining, full)` fuel, or `None` while
+	/// there is no booster (hiding its gauge.)
+	pub fn update_fuel(&mut self, fuel: Option<(units::Millis, units::Millis)>) {
+		self.fuel = fuel;
+	}
+
+	/// Draws the HUD in screen space. (It is unaffected by the camera.)
+	pub fn draw(&self, display: &graphics::Graphics) {
+		let shift = self.shift(display);
+		let screen = (units::Game(0.0) - shift, units::Game(0.0));
+
+		self.health_bar.draw(display, screen);
+		self.health_fill.draw(display, screen);
+		self.draw_number(display, self.hit_points as uint, (HEALTH_NUMBER_X + shift, HEALTH_BAR_Y));
+
+		self.level_label.draw(display, screen);
+		self.draw_number(display, self.weapon_level, (LEVEL_NUMBER_X + shift, LEVEL_Y));
+
+		if self.weapon_max {
+			self.exp_max.draw(display, screen);
+		} else {
+			self.exp_bar.draw(display, screen);
+			self.exp_fill.draw(display, screen);
+		}
+
+		match self.ammo {
+			Some((current, max)) => {
+				self.draw_number(display, current, (AMMO_X + HALF_TILE + shift, AMMO_Y));
+				self.ammo_slash.draw(display, screen);
+				self.draw_number(display, max, (AMMO_X + HALF_TILE + shift, AMMO_Y + HALF_TILE));
+			}
+			None => {
+				self.ammo_none.draw(display, screen);
+				self.ammo_slash.draw(display, screen);
+				self.ammo_none.draw(display, (units::Game(0.0) - shift, units::Game(0.0) - HALF_TILE));
+			}
+		}
+
+		match self.fuel {
+			Some((fuel, full)) => self.draw_fuel_gauge(display, FUEL_X + shift, fuel, full),
+			None => {}
+		}
+
+		match self.boss {
+			Some((hit_points, max_hit_points)) => self.draw_boss_bar(display, hit_points, max_hit_points),
+			None => {}
+		}
+	}
+
+	// a thin bar, emptying as the booster burns its fuel
+	fn draw_fuel_gauge(&self, display: &graphics::Graphics, x: units::Game, fuel: units::Millis, full: units::Millis) {
+		let (units::Pixel(x), units::Pixel(y)) = (x.to_pixel(), FUEL_Y.to_pixel());
+		let (units::Pixel(w), units::Pixel(h)) = (FUEL_WIDTH.to_pixel(), FUEL_HEIGHT.to_pixel());
+		display.fill_rect(&rect::Rect::new(x, y, w, h), pixels::RGBA(0, 0, 32, 255));
+
+		let (units::Millis(fuel), units::Millis(full)) = (fuel, full);
+		let fill_w = if full > 0 { ((w as f64) * (fuel as f64 / full as f64)) as i32 } else { 0 };
+		if fill_w > 0 {
+			display.fill_rect(&rect::Rect::new(x, y, fill_w, h), pixels::RGBA(255, 160, 32, 255));
+		}
+	}
+
+	// a dark frame along the bottom of the screen, filled red in proportion to the boss's health
+	fn draw_boss_bar(&self, display: &graphics::Graphics, hit_points: units::HitPoint, max_hit_points: units::HitPoint) {
+		let (screen_w, screen_h) = display.resolution().to_game();
+		let x = (screen_w - BOSS_BAR_WIDTH) / units::Game(2.0);
+		let y = screen_h - BOSS_BAR_MARGIN - BOSS_BAR_HEIGHT;
+		let (units::Pixel(frame_x), units::Pixel(frame_y)) = (x.to_pixel(), y.to_pixel());
+		let (units::Pixel(frame_w), units::Pixel(frame_h)) = (BOSS_BAR_WIDTH.to_pixel(), BOSS_BAR_HEIGHT.to_pixel());
+		let units::Pixel(border) = BOSS_BAR_BORDER.to_pixel();
+		display.fill_rect(&rect::Rect::new(frame_x, frame_y, frame_w, frame_h), pixels::RGBA(0, 0, 32, 255));
+
+		let fraction = if max_hit_points > 0 { hit_points as f64 / max_hit_points as f64 } else { 0.0 };
+		let fill_w = ((frame_w - border * 2) as f64 * fraction) as i32;
+		if fill_w > 0 {
+			display.fill_rect(
+				&rect::Rect::new(frame_x + border, frame_y + border, fill_w, frame_h - border * 2),
+				pixels::RGBA(192, 0, 32, 255)
+			);
+		}
+	}
+
+	/// Draws `value` right-aligned so that its last digit ends at `coords`.
+	fn draw_number(&self, display: &graphics::Graphics, value: uint, coords: (units::Game, units::Game)) {
+		let (x, y) = coords;
+		let mut remaining = value;
+		let mut digit_x = x;
+
+		loop {
+			// digits are positioned at the origin; shift them into place.
+			let offset = (units::Game(0.0) - digit_x, units::Game(0.0) - y);
+			self.digits[remaining % 10].draw(display, offset);
+
+			remaining /= 10;
+			digit_x = digit_x - HALF_TILE;
+			if remaining == 0 { break; }
+		}
+	}
+}
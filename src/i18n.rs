@@ -0,0 +1,155 @@
+use collections::hashmap::HashMap;
+
+use std::cell::RefCell;
+use std::io::File;
+
+use game::log;
+use game::vfs;
+
+/// Holds one string table per language, e.g: `assets/lang/en.lang`.
+pub static LANG_DIR: &'static str = "assets/lang";
+
+/// The language whose table fills in any string another table lacks.
+pub static DEFAULT_LANGUAGE: &'static str = "en";
+
+// text which starts w/ this is a key to be looked up, rather than shown as is
+static KEY_PREFIX: &'static str = "@";
+
+struct Strings {
+	language: ~str,
+	table: HashMap<~str, ~str>,
+	fallback: HashMap<~str, ~str>
+}
+
+local_data_key!(STRINGS: RefCell<Strings>)
+
+/// Loads the string table of `language`, & that of `DEFAULT_LANGUAGE`
+/// to fall back on. Until this is called every key looks up as itself.
+///
+/// A table holds lines of `key = text` (surrounding spaces are trimmed);
+/// the text may hold any utf-8 characters, & `|` breaks it across pages
+/// as it does in scripts. Lines starting w/ `#` are ignored.
+pub fn init(language: &str) {
+	let fallback = load_table(DEFAULT_LANGUAGE).unwrap_or_else(|| HashMap::new());
+	STRINGS.replace(Some(RefCell::new(Strings {
+		language: DEFAULT_LANGUAGE.to_owned(),
+		table: fallback.clone(),
+		fallback: fallback
+	})));
+	if language != DEFAULT_LANGUAGE { set_language(language); }
+}
+
+/// Switches every string looked up from then on to `language`. Responds
+/// false (keeping the current language) if it has no table.
+pub fn set_language(language: &str) -> bool {
+	let table = match load_table(language) {
+		Some(table) => table,
+		None => return false
+	};
+
+	match STRINGS.get() {
+		Some(strings) => {
+			let mut strings = strings.get().borrow_mut();
+			strings.get().language = language.to_owned();
+			strings.get().table = table;
+			log::info("i18n", format!("using language {}", language));
+			true
+		}
+		None => false
+	}
+}
+
+/// The name of the language in use, e.g: `en`.
+pub fn language() -> ~str {
+	match STRINGS.get() {
+		Some(strings) => strings.get().borrow().get().language.clone(),
+		None => DEFAULT_LANGUAGE.to_owned()
+	}
+}
+
+/// The name of every language w/ a table in `LANG_DIR`, in order: those
+/// of every mod & of the asset archive, as well as the loose tables.
+pub fn languages() -> ~[~str] {
+	vfs::list(LANG_DIR).iter()
+		.map(|name| Path::new(name.as_slice()))
+		.filter(|path| path.extension_str() == Some("lang"))
+		.filter_map(|path| path.filestem_str().map(|stem| stem.to_owned()))
+		.collect()
+}
+
+/// Every character the text of the current (& default) language holds,
+/// e.g: so that their glyphs may be rendered ahead of time.
+pub fn characters() -> ~[char] {
+	let mut chars: ~[char] = ~[];
+	match STRINGS.get() {
+		Some(strings) => {
+			let strings = strings.get().borrow();
+			for text in strings.get().table.values().chain(strings.get().fallback.values()) {
+				for c in text.chars() { chars.push(c); }
+			}
+		}
+		None => {}
+	}
+	chars.sort();
+	chars.dedup();
+	chars
+}
+
+/// The text of `key` in the current language, else in the default
+/// language, if either table holds it.
+pub fn lookup(key: &str) -> Option<~str> {
+	match STRINGS.get() {
+		Some(strings) => {
+			let strings = strings.get().borrow();
+			strings.get().table.find_equiv(&key)
+				.or_else(|| strings.get().fallback.find_equiv(&key))
+				.map(|text| text.clone())
+		}
+		None => None
+	}
+}
+
+/// The text of `key`; a key missing from every table is shown as itself
+/// (& logged), so that it stands out.
+pub fn text(key: &str) -> ~str {
+	match lookup(key) {
+		Some(text) => text,
+		None => {
+			log::warn("i18n", format!("no text for `{}` in {}", key, language()));
+			key.to_owned()
+		}
+	}
+}
+
+/// The text to show for a message written in a script or map: an `@key`
+/// is looked up w/ `text`, while anything else is shown as written.
+pub fn localize(message: &str) -> ~str {
+	if message.starts_with(KEY_PREFIX) {
+		text(message.slice_from(KEY_PREFIX.len()).trim())
+	} else {
+		message.to_owned()
+	}
+}
+
+// reads `LANG_DIR/<language>.lang`, if it exists
+fn load_table(language: &str) -> Option<HashMap<~str, ~str>> {
+	let path = format!("{}/{}.lang", LANG_DIR, language);
+	let contents = match File::open(&vfs::resolve(path.as_slice())).read_to_str() {
+		Ok(contents) => contents,
+		Err(msg) => { log::warn("i18n", format!("{} could not be loaded: {}", path, msg)); return None; }
+	};
+
+	let mut table = HashMap::new();
+	for raw_line in contents.lines() {
+		let line = raw_line.trim();
+		if line.len() == 0 || line.starts_with("#") { continue; }
+
+		match line.find('=') {
+			Some(split) if split > 0 => {
+				table.insert(line.slice_to(split).trim().to_owned(), line.slice_from(split + 1).trim().to_owned());
+			}
+			_ => { log::warn("i18n", format!("{}: ignoring malformed line `{}`", path, line)); }
+		}
+	}
+	Some(table)
+}
@@ -1,75 +1,483 @@
+use sdl2::controller;
+use sdl2::event;
+use sdl2::joystick;
 use sdl2::keycode;
+use sdl2::sdl;
 
 use collections::hashmap::HashMap;
 
-/// Responds to inquiries regarding three sets of keyboard input.
+use std::cell::RefCell;
+use std::libc::{c_int, c_void};
+
+use game::config;
+use game::log;
+use game::units;
+
+/// An abstract command the player can issue, independent of
+/// which physical key (or button) is bound to it.
+#[deriving(Hash,Eq,Clone)]
+pub enum Action {
+	MoveLeft,
+	MoveRight,
+	LookUp,
+	LookDown,
+	Jump,
+	Fire,
+	NextWeapon,
+	PrevWeapon,
+	Pause,
+	Quit,
+	ToggleFullscreen,
+	Screenshot,
+	ToggleDebug,
+	ToggleHitboxes,
+	ToggleInputs,
+	Skip,
+	ToggleMap,
+	SaveState,
+	LoadState
+}
+pub static ACTIONS: [Action, ..19] = [
+	MoveLeft, MoveRight, LookUp, LookDown, Jump, Fire, NextWeapon, PrevWeapon, Pause, Quit,
+	ToggleFullscreen, Screenshot, ToggleDebug, ToggleHitboxes, ToggleInputs, Skip, ToggleMap,
+	SaveState, LoadState
+];
+
+impl Action {
+	/// The name of this action as it is written in config files.
+	pub fn name(&self) -> &'static str {
+		match *self {
+			MoveLeft 	=> "move_left",
+			MoveRight 	=> "move_right",
+			LookUp 		=> "look_up",
+			LookDown 	=> "look_down",
+			Jump 		=> "jump",
+			Fire 		=> "fire",
+			NextWeapon 	=> "next_weapon",
+			PrevWeapon 	=> "prev_weapon",
+			Pause 		=> "pause",
+			Quit 		=> "quit",
+			ToggleFullscreen => "toggle_fullscreen",
+			Screenshot 	=> "screenshot",
+			ToggleDebug => "toggle_debug",
+			ToggleHitboxes => "toggle_hitboxes",
+			ToggleInputs => "toggle_inputs",
+			Skip 		=> "skip",
+			ToggleMap 	=> "toggle_map",
+			SaveState 	=> "save_state",
+			LoadState 	=> "load_state"
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<Action> {
+		ACTIONS.iter().find(|action| action.name() == name).map(|action| *action)
+	}
+}
+
+// analog stick deflection past which the stick acts as a d-pad.
+static AXIS_DEAD_ZONE: i16 = 8000;
+
+/// How many players may share the keyboard: the first is bound by
+/// `config::Bindings::key_action`, the second by
+/// `config::Bindings::second_key_action`. Controllers take the seats in
+/// the order they were attached, the first playing alongside the first
+/// player's keys; any beyond `SEATS` are ignored.
+pub static SEATS: uint = 2;
+
+/// The pressed, released & held actions of one player.
+struct Seat {
+	pressed: 	HashMap<Action, bool>,
+	released: 	HashMap<Action, bool>,
+	held: 		HashMap<Action, bool>,
+	// when (in SDL ticks) each action pressed this frame was pressed
+	press_times: HashMap<Action, units::Millis>,
+	// the action the left stick of the seat's controller is held towards
+	axis_x: 	Option<Action>,
+	axis_y: 	Option<Action>
+}
+
+impl Seat {
+	fn new() -> Seat {
+		Seat {
+			pressed: 	HashMap::<Action, bool>::new(),
+			released: 	HashMap::<Action, bool>::new(),
+			held: 		HashMap::<Action, bool>::new(),
+			press_times: HashMap::<Action, units::Millis>::new(),
+			axis_x: 	None,
+			axis_y: 	None
+		}
+	}
+}
+
+/// Responds to inquiries regarding three sets of input, per `Action`.
+///
+///- Pressed actions
+///- Released actions
+///- Held actions
+///
+/// Actions may be triggered by the keyboard, or by an attached game
+/// controller's buttons, d-pad, and left analog stick (see `SEATS`.)
 ///
-///- Pressed keys
-///- Released keys
-///- Held keys
+/// The inquiries made directly of `Input` concern the first player; those
+/// of a second player sharing the keyboard are made through `seat(1)`.
 pub struct Input {
-	priv pressed_keys: 	HashMap<u32, bool>,
-	priv released_keys: HashMap<u32, bool>,
-	priv held_keys: 	HashMap<u32, bool>
+	// may be rebound while the game runs, e.g: from the options menu
+	priv bindings: 		RefCell<config::Bindings>,
+	// the SDL code of the first key pressed this frame, bound or not
+	priv pressed_key: 	Option<u32>,
 
+	// in the order they were attached, w/ the instance id their events carry
+	priv controllers: 	~[(int, ~controller::GameController)],
+
+	// one per player, see `SEATS`
+	priv seats: 		~[Seat],
+
+	// the timestamp of the event being handled, & when events were last polled
+	priv event_time: 	units::Millis,
+	priv polled_at: 	units::Millis,
+
+	// window events since the last frame
+	priv quit_requested: bool,
+	priv focus_change: 	Option<bool>, // whether focus was (last) gained or lost
+	priv resized: 		bool
 }
 
 impl Input {
-	pub fn new() -> Input {
-		Input{
-			pressed_keys: 	HashMap::<u32, bool>::new(),
-			released_keys: 	HashMap::<u32, bool>::new(),
-			held_keys: 		HashMap::<u32, bool>::new()
+	/// Creates an input handler which maps keys & buttons through `bindings`.
+	pub fn new(bindings: config::Bindings) -> Input {
+		let mut input = Input{
+			bindings: 		RefCell::new(bindings),
+			pressed_key: 	None,
+
+			controllers: 	~[],
+
+			seats: 			range(0, SEATS).map(|_| Seat::new()).collect(),
+
+			event_time: 	units::Millis(0),
+			polled_at: 		units::Millis(0),
+
+			quit_requested: false,
+			focus_change: 	None,
+			resized: 		false
+		};
+
+		// open every controller which is already plugged in
+		for index in range(0, joystick::num_joysticks()) {
+			input.open_controller(index);
 		}
+
+		input
 	}
 
-	/// Resets the toggle states of pressed & released keys.
+	/// Resets the toggle states of pressed & released keys, and
+	/// forgets the last frame's window events.
 	pub fn begin_new_frame(&mut self) {
-		self.pressed_keys.clear();
-		self.released_keys.clear();
+		for seat in self.seats.mut_iter() {
+			seat.pressed.clear();
+			seat.released.clear();
+			seat.press_times.clear();
+		}
+		self.pressed_key = None;
+		self.focus_change = None;
+		self.resized = false;
+	}
+
+	/// Drains every event SDL has queued since the last frame.
+	///
+	/// Presses are stamped w/ the time of their event, rather than the
+	/// time they are polled, so that `press_age` is accurate to within a frame.
+	pub fn poll_events(&mut self) {
+		self.polled_at = units::Millis(sdl::get_ticks() as int);
+		self.event_time = self.polled_at;
+		loop {
+			match event::poll_event() {
+				event::KeyDownEvent(timestamp,_,key_cap,_,_) => {
+					self.event_time = units::Millis(timestamp as int);
+					self.key_down_event(key_cap);
+				}
+				event::KeyUpEvent(_,_,key_cap,_,_) => {
+					self.key_up_event(key_cap);
+				}
+				event::ControllerButtonDownEvent(timestamp,which,button) => {
+					self.event_time = units::Millis(timestamp as int);
+					self.button_down_event(which, button);
+				}
+				event::ControllerButtonUpEvent(_,which,button) => {
+					self.button_up_event(which, button);
+				}
+				event::ControllerAxisMotionEvent(timestamp,which,axis,value) => {
+					self.event_time = units::Millis(timestamp as int);
+					self.axis_event(which, axis, value);
+				}
+				event::ControllerDeviceAddedEvent(_,index) => {
+					self.open_controller(index);
+				}
+				event::ControllerDeviceRemovedEvent(_,which) => {
+					self.controllers.retain(|&(id, _)| id != which);
+					// the controllers after it move up a seat: let go of all that was held, lest
+					// a seat keep what another's controller held
+					for seat in self.seats.mut_iter() {
+						seat.held.clear();
+						seat.axis_x = None;
+						seat.axis_y = None;
+					}
+				}
+				event::QuitEvent(_) => {
+					self.quit_requested = true;
+				}
+				event::WindowEvent(_,_,event::FocusLostWindowEventId,_,_) => {
+					// keys let go of while unfocused never send a key up event
+					for seat in self.seats.mut_iter() { seat.held.clear(); }
+					self.focus_change = Some(false);
+				}
+				event::WindowEvent(_,_,event::FocusGainedWindowEventId,_,_) => {
+					self.focus_change = Some(true);
+				}
+				event::WindowEvent(_,_,event::ResizedWindowEventId,_,_) => {
+					self.resized = true;
+				}
+				event::NoEvent => { break; }
+				_ => {}
+			}
+		}
 	}
 
 	/// Handles a key down event
+	/// Keys which are not bound to an `Action` are ignored.
 	pub fn key_down_event(&mut self, key: keycode::KeyCode) {
-		self.pressed_keys.insert(key as u32, true);
-		self.held_keys.insert(key as u32, true);
+		if self.pressed_key.is_none() { self.pressed_key = Some(key as u32); }
+		match self.key_action(key) {
+			Some((seat, action)) => self.seat_down(seat, action),
+			None => {}
+		}
 	}
 
 	/// Handles a key up event
+	/// Keys which are not bound to an `Action` are ignored.
 	pub fn key_up_event(&mut self, key: keycode::KeyCode) {
-		self.released_keys.insert(key as u32, true);
-		self.held_keys.insert(key as u32, false);
+		match self.key_action(key) {
+			Some((seat, action)) => self.seat_up(seat, action),
+			None => {}
+		}
+	}
+
+	/// The seat `key` is bound for, & the action it is bound to there.
+	fn key_action(&self, key: keycode::KeyCode) -> Option<(uint, Action)> {
+		let bindings = self.bindings.borrow();
+		match bindings.get().key_action(key as u32) {
+			Some(action) => Some((0, action)),
+			None => bindings.get().second_key_action(key as u32).map(|action| (1, action))
+		}
+	}
+
+	/// Handles a button down event of the controller w/ the instance id `which`
+	pub fn button_down_event(&mut self, which: int, button: controller::ControllerButton) {
+		let action = self.bindings.borrow().get().button_action(button as u32);
+		match (self.controller_seat(which), action) {
+			(Some(seat), Some(action)) => self.seat_down(seat, action),
+			_ => {}
+		}
+	}
+
+	/// Handles a button up event of the controller w/ the instance id `which`
+	pub fn button_up_event(&mut self, which: int, button: controller::ControllerButton) {
+		let action = self.bindings.borrow().get().button_action(button as u32);
+		match (self.controller_seat(which), action) {
+			(Some(seat), Some(action)) => self.seat_up(seat, action),
+			_ => {}
+		}
+	}
+
+	/// Treats the left analog stick as a d-pad: pushing it past the dead zone
+	/// presses a direction, returning it to center releases that direction.
+	pub fn axis_event(&mut self, which: int, axis: controller::ControllerAxis, value: i16) {
+		let seat = match self.controller_seat(which) {
+			Some(seat) => seat,
+			None => { return; }
+		};
+		let (current, negative, positive) = match axis {
+			controller::LeftX => (self.seats[seat].axis_x, MoveLeft, MoveRight),
+			controller::LeftY => (self.seats[seat].axis_y, LookUp, LookDown),
+			_ => { return; }
+		};
+
+		let next = if value < -AXIS_DEAD_ZONE { 
+			Some(negative) 
+		} else if value > AXIS_DEAD_ZONE { 
+			Some(positive) 
+		} else { 
+			None 
+		};
+
+		if next == current { return; }
+		match current { Some(action) => self.seat_up(seat, action), None => {} }
+		match next { Some(action) => self.seat_down(seat, action), None => {} }
+
+		match axis {
+			controller::LeftX => { self.seats[seat].axis_x = next; }
+			_ => { self.seats[seat].axis_y = next; }
+		}
+	}
+
+	// opens the controller at device `index`, giving it the next seat
+	fn open_controller(&mut self, index: int) {
+		if !controller::is_game_controller(index) { return; }
+
+		match controller::GameController::open(index) {
+			Ok(pad) => {
+				let id = Input::instance_id(index);
+				// a controller already attached keeps the seat it has
+				if self.controllers.iter().any(|&(attached, _)| attached == id) { return; }
+				self.controllers.push((id, pad));
+			}
+			Err(msg) => { log::warn("input", format!("controller could not be opened: {}", msg)); }
+		}
+	}
+
+	// the seat of the controller w/ the instance id `which`, if it has one
+	fn controller_seat(&self, which: int) -> Option<uint> {
+		self.controllers.iter().position(|&(id, _)| id == which).and_then(|seat| {
+			if seat < SEATS { Some(seat) } else { None }
+		})
+	}
+
+	// the instance id of the (already opened) device at `index`, which its
+	// events carry in place of the index
+	fn instance_id(index: int) -> int {
+		unsafe {
+			// opening the device again only counts another reference to it
+			let joystick = SDL_JoystickOpen(index as c_int);
+			let id = SDL_JoystickInstanceID(joystick);
+			SDL_JoystickClose(joystick);
+			id as int
+		}
+	}
+
+	fn seat_down(&mut self, seat: uint, action: Action) {
+		let event_time = self.event_time;
+		let seat = &mut self.seats[seat];
+		seat.pressed.insert(action, true);
+		seat.press_times.insert(action, event_time);
+		seat.held.insert(action, true);
+	}
+
+	fn seat_up(&mut self, seat: uint, action: Action) {
+		let seat = &mut self.seats[seat];
+		seat.released.insert(action, true);
+		seat.held.insert(action, false);
+	}
+
+	/// The inputs of the player in seat `seat` (which must be below `SEATS`.)
+	pub fn seat<'a>(&'a self, seat: uint) -> PlayerInput<'a> {
+		assert!(seat < SEATS);
+		PlayerInput { input: self, seat: seat }
 	}
 
-	/// Responds true if key was pressed since last call to `beginNewFrame()`.
+	/// Responds true if `action` was pressed since last call to `beginNewFrame()`.
+	/// Responds false otherwise.
+	pub fn was_key_pressed(&self, action: Action) -> bool { self.seat(0).was_key_pressed(action) }
+	
+	/// How long before its events were polled `action` was pressed, if it
+	/// was pressed this frame. (Presses made w/o an event, e.g: replayed
+	/// input, count as pressed when polled.)
+	pub fn press_age(&self, action: Action) -> Option<units::Millis> { self.seat(0).press_age(action) }
+
+	/// Responds true if `action` was released since last call to `beginNewFrame()`.
 	/// Responds false otherwise.
-	pub fn was_key_pressed(&self, key: keycode::KeyCode) -> bool {
-		let key_cap = &(key as u32);
-		match self.pressed_keys.find_copy(key_cap) {
+	pub fn was_key_released(&self, action: Action) -> bool { self.seat(0).was_key_released(action) }
+	
+	/// Responds true if `action` has been pressed since last call to `beginNewFrame()`
+	/// but _has not yet been released._
+	///
+	/// Responds false otherwise.
+	pub fn is_key_held(&self, action: Action) -> bool { self.seat(0).is_key_held(action) }
+
+	/// The SDL code of the first key pressed since the last frame, whether
+	/// or not it is bound to an action, e.g: for rebinding keys.
+	pub fn pressed_key(&self) -> Option<u32> { self.pressed_key }
+
+	/// A copy of the bindings in use.
+	pub fn bindings(&self) -> config::Bindings { self.bindings.borrow().get().clone() }
+
+	/// Maps keys & buttons through `bindings` from the next event on.
+	pub fn set_bindings(&self, bindings: config::Bindings) {
+		let mut current = self.bindings.borrow_mut();
+		*current.get() = bindings;
+	}
+
+	/// Responds true once the window has been asked to close, e.g: by its close button.
+	pub fn was_quit_requested(&self) -> bool { self.quit_requested }
+
+	/// Responds true if the window lost focus since the last frame
+	/// (& has not regained it.)
+	pub fn lost_focus(&self) -> bool { self.focus_change == Some(false) }
+
+	/// Responds true if the window regained focus since the last frame.
+	pub fn gained_focus(&self) -> bool { self.focus_change == Some(true) }
+
+	/// Responds true if the window was resized since the last frame.
+	pub fn was_resized(&self) -> bool { self.resized }
+}
+
+/// Whatever directs a single player, answering the same inquiries about
+/// each action as `Input`, e.g: a seat at the keyboard, or a player
+/// playing from another machine (see `net::RemoteInput`.)
+pub trait Controls {
+	fn was_key_pressed(&self, action: Action) -> bool;
+	fn press_age(&self, action: Action) -> Option<units::Millis>;
+	fn was_key_released(&self, action: Action) -> bool;
+	fn is_key_held(&self, action: Action) -> bool;
+}
+
+/// The inputs of one player: answers the same inquiries as `Input`, for
+/// whichever seat it was made for.
+pub struct PlayerInput<'a> {
+	priv input: &'a Input,
+	priv seat: 	uint
+}
+
+impl<'a> PlayerInput<'a> {
+	fn get(&self) -> &'a Seat { &self.input.seats[self.seat] }
+
+	/// Which seat these inputs are of, starting from 0.
+	pub fn index(&self) -> uint { self.seat }
+}
+
+impl<'a> Controls for PlayerInput<'a> {
+	fn was_key_pressed(&self, action: Action) -> bool {
+		match self.get().pressed.find_copy(&action) {
 			Some(is_pressed) => {is_pressed}
 			None => false
 		}
 	}
-	
-	/// Responds true if key was released since last call to `beginNewFrame()`.
-	/// Responds false otherwise.
-	pub fn was_key_released(&self, key: keycode::KeyCode) -> bool {
-		let key_cap = &(key as u32);
-		match self.released_keys.find_copy(key_cap) {
+
+	fn press_age(&self, action: Action) -> Option<units::Millis> {
+		if !self.was_key_pressed(action) { return None; }
+
+		let polled_at = self.input.polled_at;
+		match self.get().press_times.find_copy(&action) {
+			Some(time) if time <= polled_at => Some(polled_at - time),
+			_ => Some(units::Millis(0))
+		}
+	}
+
+	fn was_key_released(&self, action: Action) -> bool {
+		match self.get().released.find_copy(&action) {
 			Some(is_pressed) => {is_pressed}
 			None => false
 		}
 	}
-	
-	/// Responds true if key has been pressed since last call to `beginNewFrame()`
-	/// but _has not yet been released._
-	///
-	/// Responds false otherwise.
-	pub fn is_key_held(&self, key: keycode::KeyCode) -> bool {
-		let key_cap = &(key as u32);
-		match self.held_keys.find_copy(key_cap) {
+
+	fn is_key_held(&self, action: Action) -> bool {
+		match self.get().held.find_copy(&action) {
 			Some(is_pressed) => {is_pressed}
 			None => false
 		}
 	}
 }
+
+extern {
+	fn SDL_JoystickOpen(index: c_int) -> *mut c_void;
+	fn SDL_JoystickInstanceID(joystick: *mut c_void) -> i32;
+	fn SDL_JoystickClose(joystick: *mut c_void);
+}
@@ -0,0 +1,78 @@
+use sdl2::pixels;
+use sdl2::rect;
+
+use game;
+use game::graphics;
+use game::input;
+
+use game::error::GameResult;
+use game::units;
+use game::units::{AsPixel};
+
+// the size of each button, & the gap between them (expressed as `units::Game`)
+static BUTTON_SIZE: units::Game 	= units::Game(16.0);
+static BUTTON_SPACING: units::Game 	= units::Game(2.0);
+// the pad sits at the bottom of the screen, centered
+static PAD_MARGIN: units::Game 		= units::Game(8.0);
+
+// how many buttons wide & tall the pad is
+static PAD_COLUMNS: uint = 6;
+static PAD_ROWS: uint = 2;
+
+/// Each action shown, w/ its column & row on the pad & the label of its button:
+/// the directions are laid out as a d-pad, w/ the weapons above jump & fire.
+static BUTTONS: [(input::Action, uint, uint, &'static str), ..8] = [
+	(input::LookUp, 	1, 0, "^"),
+	(input::MoveLeft, 	0, 1, "<"),
+	(input::LookDown, 	1, 1, "v"),
+	(input::MoveRight, 	2, 1, ">"),
+	(input::PrevWeapon, 4, 0, "-"),
+	(input::NextWeapon, 5, 0, "+"),
+	(input::Jump, 		4, 1, "J"),
+	(input::Fire, 		5, 1, "F")
+];
+
+/// Shows which of the player's actions are held, as a pad of buttons lit
+/// while held (toggled w/ F4), e.g: for streaming, or for debugging input
+/// handling. It shows the state of the input as of the last frame polled.
+pub struct InputDisplay {
+	priv font: graphics::Font,
+	priv visible: bool
+}
+
+impl InputDisplay {
+	pub fn new(display: &mut graphics::Graphics) -> GameResult<InputDisplay> {
+		Ok(InputDisplay {
+			font: try!(graphics::Font::new(display, game::FONT_PATH.to_owned(), game::FONT_SIZE)),
+			visible: false
+		})
+	}
+
+	pub fn toggle(&mut self) { self.visible = !self.visible; }
+	pub fn is_visible(&self) -> bool { self.visible }
+
+	/// Draws each button, lit if `controller` holds its action.
+	pub fn draw(&self, display: &graphics::Graphics, controller: &input::Input) {
+		let (units::Game(screen_w), units::Game(screen_h)) = display.resolution().to_game();
+		let (units::Game(size), units::Game(spacing), units::Game(margin)) = (BUTTON_SIZE, BUTTON_SPACING, PAD_MARGIN);
+		let pad_w = (size * PAD_COLUMNS as f64) + (spacing * (PAD_COLUMNS as f64 - 1.0));
+		let pad_h = (size * PAD_ROWS as f64) + (spacing * (PAD_ROWS as f64 - 1.0));
+		let (left, top) = ((screen_w - pad_w) / 2.0, screen_h - margin - pad_h);
+
+		for &(action, column, row, label) in BUTTONS.iter() {
+			let (x, y) = (left + ((size + spacing) * column as f64), top + ((size + spacing) * row as f64));
+			let color = if controller.is_key_held(action) {
+				pixels::RGBA(224, 224, 96, 224)
+			} else {
+				pixels::RGBA(64, 64, 96, 160)
+			};
+			let (units::Pixel(xi), units::Pixel(yi)) = (units::Game(x).to_pixel(), units::Game(y).to_pixel());
+			let units::Pixel(si) = BUTTON_SIZE.to_pixel();
+			display.fill_rect(&rect::Rect::new(xi, yi, si, si), color);
+
+			// labels are centered on their buttons
+			let (units::Game(label_w), units::Game(label_h)) = (self.font.text_width(label), self.font.line_height());
+			self.font.draw_text(display, units::Game(x + ((size - label_w) / 2.0)), units::Game(y + ((size - label_h) / 2.0)), label);
+		}
+	}
+}
@@ -0,0 +1,137 @@
+use std::cell::RefCell;
+use std::io;
+use std::io::File;
+use std::io::fs;
+
+use time;
+
+/// How serious a message is; a module logging at some level also logs
+/// every level more serious than it.
+#[deriving(Eq,Clone)]
+pub enum Level {
+	Debug,
+	Info,
+	Warn,
+	Error
+}
+
+impl Level {
+	/// The name of this level as it is written in the settings file (& the log.)
+	pub fn name(&self) -> &'static str {
+		match *self {
+			Debug 	=> "debug",
+			Info 	=> "info",
+			Warn 	=> "warn",
+			Error 	=> "error"
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<Level> {
+		[Debug, Info, Warn, Error].iter().find(|level| level.name() == name).map(|level| *level)
+	}
+
+	fn includes(&self, other: Level) -> bool { other as uint >= *self as uint }
+}
+
+/// Which messages are logged, & where to.
+#[deriving(Clone)]
+pub struct Options {
+	/// The least serious level logged by modules w/o a level of their own.
+	level: Level,
+	/// Levels for particular modules, e.g: `("graphics", Debug)`.
+	modules: ~[(~str, Level)],
+	/// A file every logged message is appended to, as well as stdout.
+	file: Option<~str>
+}
+
+impl Options {
+	pub fn default() -> Options {
+		Options { level: Info, modules: ~[], file: None }
+	}
+
+	/// The least serious level logged by `module`.
+	fn level_of(&self, module: &str) -> Level {
+		match self.modules.iter().find(|&&(ref name, _)| name.as_slice() == module) {
+			Some(&(_, level)) => level,
+			None => self.level
+		}
+	}
+}
+
+struct Logger {
+	options: Options,
+	file: Option<File>,
+	// when logging began, in ns; messages are stamped w/ the time since
+	started: u64
+}
+
+local_data_key!(LOGGER: RefCell<Logger>)
+
+/// Starts logging as `options` direct, replacing any earlier options.
+///
+/// Until this is called messages are only printed to stdout, & only
+/// those of `Info` or more serious.
+pub fn init(options: &Options) {
+	let file = match options.file {
+		Some(ref path) => open(path.as_slice()),
+		None => None
+	};
+
+	LOGGER.replace(Some(RefCell::new(Logger {
+		options: options.clone(),
+		file: file,
+		started: time::precise_time_ns()
+	})));
+}
+
+pub fn debug(module: &str, message: ~str) { log(Debug, module, message); }
+pub fn info(module: &str, message: ~str) { log(Info, module, message); }
+pub fn warn(module: &str, message: ~str) { log(Warn, module, message); }
+pub fn error(module: &str, message: ~str) { log(Error, module, message); }
+
+/// Logs `message` on behalf of `module`, if its options allow `level`.
+pub fn log(level: Level, module: &str, message: ~str) {
+	match LOGGER.get() {
+		Some(logger) => {
+			let mut logger = logger.get().borrow_mut();
+			logger.get().write(level, module, message.as_slice());
+		}
+		None => if Info.includes(level) {
+			println!("{} {}: {}", level.name(), module, message);
+		}
+	}
+}
+
+impl Logger {
+	fn write(&mut self, level: Level, module: &str, message: &str) {
+		if !self.options.level_of(module).includes(level) { return; }
+
+		let seconds = (time::precise_time_ns() - self.started) as f64 / 1000000000.0;
+		let line = format!("[{:.3f}] {} {}: {}", seconds, level.name(), module, message);
+		println!("{}", line);
+
+		let failed = match self.file {
+			Some(ref mut file) => file.write_line(line).is_err(),
+			None => false
+		};
+		// stop writing to a file which refuses writes, rather than failing on every message
+		if failed {
+			println!("could not write to the log file, logging to stdout only");
+			self.file = None;
+		}
+	}
+}
+
+// opens the log file at `path` for appending, creating its directory if needed
+fn open(path: &str) -> Option<File> {
+	let file_path = Path::new(path);
+	match fs::mkdir_recursive(&file_path.dir_path(), io::UserDir) {
+		Ok(_) => {}
+		Err(msg) => { println!("could not create log directory: {}", msg); return None; }
+	}
+
+	match File::open_mode(&file_path, io::Append, io::Write) {
+		Ok(file) => Some(file),
+		Err(msg) => { println!("log file could not be opened: {}", msg); None }
+	}
+}
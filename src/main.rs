@@ -1,12 +1,48 @@
 #[crate_id="rust-story#0.0.1"];
 
 extern crate sdl2;
+extern crate sdl2_mixer;
+extern crate sdl2_image;
+extern crate sdl2_ttf;
 extern crate collections;
+extern crate serialize;
 extern crate sync;
+extern crate time;
+
+use std::os;
+
+use game::log;
+use game::pack;
 
 pub mod game;
 
 pub fn main() {
-	let mut story = ::game::Game::new();
+	// `rust-story --pack <dir> <out>` packs a release's assets, rather than playing
+	let args = os::args();
+	if args.len() == 4 && args[1].as_slice() == "--pack" {
+		match pack::pack(&Path::new(args[2].clone()), &Path::new(args[3].clone())) {
+			Ok(count) => log::info("pack", format!("packed {} files into {}", count, args[3])),
+			Err(msg) => { log::error("pack", format!("{} could not be packed: {}", args[2], msg)); os::set_exit_status(1); }
+		}
+		return;
+	}
+
+	// `rust-story --server <port>` hosts a game w/o a display, rather than playing
+	if args.len() == 3 && args[1].as_slice() == "--server" {
+		match from_str::<u16>(args[2].as_slice()) {
+			Some(port) if port > 0 => if !::game::Game::start_server(port) { os::set_exit_status(1); },
+			_ => { log::error("game", format!("`{}` is not a port", args[2])); os::set_exit_status(1); }
+		}
+		return;
+	}
+
+	// `rust-story --join <address>` plays as the second player of a game hosted elsewhere
+	let mut story = match ::game::Game::new() {
+		Some(story) => story,
+		None => { os::set_exit_status(1); return; }
+	};
+	if args.len() == 3 && args[1].as_slice() == "--join" {
+		if !story.join(args[2].as_slice()) { os::set_exit_status(1); return; }
+	}
 	story.start();
 }
@@ -1,20 +1,83 @@
+use std::cmp;
 use std::vec;
 use sync::RWArc;
+use collections::hashmap::HashMap;
 
+use game::audio;
+use game::flags;
 use game::graphics;
+use game::graphics::transitions;
+use game::log;
+use game::npc;
+use game::script;
 use game::sprite;
+use game::weather;
 
 use game::backdrop;
 use game::collisions::Rectangle;
+use game::enemies;
+use game::enemies::Enemy;
+use game::error::{GameResult, MapError};
 use game::units;
 use game::units::{AsGame,AsTile};
 
-
+// how long each half of a map transition lasts
+static FADE_TIME: units::Millis = units::Millis(250);
 
 #[deriving(Eq,Clone)]
 pub enum TileType {
 	Air,
-	Wall
+	Wall,
+	/// A wall w/ the given hit points left, which crumbles once they run out.
+	Destructible(units::HitPoint),
+	/// A sloped floor: its surface lies the given fractions of a tile below
+	/// the top of the tile at its left & right edges, respectively.
+	Slope(f64, f64),
+	/// A one-way platform, only solid to things landing on it from above.
+	Platform,
+	/// Spikes, which hurt the player on contact but do not block them.
+	Spike,
+	/// A ladder, which the player may climb while standing in front of it.
+	Ladder,
+	/// A slippery wall, on which the player is slow to start & stop.
+	Ice,
+	/// A wall whose surface carries whatever stands on it along at the
+	/// given speed (in game units per millisecond, positive is rightward.)
+	Conveyor(f64)
+}
+
+/// The damage dealt to the player by touching a `Spike` tile.
+pub static SPIKE_DAMAGE: units::HitPoint = 2;
+
+/// How much of the usual walking acceleration & friction applies on ice.
+static ICE_FRICTION: f64 = 0.125;
+/// The speed of the conveyors written as `>` & `<` in map files.
+static CONVEYOR_SPEED: f64 = 0.08;
+
+impl TileType {
+	/// Responds true if entities can not pass through tiles of this type.
+	pub fn is_solid(&self) -> bool {
+		match *self {
+			Wall | Destructible(_) | Ice | Conveyor(_) 		=> true,
+			Air | Slope(_, _) | Platform | Spike | Ladder 	=> false
+		}
+	}
+
+	/// Scales the acceleration & friction of anything standing on this tile.
+	pub fn friction(&self) -> f64 {
+		match *self {
+			Ice => ICE_FRICTION,
+			_ 	=> 1.0
+		}
+	}
+
+	/// The velocity w/ which this tile carries anything standing on it.
+	pub fn push(&self) -> units::Velocity {
+		match *self {
+			Conveyor(speed) => units::Velocity(speed),
+			_ 				=> units::Velocity(0.0)
+		}
+	}
 }
 
 struct CollisionTile {
@@ -49,10 +112,137 @@ impl Tile {
 	}
 }
 
+/// A doorway which takes the player to the spawn point `target_spawn`
+/// on the map stored at `target_map`.
+///
+/// A door w/ a `required_flag` stays locked until that flag is set.
+#[deriving(Clone)]
+pub struct Door {
+	col: units::Tile,
+	row: units::Tile,
+	target_map: ~str,
+	target_spawn: ~str,
+	required_flag: Option<uint>
+}
+
+impl Door {
+	/// Responds true if the player may pass through this door.
+	pub fn is_open(&self, flags: &flags::FlagSet) -> bool {
+		match self.required_flag {
+			Some(id) => flags.is_set(id),
+			None => true
+		}
+	}
+
+	/// The tile occupied by this door.
+	pub fn rectangle(&self) -> Rectangle {
+		Rectangle {
+			x: self.col.to_game(), y: self.row.to_game(),
+			width: units::Tile(1).to_game(), height: units::Tile(1).to_game()
+		}
+	}
+}
+
+/// An enemy placed on a map, of the kind named `kind` (see `enemies::KINDS`.)
+///
+/// It is only placed once `required_flag` (if any) is set, & never once
+/// its `defeated_flag` is: that flag is set when the enemy is defeated,
+/// so it stays gone for good.
+#[deriving(Clone)]
+pub struct EnemySpawn {
+	kind: ~str,
+	col: units::Tile,
+	row: units::Tile,
+	required_flag: Option<uint>,
+	defeated_flag: Option<uint>
+}
+
+impl EnemySpawn {
+	/// Responds true if this enemy should be placed, given the `flags` set.
+	pub fn is_due(&self, flags: &flags::FlagSet) -> bool {
+		self.required_flag.map_or(true, |id| flags.is_set(id))
+			&& !self.defeated_flag.map_or(false, |id| flags.is_set(id))
+	}
+}
+
+/// A tile which runs event `id` of the map's script when the player
+/// interacts w/ it.
+struct Event {
+	col: units::Tile,
+	row: units::Tile,
+	id: uint
+}
+
+/// What makes a trigger run its event.
+#[deriving(Eq,Clone)]
+pub enum TriggerCause {
+	/// The player stepping into its area.
+	OnEnter,
+	/// The player stepping back out of its area.
+	OnExit,
+	/// The player examining something while inside its area, like a sign.
+	OnInteract
+}
+
+impl TriggerCause {
+	/// The cause written as `name` in map files, if there is one.
+	pub fn from_name(name: &str) -> Option<TriggerCause> {
+		match name {
+			"enter" 	=> Some(OnEnter),
+			"exit" 		=> Some(OnExit),
+			"interact" 	=> Some(OnInteract),
+			_ 			=> None
+		}
+	}
+}
+
+/// An invisible area of the map which runs event `id` of the map's
+/// script when the player enters it, leaves it, or examines it.
+struct Trigger {
+	area: Rectangle,
+	cause: TriggerCause,
+	id: uint
+}
+
+/// An area of the map (e.g: a fan shaft, or a waterfall) which pushes
+/// everything inside of it w/ a constant acceleration.
+struct ForceRegion {
+	area: Rectangle,
+	accel_x: units::Acceleration,
+	accel_y: units::Acceleration
+}
+
+// number of tiles in a single row of a stage's tileset.
+// (tile ids in a map file are indexed left-to-right, top-to-bottom.)
+static TILESET_WIDTH: uint = 16;
+
 pub struct Map {
 	priv background: 	backdrop::FixedBackdrop,
+	priv parallax: 		~[backdrop::ParallaxBackdrop],
 	priv sprites:		~[~[Tile]],
-	priv tiles: 		~[~[Tile]]
+	priv tiles: 		~[~[Tile]],
+	priv music: 		Option<audio::Track>,
+	priv weather: 		Option<weather::Kind>,
+	priv dark: 			bool,
+	priv wall_jump: 	bool,
+	// the landing speed past which the player is hurt, & by how much
+	priv fall_damage: 	Option<(units::Velocity, units::HitPoint)>,
+	priv lights: 		~[graphics::Light],
+	priv spawns: 		HashMap<~str, (units::Tile, units::Tile)>,
+	priv doors: 		~[Door],
+	priv script: 		Option<script::Script>,
+	// the boss file fought on this map, & the tile it is placed on
+	priv boss: 			Option<(~str, (units::Tile, units::Tile))>,
+	priv npcs: 			~[npc::Spawn],
+	priv enemies: 		~[EnemySpawn],
+	priv events: 		~[Event],
+	priv triggers: 		~[Trigger],
+	priv forces: 		~[ForceRegion],
+
+	// every distinct animated tile sprite, each advanced once per update
+	priv animated: 		~[RWArc<~sprite::Updatable:Freeze+Send>],
+	// the path of every image this map holds, once for each time it was loaded
+	priv images: 		~[~str]
 }
 
 impl Map {
@@ -62,49 +252,49 @@ impl Map {
 	/// * There are 15-tile high walls in the first and last columns. 
 	/// * A small "obstacle course", 5-tiles wide, is placed about 2 tiles in.
 	/// * A 3-tile high chain is placed on the left-side of this obstacle course.
-	pub fn create_test_map(graphics: &mut graphics::Graphics) -> Map {
+	pub fn create_test_map(graphics: &mut graphics::Graphics) -> GameResult<Map> {
 		static rows: uint = 15; // 480
 		static cols: uint = 20; // 640
 
 		let map_path = ~"assets/base/Stage/PrtCave.bmp";
 		let sprite = RWArc::new(
-			~sprite::Sprite::new(
+			~try!(sprite::Sprite::new(
 				graphics, 
 				(units::Game(0.0), units::Game(0.0)), 
 				(units::Tile(1) , units::Tile(0)),
 				(units::Tile(1), units::Tile(1)),
 				map_path.clone()
-			) as ~sprite::Updatable:Freeze+Send
+			)) as ~sprite::Updatable:Freeze+Send
 		);
 
 		let chain_top = RWArc::new(
-			~sprite::Sprite::new(
+			~try!(sprite::Sprite::new(
 				graphics, 
 				(units::Game(0.0), units::Game(0.0)), 
 				(units::Tile(11), units::Tile(2)),
 				(units::Tile(1), units::Tile(1)),
 				map_path.clone()
-			) as ~sprite::Updatable:Freeze+Send
+			)) as ~sprite::Updatable:Freeze+Send
 		);
 
 		let chain_middle = RWArc::new(
-			~sprite::Sprite::new(
+			~try!(sprite::Sprite::new(
 				graphics, 
 				(units::Game(0.0), units::Game(0.0)), 
 				(units::Tile(12), units::Tile(2)),
 				(units::Tile(1), units::Tile(1)),
 				map_path.clone()
-			) as ~sprite::Updatable:Freeze+Send
+			)) as ~sprite::Updatable:Freeze+Send
 		);
 
 		let chain_bottom = RWArc::new(
-			~sprite::Sprite::new(
+			~try!(sprite::Sprite::new(
 				graphics, 
 				(units::Game(0.0), units::Game(0.0)), 
 				(units::Tile(13), units::Tile(2)),
 				(units::Tile(1), units::Tile(1)),
 				map_path.clone()
-			) as ~sprite::Updatable:Freeze+Send
+			)) as ~sprite::Updatable:Freeze+Send
 		);
 
 		let blank_tile = Tile::new();
@@ -114,14 +304,33 @@ impl Map {
 		let cb_tile = Tile::from_sprite(chain_bottom, Air);
 
 		let mut map = Map {
-			background: backdrop::FixedBackdrop::new(
+			background: try!(backdrop::FixedBackdrop::new(
 				~"assets/base/bkBlue.bmp", graphics
-			),
+			)),
+			parallax: ~[],
 			sprites: vec::from_elem(rows,
 				vec::from_elem(cols, blank_tile.clone())),
 			tiles: vec::from_elem(rows,
-				vec::from_elem(cols, blank_tile.clone()))
+				vec::from_elem(cols, blank_tile.clone())),
+			music: None,
+			weather: None,
+			dark: false,
+			wall_jump: false,
+			fall_damage: None,
+			lights: ~[],
+			spawns: HashMap::new(),
+			doors: ~[],
+			script: None,
+			boss: None,
+			npcs: ~[],
+			enemies: ~[],
+			events: ~[],
+			triggers: ~[],
+			forces: ~[],
+			animated: ~[],
+			images: ~[~"assets/base/bkBlue.bmp"]
 		};
+		for _ in range(0, 4) { map.images.push(map_path.clone()); }
 	
 		// init `floor`
 		for i in range(0, cols) {
@@ -146,44 +355,475 @@ impl Map {
 		map.sprites[rows - 3][2] = cm_tile.clone();
 		map.sprites[rows - 2][2] = cb_tile.clone();
 	
-		map
+		Ok(map)
 	}
 
-	pub fn draw_background(&self, graphics: &graphics::Graphics) {
-		self.background.draw(graphics);
-	}
+	/// Parses a tile map from the text file at `path`.
+	///
+	/// The file consists of a header followed by two grids of tiles:
+	///
+	/// * `size <cols> <rows>` declares the dimensions of the map.
+	/// * `tileset <path>` is the sprite sheet tile ids are drawn from.
+	/// * `backdrop <path>` is the image painted behind the map.
+	/// * `parallax <path> <width> <height> <factor x> <factor y>` adds a layer
+	///   in front of the backdrop which scrolls at a fraction of the camera's
+	///   speed. Layers are drawn in the order they are listed.
+	/// * `music <path> <loop start> <loop end>` is the (optional) track played
+	///   on this map; loop points are given in milliseconds. (An Organya
+	///   song, `.org`, loops where it was written to, ignoring them.) The
+	///   loop must end after it starts.
+	/// * `weather <kind>` fills the screen w/ falling `rain` or `snow`.
+	/// * `dark` covers the map in darkness, lit only by lights.
+	/// * `wall_jump` lets the player slide down walls & jump off of them.
+	///   (Maps w/o it keep the original physics.)
+	/// * `fall_damage <speed> <damage>` hurts the player by `damage` when
+	///   they land at `speed` (in game units per millisecond) or faster.
+	/// * `light <id> <radius>` makes foreground tile `id` give off light
+	///   `radius` tiles around it.
+	/// * `spawn <name> <col> <row>` names a tile the player may be placed at.
+	/// * `door <col> <row> <map> <spawn> [flag]` is a doorway leading to the
+	///   spawn point named `spawn` on the map file `map`; it is locked until
+	///   event flag `flag` (if given) is set.
+	/// * `boss <path> <col> <row>` is the boss (see `boss::Boss`) fought on
+	///   this map, its top-left corner placed on that tile.
+	/// * `npc <sheet> <col> <row> <sprite col> <sprite row> <patrol> <event>`
+	///   places an npc (see `npc::Npc`) on that tile, cut from `sheet` at
+	///   the sprite's tile; it walks up to `patrol` tiles to either side, &
+	///   runs event `event` when the player talks to it.
+	/// * `enemy <kind> <col> <row> [requires <flag>] [defeated <flag>]`
+	///   places an enemy of `kind` (e.g: `bat` or `critter`) on that tile.
+	///   It is only placed once the `requires` flag is set, & is placed no
+	///   more once it has been defeated, if it has a `defeated` flag.
+	/// * `script <path>` is the script file holding this map's events.
+	/// * `event <col> <row> <id>` runs event `id` when the player interacts
+	///   w/ that tile.
+	/// * `trigger <col> <row> <width> <height> <cause> <id>` is an invisible
+	///   area (measured in tiles) which runs event `id` when the player
+	///   `enter`s it, `exit`s it, or `interact`s while inside of it.
+	/// * `force <col> <row> <width> <height> <accel x> <accel y>` is an area
+	///   (measured in tiles) which pushes anything inside of it; accelerations
+	///   are given in game units per millisecond squared.
+	/// * `animation <id> <frames> <fps>` animates tile `id`, cycling through
+	///   `frames` consecutive tiles of the tileset (starting at `id`.)
+	/// * `[foreground]` & `[background]` each begin `rows` lines of
+	///   `cols` comma-separated cells.
+	///
+	/// A cell is written as `<id>[:<type>]` where `id` indexes the tileset
+	/// (`0` is an empty tile) and `type` is either `a` (air), `w` (wall),
+	/// `p` (a one-way platform), `^` (spikes), `l` (a ladder), `i` (an icy
+	/// wall), `>` or `<` (a wall whose surface is a conveyor running right
+	/// or left), or `d<hp>` (a destructible wall w/ `hp` hit points.)
+	/// Cells without a type are air. Lines starting with `#` are ignored.
+	///
+	/// Slopes are written as `s<shape>`; `/` & `\` are 45 degree slopes
+	/// rising to the right & left, while `/1` `/2` (& `\1` `\2`) are the
+	/// lower & upper halves of a 22.5 degree slope.
+	pub fn load_from_file(path: ~str, graphics: &mut graphics::Graphics) -> GameResult<Map> {
+		let contents = match graphics.assets_mut().load_map_source(path.as_slice()) {
+			Ok(contents) => contents,
+			Err(msg) => { return Err(MapError(path, msg)); }
+		};
+		log::info("map", format!("loading {}", path));
 
-	pub fn draw_sprites(&self, graphics: &graphics::Graphics) {
-		for a in range(0, self.sprites.len()) {
-			for b in range(0, self.sprites[a].len()) {
-				match self.sprites[a][b].sprite {
-					Some(ref elem) => {
-						elem.write(|sprite| {
-							sprite.set_position(
-								(units::Tile(b).to_game(),
-								 units::Tile(a).to_game()));
+		let (mut rows, mut cols) = (0u, 0u);
+		let mut tileset_path = ~"assets/base/Stage/PrtCave.bmp";
+		let mut backdrop_path = ~"assets/base/bkBlue.bmp";
+		let mut music = None;
+		let mut weather = None;
+		let mut dark = false;
+		let mut wall_jump = false;
+		let mut fall_damage = None;
+		let mut light_radii: HashMap<uint, units::Tile> = HashMap::new();
+		let mut parallax = ~[];
+		let mut images = ~[];
+		let mut spawns = HashMap::new();
+		let mut doors = ~[];
+		let mut script = None;
+		let mut boss = None;
+		let mut npcs = ~[];
+		let mut enemies = ~[];
+		let mut events = ~[];
+		let mut triggers = ~[];
+		let mut forces = ~[];
+		let mut animations: HashMap<uint, (units::Frame, units::Fps)> = HashMap::new();
+		let mut foreground: ~[~[(uint, TileType)]] = ~[];
+		let mut background: ~[~[(uint, TileType)]] = ~[];
+		let mut section = ~"";
+
+		for raw_line in contents.lines() {
+			let line = raw_line.trim();
+			if line.len() == 0 || line.starts_with("#") { continue; }
+
+			if line.starts_with("[") {
+				section = line.to_owned();
+				continue;
+			}
+
+			match section.as_slice() {
+				"[foreground]" => foreground.push(try!(Map::parse_row(line, cols, &path))),
+				"[background]" => background.push(try!(Map::parse_row(line, cols, &path))),
+				_ => {
+					let words: ~[&str] = line.words().collect();
+					match words.as_slice() {
+						["size", w, h] => {
+							cols = try!(Map::parse_uint(w, &path));
+							rows = try!(Map::parse_uint(h, &path));
+						}
+						["tileset", asset] 	=> { tileset_path = asset.to_owned(); }
+						["backdrop", asset] => { backdrop_path = asset.to_owned(); }
+						["parallax", asset, w, h, factor_x, factor_y] => {
+							images.push(asset.to_owned());
+							parallax.push(try!(backdrop::ParallaxBackdrop::new(
+								asset.to_owned(),
+								(units::Game(try!(Map::parse_f64(w, &path))), units::Game(try!(Map::parse_f64(h, &path)))),
+								(try!(Map::parse_f64(factor_x, &path)), try!(Map::parse_f64(factor_y, &path))),
+								graphics
+							)));
+						}
+						["music", asset, loop_start, loop_end] => {
+							let (start, end) = (try!(Map::parse_uint(loop_start, &path)), try!(Map::parse_uint(loop_end, &path)));
+							// (a loop section must play for some time, lest the track never get past it)
+							if end <= start && !asset.ends_with(audio::ORGANYA_SUFFIX) {
+								return Err(MapError(path.clone(), format!("music loop ends at {} ms, not after its start at {} ms", end, start)));
+							}
+							music = Some(audio::Track::new(asset.to_owned(), units::Millis(start as int), units::Millis(end as int)));
+						}
+						["weather", name] => {
+							weather = match weather::Kind::from_name(name) {
+								Some(kind) => Some(kind),
+								None => { return Err(MapError(path.clone(), format!("unknown weather `{}`", name))); }
+							};
+						}
+						["dark"] => { dark = true; }
+						["wall_jump"] => { wall_jump = true; }
+						["fall_damage", speed, damage] => {
+							fall_damage = Some((
+								units::Velocity(try!(Map::parse_f64(speed, &path))),
+								try!(Map::parse_uint(damage, &path)) as units::HitPoint
+							));
+						}
+						["light", id, radius] => {
+							light_radii.insert(try!(Map::parse_uint(id, &path)), units::Tile(try!(Map::parse_uint(radius, &path))));
+						}
+						["spawn", name, col, row] => {
+							spawns.insert(name.to_owned(), (
+								units::Tile(try!(Map::parse_uint(col, &path))),
+								units::Tile(try!(Map::parse_uint(row, &path)))
+							));
+						}
+						["door", col, row, target_map, target_spawn, ..flag] => {
+							let required_flag = match flag {
+								[] 		=> None,
+								[id] 	=> Some(try!(Map::parse_uint(id, &path))),
+								_ => { return Err(MapError(path.clone(), format!("malformed door `{}`", line))); }
+							};
+							doors.push(Door {
+								col: units::Tile(try!(Map::parse_uint(col, &path))),
+								row: units::Tile(try!(Map::parse_uint(row, &path))),
+								target_map: target_map.to_owned(),
+								target_spawn: target_spawn.to_owned(),
+								required_flag: required_flag
+							});
+						}
+						["animation", id, frames, fps] => {
+							animations.insert(
+								try!(Map::parse_uint(id, &path)),
+								(try!(Map::parse_uint(frames, &path)), try!(Map::parse_uint(fps, &path)))
+							);
+						}
+						["script", asset] => { script = Some(try!(script::Script::load_from_file(asset.to_owned()))); }
+						["npc", asset, col, row, sprite_col, sprite_row, patrol, event] => {
+							npcs.push(npc::Spawn {
+								sheet: asset.to_owned(),
+								position: (
+									units::Tile(try!(Map::parse_uint(col, &path))),
+									units::Tile(try!(Map::parse_uint(row, &path)))
+								),
+								sprite: (
+									units::Tile(try!(Map::parse_uint(sprite_col, &path))),
+									units::Tile(try!(Map::parse_uint(sprite_row, &path)))
+								),
+								patrol: units::Tile(try!(Map::parse_uint(patrol, &path))),
+								event: try!(Map::parse_uint(event, &path))
+							});
+						}
+						["enemy", kind, col, row, ..options] => {
+							if !enemies::KINDS.iter().any(|name| *name == kind) {
+								return Err(MapError(path.clone(), format!("unknown enemy `{}`", kind)));
+							}
+							let (mut required_flag, mut defeated_flag) = (None, None);
+							for option in options.chunks(2) {
+								match option {
+									["requires", id] => { required_flag = Some(try!(Map::parse_uint(id, &path))); }
+									["defeated", id] => { defeated_flag = Some(try!(Map::parse_uint(id, &path))); }
+									_ => { return Err(MapError(path.clone(), format!("malformed enemy `{}`", line))); }
+								}
+							}
+							enemies.push(EnemySpawn {
+								kind: kind.to_owned(),
+								col: units::Tile(try!(Map::parse_uint(col, &path))),
+								row: units::Tile(try!(Map::parse_uint(row, &path))),
+								required_flag: required_flag,
+								defeated_flag: defeated_flag
+							});
+						}
+						["boss", asset, col, row] => {
+							boss = Some((asset.to_owned(), (
+								units::Tile(try!(Map::parse_uint(col, &path))),
+								units::Tile(try!(Map::parse_uint(row, &path)))
+							)));
+						}
+						["event", col, row, id] => {
+							events.push(Event {
+								col: units::Tile(try!(Map::parse_uint(col, &path))),
+								row: units::Tile(try!(Map::parse_uint(row, &path))),
+								id: try!(Map::parse_uint(id, &path))
+							});
+						}
+						["trigger", col, row, w, h, cause, id] => {
+							let cause = match TriggerCause::from_name(cause) {
+								Some(cause) => cause,
+								None => { return Err(MapError(path.clone(), format!("unknown trigger cause `{}`", cause))); }
+							};
+							triggers.push(Trigger {
+								area: Rectangle {
+									x: units::Tile(try!(Map::parse_uint(col, &path))).to_game(),
+									y: units::Tile(try!(Map::parse_uint(row, &path))).to_game(),
+									width: units::Tile(try!(Map::parse_uint(w, &path))).to_game(),
+									height: units::Tile(try!(Map::parse_uint(h, &path))).to_game()
+								},
+								cause: cause,
+								id: try!(Map::parse_uint(id, &path))
+							});
+						}
+						["force", col, row, w, h, accel_x, accel_y] => {
+							forces.push(ForceRegion {
+								area: Rectangle {
+									x: units::Tile(try!(Map::parse_uint(col, &path))).to_game(),
+									y: units::Tile(try!(Map::parse_uint(row, &path))).to_game(),
+									width: units::Tile(try!(Map::parse_uint(w, &path))).to_game(),
+									height: units::Tile(try!(Map::parse_uint(h, &path))).to_game()
+								},
+								accel_x: units::Acceleration(try!(Map::parse_f64(accel_x, &path))),
+								accel_y: units::Acceleration(try!(Map::parse_f64(accel_y, &path)))
+							});
+						}
+						_ => { return Err(MapError(path.clone(), format!("unknown map directive `{}`", line))); }
+					}
+				}
+			}
+		}
+
+		if foreground.len() != rows || background.len() != rows {
+			return Err(MapError(path.clone(), format!("expected {} rows in each layer", rows)));
+		}
+
+		// tiles w/ the same id share a single sprite
+		let mut sprite_cache: HashMap<uint, RWArc<~sprite::Updatable:Freeze+Send>> = HashMap::new();
+		images.push(backdrop_path.clone());
+		let mut map = Map {
+			background: try!(backdrop::FixedBackdrop::new(backdrop_path, graphics)),
+			parallax: parallax,
+			sprites: vec::from_elem(rows, vec::from_elem(cols, Tile::new())),
+			tiles: vec::from_elem(rows, vec::from_elem(cols, Tile::new())),
+			music: music,
+			weather: weather,
+			dark: dark,
+			wall_jump: wall_jump,
+			fall_damage: fall_damage,
+			lights: ~[],
+			spawns: spawns,
+			doors: doors,
+			script: script,
+			boss: boss,
+			npcs: npcs,
+			enemies: enemies,
+			events: events,
+			triggers: triggers,
+			forces: forces,
+			animated: ~[],
+			images: images
+		};
 
-							sprite.draw(graphics);
+		for row in range(0, rows) {
+			for col in range(0, cols) {
+				let (fg_id, fg_type) = foreground[row][col];
+				match light_radii.find(&fg_id) {
+					Some(&radius) => {
+						let half_tile = units::Tile(1).to_game() / units::Game(2.0);
+						map.lights.push(graphics::Light {
+							x: units::Tile(col).to_game() + half_tile,
+							y: units::Tile(row).to_game() + half_tile,
+							radius: radius.to_game()
 						});
 					}
-					_ => {}
-				};
+					None => {}
+				}
+				map.tiles[row][col] = try!(Map::build_tile(
+					graphics, &mut sprite_cache, &mut map.animated,
+					&tileset_path, &animations, fg_id, fg_type
+				));
+
+				let (bg_id, bg_type) = background[row][col];
+				map.sprites[row][col] = try!(Map::build_tile(
+					graphics, &mut sprite_cache, &mut map.animated,
+					&tileset_path, &animations, bg_id, bg_type
+				));
 			}
 		}
+		// each distinct tile loaded its sprite from the tileset
+		for _ in range(0, sprite_cache.len()) { map.images.push(tileset_path.clone()); }
+
+		Ok(map)
+	}
+
+	/// Parses a single comma-separated row of `(tile id, tile type)` cells.
+	fn parse_row(line: &str, cols: uint, path: &~str) -> GameResult<~[(uint, TileType)]> {
+		let mut row: ~[(uint, TileType)] = ~[];
+		for cell in line.split(',') {
+			let parts: ~[&str] = cell.trim().split(':').collect();
+			row.push(match parts.as_slice() {
+				[id] 		=> (try!(Map::parse_uint(id, path)), Air),
+				[id, "a"] 	=> (try!(Map::parse_uint(id, path)), Air),
+				[id, "w"] 	=> (try!(Map::parse_uint(id, path)), Wall),
+				[id, "p"] 	=> (try!(Map::parse_uint(id, path)), Platform),
+				[id, "^"] 	=> (try!(Map::parse_uint(id, path)), Spike),
+				[id, "l"] 	=> (try!(Map::parse_uint(id, path)), Ladder),
+				[id, "i"] 	=> (try!(Map::parse_uint(id, path)), Ice),
+				[id, ">"] 	=> (try!(Map::parse_uint(id, path)), Conveyor(CONVEYOR_SPEED)),
+				[id, "<"] 	=> (try!(Map::parse_uint(id, path)), Conveyor(-CONVEYOR_SPEED)),
+				[id, "s/"] 	=> (try!(Map::parse_uint(id, path)), Slope(1.0, 0.0)),
+				[id, "s/1"] => (try!(Map::parse_uint(id, path)), Slope(1.0, 0.5)),
+				[id, "s/2"] => (try!(Map::parse_uint(id, path)), Slope(0.5, 0.0)),
+				[id, "s\\"] 	=> (try!(Map::parse_uint(id, path)), Slope(0.0, 1.0)),
+				[id, "s\\1"] => (try!(Map::parse_uint(id, path)), Slope(0.5, 1.0)),
+				[id, "s\\2"] => (try!(Map::parse_uint(id, path)), Slope(0.0, 0.5)),
+				[id, kind] if kind.starts_with("d") => (
+					try!(Map::parse_uint(id, path)),
+					Destructible(try!(Map::parse_uint(kind.slice_from(1), path)) as units::HitPoint)
+				),
+				_ => { return Err(MapError(path.clone(), format!("malformed tile `{}`", cell))); }
+			});
+		}
+
+		if row.len() != cols {
+			return Err(MapError(path.clone(), format!("expected {} tiles per row, found {}", cols, row.len())));
+		}
+
+		Ok(row)
+	}
+
+	fn parse_uint(word: &str, path: &~str) -> GameResult<uint> {
+		match from_str::<uint>(word.trim()) {
+			Some(value) => Ok(value),
+			None => Err(MapError(path.clone(), format!("expected a number, found `{}`", word)))
+		}
+	}
+
+	fn parse_f64(word: &str, path: &~str) -> GameResult<f64> {
+		match from_str::<f64>(word.trim()) {
+			Some(value) => Ok(value),
+			None => Err(MapError(path.clone(), format!("expected a number, found `{}`", word)))
+		}
+	}
+
+	/// Creates a tile for `tile_id` on the tileset, reusing any sprite
+	/// which has already been loaded for that id.
+	///
+	/// Tiles listed in `animations` get an animated sprite, which is also
+	/// recorded in `animated` the first time it is loaded.
+	fn build_tile(
+		graphics: &mut graphics::Graphics,
+		sprite_cache: &mut HashMap<uint, RWArc<~sprite::Updatable:Freeze+Send>>,
+		animated: &mut ~[RWArc<~sprite::Updatable:Freeze+Send>],
+		tileset_path: &~str,
+		animations: &HashMap<uint, (units::Frame, units::Fps)>,
+		tile_id: uint, tile_type: TileType
+	) -> GameResult<Tile> {
+		if tile_id == 0 {
+			return Ok(Tile { tile_type: tile_type, sprite: None });
+		}
+
+		if !sprite_cache.contains_key(&tile_id) {
+			let source = (units::Tile(tile_id % TILESET_WIDTH), units::Tile(tile_id / TILESET_WIDTH));
+			let sprite = match animations.find(&tile_id) {
+				Some(&(frames, fps)) => {
+					let animation = RWArc::new(
+						~try!(sprite::AnimatedSprite::new(
+							graphics, tileset_path.clone(),
+							source, (units::Tile(1), units::Tile(1)),
+							frames, fps
+						)) as ~sprite::Updatable:Freeze+Send
+					);
+					animated.push(animation.clone());
+					animation
+				}
+				None => RWArc::new(
+					~try!(sprite::Sprite::new(
+						graphics,
+						(units::Game(0.0), units::Game(0.0)),
+						source,
+						(units::Tile(1), units::Tile(1)),
+						tileset_path.clone()
+					)) as ~sprite::Updatable:Freeze+Send
+				)
+			};
+			sprite_cache.insert(tile_id, sprite);
+		}
+
+		Ok(Tile::from_sprite(sprite_cache.get(&tile_id).clone(), tile_type))
+	}
+
+	/// Lets go of every image this map loaded, e.g: once the player has
+	/// left it. (They are freed by the next `Graphics::unload_unused()`.)
+	pub fn release(&self, graphics: &mut graphics::Graphics) {
+		for path in self.images.iter() {
+			graphics.release_image(path.as_slice());
+		}
+	}
+
+	/// Queues the backdrop & any parallax layers, each scrolled
+	/// according to the camera's `offset`.
+	pub fn draw_background(&self, graphics: &graphics::Graphics, queue: &mut graphics::RenderQueue, offset: (units::Game, units::Game)) {
+		self.background.draw(graphics, queue);
+		for layer in self.parallax.iter() {
+			layer.draw(graphics, queue, offset);
+		}
 	}
 
-	/// Draws current state to `display`
-	pub fn draw(&self, graphics: &graphics::Graphics) {
-		for a in range(0, self.tiles.len()) {
-			for b in range(0, self.tiles[a].len()) {
-				match self.tiles[a][b].sprite {
+	/// Queues the layer of tiles behind the player, shifted by the camera's `offset`
+	pub fn draw_sprites(&self, graphics: &graphics::Graphics, queue: &mut graphics::RenderQueue, offset: (units::Game, units::Game)) {
+		Map::draw_layer(&self.sprites, graphics::BackTiles, graphics, queue, offset);
+	}
+
+	/// Queues the tiles in front of the player, shifted by the camera's `offset`
+	pub fn draw(&self, graphics: &graphics::Graphics, queue: &mut graphics::RenderQueue, offset: (units::Game, units::Game)) {
+		Map::draw_layer(&self.tiles, graphics::ForeTiles, graphics, queue, offset);
+	}
+
+	// queues those tiles of `layer` which fall within the camera's view on `queue_layer`
+	fn draw_layer(
+		layer: &~[~[Tile]],
+		queue_layer: graphics::Layer,
+		graphics: &graphics::Graphics,
+		queue: &mut graphics::RenderQueue,
+		offset: (units::Game, units::Game)
+	) {
+		let (offset_x, offset_y) = offset;
+		let resolution = graphics.resolution();
+		let (first_row, last_row) = Map::visible_range(offset_y, resolution.height, layer.len());
+
+		for a in range(first_row, last_row) {
+			let (first_col, last_col) = Map::visible_range(offset_x, resolution.width, layer[a].len());
+			for b in range(first_col, last_col) {
+				match layer[a][b].sprite {
 					Some(ref elem) => {
 						elem.write(|sprite| {
 							sprite.set_position(
 								(units::Tile(b).to_game(), units::Tile(a).to_game())
 							);
 
-							sprite.draw(graphics);
+							sprite.draw_queued(queue, queue_layer, offset);
 						});
 					}
 					_ => {}
@@ -192,18 +832,130 @@ impl Map {
 		}
 	}
 
+	// the tiles [first, last) along one axis which are (even partly) on a
+	// screen `screen_size` long, scrolled `offset` into a layer of `len` tiles.
+	fn visible_range(offset: units::Game, screen_size: units::Tile, len: uint) -> (uint, uint) {
+		let units::Game(start) = offset;
+		let units::Game(end) = offset + screen_size.to_game();
+		let units::Game(tile) = units::Tile(1).to_game();
+
+		let first = (start / tile).floor();
+		let last = (end / tile).ceil();
+		let clamp = |edge: f64| if edge < 0.0 { 0 } else { cmp::min(edge as uint, len) };
+		(clamp(first), clamp(last))
+	}
+
+	/// The weather falling across this map, if any.
+	pub fn weather(&self) -> Option<weather::Kind> { self.weather }
+
+	/// Responds true if this map is dark, apart from its lights.
+	pub fn is_dark(&self) -> bool { self.dark }
+
+	/// Responds true if the player may slide down & jump off of walls here.
+	pub fn has_wall_jump(&self) -> bool { self.wall_jump }
+
+	/// The landing speed past which the player is hurt here, & by how much.
+	pub fn fall_damage(&self) -> Option<(units::Velocity, units::HitPoint)> { self.fall_damage }
+
+	/// The light given off by this map's tiles, in map coordinates.
+	pub fn lights<'a>(&'a self) -> &'a [graphics::Light] { self.lights.as_slice() }
+
+	/// The track which should play while the player is on this map
+	pub fn music(&self) -> Option<audio::Track> {
+		self.music.clone()
+	}
+
+	/// The position of the spawn point called `name`, if this map has one.
+	pub fn spawn_point(&self, name: &str) -> Option<(units::Game, units::Game)> {
+		self.spawns.find_equiv(&name).map(|&(col, row)| (col.to_game(), row.to_game()))
+	}
+
+	/// The door overlapping `rectangle`, if any.
+	pub fn door_at(&self, rectangle: &Rectangle) -> Option<Door> {
+		self.doors.iter().find(|door| door.rectangle().collides_with(rectangle)).map(|door| door.clone())
+	}
+
+	/// The boss fought on this map, & where it is placed, if there is one.
+	pub fn boss(&self) -> Option<(~str, (units::Game, units::Game))> {
+		self.boss.as_ref().map(|&(ref path, (col, row))| (path.clone(), (col.to_game(), row.to_game())))
+	}
+
+	/// Where each of this map's npcs is placed.
+	pub fn npcs<'a>(&'a self) -> &'a [npc::Spawn] { self.npcs.as_slice() }
+
+	/// Every enemy placed on this map, whether or not it is due.
+	pub fn enemies<'a>(&'a self) -> &'a [EnemySpawn] { self.enemies.as_slice() }
+
+	/// Starts running event `id` of this map's script, if it has one.
+	pub fn run_event(&self, id: uint) -> Option<script::Runner> {
+		match self.script {
+			Some(ref script) => script.run(id),
+			None => None
+		}
+	}
+
+	/// Starts running the event of this map's script placed on a tile
+	/// overlapping `rectangle`, if there is one.
+	pub fn run_event_at(&self, rectangle: &Rectangle) -> Option<script::Runner> {
+		let event = self.events.iter().find(|event| {
+			Rectangle {
+				x: event.col.to_game(), y: event.row.to_game(),
+				width: units::Tile(1).to_game(), height: units::Tile(1).to_game()
+			}.collides_with(rectangle)
+		});
+
+		match (event, &self.script) {
+			(Some(event), &Some(ref script)) => script.run(event.id),
+			_ => None
+		}
+	}
+
+	/// The index of every trigger (run on entering or leaving it) whose
+	/// area overlaps `rectangle`.
+	pub fn triggers_at(&self, rectangle: &Rectangle) -> ~[uint] {
+		self.triggers.iter().enumerate()
+			.filter(|&(_, trigger)| trigger.cause != OnInteract && trigger.area.collides_with(rectangle))
+			.map(|(index, _)| index)
+			.collect()
+	}
+
+	/// Starts running the event of trigger `index` if it is run by `cause`.
+	pub fn run_trigger(&self, index: uint, cause: TriggerCause) -> Option<script::Runner> {
+		match (self.triggers.get(index), &self.script) {
+			(Some(trigger), &Some(ref script)) if trigger.cause == cause => script.run(trigger.id),
+			_ => None
+		}
+	}
+
+	/// Starts running the event of a trigger run by interacting, whose area
+	/// overlaps `rectangle`, if there is one.
+	pub fn run_trigger_at(&self, rectangle: &Rectangle) -> Option<script::Runner> {
+		let trigger = self.triggers.iter().find(|trigger| {
+			trigger.cause == OnInteract && trigger.area.collides_with(rectangle)
+		});
+
+		match (trigger, &self.script) {
+			(Some(trigger), &Some(ref script)) => script.run(trigger.id),
+			_ => None
+		}
+	}
+
+	/// The width of the map in `Game` units
+	pub fn width(&self) -> units::Game {
+		units::Tile(self.tiles[0].len()).to_game()
+	}
+
+	/// The height of the map in `Game` units
+	pub fn height(&self) -> units::Game {
+		units::Tile(self.tiles.len()).to_game()
+	}
+
+	/// Advances the map's animated tiles.
 	pub fn update(&mut self, elapsed_time: units::Millis) {
-		for row in self.tiles.iter() {
-			for col in row.iter() {
-				match col.sprite {
-					Some(ref elem) => {
-						elem.write(|sprite| {
-							sprite.update(elapsed_time);
-						});
-					}
-					_ => {}
-				};
-			}
+		for animation in self.animated.iter() {
+			animation.write(|sprite| {
+				sprite.update(elapsed_time);
+			});
 		}
 	}
 
@@ -233,4 +985,297 @@ impl Map {
 
 		collision_tiles
 	}
+
+	/// Responds true if `rectangle` overlaps a wall, or lies (even partly)
+	/// outside of the map; entities treat the edge of the map as solid.
+	pub fn is_colliding(&self, rectangle: &Rectangle) -> bool {
+		if self.is_out_of_bounds(rectangle) { return true; }
+
+		self.get_colliding_tiles(rectangle).iter().any(|tile| tile.tile_type.is_solid())
+	}
+
+	/// Responds true if any part of `rectangle` lies outside of the map.
+	fn is_out_of_bounds(&self, rectangle: &Rectangle) -> bool {
+		rectangle.left() < units::Game(0.0) || rectangle.right() >= self.width()
+			|| rectangle.top() < units::Game(0.0) || rectangle.bottom() >= self.height()
+	}
+
+	/// The height of the first sloped floor found at `x`, between `top` & `bottom`.
+	///
+	/// Slopes are not solid; entities which care about them (i.e: the player)
+	/// must place their feet on the surface found here.
+	pub fn slope_surface(&self, x: units::Game, top: units::Game, bottom: units::Game) -> Option<units::Game> {
+		if x < units::Game(0.0) || x >= self.width() { return None; }
+
+		let units::Tile(col) = x.to_tile();
+		let units::Tile(first_row) = cmp::max(top, units::Game(0.0)).to_tile();
+		let units::Tile(last_row) = cmp::min(bottom, self.height() - units::Game(1.0)).to_tile();
+		let units::Game(tile_size) = units::Tile(1).to_game();
+		let units::Game(into_tile) = x - units::Tile(col).to_game();
+		let fraction = into_tile / tile_size;
+
+		for row in range(first_row, last_row + 1) {
+			match self.tiles[row][col].tile_type {
+				Slope(left, right) => {
+					let depth = left + ((right - left) * fraction);
+					let surface = units::Tile(row).to_game() + units::Game(depth * tile_size);
+					if surface >= top && surface <= bottom { return Some(surface); }
+				}
+				_ => {}
+			}
+		}
+
+		None
+	}
+
+	/// The top of the highest one-way platform between `left` & `right` whose
+	/// top lies between `from` & `to`; i.e: one which something whose
+	/// bottom moved from `from` down to `to` has landed on.
+	pub fn platform_surface(
+		&self,
+		left: units::Game, right: units::Game,
+		from: units::Game, to: units::Game
+	) -> Option<units::Game> {
+		let units::Tile(first_col) = cmp::max(left, units::Game(0.0)).to_tile();
+		let units::Tile(last_col) = cmp::min(right, self.width() - units::Game(1.0)).to_tile();
+		let units::Tile(first_row) = cmp::max(from, units::Game(0.0)).to_tile();
+		let units::Tile(last_row) = cmp::min(to, self.height() - units::Game(1.0)).to_tile();
+
+		for row in range(first_row, last_row + 1) {
+			let top = units::Tile(row).to_game();
+			if top < from || top > to { continue; }
+
+			for col in range(first_col, last_col + 1) {
+				if self.tiles[row][col].tile_type == Platform { return Some(top); }
+			}
+		}
+
+		None
+	}
+
+	/// The type of the tile containing the point (`x`, `y`), e.g: the one
+	/// beneath an entity's feet; points outside the map lie in `Air`.
+	pub fn tile_type_at(&self, x: units::Game, y: units::Game) -> TileType {
+		if x < units::Game(0.0) || x >= self.width()
+		|| y < units::Game(0.0) || y >= self.height() { return Air; }
+
+		let (units::Tile(col), units::Tile(row)) = (x.to_tile(), y.to_tile());
+		self.tiles[row][col].tile_type
+	}
+
+	/// The total acceleration applied by force regions overlapping `rectangle`.
+	pub fn force_at(&self, rectangle: &Rectangle) -> (units::Acceleration, units::Acceleration) {
+		self.forces.iter()
+			.filter(|force| force.area.collides_with(rectangle))
+			.fold((units::Acceleration(0.0), units::Acceleration(0.0)), |(total_x, total_y), force| {
+				(total_x + force.accel_x, total_y + force.accel_y)
+			})
+	}
+
+	/// The area of the first spike tile overlapping `rectangle`, if any.
+	pub fn spike_at(&self, rectangle: &Rectangle) -> Option<Rectangle> {
+		if self.is_out_of_bounds(rectangle) { return None; }
+
+		self.get_colliding_tiles(rectangle).iter()
+			.find(|tile| tile.tile_type == Spike)
+			.map(|tile| Rectangle {
+				x: tile.col.to_game(), y: tile.row.to_game(),
+				width: units::Tile(1).to_game(), height: units::Tile(1).to_game()
+			})
+	}
+
+	/// Responds true if `rectangle` overlaps any ladder tiles.
+	pub fn is_on_ladder(&self, rectangle: &Rectangle) -> bool {
+		if self.is_out_of_bounds(rectangle) { return false; }
+		self.get_colliding_tiles(rectangle).iter().any(|tile| tile.tile_type == Ladder)
+	}
+
+	/// Deals `damage` to each destructible tile overlapping `rectangle`.
+	///
+	/// Tiles which run out of hit points become air, losing their sprite;
+	/// the `(row, col)` of each is returned.
+	pub fn damage_tiles(&mut self, rectangle: &Rectangle, damage: units::HitPoint) -> ~[(units::Tile, units::Tile)] {
+		let mut destroyed = ~[];
+		if self.is_out_of_bounds(rectangle) { return destroyed; }
+
+		for tile in self.get_colliding_tiles(rectangle).iter() {
+			let (units::Tile(row), units::Tile(col)) = (tile.row, tile.col);
+			match tile.tile_type {
+				Destructible(hit_points) if hit_points > damage => {
+					self.tiles[row][col].tile_type = Destructible(hit_points - damage);
+				}
+				Destructible(_) => {
+					self.tiles[row][col] = Tile::new();
+					destroyed.push((tile.row, tile.col));
+				}
+				_ => {}
+			}
+		}
+
+		destroyed
+	}
+
+	/// The map's tiles as they are now, including any destroyed.
+	pub fn terrain(&self) -> Terrain {
+		Terrain { tiles: self.tiles.clone() }
+	}
+
+	/// Puts back the tiles of `terrain`, which must be of this map.
+	pub fn restore_terrain(&mut self, terrain: Terrain) {
+		self.tiles = terrain.tiles;
+	}
+}
+
+/// The tiles of a map at some point, e.g: before any were destroyed.
+/// (Tiles share their sprites, so this is cheap to keep.)
+#[deriving(Clone)]
+pub struct Terrain {
+	priv tiles: ~[~[Tile]]
+}
+
+/// Owns the map the player is currently exploring, and swaps it for
+/// another when the player walks through a door.
+///
+/// Leaving a map covers the screen in black w/ a transition; the next
+/// map is loaded once the screen is dark, and then revealed again.
+pub struct MapManager {
+	priv current: 	Map,
+	priv path: 		~str,
+
+	// the transition covering (or revealing) the screen, & its style
+	priv transition: Option<transitions::Effect>,
+	priv style: 	transitions::Style,
+	// the map & spawn point being travelled to
+	priv pending: 	Option<(~str, ~str)>
+}
+
+impl MapManager {
+	/// Starts the player off on the map stored at `path`.
+	pub fn new(path: ~str, graphics: &mut graphics::Graphics) -> GameResult<MapManager> {
+		Ok(MapManager {
+			current: try!(Map::load_from_file(path.clone(), graphics)),
+			path: path,
+
+			transition: None,
+			style: transitions::Fade,
+			pending: None
+		})
+	}
+
+	pub fn current<'a>(&'a self) -> &'a Map { &self.current }
+	pub fn current_mut<'a>(&'a mut self) -> &'a mut Map { &mut self.current }
+
+	/// The file the current map was loaded from.
+	pub fn path(&self) -> ~str { self.path.clone() }
+
+	/// Rebuilds the current map from its file, e.g: once it has been
+	/// edited w/ hot reloading on. Tiles which were destroyed are restored.
+	/// If the file no longer loads the error is logged, & the map is kept.
+	pub fn reload(&mut self, graphics: &mut graphics::Graphics) {
+		match Map::load_from_file(self.path.clone(), graphics) {
+			Ok(map) => {
+				self.current.release(graphics);
+				graphics.assets_mut().release_map(self.path.as_slice());
+				self.current = map;
+			}
+			Err(err) => log::error("map", err.message())
+		}
+	}
+
+	/// Creates the current map's enemies which are due, given the `flags`
+	/// set, each alongside the flag to set once it has been defeated.
+	/// An enemy which can not be loaded is left out (& the error logged.)
+	pub fn spawn_enemies(
+		&self,
+		graphics: &mut graphics::Graphics,
+		flags: &flags::FlagSet
+	) -> ~[(~Enemy, Option<uint>)] {
+		let mut spawned = ~[];
+		for spawn in self.current.enemies().iter().filter(|spawn| spawn.is_due(flags)) {
+			match enemies::create(graphics, spawn.kind.as_slice(), spawn.col.to_game(), spawn.row.to_game()) {
+				Ok(enemy) => spawned.push((enemy, spawn.defeated_flag)),
+				Err(err) => log::error("map", err.message())
+			}
+		}
+		spawned
+	}
+
+	/// Begins leaving the current map through `door`, fading out.
+	pub fn enter(&mut self, door: Door) {
+		self.travel(door.target_map, door.target_spawn, transitions::Fade);
+	}
+
+	/// Begins leaving the current map for the spawn point `target_spawn`
+	/// on the map stored at `target_map`, w/ a transition of `style`.
+	/// Ignored while a transition is already underway.
+	pub fn travel(&mut self, target_map: ~str, target_spawn: ~str, style: transitions::Style) {
+		if self.is_transitioning() { return; }
+
+		self.transition = Some(transitions::Effect::new(style, transitions::Out, FADE_TIME));
+		self.style = style;
+		self.pending = Some((target_map, target_spawn));
+	}
+
+	/// Responds true while the screen is transitioning between maps.
+	pub fn is_transitioning(&self) -> bool {
+		self.transition.is_some()
+	}
+
+	/// Advances any transition in progress.
+	///
+	/// Once the screen has faded out the door's target map is loaded;
+	/// the position the player should be placed at is then returned.
+	/// If that map can not be loaded (or lacks the spawn point) the error
+	/// is logged, and the screen fades back in on the current map.
+	pub fn update(
+		&mut self,
+		elapsed_time: units::Millis,
+		graphics: &mut graphics::Graphics
+	) -> Option<(units::Game, units::Game)> {
+		let direction = match self.transition {
+			Some(ref mut effect) => {
+				effect.update(elapsed_time);
+				if !effect.is_finished() { return None; }
+				effect.direction()
+			}
+			None => return None
+		};
+
+		match direction {
+			transitions::Out => {
+				let (target_map, target_spawn) = self.pending.take_unwrap();
+				self.transition = Some(transitions::Effect::new(self.style, transitions::In, FADE_TIME));
+
+				let map = match Map::load_from_file(target_map.clone(), graphics) {
+					Ok(map) => map,
+					Err(err) => { log::error("map", err.message()); return None; }
+				};
+				match map.spawn_point(target_spawn.as_slice()) {
+					Some(position) => {
+						self.current.release(graphics);
+						graphics.assets_mut().release_map(self.path.as_slice());
+						self.current = map;
+						self.path = target_map;
+						Some(position)
+					}
+					None => {
+						log::error("map", format!("{}: no spawn point named `{}`", target_map, target_spawn));
+						map.release(graphics);
+						graphics.assets_mut().release_map(target_map.as_slice());
+						None
+					}
+				}
+			}
+			transitions::In => { self.transition = None; None }
+		}
+	}
+
+	/// Draws any transition in progress over the screen, an iris closing
+	/// around `focus` (in screen space.)
+	pub fn draw_transition(&self, graphics: &graphics::Graphics, focus: (units::Game, units::Game)) {
+		match self.transition {
+			Some(ref effect) => effect.draw(graphics, focus),
+			None => {}
+		}
+	}
 }
@@ -0,0 +1,783 @@
+use sdl2::pixels;
+use sdl2::rect;
+
+use std::comm;
+use std::io;
+use std::io::{IoResult, MemReader, MemWriter};
+use std::io::net::ip::{Ipv4Addr, SocketAddr};
+use std::io::net::udp::UdpSocket;
+use std::str;
+use std::vec;
+
+use game::graphics;
+use game::input;
+use game::log;
+use game::units;
+
+/// The port games are hosted on unless another is chosen.
+pub static DEFAULT_PORT: u16 = 7777;
+
+// every packet starts w/ these bytes, the protocol's version, then its kind
+static MAGIC: &'static [u8] = bytes!("RSNT");
+static VERSION: u8 = 1;
+
+// the kinds of packet: clients send `HELLO` until they are sent a snapshot,
+// then their `INPUTS` every frame, & `BYE` as they leave; servers send
+// `SNAPSHOT`s, `FULL` to any client beyond the first, & `BYE` as they close.
+static HELLO: u8 	= 0;
+static INPUTS: u8 	= 1;
+static BYE: u8 		= 2;
+static SNAPSHOT: u8 = 3;
+static FULL: u8 	= 4;
+
+// the largest packet sent (or read), & the most draw calls a snapshot holds
+static MAX_PACKET: uint = 65507;
+static MAX_CALLS: uint 	= 2048;
+
+// a snapshot is sent every so many update steps
+static SNAPSHOT_STEPS: uint = 2;
+static SNAPSHOT_INTERVAL: units::Millis = units::Millis(2 * 1000 / 60);
+
+// how long either end waits to hear from the other before giving up on it,
+// & how often a client repeats its hello until it is answered
+static TIMEOUT: units::Millis 			= units::Millis(5000);
+static HELLO_INTERVAL: units::Millis 	= units::Millis(500);
+
+// a blit which moved further than this between snapshots is not
+// interpolated, as it is most likely some other entity's (in pixels)
+static MAX_BLEND_DISTANCE: i32 = 32;
+
+/// The actions a remote player may send, one bit apiece, in this order.
+static REMOTE_ACTIONS: [input::Action, ..8] = [
+	input::MoveLeft, input::MoveRight, input::LookUp, input::LookDown,
+	input::Jump, input::Fire, input::NextWeapon, input::PrevWeapon
+];
+
+// the bit standing for `action` in a mask of held actions (none for actions not sent)
+fn bit(action: input::Action) -> u16 {
+	match REMOTE_ACTIONS.iter().position(|remote| *remote == action) {
+		Some(index) => 1 << index,
+		None => 0
+	}
+}
+
+/// A packet of `kind`, w/ `body` after its header.
+fn packet(kind: u8, body: &[u8]) -> ~[u8] {
+	let mut bytes = MAGIC.to_owned();
+	bytes.push(VERSION);
+	bytes.push(kind);
+	bytes.push_all(body);
+	bytes
+}
+
+/// The kind & body of `bytes`, if they are a packet of this protocol.
+fn open<'a>(bytes: &'a [u8]) -> Option<(u8, &'a [u8])> {
+	let header = MAGIC.len() + 2;
+	if bytes.len() < header || bytes.slice_to(MAGIC.len()) != MAGIC || bytes[MAGIC.len()] != VERSION {
+		return None;
+	}
+	Some((bytes[MAGIC.len() + 1], bytes.slice_from(header)))
+}
+
+fn invalid(desc: &'static str) -> io::IoError {
+	io::IoError { kind: io::InvalidInput, desc: desc, detail: None }
+}
+
+/// A UDP socket which is read w/o blocking: a task blocks on it instead,
+/// handing on each packet (& its sender) as it arrives.
+struct Link {
+	socket: UdpSocket,
+	// `None` once the link is closing
+	packets: Option<comm::Receiver<(~[u8], SocketAddr)>>
+}
+
+impl Link {
+	fn bind(address: SocketAddr) -> IoResult<Link> {
+		let socket = try!(UdpSocket::bind(address));
+		let reader = socket.clone();
+		let (sender, packets) = comm::channel();
+
+		spawn(proc() {
+			let mut reader = reader;
+			let mut buffer = vec::from_elem(MAX_PACKET, 0u8);
+			loop {
+				match reader.recvfrom(buffer.as_mut_slice()) {
+					// (no one is listening once the link has closed)
+					Ok((length, from)) => if !sender.try_send((buffer.slice_to(length).to_owned(), from)) { break; },
+					Err(_) => break
+				}
+			}
+		});
+
+		Ok(Link { socket: socket, packets: Some(packets) })
+	}
+
+	fn send(&mut self, bytes: &[u8], to: SocketAddr) {
+		match self.socket.sendto(bytes, to) {
+			Ok(_) => {}
+			Err(msg) => log::debug("net", format!("a packet could not be sent to {}: {}", to, msg))
+		}
+	}
+
+	/// The next packet to have arrived (& who sent it), if any has.
+	fn receive(&mut self) -> Option<(~[u8], SocketAddr)> {
+		match self.packets {
+			Some(ref packets) => match packets.try_recv() {
+				comm::Data(packet) => Some(packet),
+				_ => None
+			},
+			None => None
+		}
+	}
+}
+
+/// The reading task is woken by an empty packet, so that it sees the link has closed.
+impl Drop for Link {
+	fn drop(&mut self) {
+		self.packets = None;
+		match self.socket.socket_name() {
+			Ok(address) => {
+				let own = SocketAddr { ip: Ipv4Addr(127, 0, 0, 1), port: address.port };
+				self.send([], own);
+			}
+			Err(_) => {}
+		}
+	}
+}
+
+/// The inputs of a player on another machine, as last sent.
+///
+/// Only which actions are held is sent; presses & releases are told apart
+/// from how that changes, so a lost packet at most delays them.
+#[deriving(Clone)]
+pub struct RemoteInput {
+	priv held: u16,
+	// since the start of the frame
+	priv pressed: u16,
+	priv released: u16
+}
+
+impl RemoteInput {
+	fn new() -> RemoteInput {
+		RemoteInput { held: 0, pressed: 0, released: 0 }
+	}
+
+	fn begin_new_frame(&mut self) {
+		self.pressed = 0;
+		self.released = 0;
+	}
+
+	fn apply(&mut self, held: u16) {
+		self.pressed = self.pressed | (held & !self.held);
+		self.released = self.released | (self.held & !held);
+		self.held = held;
+	}
+}
+
+impl input::Controls for RemoteInput {
+	fn was_key_pressed(&self, action: input::Action) -> bool { self.pressed & bit(action) != 0 }
+
+	/// (How long ago a remote press was made is not known.)
+	fn press_age(&self, action: input::Action) -> Option<units::Millis> {
+		if self.was_key_pressed(action) { Some(units::Millis(0)) } else { None }
+	}
+
+	fn was_key_released(&self, action: input::Action) -> bool { self.released & bit(action) != 0 }
+	fn is_key_held(&self, action: input::Action) -> bool { self.held & bit(action) != 0 }
+}
+
+/// What the HUD of a remote player shows.
+#[deriving(Clone)]
+pub struct Status {
+	hit_points: units::HitPoint,
+	max_hit_points: units::HitPoint,
+	/// The level & energy of the weapon at hand, & its `(current, max)` ammo (if it uses any.)
+	level: uint,
+	energy: uint,
+	ammo: Option<(uint, uint)>,
+	/// The booster's `(remaining, full)` fuel, while there is one.
+	fuel: Option<(units::Millis, units::Millis)>
+}
+
+/// A single call drawn by a snapshot, in map coordinates (pixels.)
+#[deriving(Clone)]
+pub enum Call {
+	/// A blit from the sheet w/ the given index in `Snapshot::sheets`.
+	Blit(uint, graphics::Layer, rect::Rect, rect::Rect, graphics::DrawParams),
+	Fill(graphics::Layer, rect::Rect, pixels::Color)
+}
+
+/// The world as the server last drew it, sent to the client to be drawn.
+///
+/// Rather than every entity's state, a snapshot holds the calls drawing
+/// them: the client draws them as they are, atop its own copy of the map.
+#[deriving(Clone)]
+pub struct Snapshot {
+	priv tick: u32,
+	/// The map being played, & where the client's view is centered.
+	map_path: ~str,
+	focus: (units::Game, units::Game),
+	/// The client's player, or `None` until they have joined in.
+	status: Option<Status>,
+	/// The path of each sheet drawn from, & how it is to be loaded.
+	sheets: ~[(~str, graphics::ImageOptions)],
+	calls: ~[Call]
+}
+
+static LAYERS: [graphics::Layer, ..6] = [
+	graphics::Backdrop, graphics::BackTiles, graphics::Entities,
+	graphics::Particles, graphics::ForeTiles, graphics::Overlay
+];
+
+impl Snapshot {
+	/// Takes every call in `queue` (which should be unculled, & drawn w/o
+	/// offset), naming the sheets they are drawn from. Calls from textures
+	/// w/o a file, e.g: rendered text, are left out.
+	pub fn capture(
+		display: &graphics::Graphics,
+		queue: &mut graphics::RenderQueue,
+		map_path: ~str,
+		focus: (units::Game, units::Game),
+		status: Option<Status>
+	) -> Snapshot {
+		let mut sheets: ~[(~str, graphics::ImageOptions)] = ~[];
+		let mut textures: ~[graphics::Texture] = ~[];
+		let mut calls = ~[];
+
+		for (layer, call) in queue.take_calls().move_iter() {
+			if calls.len() >= MAX_CALLS { break; }
+			match call {
+				graphics::Copy(texture, src, dest, params) => {
+					let sheet = match textures.iter().position(|known| *known == texture) {
+						Some(sheet) => sheet,
+						None => match display.image_source(texture) {
+							Some(source) => {
+								sheets.push(source);
+								textures.push(texture);
+								textures.len() - 1
+							}
+							None => continue
+						}
+					};
+					calls.push(Blit(sheet, layer, src, dest, params));
+				}
+				graphics::Fill(dest, color) => calls.push(Fill(layer, dest, color))
+			}
+		}
+
+		Snapshot { tick: 0, map_path: map_path, focus: focus, status: status, sheets: sheets, calls: calls }
+	}
+
+	/// The path of the sheet w/ index `sheet`.
+	pub fn sheet_path<'a>(&'a self, sheet: uint) -> &'a str {
+		let (ref path, _) = self.sheets[sheet];
+		path.as_slice()
+	}
+
+	/// The calls of this snapshot, w/ each blit moved back `1 - fraction`
+	/// of the way towards where it was drawn by `previous`.
+	///
+	/// Blits are matched up by the order they are drawn in, which stays the
+	/// same as long as no entity comes or goes; blits which do not match are
+	/// drawn where this snapshot has them.
+	pub fn blend(&self, previous: &Snapshot, fraction: f64) -> ~[Call] {
+		self.calls.iter().enumerate().map(|(index, call)| {
+			let before = if index < previous.calls.len() { Some(&previous.calls[index]) } else { None };
+			match (call, before) {
+				(&Blit(sheet, layer, src, dest, params), Some(&Blit(old_sheet, old_layer, _, old_dest, _)))
+					if layer == old_layer
+					&& self.sheet_path(sheet) == previous.sheet_path(old_sheet)
+					&& (dest.x - old_dest.x).abs() <= MAX_BLEND_DISTANCE
+					&& (dest.y - old_dest.y).abs() <= MAX_BLEND_DISTANCE =>
+					Blit(sheet, layer, src, rect::Rect::new(mix(old_dest.x, dest.x, fraction), mix(old_dest.y, dest.y, fraction), dest.w, dest.h), params),
+				_ => call.clone()
+			}
+		}).collect()
+	}
+
+	/// Where the view is centered, `fraction` of the way from `previous`'s focus to this one's.
+	pub fn blend_focus(&self, previous: &Snapshot, fraction: f64) -> (units::Game, units::Game) {
+		let ((from_x, from_y), (to_x, to_y)) = (previous.focus, self.focus);
+		(from_x + ((to_x - from_x) * units::Game(fraction)), from_y + ((to_y - from_y) * units::Game(fraction)))
+	}
+
+	fn write(&self, out: &mut MemWriter) -> IoResult<()> {
+		try!(out.write_le_u32(self.tick));
+		try!(write_str(out, self.map_path.as_slice()));
+		let (units::Game(x), units::Game(y)) = self.focus;
+		try!(out.write_le_f32(x as f32));
+		try!(out.write_le_f32(y as f32));
+
+		match self.status {
+			Some(ref status) => {
+				try!(out.write_u8(1));
+				try!(out.write_le_i32(status.hit_points as i32));
+				try!(out.write_le_i32(status.max_hit_points as i32));
+				try!(out.write_le_u16(status.level as u16));
+				try!(out.write_le_u16(status.energy as u16));
+				let (current, max) = status.ammo.unwrap_or((0, 0));
+				try!(out.write_u8(if status.ammo.is_some() { 1 } else { 0 }));
+				try!(out.write_le_u16(current as u16));
+				try!(out.write_le_u16(max as u16));
+				let (units::Millis(fuel), units::Millis(full)) = status.fuel.unwrap_or((units::Millis(0), units::Millis(0)));
+				try!(out.write_u8(if status.fuel.is_some() { 1 } else { 0 }));
+				try!(out.write_le_u16(fuel as u16));
+				try!(out.write_le_u16(full as u16));
+			}
+			None => try!(out.write_u8(0))
+		}
+
+		try!(out.write_le_u16(self.sheets.len() as u16));
+		for &(ref path, options) in self.sheets.iter() {
+			try!(write_str(out, path.as_slice()));
+			match options.color_key {
+				Some((r, g, b)) => { try!(out.write([1, r, g, b])); }
+				None => { try!(out.write([0, 0, 0, 0])); }
+			}
+		}
+
+		try!(out.write_le_u16(self.calls.len() as u16));
+		for call in self.calls.iter() {
+			match *call {
+				Blit(sheet, layer, src, dest, params) => {
+					try!(out.write_u8(0));
+					try!(out.write_le_u16(sheet as u16));
+					try!(out.write_u8(layer as u8));
+					try!(write_rect(out, &src));
+					try!(write_rect(out, &dest));
+					let flags = (if params.flip_horizontal { 1 } else { 0 })
+						| (if params.flip_vertical { 2 } else { 0 })
+						| (if params.blend == graphics::Additive { 4 } else { 0 });
+					try!(out.write_u8(flags));
+					let units::Degrees(angle) = params.angle;
+					try!(out.write_le_f32(angle as f32));
+					let (r, g, b) = params.tint;
+					try!(out.write([r, g, b, params.alpha]));
+				}
+				Fill(layer, dest, color) => {
+					try!(out.write_u8(1));
+					try!(out.write_u8(layer as u8));
+					try!(write_rect(out, &dest));
+					let (r, g, b, a) = match color {
+						pixels::RGB(r, g, b) => (r, g, b, 255),
+						pixels::RGBA(r, g, b, a) => (r, g, b, a)
+					};
+					try!(out.write([r, g, b, a]));
+				}
+			}
+		}
+		Ok(())
+	}
+
+	fn read(input: &mut MemReader) -> IoResult<Snapshot> {
+		let tick = try!(input.read_le_u32());
+		let map_path = try!(read_str(input));
+		let x = try!(input.read_le_f32()) as f64;
+		let y = try!(input.read_le_f32()) as f64;
+
+		let status = if try!(input.read_u8()) == 1 {
+			let hit_points = try!(input.read_le_i32()) as units::HitPoint;
+			let max_hit_points = try!(input.read_le_i32()) as units::HitPoint;
+			let level = try!(input.read_le_u16()) as uint;
+			let energy = try!(input.read_le_u16()) as uint;
+			let has_ammo = try!(input.read_u8()) == 1;
+			let ammo = (try!(input.read_le_u16()) as uint, try!(input.read_le_u16()) as uint);
+			let has_fuel = try!(input.read_u8()) == 1;
+			let fuel = (units::Millis(try!(input.read_le_u16()) as int), units::Millis(try!(input.read_le_u16()) as int));
+			Some(Status {
+				hit_points: hit_points, max_hit_points: max_hit_points,
+				level: level, energy: energy,
+				ammo: if has_ammo { Some(ammo) } else { None },
+				fuel: if has_fuel { Some(fuel) } else { None }
+			})
+		} else {
+			None
+		};
+
+		let mut sheets = ~[];
+		for _ in range(0, try!(input.read_le_u16())) {
+			let path = try!(read_str(input));
+			let key = try!(input.read_bytes(4));
+			let options = graphics::ImageOptions {
+				color_key: if key[0] == 1 { Some((key[1], key[2], key[3])) } else { None }
+			};
+			sheets.push((path, options));
+		}
+
+		let count = try!(input.read_le_u16()) as uint;
+		if count > MAX_CALLS { return Err(invalid("too many draw calls")); }
+		let mut calls = ~[];
+		for _ in range(0, count) {
+			let call = match try!(input.read_u8()) {
+				0 => {
+					let sheet = try!(input.read_le_u16()) as uint;
+					if sheet >= sheets.len() { return Err(invalid("no such sheet")); }
+					let layer = try!(read_layer(input));
+					let src = try!(read_rect(input));
+					let dest = try!(read_rect(input));
+					let flags = try!(input.read_u8());
+					let angle = try!(input.read_le_f32()) as f64;
+					let color = try!(input.read_bytes(4));
+					Blit(sheet, layer, src, dest, graphics::DrawParams {
+						flip_horizontal: flags & 1 != 0,
+						flip_vertical: flags & 2 != 0,
+						angle: units::Degrees(angle),
+						tint: (color[0], color[1], color[2]),
+						alpha: color[3],
+						blend: if flags & 4 != 0 { graphics::Additive } else { graphics::Normal }
+					})
+				}
+				1 => {
+					let layer = try!(read_layer(input));
+					let dest = try!(read_rect(input));
+					let color = try!(input.read_bytes(4));
+					Fill(layer, dest, pixels::RGBA(color[0], color[1], color[2], color[3]))
+				}
+				_ => return Err(invalid("unknown draw call"))
+			};
+			calls.push(call);
+		}
+
+		Ok(Snapshot {
+			tick: tick, map_path: map_path, focus: (units::Game(x), units::Game(y)),
+			status: status, sheets: sheets, calls: calls
+		})
+	}
+}
+
+// `fraction` of the way from `from` to `to`
+fn mix(from: i32, to: i32, fraction: f64) -> i32 {
+	from + ((to - from) as f64 * fraction).round() as i32
+}
+
+// strings are written as their length (in bytes) then their utf-8
+fn write_str(out: &mut MemWriter, text: &str) -> IoResult<()> {
+	try!(out.write_le_u16(text.len() as u16));
+	out.write(text.as_bytes())
+}
+
+fn read_str(input: &mut MemReader) -> IoResult<~str> {
+	let length = try!(input.read_le_u16()) as uint;
+	let bytes = try!(input.read_bytes(length));
+	match str::from_utf8_owned(bytes) {
+		Some(text) => Ok(text),
+		None => Err(invalid("a string is not utf-8"))
+	}
+}
+
+fn write_rect(out: &mut MemWriter, rectangle: &rect::Rect) -> IoResult<()> {
+	try!(out.write_le_i32(rectangle.x));
+	try!(out.write_le_i32(rectangle.y));
+	try!(out.write_le_u16(rectangle.w as u16));
+	out.write_le_u16(rectangle.h as u16)
+}
+
+fn read_rect(input: &mut MemReader) -> IoResult<rect::Rect> {
+	let x = try!(input.read_le_i32());
+	let y = try!(input.read_le_i32());
+	let w = try!(input.read_le_u16()) as i32;
+	let h = try!(input.read_le_u16()) as i32;
+	Ok(rect::Rect::new(x, y, w, h))
+}
+
+fn read_layer(input: &mut MemReader) -> IoResult<graphics::Layer> {
+	let index = try!(input.read_u8()) as uint;
+	if index < LAYERS.len() { Ok(LAYERS[index]) } else { Err(invalid("unknown layer")) }
+}
+
+// the player on the other machine, as the server knows them
+struct Peer {
+	address: SocketAddr,
+	input: RemoteInput,
+	// the latest inputs applied, & how long since the peer was last heard from
+	sequence: u32,
+	silence: units::Millis
+}
+
+/// Hosts a game for a player on another machine: their inputs are read
+/// each frame (to direct the second player), & the world is sent back to
+/// them as `Snapshot`s every few update steps. One client is let in at a
+/// time; any other is turned away until they leave.
+pub struct Server {
+	priv link: Link,
+	priv client: Option<Peer>,
+	// update steps since the last snapshot was sent, & snapshots sent
+	priv steps: uint,
+	priv tick: u32
+}
+
+impl Server {
+	/// Starts listening on `port`, of every address this machine has.
+	pub fn host(port: u16) -> IoResult<Server> {
+		let link = try!(Link::bind(SocketAddr { ip: Ipv4Addr(0, 0, 0, 0), port: port }));
+		log::info("net", format!("hosting on port {}", port));
+		Ok(Server { link: link, client: None, steps: 0, tick: 0 })
+	}
+
+	/// Reads every packet to have arrived since the last frame.
+	pub fn poll(&mut self) {
+		match self.client {
+			Some(ref mut peer) => peer.input.begin_new_frame(),
+			None => {}
+		}
+
+		loop {
+			let (bytes, from) = match self.link.receive() {
+				Some(packet) => packet,
+				None => break
+			};
+			match open(bytes) {
+				Some((HELLO, _)) => self.greet(from),
+				Some((INPUTS, body)) => self.read_inputs(from, body),
+				Some((BYE, _)) => if self.is_client(from) {
+					log::info("net", format!("{} has left", from));
+					self.client = None;
+				},
+				_ => log::debug("net", format!("ignoring a stray packet from {}", from))
+			}
+		}
+	}
+
+	fn is_client(&self, address: SocketAddr) -> bool {
+		self.client.as_ref().map_or(false, |peer| peer.address == address)
+	}
+
+	fn greet(&mut self, from: SocketAddr) {
+		if self.is_client(from) {
+			match self.client { Some(ref mut peer) => peer.silence = units::Millis(0), None => {} }
+		} else if self.client.is_none() {
+			log::info("net", format!("{} has joined", from));
+			self.client = Some(Peer { address: from, input: RemoteInput::new(), sequence: 0, silence: units::Millis(0) });
+		} else {
+			self.link.send(packet(FULL, []), from);
+		}
+	}
+
+	fn read_inputs(&mut self, from: SocketAddr, body: &[u8]) {
+		let mut reader = MemReader::new(body.to_owned());
+		let (sequence, held) = match (reader.read_le_u32(), reader.read_le_u16()) {
+			(Ok(sequence), Ok(held)) => (sequence, held),
+			_ => { log::debug("net", format!("ignoring malformed inputs from {}", from)); return; }
+		};
+		if !self.is_client(from) { return; }
+		match self.client {
+			Some(ref mut peer) => {
+				peer.silence = units::Millis(0);
+				// inputs which arrive out of order are stale
+				if sequence > peer.sequence {
+					peer.sequence = sequence;
+					peer.input.apply(held);
+				}
+			}
+			None => {}
+		}
+	}
+
+	/// Advances the server by a single update step, letting go of a client
+	/// which has fallen silent. Responds true when a snapshot is due.
+	pub fn update(&mut self, elapsed_time: units::Millis) -> bool {
+		let timed_out = match self.client {
+			Some(ref mut peer) => { peer.silence = peer.silence + elapsed_time; peer.silence > TIMEOUT }
+			None => false
+		};
+		if timed_out {
+			log::info("net", "the remote player has timed out");
+			self.client = None;
+		}
+
+		self.steps += 1;
+		if self.client.is_none() || self.steps < SNAPSHOT_STEPS { return false; }
+		self.steps = 0;
+		true
+	}
+
+	/// Responds true while a player on another machine is connected.
+	pub fn is_connected(&self) -> bool { self.client.is_some() }
+
+	/// The inputs of the player on another machine, while one is connected.
+	pub fn input(&self) -> Option<RemoteInput> {
+		self.client.as_ref().map(|peer| peer.input.clone())
+	}
+
+	/// Sends `snapshot` to the connected client, if any.
+	pub fn send(&mut self, snapshot: Snapshot) {
+		let address = match self.client {
+			Some(ref peer) => peer.address,
+			None => return
+		};
+		self.tick += 1;
+		let mut snapshot = snapshot;
+		snapshot.tick = self.tick;
+
+		let mut body = MemWriter::new();
+		match snapshot.write(&mut body) {
+			Ok(_) => {}
+			Err(msg) => { log::warn("net", format!("a snapshot could not be written: {}", msg)); return; }
+		}
+		let bytes = packet(SNAPSHOT, body.unwrap());
+		if bytes.len() > MAX_PACKET {
+			log::warn("net", format!("a snapshot of {} bytes is too large to be sent", bytes.len()));
+			return;
+		}
+		self.link.send(bytes, address);
+	}
+}
+
+/// The client (if any) is told as the server closes.
+impl Drop for Server {
+	fn drop(&mut self) {
+		match self.client {
+			Some(ref peer) => { let address = peer.address; self.link.send(packet(BYE, []), address); }
+			None => {}
+		}
+	}
+}
+
+/// Plays a game hosted on another machine: sends the player's inputs
+/// each frame, & keeps the two latest snapshots sent back so that what
+/// is drawn may be interpolated between them.
+pub struct Client {
+	priv link: Link,
+	priv server: SocketAddr,
+	priv sequence: u32,
+	priv previous: Option<Snapshot>,
+	priv latest: Option<Snapshot>,
+	// how long since the latest snapshot arrived, & since the server was heard from
+	priv since_latest: units::Millis,
+	priv silence: units::Millis,
+	// how long since the last hello was sent, while none has been answered
+	priv since_hello: units::Millis,
+	// why the game has ended, once it has
+	priv ended: Option<~str>
+}
+
+impl Client {
+	/// Asks the server at `address` (e.g: `192.168.0.2:7777`) to let this machine join in.
+	pub fn connect(address: &str) -> Result<Client, ~str> {
+		let server = match from_str::<SocketAddr>(address) {
+			Some(server) => server,
+			None => return Err(format!("`{}` is not an address, e.g: 192.168.0.2:{}", address, DEFAULT_PORT))
+		};
+		let mut link = match Link::bind(SocketAddr { ip: Ipv4Addr(0, 0, 0, 0), port: 0 }) {
+			Ok(link) => link,
+			Err(msg) => return Err(format!("could not open a socket: {}", msg))
+		};
+		link.send(packet(HELLO, []), server);
+		log::info("net", format!("joining {} ...", server));
+
+		Ok(Client {
+			link: link,
+			server: server,
+			sequence: 0,
+			previous: None,
+			latest: None,
+			since_latest: units::Millis(0),
+			silence: units::Millis(0),
+			since_hello: units::Millis(0),
+			ended: None
+		})
+	}
+
+	/// Sends which of its actions `controls` holds (or pressed this frame.)
+	pub fn send_inputs<C: input::Controls>(&mut self, controls: &C) {
+		let held = REMOTE_ACTIONS.iter()
+			.filter(|&&action| controls.is_key_held(action) || controls.was_key_pressed(action))
+			.fold(0u16, |held, &action| held | bit(action));
+
+		self.sequence += 1;
+		let mut body = MemWriter::new();
+		let written = body.write_le_u32(self.sequence).and_then(|_| body.write_le_u16(held));
+		if written.is_ok() {
+			let server = self.server;
+			self.link.send(packet(INPUTS, body.unwrap()), server);
+		}
+	}
+
+	/// Reads every packet to have arrived since the last frame.
+	/// Responds true if a new snapshot was among them.
+	pub fn poll(&mut self) -> bool {
+		let mut fresh = false;
+		loop {
+			let (bytes, from) = match self.link.receive() {
+				Some(packet) => packet,
+				None => break
+			};
+			if from != self.server { continue; }
+			self.silence = units::Millis(0);
+
+			match open(bytes) {
+				Some((SNAPSHOT, body)) => match Snapshot::read(&mut MemReader::new(body.to_owned())) {
+					// snapshots which arrive out of order are stale
+					Ok(snapshot) => if self.latest.as_ref().map_or(true, |latest| snapshot.tick > latest.tick) {
+						self.previous = self.latest.take();
+						self.latest = Some(snapshot);
+						self.since_latest = units::Millis(0);
+						fresh = true;
+					},
+					Err(msg) => log::debug("net", format!("ignoring a malformed snapshot: {}", msg))
+				},
+				Some((FULL, _)) => { self.ended = Some(~"the game already has a second player"); }
+				Some((BYE, _)) => { self.ended = Some(~"the game has ended"); }
+				_ => {}
+			}
+		}
+		fresh
+	}
+
+	/// Advances the client by a single update step, repeating its hello
+	/// until the server answers.
+	pub fn update(&mut self, elapsed_time: units::Millis) {
+		self.since_latest = self.since_latest + elapsed_time;
+		self.silence = self.silence + elapsed_time;
+		if self.silence > TIMEOUT && self.ended.is_none() {
+			self.ended = Some(~"the server is not answering");
+		}
+
+		if self.latest.is_none() {
+			self.since_hello = self.since_hello + elapsed_time;
+			if self.since_hello >= HELLO_INTERVAL {
+				self.since_hello = units::Millis(0);
+				let server = self.server;
+				self.link.send(packet(HELLO, []), server);
+			}
+		}
+	}
+
+	/// Why the game has ended (e.g: the server closed), once it has.
+	pub fn ended(&self) -> Option<~str> { self.ended.clone() }
+
+	/// The latest snapshot, once one has arrived.
+	pub fn latest<'a>(&'a self) -> Option<&'a Snapshot> { self.latest.as_ref() }
+
+	/// How far, `[0, 1]`, the view is from the previous snapshot to the latest.
+	fn fraction(&self) -> f64 {
+		let (units::Millis(since), units::Millis(interval)) = (self.since_latest, SNAPSHOT_INTERVAL);
+		if since >= interval { 1.0 } else { since as f64 / interval as f64 }
+	}
+
+	/// The calls to draw now, interpolated between the two latest snapshots.
+	pub fn calls(&self) -> ~[Call] {
+		match (&self.previous, &self.latest) {
+			(&Some(ref previous), &Some(ref latest)) => latest.blend(previous, self.fraction()),
+			(_, &Some(ref latest)) => latest.calls.clone(),
+			_ => ~[]
+		}
+	}
+
+	/// Where the view should be centered now, interpolated as the calls are.
+	pub fn focus(&self) -> Option<(units::Game, units::Game)> {
+		match (&self.previous, &self.latest) {
+			(&Some(ref previous), &Some(ref latest)) => Some(latest.blend_focus(previous, self.fraction())),
+			(_, &Some(ref latest)) => Some(latest.focus),
+			_ => None
+		}
+	}
+}
+
+/// The server is told as the client leaves.
+impl Drop for Client {
+	fn drop(&mut self) {
+		let server = self.server;
+		self.link.send(packet(BYE, []), server);
+	}
+}
@@ -0,0 +1,196 @@
+use collections::hashmap::HashMap;
+
+use game::graphics;
+use game::map;
+use game::sprite;
+
+use game::collisions::Rectangle;
+use game::entity;
+use game::entity::Entity;
+use game::error::GameResult;
+use game::units;
+use game::units::{AsGame};
+
+static WALK_SPEED: units::Velocity 	= units::Velocity(0.05);
+// how close the player must come for an npc to stop & face them
+static NOTICE_RANGE: units::Tile 	= units::Tile(3);
+
+static WALK_FRAMES: units::Frame 	= 3;
+static WALK_FPS: units::Fps 		= 8;
+
+// y-offsets for different horizontal facings.
+static WEST_OFFSET: units::Tile = units::Tile(0);
+static EAST_OFFSET: units::Tile = units::Tile(1);
+
+/// Where an npc is placed on a map, & how it looks & behaves.
+#[deriving(Clone)]
+pub struct Spawn {
+	sheet: ~str,
+	/// The tile the npc stands on.
+	position: (units::Tile, units::Tile),
+	/// Where the npc is drawn from on `sheet`: its first frame facing
+	/// west, w/ the frames facing east on the row below.
+	sprite: (units::Tile, units::Tile),
+	/// How far the npc walks to either side of where it stands.
+	patrol: units::Tile,
+	/// The map's event run when the player talks to the npc.
+	event: uint
+}
+
+/// A friendly character which walks back & forth on patrol (or stands
+/// still), stopping to face the player whenever they come near.
+///
+/// The player talks to an npc by examining it, i.e: pressing down while
+/// standing in front of it, which runs its event of the map's script.
+#[deriving(Clone)]
+pub struct Npc {
+	priv x: units::Game,
+	priv y: units::Game,
+	// where the npc stood before its last update
+	priv previous: (units::Game, units::Game),
+	// the left & right ends of its patrol
+	priv bounds: (units::Game, units::Game),
+	priv facing: sprite::Facing,
+	priv walking: bool,
+	priv event: uint,
+	// where a cutscene has sent the npc walking to
+	priv destination: Option<units::Game>,
+
+	priv sprites: HashMap<(sprite::Facing, bool), ~sprite::Updatable>
+}
+
+impl Npc {
+	pub fn new(display: &mut graphics::Graphics, spawn: &Spawn) -> GameResult<Npc> {
+		let (col, row) = spawn.position;
+		let (x, y) = (col.to_game(), row.to_game());
+		let reach = spawn.patrol.to_game();
+
+		let mut sprites = HashMap::<(sprite::Facing, bool), ~sprite::Updatable>::new();
+		let (sprite_col, sprite_row) = spawn.sprite;
+		for facing in sprite::FACINGS.iter() {
+			let offset = (sprite_col, sprite_row + match *facing {
+				sprite::West => WEST_OFFSET,
+				sprite::East => EAST_OFFSET
+			});
+			sprites.insert((*facing, false), ~try!(sprite::Sprite::new(
+				display, (x, y), offset, (units::Tile(1), units::Tile(1)), spawn.sheet.clone()
+			)) as ~sprite::Updatable);
+			sprites.insert((*facing, true), ~try!(sprite::AnimatedSprite::new(
+				display, spawn.sheet.clone(), offset, (units::Tile(1), units::Tile(1)), WALK_FRAMES, WALK_FPS
+			)) as ~sprite::Updatable);
+		}
+
+		Ok(Npc {
+			x: x, y: y,
+			previous: (x, y),
+			bounds: (x - reach, x + reach),
+			facing: sprite::West,
+			walking: reach > units::Game(0.0),
+			event: spawn.event,
+			destination: None,
+			sprites: sprites
+		})
+	}
+
+	/// The map's event run when the player talks to this npc.
+	pub fn event(&self) -> uint { self.event }
+
+	/// Sends the npc walking to `x`, wherever its patrol would take it,
+	/// paying the player no mind until it arrives.
+	pub fn walk_to(&mut self, x: units::Game) {
+		self.destination = Some(x);
+	}
+
+	/// Responds true once the npc has reached where it was sent walking to.
+	pub fn has_arrived(&self) -> bool { self.destination.is_none() }
+
+	/// Places the npc at `x` at once, e.g: when a cutscene is skipped.
+	pub fn place(&mut self, x: units::Game) {
+		self.x = x;
+		self.previous = (x, self.y);
+		self.destination = None;
+	}
+
+	fn center_x(&self) -> units::Game {
+		self.x + (units::Tile(1).to_game() / units::Game(2.0))
+	}
+}
+
+impl Entity for Npc {
+	/// Walks the npc along its patrol, turning back at either end of it
+	/// (or at a wall); if the player is near it stops to face them instead.
+	/// An npc sent walking by a cutscene heads straight there.
+	fn update(
+		&mut self,
+		elapsed_time: units::Millis,
+		player: (units::Game, units::Game),
+		map: &map::Map
+	) {
+		self.previous = (self.x, self.y);
+		let (player_x, player_y) = player;
+		let (left, right) = self.bounds;
+		let units::Game(dist_x) = player_x - self.center_x();
+		let units::Game(dist_y) = player_y - (self.y + (units::Tile(1).to_game() / units::Game(2.0)));
+		let units::Game(range) = NOTICE_RANGE.to_game();
+
+		match self.destination {
+			Some(destination) => {
+				let step = WALK_SPEED * elapsed_time;
+				self.walking = true;
+				if self.x < destination - step {
+					self.facing = sprite::East;
+					self.x = self.x + step;
+				} else if self.x > destination + step {
+					self.facing = sprite::West;
+					self.x = self.x - step;
+				} else {
+					self.x = destination;
+					self.walking = false;
+					self.destination = None;
+				}
+			}
+			None if dist_x.abs() < range && dist_y.abs() < range => {
+				self.walking = false;
+				self.facing = if dist_x < 0.0 { sprite::West } else { sprite::East };
+			}
+			None if left < right => {
+				self.walking = true;
+				let step = WALK_SPEED * elapsed_time;
+				let next_x = match self.facing {
+					sprite::West => self.x - step,
+					sprite::East => self.x + step
+				};
+				let blocked = map.is_colliding(&Rectangle {
+					x: next_x, y: self.y, width: units::Tile(1).to_game(), height: units::Tile(1).to_game()
+				});
+
+				if blocked || next_x < left || next_x > right {
+					self.facing = match self.facing { sprite::West => sprite::East, sprite::East => sprite::West };
+				} else {
+					self.x = next_x;
+				}
+			}
+			None => {}
+		}
+
+		let sprite = self.sprites.get_mut(&(self.facing, self.walking));
+		sprite.update(elapsed_time);
+		sprite.set_position((self.x, self.y));
+	}
+
+	fn draw(&self, queue: &mut graphics::RenderQueue, offset: (units::Game, units::Game), alpha: f64) {
+		let offset = entity::blend(offset, self.previous, (self.x, self.y), alpha);
+		self.sprites.get(&(self.facing, self.walking)).draw_queued(queue, graphics::Entities, offset);
+	}
+
+	fn hitbox(&self) -> Rectangle {
+		Rectangle {
+			x: self.x, y: self.y,
+			width: units::Tile(1).to_game(), height: units::Tile(1).to_game()
+		}
+	}
+
+	fn kind(&self) -> entity::Kind { entity::Npc }
+
+	fn is_alive(&self) -> bool { true }
+}
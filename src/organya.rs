@@ -0,0 +1,421 @@
+use std::cmp;
+use std::io::File;
+use std::libc::{c_int, c_void};
+use std::num;
+use std::vec;
+
+use game::log;
+use game::vfs;
+
+/// The waveforms melody tracks are played w/: 100 of them, each a single
+/// cycle of `WAVE_SIZE` signed 8-bit samples (i.e: `WAVE100` from the original game.)
+pub static WAVE_PATH: &'static str = "assets/base/Org/Wave100.dat";
+
+/// Holds `Drum<n>.raw` for each percussion instrument `n` (from `00`): unsigned
+/// 8-bit mono samples, played faster or slower by the pitch of each note.
+pub static DRUM_DIR: &'static str = "assets/base/Org";
+
+static MAGIC: &'static [u8] = bytes!("Org-0");
+static TRACKS: uint = 16;
+// tracks past these are percussion
+static MELODY_TRACKS: uint = 8;
+static WAVES: uint = 100;
+static WAVE_SIZE: uint = 256;
+
+// a note's key, volume or pan of this leaves it as it was
+static UNCHANGED: u8 = 255;
+static DEFAULT_VOLUME: u8 = 200;
+static CENTER_PAN: u8 = 6;
+// a track's finetune which leaves its notes in tune
+static IN_TUNE: u16 = 1000;
+
+// the pitch (in Hz) of each note of the octave starting at middle C (octave 4)
+static NOTE_FREQUENCIES: [f64, ..12] = [
+	261.63, 277.18, 293.66, 311.13, 329.63, 349.23, 369.99, 392.00, 415.30, 440.00, 466.16, 493.88
+];
+// how many cycles of its wave a pizzicato note lasts, by octave
+static PIZZICATO_CYCLES: [uint, ..8] = [4, 8, 12, 16, 20, 24, 28, 32];
+// the balance of each pan position, where 256 is centered
+static PAN_BALANCE: [int, ..13] = [0, 43, 86, 129, 172, 215, 256, 297, 340, 383, 426, 469, 512];
+
+// how loud each voice is mixed, leaving room for several to play at once
+static VOICE_LEVEL: f64 = 0.25;
+
+/// A single event of a track: on the tick at `position` a note of `key`
+/// starts (lasting `duration` ticks), & the track's volume & pan change;
+/// each is `UNCHANGED` if it does not.
+struct Note {
+	position: uint,
+	key: u8,
+	duration: u8,
+	volume: u8,
+	pan: u8
+}
+
+struct Track {
+	// detunes every note, `IN_TUNE` being none at all
+	finetune: u16,
+	// the wave (or drum) the track is played w/
+	instrument: uint,
+	// whether notes stop after a few cycles, rather than lasting their duration
+	pizzicato: bool,
+	// in order of position
+	notes: ~[Note]
+}
+
+/// A song in Cave Story's Organya (`.org`) format: up to 8 melody & 8
+/// percussion tracks of notes, on a grid of ticks, which repeats from
+/// `loop_start` once it reaches `loop_end`.
+pub struct Song {
+	priv tick_time: uint, // in milliseconds
+	priv loop_start: uint,
+	priv loop_end: uint,
+	priv tracks: ~[Track]
+}
+
+impl Song {
+	pub fn load(path: &str) -> Result<Song, ~str> {
+		let bytes = match File::open(&vfs::resolve(path)).read_to_end() {
+			Ok(bytes) => bytes,
+			Err(msg) => return Err(format!("{}", msg))
+		};
+		Song::parse(bytes)
+	}
+
+	/// Reads a song from the contents of an `.org` file: a header, then the
+	/// settings of each track, then each track's notes (all positions, then
+	/// all keys, durations, volumes & pans in turn.)
+	pub fn parse(bytes: &[u8]) -> Result<Song, ~str> {
+		let mut reader = Reader { bytes: bytes, position: 0 };
+		let magic = try!(reader.take(6));
+		if !magic.starts_with(MAGIC) { return Err(~"not an organya song"); }
+
+		let tick_time = try!(reader.u16()) as uint;
+		try!(reader.take(2)); // beats per bar & steps per beat only matter when editing
+		let loop_start = try!(reader.u32()) as uint;
+		let loop_end = try!(reader.u32()) as uint;
+		if tick_time == 0 || loop_end <= loop_start { return Err(~"invalid tempo or loop"); }
+
+		let mut headers = ~[];
+		for _ in range(0, TRACKS) {
+			let finetune = try!(reader.u16());
+			let instrument = try!(reader.u8()) as uint;
+			let pizzicato = try!(reader.u8()) != 0;
+			let count = try!(reader.u16()) as uint;
+			headers.push((finetune, instrument, pizzicato, count));
+		}
+
+		let mut tracks = ~[];
+		for &(finetune, instrument, pizzicato, count) in headers.iter() {
+			let mut positions = ~[];
+			for _ in range(0, count) { positions.push(try!(reader.u32()) as uint); }
+			let keys 		= try!(reader.take(count));
+			let durations 	= try!(reader.take(count));
+			let volumes 	= try!(reader.take(count));
+			let pans 		= try!(reader.take(count));
+
+			let mut notes: ~[Note] = range(0, count).map(|i| {
+				Note { position: positions[i], key: keys[i], duration: durations[i], volume: volumes[i], pan: pans[i] }
+			}).collect();
+			notes.sort_by(|a, b| a.position.cmp(&b.position));
+			tracks.push(Track { finetune: finetune, instrument: instrument, pizzicato: pizzicato, notes: notes });
+		}
+
+		Ok(Song { tick_time: tick_time, loop_start: loop_start, loop_end: loop_end, tracks: tracks })
+	}
+}
+
+// reads little-endian values from the front of a file's bytes
+struct Reader<'a> {
+	bytes: &'a [u8],
+	position: uint
+}
+
+impl<'a> Reader<'a> {
+	fn take(&mut self, count: uint) -> Result<&'a [u8], ~str> {
+		if self.position + count > self.bytes.len() { return Err(~"unexpected end of file"); }
+		let taken = self.bytes.slice(self.position, self.position + count);
+		self.position += count;
+		Ok(taken)
+	}
+
+	fn u8(&mut self) -> Result<u8, ~str> {
+		let bytes = try!(self.take(1));
+		Ok(bytes[0])
+	}
+
+	fn u16(&mut self) -> Result<u16, ~str> {
+		let bytes = try!(self.take(2));
+		Ok((bytes[0] as u16) | (bytes[1] as u16 << 8))
+	}
+
+	fn u32(&mut self) -> Result<u32, ~str> {
+		let bytes = try!(self.take(4));
+		Ok((bytes[0] as u32) | (bytes[1] as u32 << 8) | (bytes[2] as u32 << 16) | (bytes[3] as u32 << 24))
+	}
+}
+
+/// The sounds songs are played w/: `WAVES` melodic waveforms & however
+/// many drum samples there are.
+struct Instruments {
+	waves: ~[~[i8]],
+	drums: ~[~[u8]]
+}
+
+impl Instruments {
+	fn load() -> Result<Instruments, ~str> {
+		let data = match File::open(&vfs::resolve(WAVE_PATH)).read_to_end() {
+			Ok(data) if data.len() >= WAVES * WAVE_SIZE => data,
+			Ok(_) => return Err(format!("{} is too short", WAVE_PATH)),
+			Err(msg) => return Err(format!("{} could not be loaded: {}", WAVE_PATH, msg))
+		};
+		let waves = data.chunks(WAVE_SIZE).take(WAVES).map(|wave| {
+			wave.iter().map(|&sample| sample as i8).collect()
+		}).collect();
+
+		// drums are numbered from 0 until the first one missing
+		let mut drums = ~[];
+		loop {
+			let path = format!("{}/Drum{:02u}.raw", DRUM_DIR, drums.len());
+			let file_path = vfs::resolve(path.as_slice());
+			if !file_path.exists() { break; }
+			match File::open(&file_path).read_to_end() {
+				Ok(samples) => drums.push(samples),
+				Err(msg) => { log::warn("organya", format!("{} could not be loaded: {}", path, msg)); break; }
+			}
+		}
+
+		Ok(Instruments { waves: waves, drums: drums })
+	}
+}
+
+// the note a single track is sounding
+struct Voice {
+	playing: bool,
+	// how far through its wave (or drum sample) the note is, in samples
+	phase: f64,
+	// how far `phase` moves for each sample mixed
+	step: f64,
+	// where the note stops: after this many more ticks, or samples of its wave
+	ticks_left: uint,
+	phase_limit: Option<f64>,
+	volume: u8,
+	pan: u8
+}
+
+/// Plays a song, one buffer of samples at a time.
+struct Synth {
+	song: Song,
+	instruments: Instruments,
+	looping: bool,
+	rate: f64, // in samples per second
+	voices: ~[Voice],
+	// the next note of each track
+	next_notes: ~[uint],
+	tick: uint,
+	// samples left to mix before the next tick
+	samples_to_tick: f64,
+	// the loudness of the whole song, `[0, 1]`
+	gain: f64,
+	paused: bool,
+	finished: bool
+}
+
+impl Synth {
+	fn new(song: Song, instruments: Instruments, rate: uint, looping: bool) -> Synth {
+		let voices = range(0, song.tracks.len()).map(|_| {
+			Voice {
+				playing: false, phase: 0.0, step: 0.0, ticks_left: 0, phase_limit: None,
+				volume: DEFAULT_VOLUME, pan: CENTER_PAN
+			}
+		}).collect();
+		let next_notes = vec::from_elem(song.tracks.len(), 0u);
+		Synth {
+			song: song, instruments: instruments, looping: looping, rate: rate as f64,
+			voices: voices, next_notes: next_notes,
+			tick: 0, samples_to_tick: 0.0,
+			gain: 1.0, paused: false, finished: false
+		}
+	}
+
+	/// Fills `out` w/ interleaved stereo samples (left, then right.)
+	fn mix(&mut self, out: &mut [i16]) {
+		for frame in out.mut_chunks(2) {
+			if self.paused || self.finished {
+				for sample in frame.mut_iter() { *sample = 0; }
+				continue;
+			}
+
+			if self.samples_to_tick <= 0.0 {
+				self.advance();
+				self.samples_to_tick += (self.song.tick_time as f64 * self.rate) / 1000.0;
+			}
+			self.samples_to_tick -= 1.0;
+
+			let (left, right) = self.next_sample();
+			let clip = |value: f64| num::clamp(value * self.gain * 32767.0, -32768.0, 32767.0) as i16;
+			frame[0] = clip(left);
+			if frame.len() > 1 { frame[1] = clip(right); }
+		}
+	}
+
+	// plays the notes of the current tick, then moves on to the next
+	fn advance(&mut self) {
+		for voice in self.voices.mut_iter() {
+			if voice.ticks_left > 0 {
+				voice.ticks_left -= 1;
+				if voice.ticks_left == 0 && voice.phase_limit.is_none() { voice.playing = false; }
+			}
+		}
+
+		for index in range(0, self.song.tracks.len()) {
+			while self.next_notes[index] < self.song.tracks[index].notes.len()
+				&& self.song.tracks[index].notes[self.next_notes[index]].position == self.tick {
+				let note = self.song.tracks[index].notes[self.next_notes[index]];
+				self.play_note(index, &note);
+				self.next_notes[index] += 1;
+			}
+		}
+
+		self.tick += 1;
+		if self.tick >= self.song.loop_end {
+			if !self.looping { self.finished = true; return; }
+			self.tick = self.song.loop_start;
+			for index in range(0, self.song.tracks.len()) {
+				let notes = &self.song.tracks[index].notes;
+				self.next_notes[index] = notes.iter().position(|note| note.position >= self.tick).unwrap_or(notes.len());
+			}
+		}
+	}
+
+	fn play_note(&mut self, index: uint, note: &Note) {
+		let track = &self.song.tracks[index];
+		let voice = &mut self.voices[index];
+		if note.volume != UNCHANGED { voice.volume = note.volume; }
+		if note.pan != UNCHANGED && note.pan <= 12 { voice.pan = note.pan; }
+		if note.key == UNCHANGED { return; }
+
+		let key = note.key as uint;
+		voice.playing = true;
+		voice.phase = 0.0;
+		if index < MELODY_TRACKS {
+			let octave = cmp::min(key / 12, PIZZICATO_CYCLES.len() - 1);
+			let detune = (track.finetune as f64 - IN_TUNE as f64) / 100.0;
+			let pitch = (NOTE_FREQUENCIES[key % 12] * num::powf(2.0, octave as f64 - 4.0)) + detune;
+			voice.step = (pitch * WAVE_SIZE as f64) / self.rate;
+			voice.ticks_left = note.duration as uint;
+			voice.phase_limit = if track.pizzicato {
+				Some((PIZZICATO_CYCLES[octave] * WAVE_SIZE) as f64)
+			} else {
+				None
+			};
+		} else {
+			// drums are played back faster the higher their note, & always to their end
+			voice.step = ((key * 800) + 100) as f64 / self.rate;
+			voice.ticks_left = 0;
+			voice.phase_limit = None;
+		}
+	}
+
+	// the next sample of every voice, mixed together
+	fn next_sample(&mut self) -> (f64, f64) {
+		let (mut left, mut right) = (0.0, 0.0);
+		for (index, voice) in self.voices.mut_iter().enumerate() {
+			if !voice.playing { continue; }
+			let track = &self.song.tracks[index];
+
+			let sample = if index < MELODY_TRACKS {
+				match self.instruments.waves.get_opt(track.instrument) {
+					Some(wave) => wave[(voice.phase as uint) % WAVE_SIZE] as f64 / 128.0,
+					None => 0.0
+				}
+			} else {
+				match self.instruments.drums.get_opt(track.instrument) {
+					Some(drum) if (voice.phase as uint) < drum.len() => (drum[voice.phase as uint] as f64 - 128.0) / 128.0,
+					_ => { voice.playing = false; 0.0 }
+				}
+			};
+
+			voice.phase += voice.step;
+			match voice.phase_limit {
+				Some(limit) if voice.phase >= limit => { voice.playing = false; }
+				_ => {}
+			}
+
+			// volumes & pans are measured in hundredths of a decibel, as by DirectSound
+			let attenuate = |centibels: f64| num::powf(10.0, centibels / 2000.0);
+			let loudness = attenuate(((voice.volume as int - 255) * 8) as f64) * VOICE_LEVEL;
+			let balance = ((PAN_BALANCE[voice.pan] - 256) * 10) as f64;
+			let (left_gain, right_gain) = if balance > 0.0 {
+				(attenuate(-balance), 1.0)
+			} else {
+				(1.0, attenuate(balance))
+			};
+			left += sample * loudness * left_gain;
+			right += sample * loudness * right_gain;
+		}
+		(left, right)
+	}
+}
+
+// `SDL_mixer` calls this for each buffer of music it needs, w/ the synth playing it
+extern "C" fn fill_buffer(synth: *mut c_void, stream: *mut u8, length: c_int) {
+	unsafe {
+		let synth = &mut *(synth as *mut Synth);
+		vec::raw::mut_buf_as_slice(stream as *mut i16, (length as uint) / 2, |out| synth.mix(out));
+	}
+}
+
+extern {
+	fn Mix_HookMusic(mix_func: Option<extern "C" fn(*mut c_void, *mut u8, c_int)>, arg: *mut c_void);
+	fn SDL_LockAudio();
+	fn SDL_UnlockAudio();
+}
+
+/// Plays a song in place of `SDL_mixer`'s music, for as long as it lives.
+///
+/// The mixer must have been opened for signed 16-bit stereo at `rate`.
+pub struct Player {
+	priv synth: ~Synth
+}
+
+impl Player {
+	/// Starts `song` from the beginning, repeating its loop if `looping` is set.
+	pub fn start(song: Song, rate: uint, looping: bool) -> Result<Player, ~str> {
+		let instruments = try!(Instruments::load());
+		let mut player = Player { synth: ~Synth::new(song, instruments, rate, looping) };
+		unsafe {
+			let synth: *mut Synth = &mut *player.synth;
+			Mix_HookMusic(Some(fill_buffer), synth as *mut c_void);
+		}
+		Ok(player)
+	}
+
+	/// Sets the loudness of the whole song, `[0, 1]`.
+	pub fn set_gain(&mut self, gain: f64) {
+		self.while_locked(|synth| synth.gain = gain);
+	}
+
+	/// Stops (or resumes) the song where it is.
+	pub fn set_paused(&mut self, paused: bool) {
+		self.while_locked(|synth| synth.paused = paused);
+	}
+
+	// changes the synth w/o the mixer reading it halfway through
+	fn while_locked(&mut self, change: |&mut Synth|) {
+		unsafe { SDL_LockAudio(); }
+		change(&mut *self.synth);
+		unsafe { SDL_UnlockAudio(); }
+	}
+}
+
+/// When the `Player` leaves scope the mixer stops calling on its synth.
+impl Drop for Player {
+	fn drop(&mut self) {
+		unsafe {
+			SDL_LockAudio();
+			Mix_HookMusic(None, 0 as *mut c_void);
+			SDL_UnlockAudio();
+		}
+	}
+}
@@ -0,0 +1,142 @@
+use collections::hashmap::HashMap;
+
+use std::io;
+use std::io::{File, IoError, IoResult};
+use std::io::fs;
+use std::str;
+
+/// Where a release's assets are packed, shipped in place of `assets/`.
+pub static ARCHIVE_PATH: &'static str = "assets.pak";
+
+// the first bytes of every archive
+static MAGIC: &'static str = "RSPK";
+
+/// Many asset files packed into one, so that a release may ship a single
+/// data file rather than a tree of loose files.
+///
+/// An archive starts w/ the bytes `RSPK` & the number of files it holds
+/// (a little-endian u32). A table follows, w/ an entry per file: the
+/// length of its path (u16), the path itself (utf-8, relative to the
+/// packed directory), then the offset of its contents from the start of
+/// the archive & their length (both u64). After the table come the
+/// contents of every file.
+pub struct Archive {
+	priv path: Path,
+	// each file's offset & length, by path
+	priv entries: HashMap<~str, (u64, u64)>
+}
+
+impl Archive {
+	/// Reads the table of the archive at `path`; files are only read once asked for.
+	pub fn open(path: &Path) -> Result<Archive, ~str> {
+		match read_table(path) {
+			Ok(entries) => Ok(Archive { path: path.clone(), entries: entries }),
+			Err(msg) => Err(format!("{}", msg))
+		}
+	}
+
+	pub fn len(&self) -> uint { self.entries.len() }
+
+	pub fn contains(&self, name: &str) -> bool {
+		self.entries.contains_key(&name.to_owned())
+	}
+
+	/// The name of every file packed, in no particular order.
+	pub fn names(&self) -> ~[~str] {
+		self.entries.keys().map(|name| name.clone()).collect()
+	}
+
+	/// The contents of the file packed as `name`.
+	pub fn read(&self, name: &str) -> Result<~[u8], ~str> {
+		let (offset, length) = match self.entries.find(&name.to_owned()) {
+			Some(&entry) => entry,
+			None => return Err(format!("{} is not in the archive", name))
+		};
+
+		match read_range(&self.path, offset, length) {
+			Ok(contents) => Ok(contents),
+			Err(msg) => Err(format!("{}", msg))
+		}
+	}
+}
+
+/// Packs every file beneath `dir` into an archive at `out`, responding
+/// w/ how many files were packed.
+///
+/// This is run by `rust-story --pack <dir> <out>` (see `make pack`).
+pub fn pack(dir: &Path, out: &Path) -> Result<uint, ~str> {
+	match write_archive(dir, out) {
+		Ok(count) => Ok(count),
+		Err(msg) => Err(format!("{}", msg))
+	}
+}
+
+fn read_table(path: &Path) -> IoResult<HashMap<~str, (u64, u64)>> {
+	let mut file = try!(File::open(path));
+	let magic = try!(file.read_exact(MAGIC.len()));
+	if magic.as_slice() != MAGIC.as_bytes() { return Err(invalid("not an asset archive")); }
+
+	let count = try!(file.read_le_u32());
+	let mut entries = HashMap::new();
+	for _ in range(0, count) {
+		let name_length = try!(file.read_le_u16()) as uint;
+		let name = match str::from_utf8_owned(try!(file.read_exact(name_length))) {
+			Some(name) => name,
+			None => return Err(invalid("a packed path is not utf-8"))
+		};
+		let offset = try!(file.read_le_u64());
+		let length = try!(file.read_le_u64());
+		entries.insert(name, (offset, length));
+	}
+	Ok(entries)
+}
+
+fn read_range(path: &Path, offset: u64, length: u64) -> IoResult<~[u8]> {
+	let mut file = try!(File::open(path));
+	try!(file.seek(offset as i64, io::SeekSet));
+	file.read_exact(length as uint)
+}
+
+fn write_archive(dir: &Path, out: &Path) -> IoResult<uint> {
+	// packed in order of their paths, so that packing the same files twice gives the same archive
+	let mut files = ~[];
+	for path in try!(fs::walk_dir(dir)) {
+		if !path.is_file() || path == *out { continue; }
+		match path.path_relative_from(dir) {
+			Some(relative) => match relative.as_str() {
+				Some(name) => files.push((name.to_owned(), path.clone())),
+				None => return Err(invalid("a path to pack is not utf-8"))
+			},
+			None => {}
+		}
+	}
+	files.sort_by(|&(ref a, _), &(ref b, _)| a.cmp(b));
+
+	// contents begin after the header & table
+	let mut offset = (MAGIC.len() + 4) as u64;
+	for &(ref name, _) in files.iter() {
+		offset += (2 + name.len() + 8 + 8) as u64;
+	}
+
+	let mut archive = try!(File::create(out));
+	try!(archive.write_str(MAGIC));
+	try!(archive.write_le_u32(files.len() as u32));
+	for &(ref name, ref path) in files.iter() {
+		let length = try!(fs::stat(path)).size;
+		try!(archive.write_le_u16(name.len() as u16));
+		try!(archive.write_str(*name));
+		try!(archive.write_le_u64(offset));
+		try!(archive.write_le_u64(length));
+		offset += length;
+	}
+
+	for &(_, ref path) in files.iter() {
+		let contents = try!(File::open(path).read_to_end());
+		try!(archive.write(contents));
+	}
+	Ok(files.len())
+}
+
+fn invalid(desc: &'static str) -> IoError {
+	IoError { kind: io::InvalidInput, desc: desc, detail: None }
+}
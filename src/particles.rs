@@ -0,0 +1,256 @@
+use std::f64;
+use std::rand::Rng;
+
+use game::graphics;
+use game::map;
+use game::rng;
+use game::sprite;
+use game::sprite::Drawable;
+
+use game::collisions::Rectangle;
+use game::error::GameResult;
+use game::units;
+use game::units::{AsGame};
+
+static PARTICLE_ATLAS: &'static str = "assets/atlases/caret.atlas";
+
+// the most particles alive at once; further emissions are dropped
+static POOL_SIZE: uint = 256;
+
+static GRAVITY: units::Acceleration = units::Acceleration(0.00078125);
+
+// landing dust: a few puffs drifting out from the player's feet
+static DUST_COUNT: uint 						= 3;
+static DUST_SPEED: f64 							= 0.05;
+static DUST_LIFETIME: units::Millis 			= units::Millis(250);
+
+// a spark where a shot strikes something
+static SPARK_COUNT: uint 						= 2;
+static SPARK_SPEED: f64 						= 0.08;
+static SPARK_LIFETIME: units::Millis 			= units::Millis(150);
+
+// debris thrown up when a tile crumbles
+static DEBRIS_COUNT: uint 							= 6;
+static DEBRIS_SPEED: f64 							= 0.15;
+static DEBRIS_LIFETIME: units::Millis 				= units::Millis(600);
+
+// a heavy landing: a wide cloud of dust kicked up from the player's feet
+static HEAVY_DUST_COUNT: uint 					= 10;
+static HEAVY_DUST_SPEED: f64 					= 0.12;
+static HEAVY_DUST_LIFETIME: units::Millis 		= units::Millis(400);
+
+// exhaust from the player's booster, blown back against its thrust
+static EXHAUST_SPEED: f64 						= 0.1;
+static EXHAUST_SPREAD: f64 						= 0.4;
+static EXHAUST_LIFETIME: units::Millis 			= units::Millis(200);
+
+// an explosion: sparks flung every way, w/ a cloud of dust
+static EXPLOSION_SPARKS: uint 					= 12;
+static EXPLOSION_SPARK_SPEED: f64 				= 0.16;
+static EXPLOSION_DUST: uint 					= 6;
+static EXPLOSION_DUST_SPEED: f64 				= 0.06;
+static EXPLOSION_LIFETIME: units::Millis 		= units::Millis(300);
+
+/// The look (& behavior) of a particle.
+#[deriving(Eq)]
+pub enum Kind {
+	/// Drifts in a straight line, defying gravity.
+	Dust,
+	/// Flies in a straight line, quickly.
+	Spark,
+	/// Falls under gravity.
+	Debris
+}
+
+// a single particle in the pool, which is reused once it has died
+struct Particle {
+	kind: Kind,
+	x: units::Game,
+	y: units::Game,
+	velocity_x: units::Velocity,
+	velocity_y: units::Velocity,
+	lifetime: units::Millis,
+	alive: bool
+}
+
+/// A fixed pool of short-lived visual effects: dust, sparks & debris.
+///
+/// Particles are plain data; one sprite per `Kind` is shared by every
+/// particle of that kind, so emitting a particle never allocates.
+pub struct ParticleSystem {
+	priv particles: ~[Particle],
+	priv dust: sprite::Sprite,
+	priv spark: sprite::Sprite,
+	priv debris: sprite::Sprite,
+	priv rng: rng::Rng
+}
+
+impl ParticleSystem {
+	pub fn new(graphics: &mut graphics::Graphics, rng: rng::Rng) -> GameResult<ParticleSystem> {
+		let origin = (units::Game(0.0), units::Game(0.0));
+		let atlas = try!(graphics::Atlas::load(PARTICLE_ATLAS.to_owned()));
+		let unused = Particle {
+			kind: Dust,
+			x: units::Game(0.0), y: units::Game(0.0),
+			velocity_x: units::Velocity(0.0), velocity_y: units::Velocity(0.0),
+			lifetime: units::Millis(0),
+			alive: false
+		};
+		let mut particles = ~[];
+		for _ in range(0, POOL_SIZE) { particles.push(unused); }
+
+		Ok(ParticleSystem {
+			particles: particles,
+			dust: try!(sprite::Sprite::from_atlas(graphics, origin, &atlas, "dust")),
+			spark: try!(sprite::Sprite::from_atlas(graphics, origin, &atlas, "spark")),
+			debris: try!(sprite::Sprite::from_atlas(graphics, origin, &atlas, "debris")),
+			rng: rng
+		})
+	}
+
+	/// Emits a particle of `kind` centered on `position`, reusing the
+	/// slot of a dead particle. Nothing is emitted if the pool is full.
+	pub fn emit(
+		&mut self,
+		kind: Kind,
+		position: (units::Game, units::Game),
+		velocity: (units::Velocity, units::Velocity),
+		lifetime: units::Millis
+	) {
+		let (center_x, center_y) = position;
+		let (width, height) = self.sprite_of(kind).size();
+		let (velocity_x, velocity_y) = velocity;
+
+		match self.particles.mut_iter().find(|particle| !particle.alive) {
+			Some(particle) => {
+				*particle = Particle {
+					kind: kind,
+					x: center_x - (width / units::Game(2.0)),
+					y: center_y - (height / units::Game(2.0)),
+					velocity_x: velocity_x, velocity_y: velocity_y,
+					lifetime: lifetime,
+					alive: true
+				};
+			}
+			None => {}
+		}
+	}
+
+	/// Puffs of dust from the player's feet as they land.
+	pub fn landing_dust(&mut self, feet: (units::Game, units::Game)) {
+		self.burst(Dust, feet, DUST_COUNT, DUST_SPEED, DUST_LIFETIME, (-f64::consts::PI, 0.0));
+	}
+
+	/// A cloud of dust from the player's feet as they land hard.
+	pub fn heavy_landing(&mut self, feet: (units::Game, units::Game)) {
+		self.burst(Dust, feet, HEAVY_DUST_COUNT, HEAVY_DUST_SPEED, HEAVY_DUST_LIFETIME, (-f64::consts::PI, 0.0));
+	}
+
+	/// Sparks where a shot struck a wall or enemy.
+	pub fn impact(&mut self, position: (units::Game, units::Game)) {
+		self.burst(Spark, position, SPARK_COUNT, SPARK_SPEED, SPARK_LIFETIME, (-f64::consts::PI, f64::consts::PI));
+	}
+
+	/// A puff of exhaust from `nozzle`, blown the way of `angle` (in radians.)
+	pub fn exhaust(&mut self, nozzle: (units::Game, units::Game), angle: f64) {
+		self.burst(Dust, nozzle, 1, EXHAUST_SPEED, EXHAUST_LIFETIME, (angle - EXHAUST_SPREAD, angle + EXHAUST_SPREAD));
+	}
+
+	/// The blast of an explosive shot, centered on `position`.
+	pub fn explosion(&mut self, position: (units::Game, units::Game)) {
+		let every_way = (-f64::consts::PI, f64::consts::PI);
+		self.burst(Spark, position, EXPLOSION_SPARKS, EXPLOSION_SPARK_SPEED, EXPLOSION_LIFETIME, every_way);
+		self.burst(Dust, position, EXPLOSION_DUST, EXPLOSION_DUST_SPEED, EXPLOSION_LIFETIME, every_way);
+	}
+
+	/// Debris thrown up from the tile at `(row, col)`, which has crumbled.
+	pub fn tile_destroyed(&mut self, row: units::Tile, col: units::Tile) {
+		let half_tile = units::Tile(1).to_game() / units::Game(2.0);
+		let center = (col.to_game() + half_tile, row.to_game() + half_tile);
+		self.burst(Debris, center, DEBRIS_COUNT, DEBRIS_SPEED, DEBRIS_LIFETIME, (-f64::consts::PI, 0.0));
+	}
+
+	/// Kills every particle, e.g: when the player leaves the map.
+	pub fn clear(&mut self) {
+		for particle in self.particles.mut_iter() {
+			particle.alive = false;
+		}
+	}
+
+	/// Moves every particle, killing those which have outlived their lifetime.
+	///
+	/// Particles pass through walls, but are pushed by force regions.
+	pub fn update(&mut self, elapsed_time: units::Millis, map: &map::Map) {
+		let sizes = (self.dust.size(), self.spark.size(), self.debris.size());
+		for particle in self.particles.mut_iter() {
+			if !particle.alive { continue; }
+
+			particle.lifetime = particle.lifetime - elapsed_time;
+			if particle.lifetime <= units::Millis(0) {
+				particle.alive = false;
+				continue;
+			}
+
+			let (width, height) = match (particle.kind, sizes) {
+				(Dust, (size, _, _)) 	=> size,
+				(Spark, (_, size, _)) 	=> size,
+				(Debris, (_, _, size)) 	=> size
+			};
+			let (force_x, force_y) = map.force_at(&Rectangle {
+				x: particle.x, y: particle.y, width: width, height: height
+			});
+			let gravity = match particle.kind {
+				Debris 			=> GRAVITY,
+				Dust | Spark 	=> units::Acceleration(0.0)
+			};
+
+			particle.velocity_x = particle.velocity_x + (force_x * elapsed_time);
+			particle.velocity_y = particle.velocity_y + ((gravity + force_y) * elapsed_time);
+			particle.x = particle.x + (particle.velocity_x * elapsed_time);
+			particle.y = particle.y + (particle.velocity_y * elapsed_time);
+		}
+	}
+
+	pub fn draw(&self, queue: &mut graphics::RenderQueue, offset: (units::Game, units::Game)) {
+		let (offset_x, offset_y) = offset;
+		for particle in self.particles.iter() {
+			if !particle.alive { continue; }
+
+			// each sprite sits at the origin, so it is drawn at the particle
+			// by shifting the camera's offset
+			self.sprite_of(particle.kind).draw_queued(queue, graphics::Particles, (offset_x - particle.x, offset_y - particle.y));
+		}
+	}
+
+	// emits `count` particles flying away from `position` at up to `speed`,
+	// each in a random direction between the angles (in radians) `directions`.
+	fn burst(
+		&mut self,
+		kind: Kind,
+		position: (units::Game, units::Game),
+		count: uint,
+		speed: f64,
+		lifetime: units::Millis,
+		directions: (f64, f64)
+	) {
+		let (first, last) = directions;
+		let mut rng = self.rng.clone();
+		for _ in range(0, count) {
+			let angle = rng.gen_range(first, last);
+			let magnitude = rng.gen_range(speed / 2.0, speed);
+			self.emit(
+				kind, position,
+				(units::Velocity(f64::cos(angle) * magnitude), units::Velocity(f64::sin(angle) * magnitude)),
+				lifetime
+			);
+		}
+	}
+
+	fn sprite_of<'a>(&'a self, kind: Kind) -> &'a sprite::Sprite {
+		match kind {
+			Dust 	=> &self.dust,
+			Spark 	=> &self.spark,
+			Debris 	=> &self.debris
+		}
+	}
+}
@@ -0,0 +1,248 @@
+use std::cmp;
+use std::f64;
+
+use game::graphics;
+use game::map;
+use game::sprite;
+
+use game::collisions::Rectangle;
+use game::entity;
+use game::entity::Entity;
+use game::error::GameResult;
+use game::units;
+use game::units::{AsGame};
+
+// physics
+static GRAVITY: units::Acceleration 	= units::Acceleration(0.00078125);
+static MAX_VELOCITY_Y: units::Velocity 	= units::Velocity(0.2998046875);
+static BOUNCE_SPEED: units::Velocity 	= units::Velocity(0.225);
+static FRICTION: units::Acceleration 	= units::Acceleration(0.00005);
+
+// attraction towards the player
+static ATTRACT_RADIUS: units::Tile 			= units::Tile(3);
+static ATTRACT_ACCEL: units::Acceleration 	= units::Acceleration(0.0008);
+static MAX_ATTRACT_SPEED: units::Velocity 	= units::Velocity(0.3);
+
+// pickups which are not collected eventually disappear
+static LIFETIME: units::Millis 			= units::Millis(8000);
+static FLASH_TIME: units::Millis 		= units::Millis(6000);
+static FLASH_INTERVAL: units::Millis 	= units::Millis(50);
+
+// experience crystal animation
+static EXPERIENCE_SOURCE: (units::Tile, units::Tile) = (units::Tile(0), units::Tile(1));
+static EXPERIENCE_FRAMES: units::Frame 	= 6;
+static EXPERIENCE_FPS: units::Fps 		= 14;
+
+// heart animation
+static HEART_SOURCE: (units::Tile, units::Tile) = (units::Tile(2), units::Tile(5));
+static HEART_FRAMES: units::Frame 	= 2;
+static HEART_FPS: units::Fps 		= 4;
+
+// missile ammo (a still sprite)
+static AMMO_SOURCE: (units::Tile, units::Tile) = (units::Tile(0), units::Tile(4));
+
+// collision box (expressed as `units::Game`)
+static COLLISION_BOX: Rectangle = Rectangle {
+	x: units::Game(8.0), y: units::Game(8.0),
+	width: units::Game(16.0), height: units::Game(16.0)
+};
+
+/// The effect a pickup has upon the player once it is collected.
+#[deriving(Clone)]
+pub enum Kind {
+	/// Energy for the player's current weapon.
+	Experience(uint),
+	/// Ammo for each of the player's weapons which uses it.
+	Ammo(uint),
+	/// Hit points restored to the player.
+	Health(units::HitPoint)
+}
+
+/// An item lying on the map which the player can collect by touching it.
+///
+/// Pickups are subject to gravity, bounce off the floor, and drift
+/// towards the player once they come close enough.
+#[deriving(Clone)]
+pub struct Pickup {
+	priv x: units::Game,
+	priv y: units::Game,
+	// where the pickup was before its last update
+	priv previous: (units::Game, units::Game),
+	priv velocity_x: units::Velocity,
+	priv velocity_y: units::Velocity,
+
+	priv kind: Kind,
+	priv lifetime: units::Millis,
+	priv collected: bool,
+
+	priv sprite: ~sprite::Updatable
+}
+
+impl Pickup {
+	/// Drops an experience crystal worth `energy`, centered on `origin`.
+	pub fn experience(
+		graphics: &mut graphics::Graphics,
+		origin: (units::Game, units::Game),
+		energy: uint
+	) -> GameResult<Pickup> {
+		let sprite = ~try!(sprite::AnimatedSprite::new(
+			graphics, ~"assets/base/Npc/NpcSym.bmp",
+			EXPERIENCE_SOURCE,
+			(units::Tile(1), units::Tile(1)),
+			EXPERIENCE_FRAMES, EXPERIENCE_FPS
+		)) as ~sprite::Updatable;
+
+		Ok(Pickup::new(origin, Experience(energy), sprite))
+	}
+
+	/// Drops `amount` missiles' worth of ammo, centered on `origin`.
+	pub fn ammo(
+		graphics: &mut graphics::Graphics,
+		origin: (units::Game, units::Game),
+		amount: uint
+	) -> GameResult<Pickup> {
+		let sprite = ~try!(sprite::Sprite::new(
+			graphics,
+			(units::Game(0.0), units::Game(0.0)),
+			AMMO_SOURCE,
+			(units::Tile(1), units::Tile(1)),
+			~"assets/base/Npc/NpcSym.bmp"
+		)) as ~sprite::Updatable;
+
+		Ok(Pickup::new(origin, Ammo(amount), sprite))
+	}
+
+	/// Drops a heart restoring `hit_points`, centered on `origin`.
+	pub fn heart(
+		graphics: &mut graphics::Graphics,
+		origin: (units::Game, units::Game),
+		hit_points: units::HitPoint
+	) -> GameResult<Pickup> {
+		let sprite = ~try!(sprite::AnimatedSprite::new(
+			graphics, ~"assets/base/Npc/NpcSym.bmp",
+			HEART_SOURCE,
+			(units::Tile(1), units::Tile(1)),
+			HEART_FRAMES, HEART_FPS
+		)) as ~sprite::Updatable;
+
+		Ok(Pickup::new(origin, Health(hit_points), sprite))
+	}
+
+	fn new(origin: (units::Game, units::Game), kind: Kind, sprite: ~sprite::Updatable) -> Pickup {
+		let (center_x, center_y) = origin;
+		let half_tile = units::Tile(1).to_game() / units::Game(2.0);
+
+		Pickup {
+			x: center_x - half_tile,
+			y: center_y - half_tile,
+			previous: (center_x - half_tile, center_y - half_tile),
+			velocity_x: units::Velocity(0.0),
+			velocity_y: -BOUNCE_SPEED,
+
+			kind: kind,
+			lifetime: units::Millis(0),
+			collected: false,
+
+			sprite: sprite
+		}
+	}
+
+	/// Marks this pickup as collected, responding w/ its effect.
+	pub fn collect(&mut self) -> Kind {
+		self.collected = true;
+		self.kind
+	}
+
+	fn center(&self) -> (units::Game, units::Game) {
+		let half_tile = units::Tile(1).to_game() / units::Game(2.0);
+		(self.x + half_tile, self.y + half_tile)
+	}
+
+	fn hitbox_at(&self, x: units::Game, y: units::Game) -> Rectangle {
+		Rectangle {
+			x: x + COLLISION_BOX.left(), y: y + COLLISION_BOX.top(),
+			width: COLLISION_BOX.width(), height: COLLISION_BOX.height()
+		}
+	}
+
+	/// Restricts `velocity` to `[-max, max]`
+	fn clamp(velocity: units::Velocity, max: units::Velocity) -> units::Velocity {
+		cmp::max(-max, cmp::min(velocity, max))
+	}
+}
+
+impl Entity for Pickup {
+	/// Moves the pickup, attracting it to the player when `player`
+	/// (the player's center) is within range.
+	fn update(
+		&mut self,
+		elapsed_time: units::Millis,
+		player: (units::Game, units::Game),
+		map: &map::Map
+	) {
+		self.previous = (self.x, self.y);
+		self.lifetime = self.lifetime + elapsed_time;
+
+		let (player_x, player_y) = player;
+		let (center_x, center_y) = self.center();
+		let units::Game(dist_x) = player_x - center_x;
+		let units::Game(dist_y) = player_y - center_y;
+		let units::Game(radius) = ATTRACT_RADIUS.to_game();
+
+		if f64::sqrt(dist_x * dist_x + dist_y * dist_y) < radius {
+			// drift in a straight line towards the player
+			let accel_x = if dist_x < 0.0 { -ATTRACT_ACCEL } else { ATTRACT_ACCEL };
+			let accel_y = if dist_y < 0.0 { -ATTRACT_ACCEL } else { ATTRACT_ACCEL };
+
+			self.velocity_x = Pickup::clamp(self.velocity_x + (accel_x * elapsed_time), MAX_ATTRACT_SPEED);
+			self.velocity_y = Pickup::clamp(self.velocity_y + (accel_y * elapsed_time), MAX_ATTRACT_SPEED);
+		} else {
+			self.velocity_y = cmp::min(self.velocity_y + (GRAVITY * elapsed_time), MAX_VELOCITY_Y);
+			self.velocity_x = if self.velocity_x > units::Velocity(0.0) {
+				cmp::max(units::Velocity(0.0), self.velocity_x - (FRICTION * elapsed_time))
+			} else {
+				cmp::min(units::Velocity(0.0), self.velocity_x + (FRICTION * elapsed_time))
+			};
+		}
+
+		// x-axis: stop against walls
+		let dx = self.velocity_x * elapsed_time;
+		if map.is_colliding(&self.hitbox_at(self.x + dx, self.y)) {
+			self.velocity_x = units::Velocity(0.0);
+		} else {
+			self.x = self.x + dx;
+		}
+
+		// y-axis: bounce off floors, stop against ceilings
+		let dy = self.velocity_y * elapsed_time;
+		if map.is_colliding(&self.hitbox_at(self.x, self.y + dy)) {
+			self.velocity_y = if dy > units::Game(0.0) { -BOUNCE_SPEED } else { units::Velocity(0.0) };
+		} else {
+			self.y = self.y + dy;
+		}
+
+		self.sprite.update(elapsed_time);
+		self.sprite.set_position((self.x, self.y));
+	}
+
+	/// Pickups flash for a short while before they disappear.
+	fn draw(&self, queue: &mut graphics::RenderQueue, offset: (units::Game, units::Game), alpha: f64) {
+		if self.lifetime > FLASH_TIME {
+			let (units::Millis(t), units::Millis(flash)) = (self.lifetime, FLASH_INTERVAL);
+			if (t / flash) % 2 == 0 { return; }
+		}
+
+		self.sprite.draw_queued(queue, graphics::Entities, entity::blend(offset, self.previous, (self.x, self.y), alpha));
+	}
+
+	fn hitbox(&self) -> Rectangle {
+		self.hitbox_at(self.x, self.y)
+	}
+
+	fn kind(&self) -> entity::Kind { entity::Pickup }
+
+	/// Responds true while this pickup should remain on the map.
+	fn is_alive(&self) -> bool {
+		!self.collected && self.lifetime < LIFETIME
+	}
+}
@@ -1,16 +1,33 @@
 use std::cmp;
+use std::mem;
 use collections::hashmap::HashMap;
 
+use game::animation;
+use game::audio;
+use game::equipment;
 use game::graphics;
 use game::sprite;
 
 
+use game::collisions;
 use game::collisions::{Info,Rectangle};
+use game::error::GameResult;
 use game::units;
 use game::units::{AsGame};
 use game::map;
-
-type MotionTup = (sprite::Motion, sprite::Facing, sprite::Looking);
+use game::projectiles;
+use game::weapons;
+
+/// How the player is moving, which picks the sprite they are drawn w/.
+pub type MotionTup = (sprite::Motion, sprite::Facing, sprite::Looking);
+
+/// The way the player's booster is pushing them.
+#[deriving(Eq,Clone)]
+pub enum Thrust {
+	Up,
+	Left,
+	Right
+}
 
 // physics
 static FRICTION: units::Acceleration 	= units::Acceleration(0.00049804687);
@@ -21,28 +38,42 @@ static MAX_VELOCITY_X: units::Velocity 		= units::Velocity(0.15859375);
 static MAX_VELOCITY_Y: units::Velocity		= units::Velocity(0.2998046875);
 
 static	AIR_ACCELERATION: units::Acceleration 	=	units::Acceleration(0.0003125);
-static 	JUMP_GRAVITY: units::Acceleration		= units::Acceleration(0.0003125);
 static 	JUMP_SPEED: units::Velocity				= units::Velocity(0.25);
+// how long holding jump keeps the player rising at full speed
+static 	JUMP_TIME: units::Millis				= units::Millis(250);
+
+// health
+static MAX_HIT_POINTS: units::HitPoint 		= 3;
+static INVINCIBLE_TIME: units::Millis 		= units::Millis(3000);
+static INVINCIBLE_FLASH_TIME: units::Millis = units::Millis(50);
+static KNOCKBACK_SPEED_X: units::Velocity 	= units::Velocity(0.15);
+static KNOCKBACK_SPEED_Y: units::Velocity 	= units::Velocity(0.25);
+
+// how long one-way platforms are ignored after dropping through one
+static DROP_TIME: units::Millis 			= units::Millis(200);
+
+static CLIMB_SPEED: units::Velocity 		= units::Velocity(0.1);
+
+// a jump still leaves the ground this long after walking off a ledge,
+// & a jump pressed this long before landing is made once the player lands
+static COYOTE_TIME: units::Millis 			= units::Millis(80);
+static JUMP_BUFFER_TIME: units::Millis 		= units::Millis(100);
 
+// a heavy landing (on maps w/ fall damage) leaves the player unable to move for a moment
+static STUN_TIME: units::Millis 			= units::Millis(400);
 
-// player sprite animation
-static CHAR_OFFSET: uint				= 12;
-static SPRITE_NUM_FRAMES: units::Frame	= (3); 
-static SPRITE_FPS: units::Fps			= (20);
+// sliding down (& jumping off) walls, on maps which allow it
+static WALL_SLIDE_SPEED: units::Velocity 	= units::Velocity(0.06);
 
-// motion
-static STAND_FRAME: units::Tile 	= units::Tile(0);
-static JUMP_FRAME: units::Tile 		= units::Tile(1);
-static FALL_FRAME: units::Tile 		= units::Tile(2);
+// how fast a crouching player may creep along
+static CROUCH_MAX_VELOCITY_X: units::Velocity = units::Velocity(0.08);
 
-// horizontal facing (Facing)
-static FACING_WEST: units::Tile			= units::Tile(0 + CHAR_OFFSET);
-static FACING_EAST: units::Tile 		= units::Tile(1 + CHAR_OFFSET);
+// the booster (while a jetpack is worn): how long it may fire between
+// landings, & its thrust
+static BOOSTER_FUEL: units::Millis 			= units::Millis(1200);
+static BOOST_ACCEL: units::Acceleration 	= units::Acceleration(0.0012);
+static MAX_BOOST_SPEED: units::Velocity 	= units::Velocity(0.2);
 
-// vertical facing (Looking)
-static WALK_UP_OFFSET: units::Tile			= units::Tile(3);
-static JUMP_DOWN_FRAME:  units::Tile		= units::Tile(6);
-static STAND_DOWN_FRAME: units::Tile 		= units::Tile(7);
 
 // collision detection boxes
 // (expressed as `units::Game`)
@@ -54,18 +85,31 @@ static Y_BOX: Rectangle = Rectangle {
 	x: units::Game(10.0), y: units::Game(2.0), 
 	width: units::Game(12.0), height: units::Game(30.0)
 };
+// while crouching: short enough to fit under a one-tile gap, w/ the
+// player's feet where they were
+static CROUCH_X_BOX: Rectangle = Rectangle {
+	x: units::Game(6.0), y: units::Game(18.0),
+	width: units::Game(20.0), height: units::Game(10.0)
+};
+static CROUCH_Y_BOX: Rectangle = Rectangle {
+	x: units::Game(10.0), y: units::Game(16.0),
+	width: units::Game(12.0), height: units::Game(16.0)
+};
 
 
 /// Encapsulates the pysical motion of a player as it relates to
 /// a sprite which can be animated, positioned, and drawn on the screen.
+#[deriving(Clone)]
 pub struct Player {
 	priv sprites: HashMap<MotionTup, ~sprite::Updatable>,
+	priv animations: animation::AnimationSet,
 	
 	// positioning
 	priv x: units::Game,
 	priv y: units::Game,
 	priv movement: MotionTup,
 	priv on_ground: bool,
+	priv sprite_position: (units::Game, units::Game),
 
 	// physics
 	priv elapsed_time: units::Millis,
@@ -75,18 +119,65 @@ pub struct Player {
 
 	// state
 	priv is_interacting: bool,
-	priv is_jump_active: bool
+	priv is_jump_active: bool,
+	priv jump_time: units::Millis,
+	priv drop_time: units::Millis,
+	priv landed: bool,
+
+	// whether the player is crouching, & whether they are holding down to
+	// (they stay crouched while there is no room to stand)
+	priv crouching: bool,
+	priv crouch_requested: bool,
+
+	// the fastest the player has fallen since leaving the ground, whether
+	// they just landed hard, & how much longer they are stunned for
+	priv peak_fall_speed: units::Velocity,
+	priv landed_hard: bool,
+	priv stun_time: units::Millis,
+
+	// time since the player was last on the ground, whether they have
+	// jumped since, & how much longer an early jump press is kept for
+	priv airborne_time: units::Millis,
+	priv jumped: bool,
+	priv jump_buffer: units::Millis,
+
+	// climbing ladders: the direction asked for (-1 up, 1 down, 0 still)
+	priv climbing: bool,
+	priv climb_direction: int,
+
+	// the side of the player of a wall they are sliding down
+	priv wall: Option<sprite::Facing>,
+
+	// the booster's remaining fuel, & its thrust while it fires
+	priv fuel: units::Millis,
+	priv boosting: Option<Thrust>,
+
+	// health
+	priv hit_points: units::HitPoint,
+	priv invincible: bool,
+	priv invincible_time: units::Millis,
+
+	// weapons
+	priv arsenal: weapons::Arsenal,
+
+	// jumps made & damage taken since they were last taken (for statistics)
+	priv jumps: uint,
+	priv wounds: units::HitPoint,
+
+	// items worn, which modify the physics above
+	priv equipment: equipment::Equipment
 }
 
 
 impl Player {
 	/// Loads and initializes a set of sprite-sheets for the various combinatoins of directions.
 	/// (These incl: facing west and east for: standing, walking, jumping, falling.)
+	/// Which frames are drawn for each is read from `animation::PLAYER_ANIMATIONS`.
 	///
 	/// The player will spawn at `x` and `y`, though it will immediately be subject to gravity.
 	/// The player is initailized `standing` facing `east`.
 	/// The player will continue to fall until some collision is detected.
-	pub fn new(graphics: &mut graphics::Graphics, x: units::Game, y: units::Game) -> Player {
+	pub fn new(graphics: &mut graphics::Graphics, x: units::Game, y: units::Game) -> GameResult<Player> {
 		// insert sprites into map
 		let sprite_map = 
 			HashMap::<MotionTup, ~sprite::Updatable>::new();
@@ -95,41 +186,90 @@ impl Player {
 		let mut new_player = Player{
 			elapsed_time: units::Millis(0),
 			sprites: sprite_map,
+			animations: try!(animation::AnimationSet::load(animation::PLAYER_ANIMATIONS)),
 
 			x: x, 
 			y: y,
 			movement: (sprite::Standing, sprite::East, sprite::Horizontal),
 			on_ground: false,
+			sprite_position: (x, y),
 			
 			velocity_x: units::Velocity(0.0),
 			velocity_y: units::Velocity(0.0),
 			accel_x: 1,
 
 			is_interacting: false,
-			is_jump_active: false
+			is_jump_active: false,
+			jump_time: units::Millis(0),
+			drop_time: units::Millis(0),
+			landed: false,
+
+			crouching: false,
+			crouch_requested: false,
+
+			peak_fall_speed: units::Velocity(0.0),
+			landed_hard: false,
+			stun_time: units::Millis(0),
+
+			airborne_time: units::Millis(0),
+			jumped: false,
+			jump_buffer: units::Millis(0),
+
+			climbing: false,
+			climb_direction: 0,
+
+			wall: None,
+
+			fuel: BOOSTER_FUEL,
+			boosting: None,
+
+			hit_points: MAX_HIT_POINTS,
+			invincible: false,
+			invincible_time: units::Millis(0),
+
+			arsenal: weapons::Arsenal::new(),
+
+			jumps: 0,
+			wounds: 0,
+
+			equipment: equipment::Equipment::new()
 		};
 
 		// load sprites for every possible movement tuple.
 		for motion in sprite::MOTIONS.iter() {
 			for facing in sprite::FACINGS.iter() {
 				for looking in sprite::LOOKINGS.iter() {
-					new_player.load_sprite(graphics, (*motion, *facing, *looking));
+					try!(new_player.load_sprite(graphics, (*motion, *facing, *looking)));
 				}
 			}
 		}
 
-		new_player
+		Ok(new_player)
 	}
 
 	/// Draws player to screen
-	pub fn draw(&self, display: &graphics::Graphics) {
-		self.sprites.get(&self.movement).draw(display);
+	/// While invincible the player blinks, skipping every other flash interval.
+	///
+	/// The sprite is positioned where the player stood before their last update;
+	/// `alpha` moves it that fraction of the way to where they stand now.
+	pub fn draw(&self, queue: &mut graphics::RenderQueue, offset: (units::Game, units::Game), alpha: f64) {
+		if self.invincible {
+			let (units::Millis(t), units::Millis(flash)) = (self.invincible_time, INVINCIBLE_FLASH_TIME);
+			if (t / flash) % 2 == 0 { return; }
+		}
+
+		let (ox, oy) = offset;
+		let (sprite_x, sprite_y) = self.sprite_position;
+		let dx = (self.x - sprite_x) * units::Game(alpha);
+		let dy = (self.y - sprite_y) * units::Game(alpha);
+
+		self.sprites.get(&self.movement).draw_queued(queue, graphics::Entities, (ox - dx, oy - dy));
 	}
 
 	/// Updates player-state that relies on time data. (Namely physics calculations.)
 	/// Determines which sprite-sheet should be used for thsi frame.
 	/// Forwards the elapsed time to the current sprite.
-	pub fn update(&mut self, elapsed_time: units::Millis, map: &map::Map) {
+	pub fn update(&mut self, elapsed_time: units::Millis, map: &map::Map, audio: &audio::Audio) {
 		// calculate current position
 		self.elapsed_time = elapsed_time;
 		
@@ -137,68 +277,145 @@ impl Player {
 		self.current_motion(); // update motion once at beginning of frame for consistency
 		self.set_position((self.x, self.y));
 		self.sprites.get_mut(&self.movement).update(elapsed_time);
+		self.arsenal.update(elapsed_time);
+
+		if self.stun_time > units::Millis(0) {
+			self.stun_time = self.stun_time - elapsed_time;
+		}
+
+		// tick down any drop through a platform
+		if self.drop_time > units::Millis(0) {
+			self.drop_time = self.drop_time - elapsed_time;
+		}
+
+		// tick down invincibility
+		if self.invincible {
+			self.invincible_time = self.invincible_time + elapsed_time;
+			self.invincible = self.invincible_time < INVINCIBLE_TIME;
+		}
+
+		// grab onto (or let go of) any ladder the player is in front of
+		// (climbing down onto the ground also lets go.)
+		if !map.is_on_ladder(&self.damage_rectangle())
+			|| (self.on_ground() && self.climb_direction > 0) {
+			self.climbing = false;
+		} else if self.climb_direction != 0 {
+			self.climbing = true;
+		}
+
+		self.update_booster();
+		self.update_crouch(map);
 
 		// run physics sim
+		let was_on_ground = self.on_ground();
 		self.update_x(map);
+		self.update_wall(map);
 		self.update_y(map);
+
+		self.landed = self.on_ground() && !was_on_ground;
+		if self.on_ground() || self.climbing {
+			self.fuel = BOOSTER_FUEL;
+			self.airborne_time = units::Millis(0);
+			self.jumped = false;
+		} else {
+			self.airborne_time = self.airborne_time + elapsed_time;
+		}
+		if self.landed {
+			audio.play_sfx_at(audio::Land, (self.center_x(), self.center_y()));
+		}
+		self.check_fall(map);
+
+		// a jump pressed just before landing is made now, if still held
+		if self.jump_buffer > units::Millis(0) {
+			self.jump_buffer = self.jump_buffer - elapsed_time;
+			if self.on_ground() && self.is_jump_active {
+				self.jump_buffer = units::Millis(0);
+				self.jump(audio);
+			}
+		}
 	}
 
 	fn update_x(&mut self, map: &map::Map) {
+		// the surface underfoot changes how well the player grips the ground
+		let ground = if self.on_ground() {
+			map.tile_type_at(self.center_x(), self.y + self.y_box().bottom() + units::Game(1.0))
+		} else { map::Air };
+		let (units::Acceleration(walking), units::Acceleration(friction)) = (WALKING_ACCEL, FRICTION);
+		let walking_accel = units::Acceleration(walking * ground.friction());
+		let friction = units::Acceleration(friction * ground.friction());
+
 		// compute next velocity
-		let accel_x: units::Acceleration = if self.accel_x < 0  {
-			if self.on_ground() { -WALKING_ACCEL } else { -AIR_ACCELERATION }
-		} else if self.accel_x > 0 {
-			if self.on_ground() {  WALKING_ACCEL } else {  AIR_ACCELERATION }
+		// a stunned player can not steer
+		let steering = if self.stun_time > units::Millis(0) { 0 } else { self.accel_x };
+		let max_velocity_x = if self.crouching { CROUCH_MAX_VELOCITY_X } else { MAX_VELOCITY_X };
+		let air_accel = match self.boosting {
+			Some(Left) | Some(Right) => BOOST_ACCEL,
+			_ => self.equipment.modifiers().air_acceleration(AIR_ACCELERATION)
+		};
+		let accel_x: units::Acceleration = if steering < 0  {
+			if self.on_ground() { -walking_accel } else { -air_accel }
+		} else if steering > 0 {
+			if self.on_ground() {  walking_accel } else {  air_accel }
 		} else { units::Acceleration(0.0) };
 
 		self.velocity_x = self.velocity_x + (accel_x * self.elapsed_time);
 
-		if self.accel_x < 0 {
-			self.velocity_x = cmp::max(self.velocity_x, -MAX_VELOCITY_X);
-		} else if self.accel_x > 0 {
-			self.velocity_x = cmp::min(self.velocity_x, MAX_VELOCITY_X);
+		if steering < 0 {
+			self.velocity_x = cmp::max(self.velocity_x, -max_velocity_x);
+		} else if steering > 0 {
+			self.velocity_x = cmp::min(self.velocity_x, max_velocity_x);
 		} else if self.on_ground() {
 			self.velocity_x = if self.velocity_x > units::Velocity(0.0) {
-				cmp::max(units::Velocity(0.0), self.velocity_x - (FRICTION * self.elapsed_time))
+				cmp::max(units::Velocity(0.0), self.velocity_x - (friction * self.elapsed_time))
 			} else {
-				cmp::min(units::Velocity(0.0), self.velocity_x + (FRICTION * self.elapsed_time))
+				cmp::min(units::Velocity(0.0), self.velocity_x + (friction * self.elapsed_time))
 			};
 		}
 
+		// force regions push the player regardless of their footing
+		let (force_x, _) = map.force_at(&self.damage_rectangle());
+		if force_x != units::Acceleration(0.0) {
+			self.velocity_x = cmp::max(-MAX_VELOCITY_X, cmp::min(
+				self.velocity_x + (force_x * self.elapsed_time),
+				MAX_VELOCITY_X
+			));
+		}
+
 		// x-axis collision checking 
-		let delta = self.velocity_x * self.elapsed_time;
+		// (conveyors carry the player w/o changing their own velocity)
+		let delta = (self.velocity_x + ground.push()) * self.elapsed_time;
 		if delta > units::Game(0.0) { // moving right
 			// collisions right-side
-			let mut info = self.get_collision_info(&self.right_collision(delta), map);
+			let mut info = self.get_impact_info(&self.right_collision(units::Game(0.0)), (delta, units::Game(0.0)), map);
 			self.x = if info.collided {
 				self.velocity_x = units::Velocity(0.0);
-				(info.col.to_game() - X_BOX.right())
+				(info.col.to_game() - self.x_box().right())
 			} else {
 				(self.x + delta)
 			};
 
 			// collisions left-side
-			info = self.get_collision_info(&self.left_collision(units::Game(0.0)), map);
+			info = self.get_collision_info(&self.left_collision(units::Game(0.0)), collisions::Left, map);
 			self.x = if info.collided {
-				(info.col.to_game() + X_BOX.right())
+				(info.col.to_game() + self.x_box().right())
 			} else {
 				self.x
 			};
 
 		} else { // moving left
 			// collisions left-side
-			let mut info = self.get_collision_info(&self.left_collision(delta), map);
+			let mut info = self.get_impact_info(&self.left_collision(units::Game(0.0)), (delta, units::Game(0.0)), map);
 			self.x = if info.collided {
 				self.velocity_x = units::Velocity(0.0);
-				(info.col.to_game() + X_BOX.right())
+				(info.col.to_game() + self.x_box().right())
 			} else {
 				(self.x + delta) 
 			};
 
 			// collisions right-side
-			info = self.get_collision_info(&self.right_collision(units::Game(0.0)), map);
+			info = self.get_collision_info(&self.right_collision(units::Game(0.0)), collisions::Right, map);
 			self.x = if info.collided {
-				(info.col.to_game() - X_BOX.right()) 
+				(info.col.to_game() - self.x_box().right()) 
 			} else {
 				self.x
 			};
@@ -206,19 +423,40 @@ impl Player {
 	}
 
 	fn update_y (&mut self, map: &map::Map) {
+		let was_on_ground = self.on_ground();
+
+		// a held jump rises at full speed until its timer runs out;
+		// releasing jump (or running out of time) gives way to gravity
+		let rising = self.is_jump_active && self.jump_time > units::Millis(0);
+		self.jump_time = if rising {
+			cmp::max(units::Millis(0), self.jump_time - self.elapsed_time)
+		} else {
+			units::Millis(0)
+		};
+
 		// update velocity
-		let gravity: units::Acceleration = 
-			if self.is_jump_active 
-			&& self.velocity_y < units::Velocity(0.0) {
-				JUMP_GRAVITY
-			} else {
-				GRAVITY
-			};
+		let (_, force_y) = map.force_at(&self.damage_rectangle());
+
+		let max_velocity_y = self.equipment.modifiers().max_fall_speed(MAX_VELOCITY_Y);
+		let max_velocity_y = if self.wall.is_some() { cmp::min(WALL_SLIDE_SPEED, max_velocity_y) } else { max_velocity_y };
 
-		self.velocity_y = cmp::min(
-			self.velocity_y + (gravity * self.elapsed_time), 
-			MAX_VELOCITY_Y
-		);
+		// gravity is suspended while climbing
+		self.velocity_y = if self.climbing {
+			if self.climb_direction < 0 { -CLIMB_SPEED }
+			else if self.climb_direction > 0 { CLIMB_SPEED }
+			else { units::Velocity(0.0) }
+		} else {
+			match self.boosting {
+				// thrusting sideways holds the player level
+				Some(Up) => cmp::max(-MAX_BOOST_SPEED, self.velocity_y - (BOOST_ACCEL * self.elapsed_time)),
+				Some(Left) | Some(Right) => units::Velocity(0.0),
+				None if rising => -self.equipment.modifiers().jump_speed(JUMP_SPEED),
+				None => cmp::max(-max_velocity_y, cmp::min(
+					self.velocity_y + ((GRAVITY + force_y) * self.elapsed_time), 
+					max_velocity_y
+				))
+			}
+		};
 
 		// calculate delta
 		let delta = self.velocity_y * self.elapsed_time;
@@ -226,54 +464,127 @@ impl Player {
 		// check collision in direction of delta
 		if delta > units::Game(0.0) {
 			// react to collision
-			let mut info = self.get_collision_info(&self.bottom_collision(delta), map);
+			let mut info = self.get_impact_info(&self.bottom_collision(units::Game(0.0)), (units::Game(0.0), delta), map);
 			self.y = if info.collided {
 				self.velocity_y = units::Velocity(0.0);
 				self.on_ground = true;
 
-				(info.row.to_game() - Y_BOX.bottom())
+				(info.row.to_game() - self.y_box().bottom())
 			} else {
-				self.on_ground = false;
-				(self.y + delta)
+				// one-way platforms only catch the player as they fall onto them
+				let foot_y = self.y + self.y_box().bottom();
+				let platform = if self.drop_time > units::Millis(0) { None } else {
+					map.platform_surface(
+						self.x + self.y_box().left(), self.x + self.y_box().right(),
+						foot_y, foot_y + delta
+					)
+				};
+
+				match platform {
+					Some(top) => {
+						self.velocity_y = units::Velocity(0.0);
+						self.on_ground = true;
+						(top - self.y_box().bottom())
+					}
+					None => {
+						self.on_ground = false;
+						(self.y + delta)
+					}
+				}
 			};
 
-			info = self.get_collision_info(&self.top_collision(units::Game(0.0)), map);
+			info = self.get_collision_info(&self.top_collision(units::Game(0.0)), collisions::Top, map);
 			self.y = if info.collided {
-				(info.row.to_game() + Y_BOX.height())
+				(info.row.to_game() + self.y_box().height())
 			} else {
 				self.y
 			};
 
 		} else {
 			// react to collision
-			let mut info = self.get_collision_info(&self.top_collision(delta), map);
+			let mut info = self.get_impact_info(&self.top_collision(units::Game(0.0)), (units::Game(0.0), delta), map);
 			self.y = if info.collided {
+				// bumping a ceiling cuts the jump short
 				self.velocity_y = units::Velocity(0.0);
-				(info.row.to_game() + Y_BOX.height())
+				self.jump_time = units::Millis(0);
+				(info.row.to_game() + self.y_box().height())
 			} else {
 				self.on_ground = false;
 				(self.y + delta)
 			};
 
-			info = self.get_collision_info(&self.bottom_collision(units::Game(0.0)), map);
+			info = self.get_collision_info(&self.bottom_collision(units::Game(0.0)), collisions::Bottom, map);
 			self.y = if info.collided {
 				self.on_ground = true;
-				(info.row.to_game() - Y_BOX.bottom())
+				(info.row.to_game() - self.y_box().bottom())
 			} else {
 				self.y
 			};
 		}
+
+		// slopes are not solid: instead the player's feet are rested on their surface.
+		// (while walking downhill the player is pulled onto the slope, rather than
+		// falling off of it.)
+		if self.velocity_y >= units::Velocity(0.0) {
+			let foot_y = self.y + self.y_box().bottom();
+			let half_tile = units::Tile(1).to_game() / units::Game(2.0);
+			let reach = if was_on_ground { half_tile } else { units::Game(0.0) };
+
+			match map.slope_surface(self.center_x(), foot_y - half_tile, foot_y + reach) {
+				Some(surface) => {
+					self.y = surface - self.y_box().bottom();
+					self.velocity_y = units::Velocity(0.0);
+					self.on_ground = true;
+				}
+				None => {}
+			}
+		}
+	}
+
+	/// Finds the solid tile overlapping `hitbox` which lies closest to
+	/// its `side`, so that the player is pushed out by the nearest surface.
+	fn get_collision_info(&self, hitbox: &Rectangle, side: collisions::Side, tile_map: &map::Map) -> Info {
+		collisions::nearest(self.get_collisions(hitbox, tile_map).as_slice(), side)
 	}
 
-	fn get_collision_info(&self, hitbox: &Rectangle, tile_map: &map::Map) -> Info {
-		let tiles = 
-			tile_map.get_colliding_tiles(hitbox);
+	/// Every solid tile overlapping `hitbox`.
+	fn get_collisions(&self, hitbox: &Rectangle, tile_map: &map::Map) -> ~[Info] {
+		tile_map.get_colliding_tiles(hitbox).iter()
+			.filter(|tile| tile.tile_type.is_solid())
+			.map(|tile| Info { collided: true, row: tile.row, col: tile.col })
+			.collect()
+	}
+
+	/// Finds the solid tile which `hitbox` would strike first while moving
+	/// by `delta`, i.e: the one w/ the earliest time of impact.
+	fn get_impact_info(
+		&self,
+		hitbox: &Rectangle,
+		delta: (units::Game, units::Game),
+		tile_map: &map::Map
+	) -> Info {
+		let (dx, dy) = delta;
+		let zero = units::Game(0.0);
+		let swept = Rectangle {
+			x: hitbox.left() + if dx < zero { dx } else { zero },
+			y: hitbox.top() + if dy < zero { dy } else { zero },
+			width: hitbox.width() + if dx < zero { zero - dx } else { dx },
+			height: hitbox.height() + if dy < zero { zero - dy } else { dy }
+		};
 
 		let mut info = Info { collided: false, row: units::Tile(0), col: units::Tile(0) };
-		for tile in tiles.iter() {
-			if tile.tile_type == map::Wall {
-				info = Info {collided: true, row: tile.row, col: tile.col};
-				break;
+		let mut earliest = 1.0;
+		for tile in self.get_collisions(&swept, tile_map).iter() {
+			let tile_rect = Rectangle {
+				x: tile.col.to_game(), y: tile.row.to_game(),
+				width: units::Tile(1).to_game(), height: units::Tile(1).to_game()
+			};
+			match collisions::sweep(hitbox, delta, &tile_rect) {
+				Some(time) if !info.collided || time < earliest => {
+					earliest = time;
+					info = *tile;
+				}
+				_ => {}
 			}
 		}
 
@@ -299,85 +610,40 @@ impl Player {
 	/// Instructs the current sprite-sheet to position itself
 	/// at the coordinates specified by `coords:(x,y)`.
 	fn set_position(&mut self, coords: (units::Game, units::Game)) {
+		self.sprite_position = coords;
 		self.sprites.get_mut(&self.movement).set_position(coords);
 	}
 
 	/// Loads a sprite for the selected `movement`, stores it in the player's sprite map.
-	/// This exhaustively matches all tuples of (Motion,Facing,Looking), though certain
-	/// sprites are considered invalid states [for e.g: walking + looking down]
+	/// Each tuple of (Motion,Facing,Looking) is drawn as the player's animations define,
+	/// though certain sprites are considered invalid states [for e.g: walking + looking down]
 	fn load_sprite(
 		&mut self, 
 		graphics: &mut graphics::Graphics, 
 		movement: (sprite::Motion, sprite::Facing, sprite::Looking)
-	) {
-		self.sprites.find_or_insert_with(movement, |key| -> ~sprite::Updatable {
-			let file_path = ~"assets/base/MyChar.bmp";
-			let (motion, facing, _) = *key;
-			let motion_frame = match motion {
-				sprite::Standing | sprite::Walking => STAND_FRAME,
-				sprite::Interacting => STAND_DOWN_FRAME,
-				sprite::Jumping => JUMP_FRAME,
-				sprite::Falling => FALL_FRAME
-			};
-
-			let facing_frame = match facing {
-				sprite::West => FACING_WEST,
-				sprite::East => FACING_EAST
-			};
-
-			match movement {
-				// static: standing in place
-				  (sprite::Standing, _, looking)
-				| (sprite::Interacting, _, looking) => {
-					let looking_frame = match looking {
-						sprite::Up => WALK_UP_OFFSET,
-						_ => units::Tile(0)
-					};
-				
-					~sprite::Sprite::new(
-						graphics, 
-						(units::Game(0.0), units::Game(0.0)), 
-						(motion_frame + (looking_frame), facing_frame), 
-						(units::Tile(1), units::Tile(1)),	
-						file_path
-					) as ~sprite::Updatable 
-				}
-
-				// static: jumping or falling
-				// (overrides 'STAND_DOWN_FRAME')
-				(sprite::Jumping, _, looking)
-				| (sprite::Falling, _, looking) => {
-					let looking_frame = match looking { // ignored while jumping / falling for now
-						sprite::Down => JUMP_DOWN_FRAME,
-						sprite::Up => WALK_UP_OFFSET,
-						_ => motion_frame
-					};
-					
-					~sprite::Sprite::new(
-						graphics, 
-						(units::Game(0.0), units::Game(0.0)), 
-						(looking_frame, facing_frame), 
-						(units::Tile(1), units::Tile(1)),	
-						file_path
-					) as ~sprite::Updatable 
-				}
+	) -> GameResult<()> {
+		if self.sprites.contains_key(&movement) { return Ok(()); }
+
+		let animation = self.animations.get(movement).clone();
+		let sprite = if animation.frames > 1 {
+			~try!(sprite::AnimatedSprite::new(
+				graphics, animation.sheet,
+				animation.offset,
+				(units::Tile(1), units::Tile(1)),
+				animation.frames, animation.fps
+			)) as ~sprite::Updatable
+		} else {
+			~try!(sprite::Sprite::new(
+				graphics, 
+				(units::Game(0.0), units::Game(0.0)), 
+				animation.offset, 
+				(units::Tile(1), units::Tile(1)),	
+				animation.sheet
+			)) as ~sprite::Updatable
+		};
 
-				// dynamic: 
-				(sprite::Walking, _, looking) => {
-					let looking_frame = match looking {
-						sprite::Up => WALK_UP_OFFSET,
-						_ => units::Tile(0)
-					};
-	
-					~sprite::AnimatedSprite::new(
-						graphics, file_path, 
-						(motion_frame + looking_frame, facing_frame), 
-						(units::Tile(1), units::Tile(1)),
-						SPRITE_NUM_FRAMES, SPRITE_FPS
-					).unwrap() as ~sprite::Updatable
-				}
-			}
-		});
+		self.sprites.insert(movement, sprite);
+		Ok(())
 	}
 
 	/// The player will immediately face `West`
@@ -404,10 +670,14 @@ impl Player {
 
 	pub fn look_up(&mut self) {
 		self.is_interacting = false;
+		self.crouch_requested = false;
 		self.set_looking(sprite::Up);
 	}
 
+	/// Examines whatever the player stands in front of, & crouches for as
+	/// long as down is held (on the ground.)
 	pub fn look_down(&mut self) {
+		self.crouch_requested = true;
 		let(motion,_,looking) = self.movement;
 		if looking == sprite::Down {return;}
 		if motion == sprite::Walking {return;}
@@ -417,31 +687,297 @@ impl Player {
 	}
 
 	pub fn look_horizontal(&mut self) {
+		self.crouch_requested = false;
 		self.set_looking(sprite::Horizontal);
 	}
 
-	/// Resets the player's jump timer if they are currently on the ground.
-	/// Otherwise: uses the remainder of the player's jump timer to extend
-	/// their jump.
+	/// Fires the weapon at hand from the player's gun: upwards while they
+	/// look up, downwards while they look down in midair, & otherwise in
+	/// the direction they are facing. (Responds w/ no projectiles while
+	/// the weapon is cooling down.)
+	pub fn fire(
+		&mut self, 
+		graphics: &mut graphics::Graphics, 
+		audio: &audio::Audio
+	) -> GameResult<~[projectiles::Projectile]> {
+		let (_, facing, looking) = self.movement;
+		let aim = match looking {
+			sprite::Down if self.on_ground() => (facing, sprite::Horizontal),
+			_ => (facing, looking)
+		};
+		let origin = (self.center_x(), self.center_y());
+		let shots = try!(self.arsenal.current_mut().fire(graphics, origin, aim));
+		if !shots.is_empty() { audio.play_sfx_at(audio::Shoot, origin); }
+		Ok(shots)
+	}
+
+	/// Switches to the next weapon (or, for a negative `step`, a previous one.)
+	pub fn switch_weapon(&mut self, step: int) {
+		self.arsenal.switch(step);
+	}
+
+	/// Hurts the player for `amount` hit points unless they are still
+	/// invincible from a previous hit. Their weapon loses as much energy.
 	///
-	/// The effects of a jump against gravity are `instantaneous` and do not
-	/// consider acceleration.
-	pub fn start_jump(&mut self) {
+	/// The player is knocked up and away from the center of `source`,
+	/// and is then invincible for a short period of time.
+	pub fn take_damage(&mut self, amount: units::HitPoint, source: &Rectangle) {
+		if self.invincible { return; }
+
+		self.wounds = self.wounds + cmp::min(self.hit_points, amount);
+		self.hit_points = cmp::max(0, self.hit_points - amount);
+		self.arsenal.current_mut().experience_mut().lose(amount as uint);
+		self.invincible = true;
+		self.invincible_time = units::Millis(0);
+
+		let source_x = source.left() + (source.width() / units::Game(2.0));
+		self.velocity_x = if source_x > self.center_x() { -KNOCKBACK_SPEED_X } else { KNOCKBACK_SPEED_X };
+		self.velocity_y = -KNOCKBACK_SPEED_Y;
+		self.on_ground = false;
+	}
+
+	/// The jumps made & damage taken since this was last called.
+	pub fn take_tallies(&mut self) -> (uint, units::HitPoint) {
+		(mem::replace(&mut self.jumps, 0), mem::replace(&mut self.wounds, 0))
+	}
+
+	/// Restores `amount` hit points, up to the player's maximum.
+	pub fn heal(&mut self, amount: units::HitPoint) {
+		self.hit_points = cmp::min(self.hit_points + amount, MAX_HIT_POINTS);
+	}
+
+	/// The area in which the player can be hurt by enemies.
+	pub fn damage_rectangle(&self) -> Rectangle {
+		Rectangle {
+			x: self.x + self.x_box().left(), y: self.y + self.y_box().top(),
+			width: self.x_box().width(), height: self.y_box().height()
+		}
+	}
+
+	/// Adds `energy` to the weapon at hand, possibly leveling it up.
+	pub fn gain_experience(&mut self, energy: uint) {
+		self.arsenal.current_mut().experience_mut().gain(energy);
+	}
+
+	/// The weapon at hand.
+	pub fn weapon<'a>(&'a self) -> &'a weapons::Weapon { self.arsenal.current() }
+	pub fn arsenal<'a>(&'a self) -> &'a weapons::Arsenal { &self.arsenal }
+	pub fn arsenal_mut<'a>(&'a mut self) -> &'a mut weapons::Arsenal { &mut self.arsenal }
+
+	/// The way the booster is pushing the player, while it fires.
+	pub fn boosting(&self) -> Option<Thrust> { self.boosting }
+
+	/// The booster's `(remaining, full)` fuel, while a jetpack is worn.
+	pub fn fuel(&self) -> Option<(units::Millis, units::Millis)> {
+		if self.equipment.is_equipped(equipment::Jetpack) { Some((self.fuel, BOOSTER_FUEL)) } else { None }
+	}
+
+	// clings to a wall the player is falling beside & pressing against,
+	// using the same rectangles which stop them running into it
+	fn update_wall(&mut self, map: &map::Map) {
+		let nudge = units::Game(1.0);
+		self.wall = if !map.has_wall_jump() || self.on_ground() || self.climbing
+			|| self.velocity_y < units::Velocity(0.0) {
+			None
+		} else if self.accel_x < 0 && map.is_colliding(&self.left_collision(units::Game(0.0) - nudge)) {
+			Some(sprite::West)
+		} else if self.accel_x > 0 && map.is_colliding(&self.right_collision(nudge)) {
+			Some(sprite::East)
+		} else {
+			None
+		};
+	}
+
+	// crouches while down is held on the ground; standing back up waits
+	// until there is room overhead for the full height hitbox
+	fn update_crouch(&mut self, map: &map::Map) {
+		if self.crouch_requested && self.on_ground() && !self.climbing {
+			self.crouching = true;
+		} else if self.crouching {
+			self.crouching = false;
+			if map.is_colliding(&self.top_collision(units::Game(0.0))) {
+				self.crouching = true;
+			}
+		}
+	}
+
+	/// Responds true while the player is crouching w/ their shorter hitbox.
+	pub fn is_crouching(&self) -> bool { self.crouching }
+
+	// hurts (& stuns) a player who has landed too fast, unless the map is
+	// forgiving or something worn breaks their fall
+	fn check_fall(&mut self, map: &map::Map) {
+		self.landed_hard = false;
+		if !self.on_ground() && !self.climbing && self.boosting.is_none() {
+			self.peak_fall_speed = cmp::max(self.peak_fall_speed, self.velocity_y);
+			return;
+		}
+
+		let peak = self.peak_fall_speed;
+		self.peak_fall_speed = units::Velocity(0.0);
+		if !self.landed || self.equipment.breaks_falls() { return; }
+
+		match map.fall_damage() {
+			Some((threshold, damage)) if peak >= threshold => {
+				self.landed_hard = true;
+				self.stun_time = STUN_TIME;
+				if !self.invincible {
+					self.hit_points = cmp::max(0, self.hit_points - damage);
+					self.invincible = true;
+					self.invincible_time = units::Millis(0);
+				}
+			}
+			_ => {}
+		}
+	}
+
+	/// Responds true if the player landed hard enough to be hurt this frame.
+	pub fn has_landed_hard(&self) -> bool { self.landed_hard }
+
+	// fires the booster while jump is held in midair (once the jump itself
+	// has peaked), pushing the player the way they are steering, until its
+	// fuel runs out
+	fn update_booster(&mut self) {
+		let can_boost = self.equipment.is_equipped(equipment::Jetpack)
+			&& self.is_jump_active && !self.on_ground() && !self.climbing
+			&& self.fuel > units::Millis(0)
+			&& (self.boosting.is_some() || self.velocity_y >= units::Velocity(0.0));
+
+		self.boosting = if !can_boost {
+			None
+		} else if self.accel_x < 0 {
+			Some(Left)
+		} else if self.accel_x > 0 {
+			Some(Right)
+		} else {
+			Some(Up)
+		};
+
+		if self.boosting.is_some() {
+			self.fuel = cmp::max(units::Millis(0), self.fuel - self.elapsed_time);
+		}
+	}
+
+	/// The items the player is wearing.
+	pub fn equipment<'a>(&'a self) -> &'a equipment::Equipment { &self.equipment }
+	pub fn equipment_mut<'a>(&'a mut self) -> &'a mut equipment::Equipment { &mut self.equipment }
+
+	/// Restores the player to a previously saved position & condition.
+	/// Any motion or invincibility they had is discarded.
+	pub fn restore(
+		&mut self,
+		position: (units::Game, units::Game),
+		hit_points: units::HitPoint
+	) {
+		self.teleport(position);
+
+		self.hit_points = cmp::max(1, cmp::min(hit_points, MAX_HIT_POINTS));
+		self.invincible = false;
+		self.invincible_time = units::Millis(0);
+	}
+
+	/// Moves the player straight to `position`, stopping them in place.
+	pub fn teleport(&mut self, position: (units::Game, units::Game)) {
+		let (x, y) = position;
+		self.x = x;
+		self.y = y;
+		self.sprite_position = position;
+		self.velocity_x = units::Velocity(0.0);
+		self.velocity_y = units::Velocity(0.0);
+		self.on_ground = false;
+	}
+
+	pub fn position(&self) -> (units::Game, units::Game) { (self.x, self.y) }
+	pub fn movement(&self) -> MotionTup { self.movement }
+
+	/// A copy of the sprite for each movement, e.g: to draw a ghost of the player.
+	pub fn sprites(&self) -> HashMap<MotionTup, ~sprite::Updatable> { self.sprites.clone() }
+	pub fn velocity(&self) -> (units::Velocity, units::Velocity) { (self.velocity_x, self.velocity_y) }
+
+	/// The boxes, in map coordinates, which collide w/ tiles while moving
+	/// horizontally & vertically (respectively.)
+	pub fn collision_boxes(&self) -> (Rectangle, Rectangle) {
+		(Rectangle { x: self.x + self.x_box().left(), y: self.y + self.x_box().top(), width: self.x_box().width(), height: self.x_box().height() },
+		 Rectangle { x: self.x + self.y_box().left(), y: self.y + self.y_box().top(), width: self.y_box().width(), height: self.y_box().height() })
+	}
+
+	/// Responds true while the player is examining whatever they stand in front of.
+	pub fn is_interacting(&self) -> bool { self.is_interacting }
+
+	/// Responds true if the player touched down on the ground during the last update.
+	pub fn has_landed(&self) -> bool { self.landed }
+
+	pub fn hit_points(&self) -> units::HitPoint { self.hit_points }
+	pub fn max_hit_points(&self) -> units::HitPoint { MAX_HIT_POINTS }
+
+	/// Responds true once the player has run out of hit points.
+	pub fn is_dead(&self) -> bool {
+		self.hit_points <= 0
+	}
+
+	/// Starts a jump if the player is on the ground, resetting their jump
+	/// timer: for as long as jump is held (up to `JUMP_TIME`) they keep
+	/// rising at full speed, so a tap makes a short hop & holding a full jump.
+	///
+	/// A player jumping while climbing lets go of the ladder, & one
+	/// sliding down a wall jumps away from it.
+	///
+	/// `lag` is how long ago the jump was pressed: a player who walked off
+	/// a ledge only moments before it still jumps, while one who pressed it
+	/// just too early to land jumps as soon as they do.
+	pub fn start_jump(&mut self, audio: &audio::Audio, lag: units::Millis) {
 		self.is_jump_active = true;
 		self.is_interacting = false;
+		if self.stun_time > units::Millis(0) { return; }
 
-		if self.on_ground() {
-			self.velocity_y = -JUMP_SPEED;
+		let pressed_airborne_for = self.airborne_time - lag;
+		if self.on_ground() || self.climbing || (!self.jumped && pressed_airborne_for < COYOTE_TIME) {
+			self.jump(audio);
+		} else {
+			// a player sliding down a wall kicks off, away from it
+			match self.wall.take() {
+				Some(side) => {
+					self.jump(audio);
+					self.velocity_x = match side { sprite::West => MAX_VELOCITY_X, sprite::East => -MAX_VELOCITY_X };
+				}
+				None => { self.jump_buffer = JUMP_BUFFER_TIME - lag; }
+			}
 		}
 	}
 
+	// leaves the ground (or a ladder, or a wall) at full jump speed
+	fn jump(&mut self, audio: &audio::Audio) {
+		self.climbing = false;
+		self.jumped = true;
+		self.jumps += 1;
+		self.jump_time = JUMP_TIME;
+		audio.play_sfx_at(audio::Jump, (self.center_x(), self.center_y()));
+		self.velocity_y = -self.equipment.modifiers().jump_speed(JUMP_SPEED);
+	}
+
+	/// Climbs any ladder the player is in front of: upwards for a negative
+	/// `direction`, downwards for a positive one, holding still for `0`.
+	pub fn climb(&mut self, direction: int) {
+		self.climb_direction = direction;
+	}
+
+	/// Drops the player down through the one-way platform they stand on.
+	/// Does nothing while they are airborne.
+	pub fn drop_down(&mut self) {
+		if !self.on_ground() { return; }
+
+		self.drop_time = DROP_TIME;
+		self.on_ground = false;
+	}
+
 	/// A player will immediately cease their jump and become subject
 	/// to the effects of gravity.
 	///
-	/// While the player is in this state: their remaining `jump time` is
-	/// temporarily suspended.
+	/// Whatever remains of their `jump time` is forfeit: pressing jump
+	/// again in midair does not resume the jump.
 	pub fn stop_jump(&mut self) {
 		self.is_jump_active = false;
+		self.jump_buffer = units::Millis(0);
 	}
 
 	/// This is called to update the player's `movement` based on
@@ -456,8 +992,12 @@ impl Player {
 	pub fn current_motion(&mut self) {
 		let (_, last_facing, last_looking) = self.movement;
 
-		self.movement = if self.on_ground() {
-			if self.is_interacting {
+		self.movement = if self.climbing {
+			(sprite::Climbing, last_facing, last_looking)
+		} else if self.on_ground() {
+			if self.crouching && !self.is_interacting {
+				(sprite::Crouching, last_facing, last_looking)
+			} else if self.is_interacting {
 				(sprite::Interacting, last_facing, last_looking)
 			} else if self.accel_x == 0 {
 				(sprite::Standing, last_facing, last_looking)
@@ -465,7 +1005,9 @@ impl Player {
 				(sprite::Walking, last_facing, last_looking)
 			}	
 		} else {
-			if self.velocity_y < units::Velocity(0.0) {
+			if self.boosting.is_some() {
+				(sprite::Boosting, last_facing, last_looking)
+			} else if self.velocity_y < units::Velocity(0.0) {
 				(sprite::Jumping, last_facing, last_looking)
 			} else {
 				(sprite::Falling, last_facing, last_looking)
@@ -477,15 +1019,28 @@ impl Player {
 		self.x + (units::Tile(1).to_game() / units::Game(2.0))
 	}
 
+	pub fn center_y(&self) -> units::Game {
+		self.y + (units::Tile(1).to_game() / units::Game(2.0))
+	}
+
+	// the boxes which collide w/ tiles, shorter while crouching
+	fn x_box(&self) -> Rectangle {
+		if self.crouching { CROUCH_X_BOX } else { X_BOX }
+	}
+
+	fn y_box(&self) -> Rectangle {
+		if self.crouching { CROUCH_Y_BOX } else { Y_BOX }
+	}
+
 	// x-axis collision detection
 	fn left_collision(&self, delta: units::Game) -> Rectangle {
 		assert!(delta <= units::Game(0.0));
 
 		Rectangle {
-			x: self.x + (X_BOX.left() + delta),
-			y: self.y + X_BOX.top(),
-			width: (X_BOX.width() / units::Game(2.0)) - delta,
-			height: X_BOX.height()
+			x: self.x + (self.x_box().left() + delta),
+			y: self.y + self.x_box().top(),
+			width: (self.x_box().width() / units::Game(2.0)) - delta,
+			height: self.x_box().height()
 		}
 	}
 
@@ -494,10 +1049,10 @@ impl Player {
 		assert!(delta >= units::Game(0.0));
 		
 		Rectangle {
-			x: self.x + X_BOX.left() + (X_BOX.width() / units::Game(2.0)),
-			y: self.y + X_BOX.top(),
-			width: 	(X_BOX.width() / units::Game(2.0)) + delta,
-			height: X_BOX.height()
+			x: self.x + self.x_box().left() + (self.x_box().width() / units::Game(2.0)),
+			y: self.y + self.x_box().top(),
+			width: 	(self.x_box().width() / units::Game(2.0)) + delta,
+			height: self.x_box().height()
 		}
 	}
 
@@ -506,10 +1061,10 @@ impl Player {
 		assert!(delta <= units::Game(0.0));
 
 		Rectangle {
-			x: self.x + Y_BOX.left(),
-			y: self.y + (Y_BOX.top() + delta),
-			width: Y_BOX.width(),
-			height: (Y_BOX.height() / units::Game(2.0)) - delta
+			x: self.x + self.y_box().left(),
+			y: self.y + (self.y_box().top() + delta),
+			width: self.y_box().width(),
+			height: (self.y_box().height() / units::Game(2.0)) - delta
 		}
 	}
 
@@ -517,10 +1072,10 @@ impl Player {
 		assert!(delta >= units::Game(0.0));
 		
 		Rectangle {
-			x: self.x + Y_BOX.left(),
-			y: self.y + Y_BOX.top() + (Y_BOX.height() / units::Game(2.0)),
-			width: 	Y_BOX.width(),
-			height: (Y_BOX.height() / units::Game(2.0)) + delta
+			x: self.x + self.y_box().left(),
+			y: self.y + self.y_box().top() + (self.y_box().height() / units::Game(2.0)),
+			width: 	self.y_box().width(),
+			height: (self.y_box().height() / units::Game(2.0)) + delta
 		}
 	}
 	
@@ -531,3 +1086,114 @@ impl Player {
 		self.on_ground
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use game::audio;
+	use game::config;
+	use game::graphics;
+	use game::map;
+	use game::rng;
+	use game::units;
+	use game::units::{AsGame};
+
+	use super::{Player, COYOTE_TIME};
+
+	static STEP: units::Millis = units::Millis(16);
+	// enough steps for anything in these tests to come to rest
+	static MAX_STEPS: uint = 600;
+
+	fn headless() -> (graphics::Graphics, audio::Audio) {
+		let settings = config::Settings::default();
+		let backend = ~graphics::NullBackend::new(settings.resolution) as ~graphics::Backend;
+		(graphics::Graphics::with_backend(backend, &settings), audio::Audio::silent(rng::Rng::new(0)))
+	}
+
+	// a player dropped in midair at the tile (`col`, `row`) of the test map
+	fn player_at(display: &mut graphics::Graphics, col: uint, row: uint) -> Player {
+		Player::new(display, units::Tile(col).to_game(), units::Tile(row).to_game()).unwrap()
+	}
+
+	// steps `quote` until they stand on the ground, responding w/ how many steps that took
+	fn settle(quote: &mut Player, map: &map::Map, audio: &audio::Audio) -> uint {
+		for step in range(0, MAX_STEPS) {
+			if quote.on_ground() { return step; }
+			quote.update(STEP, map, audio);
+		}
+		fail!("the player never landed");
+	}
+
+	// walks `quote` (standing on the floating block of the test map) left
+	// until they step off of it
+	fn walk_off_ledge(quote: &mut Player, map: &map::Map, audio: &audio::Audio) {
+		quote.start_moving_left();
+		for _ in range(0, MAX_STEPS) {
+			quote.update(STEP, map, audio);
+			if !quote.on_ground() {
+				quote.stop_moving();
+				return;
+			}
+		}
+		fail!("the player never left the ledge");
+	}
+
+	fn is_rising(quote: &Player) -> bool { quote.velocity_y < units::Velocity(0.0) }
+
+	#[test]
+	fn jump_just_after_leaving_a_ledge() {
+		let (mut display, audio) = headless();
+		let map = map::Map::create_test_map(&mut display).unwrap();
+		let mut quote = player_at(&mut display, 2, 9);
+		settle(&mut quote, &map, &audio);
+
+		walk_off_ledge(&mut quote, &map, &audio);
+		assert!(quote.airborne_time < COYOTE_TIME);
+		quote.start_jump(&audio, units::Millis(0));
+		assert!(is_rising(&quote));
+	}
+
+	#[test]
+	fn no_jump_long_after_leaving_a_ledge() {
+		let (mut display, audio) = headless();
+		let map = map::Map::create_test_map(&mut display).unwrap();
+		let mut quote = player_at(&mut display, 2, 9);
+		settle(&mut quote, &map, &audio);
+
+		walk_off_ledge(&mut quote, &map, &audio);
+		while quote.airborne_time <= COYOTE_TIME { quote.update(STEP, &map, &audio); }
+		assert!(!quote.on_ground());
+		quote.start_jump(&audio, units::Millis(0));
+		assert!(!is_rising(&quote));
+	}
+
+	#[test]
+	fn jump_pressed_just_before_landing_fires_on_touchdown() {
+		let (mut display, audio) = headless();
+		let map = map::Map::create_test_map(&mut display).unwrap();
+		// (how long a fall from this height takes, so the press may be timed)
+		let fall = settle(&mut player_at(&mut display, 10, 5), &map, &audio);
+
+		let mut quote = player_at(&mut display, 10, 5);
+		for _ in range(0, fall - 3) { quote.update(STEP, &map, &audio); }
+		quote.start_jump(&audio, units::Millis(0));
+		assert!(!is_rising(&quote));
+
+		for _ in range(0, 3) { quote.update(STEP, &map, &audio); }
+		assert!(is_rising(&quote));
+	}
+
+	#[test]
+	fn stale_jump_press_does_not_fire_on_landing() {
+		let (mut display, audio) = headless();
+		let map = map::Map::create_test_map(&mut display).unwrap();
+		let fall = settle(&mut player_at(&mut display, 10, 5), &map, &audio);
+
+		let mut quote = player_at(&mut display, 10, 5);
+		for _ in range(0, fall - 12) { quote.update(STEP, &map, &audio); }
+		quote.start_jump(&audio, units::Millis(0));
+
+		for _ in range(0, 12) { quote.update(STEP, &map, &audio); }
+		assert!(quote.on_ground());
+		assert!(!is_rising(&quote));
+	}
+}
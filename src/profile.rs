@@ -0,0 +1,145 @@
+use std::cell::RefCell;
+use std::io;
+use std::io::File;
+use std::io::fs;
+
+use game::log;
+
+/// Holds one directory per profile, named for it, w/ its settings,
+/// bindings, saves & statistics inside.
+pub static PROFILES_DIR: &'static str = "profiles";
+
+/// The profile used until another is chosen, e.g: on the first run.
+pub static DEFAULT_PROFILE: &'static str = "default";
+
+// the name of the profile chosen last, so that it is chosen again next run
+static LAST_PROFILE_PATH: &'static str = "profiles/last.txt";
+
+// the files of each profile, relative to its directory
+static SETTINGS_FILE: &'static str 	= "settings.cfg";
+static BINDINGS_FILE: &'static str 	= "bindings.cfg";
+static STATS_FILE: &'static str 	= "stats.txt";
+static SAVES_DIR: &'static str 		= "saves";
+
+// where each file lived before there were profiles, & the file of the
+// default profile it becomes (the old saves directory held the stats too)
+static LEGACY_FILES: [(&'static str, &'static str), ..4] = [
+	("config/settings.cfg", SETTINGS_FILE),
+	("config/bindings.cfg", BINDINGS_FILE),
+	("saves/stats.txt", 	STATS_FILE),
+	("saves", 				SAVES_DIR)
+];
+
+struct Profile {
+	name: ~str
+}
+
+local_data_key!(PROFILE: RefCell<Profile>)
+
+/// Makes the profile chosen last (or else `DEFAULT_PROFILE`) the active one.
+///
+/// Until this is called every path is that of `DEFAULT_PROFILE`.
+///
+/// On the first run w/ profiles (when `PROFILES_DIR` does not exist yet)
+/// the settings, bindings, saves & statistics kept before there were
+/// profiles are moved into `DEFAULT_PROFILE`, so that none are lost.
+pub fn init() {
+	if !Path::new(PROFILES_DIR).exists() { migrate(); }
+
+	let last = match File::open(&Path::new(LAST_PROFILE_PATH)).read_to_str() {
+		Ok(contents) => contents.trim().to_owned(),
+		Err(_) => DEFAULT_PROFILE.to_owned()
+	};
+	let name = if is_valid_name(last.as_slice()) { last } else { DEFAULT_PROFILE.to_owned() };
+	PROFILE.replace(Some(RefCell::new(Profile { name: name })));
+}
+
+// moves every file kept before there were profiles into the default profile
+fn migrate() {
+	let dir = Path::new(PROFILES_DIR).join(DEFAULT_PROFILE);
+	if !LEGACY_FILES.iter().any(|&(old, _)| Path::new(old).exists()) { return; }
+	match fs::mkdir_recursive(&dir, io::UserDir) {
+		Ok(_) => {}
+		Err(msg) => { log::warn("profile", format!("could not create profile directory: {}", msg)); return; }
+	}
+
+	for &(old, new) in LEGACY_FILES.iter() {
+		let (from, to) = (Path::new(old), dir.join(new));
+		if !from.exists() { continue; }
+		match fs::rename(&from, &to) {
+			Ok(_) => log::info("profile", format!("moved {} into profile {}", old, DEFAULT_PROFILE)),
+			Err(msg) => log::warn("profile", format!("{} could not be moved into profile {}: {}", old, DEFAULT_PROFILE, msg))
+		}
+	}
+}
+
+/// Makes `name` the active profile, creating its directory if it has none,
+/// & remembers it as the profile chosen last. Responds false (keeping the
+/// active profile) if `name` is not a valid name (see `is_valid_name`.)
+pub fn activate(name: &str) -> bool {
+	if !is_valid_name(name) {
+		log::warn("profile", format!("ignoring invalid profile name `{}`", name));
+		return false;
+	}
+
+	match fs::mkdir_recursive(&Path::new(PROFILES_DIR).join(name), io::UserDir) {
+		Ok(_) => {}
+		Err(msg) => { log::warn("profile", format!("could not create profile directory: {}", msg)); return false; }
+	}
+	match File::create(&Path::new(LAST_PROFILE_PATH)).write_str(name) {
+		Ok(_) => {}
+		Err(msg) => { log::warn("profile", format!("the chosen profile could not be remembered: {}", msg)); }
+	}
+
+	match PROFILE.get() {
+		Some(profile) => { profile.get().borrow_mut().get().name = name.to_owned(); }
+		None => { PROFILE.replace(Some(RefCell::new(Profile { name: name.to_owned() }))); }
+	}
+	log::info("profile", format!("using profile {}", name));
+	true
+}
+
+/// The name of the active profile.
+pub fn name() -> ~str {
+	match PROFILE.get() {
+		Some(profile) => profile.get().borrow().get().name.clone(),
+		None => DEFAULT_PROFILE.to_owned()
+	}
+}
+
+/// The name of every profile w/ a directory in `PROFILES_DIR`, in order.
+pub fn list() -> ~[~str] {
+	let dir = Path::new(PROFILES_DIR);
+	if !dir.is_dir() { return ~[]; }
+
+	let mut names: ~[~str] = match fs::readdir(&dir) {
+		Ok(entries) => entries.iter()
+			.filter(|entry| entry.is_dir())
+			.filter_map(|entry| entry.filename_str().map(|name| name.to_owned()))
+			.filter(|name| is_valid_name(name.as_slice()))
+			.collect(),
+		Err(msg) => { log::warn("profile", format!("profiles could not be listed: {}", msg)); ~[] }
+	};
+	names.sort();
+	names
+}
+
+/// Responds true if `name` may name a profile: it must be made up only of
+/// letters, digits, `-` & `_`, so that it is a valid directory name.
+pub fn is_valid_name(name: &str) -> bool {
+	name.len() > 0 && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+}
+
+/// The paths of the active profile's settings & its key bindings.
+pub fn settings_path() -> ~str { path_of(SETTINGS_FILE) }
+pub fn bindings_path() -> ~str { path_of(BINDINGS_FILE) }
+
+/// The path of the active profile's statistics.
+pub fn stats_path() -> ~str { path_of(STATS_FILE) }
+
+/// The directory holding the active profile's save slots.
+pub fn saves_dir() -> ~str { path_of(SAVES_DIR) }
+
+fn path_of(file: &str) -> ~str {
+	format!("{}/{}/{}", PROFILES_DIR, name(), file)
+}
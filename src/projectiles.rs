@@ -0,0 +1,193 @@
+use game::graphics;
+use game::map;
+use game::sprite;
+
+use game::collisions::Rectangle;
+use game::entity;
+use game::entity::Entity;
+use game::error::GameResult;
+use game::units;
+use game::units::{AsGame};
+
+// collision boxes of shots traveling sideways & up or down
+// (expressed as `units::Game`)
+static COLLISION_BOX: Rectangle = Rectangle {
+	x: units::Game(8.0), y: units::Game(12.0),
+	width: units::Game(16.0), height: units::Game(8.0)
+};
+static VERTICAL_BOX: Rectangle = Rectangle {
+	x: units::Game(12.0), y: units::Game(8.0),
+	width: units::Game(8.0), height: units::Game(16.0)
+};
+
+/// How a kind of shot looks & behaves.
+pub struct Shot {
+	/// Where the shot is drawn from on `Bullet.bmp`.
+	sprite: (units::Tile, units::Tile),
+	speed: units::Velocity,
+	/// How far the shot travels before it fizzles out.
+	range: units::Tile,
+	damage: units::HitPoint,
+	/// How far from its center the shot's explosion reaches, if it explodes.
+	/// (An exploding shot deals its damage through the explosion alone.)
+	blast: Option<units::Game>
+}
+
+/// A single shot fired by the player.
+///
+/// Projectiles travel in a straight line until they strike a wall,
+/// an enemy, or exceed their maximum range; only force regions
+/// (e.g: wind) bend their path.
+#[deriving(Clone)]
+pub struct Projectile {
+	priv x: units::Game,
+	priv y: units::Game,
+	// where the projectile was before its last update
+	priv previous: (units::Game, units::Game),
+	priv velocity_x: units::Velocity,
+	priv velocity_y: units::Velocity,
+	priv vertical: bool,
+	priv distance_traveled: units::Game,
+	priv range: units::Game,
+	priv damage: units::HitPoint,
+	priv blast: Option<units::Game>,
+	priv alive: bool,
+	priv struck_wall: bool,
+
+	priv sprite: ~sprite::Updatable
+}
+
+impl Projectile {
+	/// Fires a new `shot`, centered on `origin`, traveling along `aim`: up
+	/// or down if it is looking either way, else towards where it faces.
+	///
+	/// The shot drifts off to one side at `drift` as it goes: downwards
+	/// (or upwards, if `drift` is negative) for a shot fired sideways,
+	/// rightwards (or leftwards) for one fired up or down.
+	pub fn new(
+		graphics: &mut graphics::Graphics,
+		origin: (units::Game, units::Game),
+		aim: (sprite::Facing, sprite::Looking),
+		drift: units::Velocity,
+		shot: &Shot
+	) -> GameResult<Projectile> {
+		let (center_x, center_y) = origin;
+		let half_tile = units::Tile(1).to_game() / units::Game(2.0);
+		let mut sprite = try!(sprite::Sprite::new(
+			graphics,
+			(center_x - half_tile, center_y - half_tile),
+			shot.sprite,
+			(units::Tile(1), units::Tile(1)),
+			~"assets/base/Bullet.bmp"
+		));
+
+		// the sheet's shots point sideways, so vertical ones are turned to point along their path
+		let (facing, looking) = aim;
+		let (velocity_x, velocity_y) = match looking {
+			sprite::Up 			=> { sprite.set_rotation(units::Degrees(-90.0)); (drift, -shot.speed) }
+			sprite::Down 		=> { sprite.set_rotation(units::Degrees(90.0)); (drift, shot.speed) }
+			sprite::Horizontal 	=> match facing {
+				sprite::West => (-shot.speed, drift),
+				sprite::East => ( shot.speed, drift)
+			}
+		};
+
+		Ok(Projectile {
+			x: center_x - half_tile,
+			y: center_y - half_tile,
+			previous: (center_x - half_tile, center_y - half_tile),
+			velocity_x: velocity_x,
+			velocity_y: velocity_y,
+			vertical: looking != sprite::Horizontal,
+			distance_traveled: units::Game(0.0),
+			range: shot.range.to_game(),
+			damage: shot.damage,
+			blast: shot.blast,
+			alive: true,
+			struck_wall: false,
+
+			sprite: ~sprite as ~sprite::Updatable
+		})
+	}
+
+	/// The damage dealt to whatever this projectile strikes.
+	pub fn damage(&self) -> units::HitPoint { self.damage }
+
+	/// Destroys the projectile, e.g: after it has struck an enemy.
+	pub fn kill(&mut self) { self.alive = false; }
+
+	/// Responds true if this projectile was destroyed by running into a wall.
+	pub fn struck_wall(&self) -> bool { self.struck_wall }
+
+	/// Responds true if this projectile explodes once it is destroyed.
+	pub fn is_explosive(&self) -> bool { self.blast.is_some() }
+
+	/// The area its explosion damages, once an explosive projectile has
+	/// been destroyed (however it was.) Responds `None` for any other.
+	pub fn explosion(&self) -> Option<Rectangle> {
+		if self.alive { return None; }
+		self.blast.map(|radius| {
+			let hitbox = self.hitbox();
+			let center_x = hitbox.left() + (hitbox.width() / units::Game(2.0));
+			let center_y = hitbox.top() + (hitbox.height() / units::Game(2.0));
+			Rectangle {
+				x: center_x - radius, y: center_y - radius,
+				width: radius * units::Game(2.0), height: radius * units::Game(2.0)
+			}
+		})
+	}
+}
+
+impl Entity for Projectile {
+	/// Moves the projectile along its path; it is destroyed upon
+	/// striking a wall or leaving the map.
+	#[allow(unused_variable)]
+	fn update(
+		&mut self,
+		elapsed_time: units::Millis,
+		player: (units::Game, units::Game),
+		map: &map::Map
+	) {
+		self.previous = (self.x, self.y);
+		let (force_x, force_y) = map.force_at(&self.hitbox());
+		self.velocity_x = self.velocity_x + (force_x * elapsed_time);
+		self.velocity_y = self.velocity_y + (force_y * elapsed_time);
+
+		// range is measured along the direction the shot was fired in
+		let (delta_x, delta_y) = (self.velocity_x * elapsed_time, self.velocity_y * elapsed_time);
+		self.x = self.x + delta_x;
+		self.y = self.y + delta_y;
+		let delta = if self.vertical { delta_y } else { delta_x };
+		self.distance_traveled = self.distance_traveled 
+			+ if delta < units::Game(0.0) { units::Game(0.0) - delta } else { delta };
+
+		if self.distance_traveled > self.range {
+			self.alive = false;
+		}
+
+		if map.is_colliding(&self.hitbox()) {
+			self.alive = false;
+			self.struck_wall = true;
+		}
+
+		self.sprite.update(elapsed_time);
+		self.sprite.set_position((self.x, self.y));
+	}
+
+	fn draw(&self, queue: &mut graphics::RenderQueue, offset: (units::Game, units::Game), alpha: f64) {
+		self.sprite.draw_queued(queue, graphics::Entities, entity::blend(offset, self.previous, (self.x, self.y), alpha));
+	}
+
+	/// The area in which this projectile strikes walls & enemies.
+	fn hitbox(&self) -> Rectangle {
+		let collision_box = if self.vertical { &VERTICAL_BOX } else { &COLLISION_BOX };
+		Rectangle {
+			x: self.x + collision_box.left(), y: self.y + collision_box.top(),
+			width: collision_box.width(), height: collision_box.height()
+		}
+	}
+
+	fn kind(&self) -> entity::Kind { entity::Projectile }
+
+	fn is_alive(&self) -> bool { self.alive }
+}
@@ -0,0 +1,69 @@
+use std::cell::RefCell;
+use std::rand;
+use std::rc::Rc;
+
+// mixed into the seed so that no half of the generator's state is ever all zeros
+static SEED_MIX: u32 = 0x9e3779b9;
+
+/// The game's single source of randomness (e.g: for loot drops, particles
+/// & camera shake), so that a session may be played out again from its seed.
+/// The seed is logged, shown by the debug overlay, & saved w/ each ghost
+/// (see `ghost::Recorder`.)
+///
+/// `Game` seeds it once, then hands a clone to each system which needs
+/// randomness; every clone draws from the same sequence. Use it w/ the
+/// methods of `std::rand::Rng`, e.g: `rng.gen_range(0, 10)`.
+#[deriving(Clone)]
+pub struct Rng {
+	priv seed: u64,
+	priv state: Rc<RefCell<State>>
+}
+
+/// Where a sequence is at (an xorshift generator's state), so that it
+/// can be rewound to draw the same numbers again, e.g: by a savestate.
+#[deriving(Clone)]
+pub struct State {
+	priv x: u32,
+	priv y: u32,
+	priv z: u32,
+	priv w: u32
+}
+
+impl Rng {
+	/// Starts the sequence drawn from `seed`; the same seed always draws the same sequence.
+	pub fn new(seed: u64) -> Rng {
+		let (low, high) = (seed as u32, (seed >> 32) as u32);
+		let state = State { x: low, y: high, z: low ^ SEED_MIX, w: high ^ SEED_MIX | 1 };
+		Rng { seed: seed, state: Rc::new(RefCell::new(state)) }
+	}
+
+	/// Starts a sequence from a seed of its own, e.g: when none was asked for.
+	pub fn unseeded() -> Rng {
+		Rng::new(rand::random())
+	}
+
+	/// The seed this sequence was started from, e.g: to reproduce a bug.
+	/// (Anything which records a session to be played back must record it too.)
+	pub fn seed(&self) -> u64 { self.seed }
+
+	/// Where the sequence is at; every clone shares it.
+	pub fn state(&self) -> State { self.state.borrow().borrow().get().clone() }
+
+	/// Rewinds (or winds) the sequence to `state`, for every clone.
+	pub fn restore(&self, state: State) {
+		*self.state.borrow().borrow_mut().get() = state;
+	}
+}
+
+impl rand::Rng for Rng {
+	fn next_u32(&mut self) -> u32 {
+		let mut state = self.state.borrow().borrow_mut();
+		let state = state.get();
+		let t = state.x ^ (state.x << 11);
+		state.x = state.y;
+		state.y = state.z;
+		state.z = state.w;
+		state.w = state.w ^ (state.w >> 19) ^ (t ^ (t >> 8));
+		state.w
+	}
+}
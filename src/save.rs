@@ -0,0 +1,179 @@
+use std::io;
+use std::io::File;
+use std::io::fs;
+
+use game::log;
+use game::profile;
+use game::units;
+
+/// The slot used by the pause menu & the title screen.
+pub static QUICK_SLOT: uint = 0;
+
+/// A snapshot of the player's progress through the game.
+///
+/// Only what is needed to rebuild the world is kept: everything else
+/// (enemies, pickups, the camera) starts afresh when a save is loaded.
+#[deriving(Clone)]
+pub struct SaveData {
+	/// The file the current map was loaded from.
+	map_path: ~str,
+	position: (units::Game, units::Game),
+	hit_points: units::HitPoint,
+
+	/// The level & energy of each of the player's weapons, in order,
+	/// & which of them is at hand.
+	weapons: ~[(uint, uint)],
+	current_weapon: uint,
+
+	/// Ids of the items the player has collected, & of those worn.
+	items: ~[uint],
+	equipment: ~[uint],
+	/// Ids of every event flag which has been set.
+	flags: ~[uint],
+	/// The cells of each map the player has seen (see `exploration::Exploration`.)
+	explored: ~[(~str, ~[(uint, uint)])]
+}
+
+/// The file which backs save slot `slot` (of the active profile.)
+pub fn slot_path(slot: uint) -> Path {
+	Path::new(format!("{}/slot{}.sav", profile::saves_dir(), slot))
+}
+
+/// Responds true if something has been saved to `slot`.
+pub fn slot_exists(slot: uint) -> bool {
+	slot_path(slot).exists()
+}
+
+/// Writes `data` to save slot `slot`, replacing whatever it held.
+///
+/// Each line of a save is a field name followed by its values, e.g:
+/// `position 320 240` or `flags 1 4 9`. (There is a `weapon` line for each
+/// of the player's weapons, & an `explored` line of `col,row` cells for
+/// each map they have seen.)
+pub fn save_to_slot(slot: uint, data: &SaveData) -> bool {
+	let file_path = slot_path(slot);
+	match fs::mkdir_recursive(&file_path.dir_path(), io::UserDir) {
+		Ok(_) => {}
+		Err(msg) => { log::warn("save", format!("could not create save directory: {}", msg)); return false; }
+	}
+
+	let (units::Game(x), units::Game(y)) = data.position;
+
+	let mut contents = ~"";
+	contents.push_str(format!("map {}\n", data.map_path));
+	contents.push_str(format!("position {} {}\n", x, y));
+	contents.push_str(format!("hit_points {}\n", data.hit_points));
+	for &(level, energy) in data.weapons.iter() {
+		contents.push_str(format!("weapon {} {}\n", level, energy));
+	}
+	contents.push_str(format!("current_weapon {}\n", data.current_weapon));
+	contents.push_str(format!("items{}\n", join_ids(data.items.as_slice())));
+	contents.push_str(format!("equipment{}\n", join_ids(data.equipment.as_slice())));
+	contents.push_str(format!("flags{}\n", join_ids(data.flags.as_slice())));
+	for &(ref path, ref cells) in data.explored.iter() {
+		contents.push_str(format!("explored {}", *path));
+		for &(col, row) in cells.iter() {
+			contents.push_str(format!(" {},{}", col, row));
+		}
+		contents.push_str("\n");
+	}
+
+	match File::create(&file_path).write_str(contents) {
+		Ok(_) => true,
+		Err(msg) => { log::warn("save", format!("game could not be saved: {}", msg)); false }
+	}
+}
+
+/// Reads back the progress saved to slot `slot`.
+///
+/// Responds `None` if the slot is empty or its save is unreadable.
+pub fn load_from_slot(slot: uint) -> Option<SaveData> {
+	let file_path = slot_path(slot);
+	if !file_path.exists() { return None; }
+
+	let contents = match File::open(&file_path).read_to_str() {
+		Ok(contents) => contents,
+		Err(msg) => { log::warn("save", format!("game could not be loaded: {}", msg)); return None; }
+	};
+
+	let mut map_path 	= None;
+	let mut position 	= None;
+	let mut hit_points 	= None;
+	let mut weapons 	= ~[];
+	let mut current_weapon = 0;
+	let mut items 		= ~[];
+	let mut equipment 	= ~[];
+	let mut flags 		= ~[];
+	let mut explored 	= ~[];
+
+	for raw_line in contents.lines() {
+		let line = raw_line.trim();
+		if line.len() == 0 { continue; }
+
+		let words: ~[&str] = line.words().collect();
+		match words.as_slice() {
+			["map", path] => { map_path = Some(path.to_owned()); }
+			["position", x, y] => {
+				position = match (from_str::<f64>(x), from_str::<f64>(y)) {
+					(Some(x), Some(y)) => Some((units::Game(x), units::Game(y))),
+					_ => None
+				};
+			}
+			["hit_points", hp] => { hit_points = from_str::<units::HitPoint>(hp); }
+			["weapon", level, energy] => {
+				match (from_str::<uint>(level), from_str::<uint>(energy)) {
+					(Some(level), Some(energy)) => { weapons.push((level, energy)); }
+					_ => {}
+				}
+			}
+			["current_weapon", index] => { current_weapon = from_str::<uint>(index).unwrap_or(0); }
+			["items", ..ids] => { items = parse_ids(ids); }
+			["equipment", ..ids] => { equipment = parse_ids(ids); }
+			["flags", ..ids] => { flags = parse_ids(ids); }
+			["explored", path, ..cells] => { explored.push((path.to_owned(), parse_cells(cells))); }
+			_ => { log::warn("save", format!("slot {}: ignoring malformed line `{}`", slot, line)); }
+		}
+	}
+
+	match (map_path, position, hit_points) {
+		(Some(map_path), Some(position), Some(hit_points)) => Some(SaveData {
+			map_path: map_path,
+			position: position,
+			hit_points: hit_points,
+			weapons: weapons,
+			current_weapon: current_weapon,
+			items: items,
+			equipment: equipment,
+			flags: flags,
+			explored: explored
+		}),
+		_ => { log::warn("save", format!("slot {}: save is incomplete", slot)); None }
+	}
+}
+
+// formats `ids` as a list of space-prefixed numbers
+fn join_ids(ids: &[uint]) -> ~str {
+	let mut joined = ~"";
+	for id in ids.iter() {
+		joined.push_str(format!(" {}", *id));
+	}
+	joined
+}
+
+fn parse_ids(words: &[&str]) -> ~[uint] {
+	words.iter().filter_map(|word| from_str::<uint>(*word)).collect()
+}
+
+// parses `col,row` pairs, skipping any which are malformed
+fn parse_cells(words: &[&str]) -> ~[(uint, uint)] {
+	words.iter().filter_map(|word| {
+		let parts: ~[&str] = word.split(',').collect();
+		match parts.as_slice() {
+			[col, row] => match (from_str::<uint>(col), from_str::<uint>(row)) {
+				(Some(col), Some(row)) => Some((col, row)),
+				_ => None
+			},
+			_ => None
+		}
+	}).collect()
+}
@@ -0,0 +1,218 @@
+use std::io::File;
+
+use collections::hashmap::HashMap;
+
+use game::graphics::transitions;
+use game::units;
+use game::vfs;
+
+use game::error::{GameResult, ScriptError};
+
+/// Something moved about by a cutscene: the player, or the npc listed
+/// `n`th (counting from 1) in the map file.
+#[deriving(Eq,Clone)]
+pub enum Actor {
+	Player,
+	Npc(uint)
+}
+
+/// A single instruction in an event script.
+#[deriving(Clone)]
+pub enum Command {
+	/// `<MSG text`: shows `text`, then waits for the player to continue.
+	/// (`<MSG @key` shows the text of `key` in the current language.)
+	Message(~str),
+	/// `<MOV col row`: places the player on a tile.
+	MovePlayer(units::Tile, units::Tile),
+	/// `<IT+ id`: gives the player an item.
+	GiveItem(uint),
+	/// `<EQ+ id` & `<EQ- id`: puts on or takes off an item of equipment.
+	Equip(uint),
+	Unequip(uint),
+	/// `<FL+ id` & `<FL- id`: sets or clears an event flag.
+	SetFlag(uint),
+	ClearFlag(uint),
+	/// `<TRA map spawn [style]`: moves the player to a spawn point on another
+	/// map, w/ a transition of `style` (`fade`, `wipe` or `iris`; `fade` if omitted.)
+	ChangeMap(~str, ~str, transitions::Style),
+	/// `<QUA millis`: shakes the screen.
+	Shake(units::Millis),
+	/// `<CUT`: plays the rest of the event as a cutscene, which the player
+	/// can not move during (though they may skip it.)
+	BeginCutscene,
+	/// `<WAI millis`: does nothing for a while.
+	Wait(units::Millis),
+	/// `<WLK actor col`: walks an actor (`player`, or an npc's number) to
+	/// a column, waiting until they reach it.
+	WalkTo(Actor, units::Tile),
+	/// `<CAM col row millis`: pans the camera over to center on a tile,
+	/// waiting until it gets there. It stays until `<FOL` (or the event ends.)
+	PanCamera(units::Tile, units::Tile, units::Millis),
+	/// `<FOL`: has the camera follow the player again.
+	FollowPlayer,
+	/// `<FAO style millis` & `<FAI style millis`: covers the screen in black
+	/// (or reveals it again) w/ a transition, waiting until it is done.
+	/// A screen left covered is revealed once the event ends.
+	FadeScreen(transitions::Style, transitions::Direction, units::Millis),
+	/// `<FIN`: finishes a speedrun, stopping its timer & saving its times.
+	FinishRun,
+	/// `<END`: stops the event.
+	End
+}
+
+/// The events of a map's script file, each a list of commands.
+///
+/// Scripts are written in a small TSC-like language: `#<id>` begins
+/// an event, and each following line holds one `<CMD args` command.
+/// Lines starting w/ `//` are ignored.
+pub struct Script {
+	priv events: HashMap<uint, ~[Command]>
+}
+
+impl Script {
+	/// Reads the script at `path`, failing w/ a `ScriptError` if it can not be
+	/// read or holds a line it does not understand.
+	pub fn load_from_file(path: ~str) -> GameResult<Script> {
+		let contents = match File::open(&vfs::resolve(path.as_slice())).read_to_str() {
+			Ok(contents) => contents,
+			Err(msg) => { return Err(ScriptError(path, format!("{}", msg))); }
+		};
+
+		let mut events = HashMap::new();
+		let mut current: Option<uint> = None;
+
+		for raw_line in contents.lines() {
+			let line = raw_line.trim();
+			if line.len() == 0 || line.starts_with("//") { continue; }
+
+			if line.starts_with("#") {
+				let id = try!(Script::parse_uint(line.slice_from(1), &path));
+				events.insert(id, ~[]);
+				current = Some(id);
+				continue;
+			}
+
+			let id = match current {
+				Some(id) => id,
+				None => { return Err(ScriptError(path.clone(), format!("command `{}` is outside of any event", line))); }
+			};
+			events.get_mut(&id).push(try!(Script::parse_command(line, &path)));
+		}
+
+		Ok(Script { events: events })
+	}
+
+	/// Starts running event `id`, if this script has such an event.
+	pub fn run(&self, id: uint) -> Option<Runner> {
+		self.events.find(&id).map(|commands| Runner::new(commands.clone()))
+	}
+
+	fn parse_command(line: &str, path: &~str) -> GameResult<Command> {
+		let (name, args) = match line.find(' ') {
+			Some(split) => (line.slice_to(split), line.slice_from(split + 1).trim()),
+			None => (line, "")
+		};
+		let words: ~[&str] = args.words().collect();
+
+		Ok(match (name, words.as_slice()) {
+			("<MSG", _) 			=> Message(args.to_owned()),
+			("<MOV", [col, row]) 	=> MovePlayer(
+				units::Tile(try!(Script::parse_uint(col, path))),
+				units::Tile(try!(Script::parse_uint(row, path)))
+			),
+			("<IT+", [id]) 			=> GiveItem(try!(Script::parse_uint(id, path))),
+			("<EQ+", [id]) 			=> Equip(try!(Script::parse_uint(id, path))),
+			("<EQ-", [id]) 			=> Unequip(try!(Script::parse_uint(id, path))),
+			("<FL+", [id]) 			=> SetFlag(try!(Script::parse_uint(id, path))),
+			("<FL-", [id]) 			=> ClearFlag(try!(Script::parse_uint(id, path))),
+			("<TRA", [map, spawn]) 	=> ChangeMap(map.to_owned(), spawn.to_owned(), transitions::Fade),
+			("<TRA", [map, spawn, style]) => ChangeMap(map.to_owned(), spawn.to_owned(), try!(Script::parse_style(style, path))),
+			("<QUA", [time]) 		=> Shake(units::Millis(try!(Script::parse_uint(time, path)) as int)),
+			("<CUT", []) 			=> BeginCutscene,
+			("<WAI", [time]) 		=> Wait(units::Millis(try!(Script::parse_uint(time, path)) as int)),
+			("<WLK", ["player", col]) => WalkTo(Player, units::Tile(try!(Script::parse_uint(col, path)))),
+			("<WLK", [npc, col]) 	=> WalkTo(
+				Npc(try!(Script::parse_uint(npc, path))),
+				units::Tile(try!(Script::parse_uint(col, path)))
+			),
+			("<CAM", [col, row, time]) => PanCamera(
+				units::Tile(try!(Script::parse_uint(col, path))),
+				units::Tile(try!(Script::parse_uint(row, path))),
+				units::Millis(try!(Script::parse_uint(time, path)) as int)
+			),
+			("<FOL", []) 			=> FollowPlayer,
+			("<FAO", [style, time]) => FadeScreen(
+				try!(Script::parse_style(style, path)), transitions::Out,
+				units::Millis(try!(Script::parse_uint(time, path)) as int)
+			),
+			("<FAI", [style, time]) => FadeScreen(
+				try!(Script::parse_style(style, path)), transitions::In,
+				units::Millis(try!(Script::parse_uint(time, path)) as int)
+			),
+			("<FIN", []) 			=> FinishRun,
+			("<END", []) 			=> End,
+			_ => { return Err(ScriptError(path.clone(), format!("unknown command `{}`", line))); }
+		})
+	}
+
+	fn parse_style(word: &str, path: &~str) -> GameResult<transitions::Style> {
+		match transitions::Style::from_name(word) {
+			Some(style) => Ok(style),
+			None => Err(ScriptError(path.clone(), format!("unknown transition `{}`", word)))
+		}
+	}
+
+	fn parse_uint(word: &str, path: &~str) -> GameResult<uint> {
+		match from_str::<uint>(word.trim()) {
+			Some(value) => Ok(value),
+			None => Err(ScriptError(path.clone(), format!("expected a number, found `{}`", word)))
+		}
+	}
+}
+
+/// Steps through the commands of a single running event.
+///
+/// The runner hands commands out one at a time; after any command which
+/// takes time to play out (a `Message`, `Wait`, `WalkTo`, `PanCamera` or `FadeScreen`)
+/// it waits until `resume()` is called, e.g: once the player has read it.
+pub struct Runner {
+	priv commands: ~[Command],
+	priv next: uint,
+	priv waiting: bool
+}
+
+impl Runner {
+	fn new(commands: ~[Command]) -> Runner {
+		Runner { commands: commands, next: 0, waiting: false }
+	}
+
+	/// The next command to carry out, or `None` if the runner is waiting
+	/// or the event has finished.
+	pub fn step(&mut self) -> Option<Command> {
+		if self.waiting || self.is_finished() { return None; }
+
+		let command = self.commands[self.next].clone();
+		self.next += 1;
+
+		match command {
+			Message(_) | Wait(_) | WalkTo(_, _) | PanCamera(_, _, _) | FadeScreen(_, _, _) => {
+				self.waiting = true;
+			}
+			End 		=> { self.next = self.commands.len(); }
+			_ => {}
+		}
+
+		Some(command)
+	}
+
+	/// Continues past the command currently being waited on.
+	pub fn resume(&mut self) {
+		self.waiting = false;
+	}
+
+	pub fn is_waiting(&self) -> bool { self.waiting }
+
+	pub fn is_finished(&self) -> bool {
+		!self.waiting && self.next >= self.commands.len()
+	}
+}
@@ -0,0 +1,232 @@
+use std::cell::RefCell;
+use std::io;
+use std::io::File;
+use std::io::fs;
+use std::rc::Rc;
+use time;
+
+use sdl2::sdl;
+
+use game;
+use game::graphics;
+use game::log;
+
+use game::error::GameResult;
+use game::units;
+
+/// Where the times of each finished run are saved, each named for when it finished.
+pub static RUNS_DIR: &'static str = "speedruns";
+
+// the timer sits in the bottom-right corner, right-aligned
+static TEXT_MARGIN: units::Game = units::Game(8.0);
+
+// the time each map of a run was left at
+struct Split {
+	map: ~str,
+	real_time: units::Millis,
+	game_time: units::Millis
+}
+
+struct Run {
+	enabled: bool,
+	// whether loading a map stops the real-time clock
+	pause_loads: bool,
+	// when the run started, & when it finished (if it has)
+	started_at: Option<units::Millis>,
+	finished_at: Option<units::Millis>,
+	// when the map being loaded started loading, & how long all loads have taken
+	loading_since: Option<units::Millis>,
+	excluded: units::Millis,
+	game_time: units::Millis,
+	splits: ~[Split]
+}
+
+/// Times a run of the game, from starting a new game to its end, both in
+/// real time & in game time (which leaves out menus & anything else
+/// pausing the game), w/ a split each time the player leaves a map.
+///
+/// The timer is shared: `Game` draws it over everything else, while the
+/// states of a run report when it starts, splits & finishes. Once finished
+/// its times are saved to `RUNS_DIR`. A disabled timer ignores everything.
+#[deriving(Clone)]
+pub struct Timer {
+	priv run: Rc<RefCell<Run>>
+}
+
+impl Timer {
+	/// A timer, which only times anything if `enabled`; if `pause_loads`
+	/// is set the time spent loading maps is left out of its real time.
+	pub fn new(enabled: bool, pause_loads: bool) -> Timer {
+		Timer { run: Rc::new(RefCell::new(Run {
+			enabled: enabled,
+			pause_loads: pause_loads,
+			started_at: None,
+			finished_at: None,
+			loading_since: None,
+			excluded: units::Millis(0),
+			game_time: units::Millis(0),
+			splits: ~[]
+		})) }
+	}
+
+	pub fn is_enabled(&self) -> bool { self.run.borrow().borrow().get().enabled }
+
+	/// Starts a new run, discarding any other.
+	pub fn start(&self) {
+		let mut run = self.run.borrow().borrow_mut();
+		let run = run.get();
+		if !run.enabled { return; }
+		run.started_at = Some(Timer::now());
+		run.finished_at = None;
+		run.loading_since = None;
+		run.excluded = units::Millis(0);
+		run.game_time = units::Millis(0);
+		run.splits = ~[];
+	}
+
+	/// Discards the current run, e.g: once a saved game is continued
+	/// (which can not count as a run.)
+	pub fn reset(&self) {
+		let mut run = self.run.borrow().borrow_mut();
+		run.get().started_at = None;
+		run.get().finished_at = None;
+	}
+
+	/// Responds true while a run is being timed.
+	pub fn is_running(&self) -> bool {
+		let run = self.run.borrow().borrow();
+		run.get().started_at.is_some() && run.get().finished_at.is_none()
+	}
+
+	/// Counts `elapsed_time` of the game being played towards the game time.
+	pub fn tick(&self, elapsed_time: units::Millis) {
+		if !self.is_running() { return; }
+		let mut run = self.run.borrow().borrow_mut();
+		run.get().game_time = run.get().game_time + elapsed_time;
+	}
+
+	/// Marks the start & end of loading a map.
+	pub fn begin_load(&self) {
+		if !self.is_running() { return; }
+		let mut run = self.run.borrow().borrow_mut();
+		if run.get().pause_loads { run.get().loading_since = Some(Timer::now()); }
+	}
+
+	pub fn end_load(&self) {
+		let mut run = self.run.borrow().borrow_mut();
+		match run.get().loading_since.take() {
+			Some(since) => { run.get().excluded = run.get().excluded + (Timer::now() - since); }
+			None => {}
+		}
+	}
+
+	/// Records a split as the player leaves the map at `map`, named for its file.
+	pub fn split(&self, map: &str) {
+		if !self.is_running() { return; }
+		let (real_time, game_time) = self.times().unwrap();
+		let name = Path::new(map).filestem_str().unwrap_or(map).to_owned();
+		let mut run = self.run.borrow().borrow_mut();
+		run.get().splits.push(Split { map: name, real_time: real_time, game_time: game_time });
+	}
+
+	/// Finishes the run on the map at `map` (its last split), & saves its times.
+	pub fn finish(&self, map: &str) {
+		if !self.is_running() { return; }
+		self.split(map);
+		{
+			let mut run = self.run.borrow().borrow_mut();
+			run.get().finished_at = Some(Timer::now());
+		}
+		self.export();
+	}
+
+	/// The real time & game time of the current (or finished) run, if any.
+	pub fn times(&self) -> Option<(units::Millis, units::Millis)> {
+		let run = self.run.borrow().borrow();
+		let run = run.get();
+		run.started_at.map(|started_at| {
+			let until = run.finished_at.unwrap_or_else(|| Timer::now());
+			// a load still underway is excluded as it goes
+			let loading = match run.loading_since {
+				Some(since) => until - since,
+				None => units::Millis(0)
+			};
+			(until - started_at - run.excluded - loading, run.game_time)
+		})
+	}
+
+	// writes the run's splits & final times to a new file in `RUNS_DIR`
+	fn export(&self) {
+		let dir = Path::new(RUNS_DIR);
+		match fs::mkdir_recursive(&dir, io::UserDir) {
+			Ok(_) => {}
+			Err(msg) => { log::warn("speedrun", format!("could not create runs directory: {}", msg)); return; }
+		}
+
+		let mut contents = ~"# map real_time game_time\n";
+		let run = self.run.borrow().borrow();
+		for split in run.get().splits.iter() {
+			contents.push_str(format!("{} {} {}\n", split.map, format_time(split.real_time), format_time(split.game_time)));
+		}
+		match self.times() {
+			Some((real_time, game_time)) => contents.push_str(format!("final {} {}\n", format_time(real_time), format_time(game_time))),
+			None => {}
+		}
+
+		let path = dir.join(format!("{}.txt", time::now().strftime("%Y-%m-%d_%H-%M-%S")));
+		match File::create(&path).write_str(contents) {
+			Ok(_) => log::info("speedrun", format!("run saved to {}", path.display())),
+			Err(msg) => log::warn("speedrun", format!("run could not be saved: {}", msg))
+		}
+	}
+
+	fn now() -> units::Millis {
+		units::Millis(sdl::get_ticks() as int)
+	}
+}
+
+/// Draws a `Timer`'s times over the game, in the bottom-right corner.
+pub struct Overlay {
+	priv font: graphics::Font,
+	priv timer: Timer
+}
+
+impl Overlay {
+	pub fn new(display: &mut graphics::Graphics, timer: Timer) -> GameResult<Overlay> {
+		Ok(Overlay {
+			font: try!(graphics::Font::new(display, game::FONT_PATH.to_owned(), game::FONT_SIZE)),
+			timer: timer
+		})
+	}
+
+	/// Draws the real time above the game time, the latter followed by the last split (if any.)
+	pub fn draw(&self, display: &graphics::Graphics) {
+		let (real_time, game_time) = match self.timer.times() {
+			Some(times) => times,
+			None => return
+		};
+		let mut lines = ~[format!("RTA {}", format_time(real_time)), format!("IGT {}", format_time(game_time))];
+		{
+			let run = self.timer.run.borrow().borrow();
+			match run.get().splits.last() {
+				Some(split) => lines.push(format!("{} {}", split.map, format_time(split.real_time))),
+				None => {}
+			}
+		}
+
+		let (screen_w, screen_h) = display.resolution().to_game();
+		let mut y = screen_h - TEXT_MARGIN - (self.font.line_height() * units::Game(lines.len() as f64));
+		for line in lines.iter() {
+			let x = screen_w - TEXT_MARGIN - self.font.text_width(line.as_slice());
+			self.font.draw_text(display, x, y, line.as_slice());
+			y = y + self.font.line_height();
+		}
+	}
+}
+
+/// `time` as minutes, seconds & hundredths, e.g: `12:04.37`.
+pub fn format_time(time: units::Millis) -> ~str {
+	let units::Millis(ms) = time;
+	let ms = if ms < 0 { 0 } else { ms };
+	format!("{}:{:02d}.{:02d}", ms / 60000, (ms / 1000) % 60, (ms % 1000) / 10)
+}
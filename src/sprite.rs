@@ -1,31 +1,68 @@
 use sdl2::rect;
-use sdl2::render;
+use std::cmp;
 
-use sync::Arc;
+use game::aseprite;
 use game::graphics;
 
+use game::error::GameResult;
 use game::units;
-use game::units::{AsPixel};
+use game::units::{AsGame,AsPixel};
 
-#[deriving(Hash,Eq)]
+#[deriving(Hash,Eq,Clone)]
 pub enum Motion {
 	Walking,
 	Standing,
 	Interacting,
 	Jumping,
-	Falling
+	Falling,
+	Climbing,
+	Boosting,
+	Crouching
+}
+pub static MOTIONS: [Motion, ..8] = [Walking, Standing, Interacting, Jumping, Falling, Climbing, Boosting, Crouching];
+
+impl Motion {
+	/// The name of this motion as it is written in an animation file.
+	pub fn name(&self) -> &'static str {
+		match *self {
+			Walking 	=> "walking",
+			Standing 	=> "standing",
+			Interacting => "interacting",
+			Jumping 	=> "jumping",
+			Falling 	=> "falling",
+			Climbing 	=> "climbing",
+			Boosting 	=> "boosting",
+			Crouching 	=> "crouching"
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<Motion> {
+		MOTIONS.iter().find(|motion| motion.name() == name).map(|motion| *motion)
+	}
 }
-pub static MOTIONS: [Motion, ..5] = [Walking, Standing, Interacting, Jumping, Falling];
 
 
-#[deriving(Hash,Eq)]
+#[deriving(Hash,Eq,Clone)]
 pub enum Facing {
 	West,
 	East
 }
 pub static FACINGS: [Facing, ..2] = [West, East];
 
-#[deriving(Hash,Eq)]
+impl Facing {
+	pub fn name(&self) -> &'static str {
+		match *self {
+			West => "west",
+			East => "east"
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<Facing> {
+		FACINGS.iter().find(|facing| facing.name() == name).map(|facing| *facing)
+	}
+}
+
+#[deriving(Hash,Eq,Clone)]
 pub enum Looking {
 	Up,
 	Down,
@@ -33,23 +70,81 @@ pub enum Looking {
 }
 pub static LOOKINGS: [Looking, ..3] = [Up, Down, Horizontal];
 
+impl Looking {
+	pub fn name(&self) -> &'static str {
+		match *self {
+			Up 			=> "up",
+			Down 		=> "down",
+			Horizontal 	=> "horizontal"
+		}
+	}
+
+	pub fn from_name(name: &str) -> Option<Looking> {
+		LOOKINGS.iter().find(|looking| looking.name() == name).map(|looking| *looking)
+	}
+}
+
 /// Any object which can be represented in 2D space
+///
+/// `offset` is subtracted from the object's world coordinates before
+/// it is drawn; this is how the camera scrolls the world.
+/// (Objects drawn in screen space pass an offset of zero.)
 pub trait Drawable { 
-	fn draw(&self, display: &graphics::Graphics); 
+	fn draw(&self, display: &graphics::Graphics, offset: (units::Game, units::Game)); 
+
+	/// Draws this object w/ `params` in place of its own, e.g: tinted
+	/// red for a moment as it takes damage.
+	fn draw_with(&self, display: &graphics::Graphics, offset: (units::Game, units::Game), params: &graphics::DrawParams);
+
+	/// Queues this object's blit on `layer` of `queue` instead of drawing it at once.
+	fn draw_queued(&self, queue: &mut graphics::RenderQueue, layer: graphics::Layer, offset: (units::Game, units::Game));
+
+	/// Queues this object's blit w/ `params` in place of its own.
+	fn draw_queued_with(
+		&self,
+		queue: &mut graphics::RenderQueue, layer: graphics::Layer,
+		offset: (units::Game, units::Game), params: &graphics::DrawParams
+	);
 }
 
 /// Any object which understands time and placement in 2D space.
 pub trait Updatable : Drawable { 
 	fn update(&mut self, elapsed_time: units::Millis); 
 	fn set_position(&mut self, coords: (units::Game,units::Game));
+
+	/// Responds true once an animation which plays only once has ended.
+	/// (Looping animations & still sprites never end.)
+	fn on_complete(&self) -> bool { false }
+
+	/// A copy of this object, as it is now, e.g: for a snapshot of the world.
+	fn clone_updatable(&self) -> ~Updatable;
+}
+
+impl Clone for ~Updatable {
+	fn clone(&self) -> ~Updatable { self.clone_updatable() }
+}
+
+/// How an `AnimatedSprite` steps through its frames.
+#[deriving(Eq,Clone)]
+pub enum Playback {
+	/// First to last, then over again.
+	Loop,
+	/// First to last, then held on the last frame.
+	Once,
+	/// First to last & back again, over & over.
+	PingPong,
+	/// Last to first, then over again.
+	Reverse
 }
 
 /// Represents a static 32x32 2D character
+#[deriving(Clone)]
 pub struct Sprite {
-	sprite_sheet: Arc<~render::Texture>, 
+	sprite_sheet: graphics::Texture, 
 	source_rect: rect::Rect,
-	size: (units::Tile, units::Tile),
+	size: (units::Game, units::Game),
 	coords: (units::Game,units::Game),
+	params: graphics::DrawParams
 }
 
 impl Sprite {
@@ -61,38 +156,138 @@ impl Sprite {
 		offset: (units::Tile,units::Tile), // source_x, source_y
 		size: 	(units::Tile,units::Tile), // width, height
 		file_name: ~str
-	) -> Sprite {
+	) -> GameResult<Sprite> {
+		let (w,h) = size;
+		let (x,y) = offset;
+		
+		Sprite::from_region(
+			graphics, coords,
+			(x.to_game(), y.to_game()), 
+			(w.to_game(), h.to_game()), 
+			file_name
+		)
+	}
+
+	/// A new sprite whose source region need not be aligned to the tile grid.
+	/// (e.g: the half-tile pieces of the HUD.)
+	pub fn from_region(
+		graphics: &mut graphics::Graphics, 
+		coords: (units::Game,units::Game), // position on screen
+		offset: (units::Game,units::Game), // source_x, source_y
+		size: 	(units::Game,units::Game), // width, height
+		file_name: ~str
+	) -> GameResult<Sprite> {
+		Sprite::with_options(graphics, coords, offset, size, file_name, graphics::BLACK_KEYED)
+	}
+
+	/// A new sprite cut from the region `name` of `atlas`.
+	pub fn from_atlas(
+		graphics: &mut graphics::Graphics, 
+		coords: (units::Game,units::Game), // position on screen
+		atlas: &graphics::Atlas,
+		name: &str
+	) -> GameResult<Sprite> {
+		let (offset, size) = try!(atlas.region(name));
+		Sprite::from_region(graphics, coords, offset, size, atlas.sheet_path())
+	}
+
+	/// A new sprite cut from a sheet which is loaded w/ `options`,
+	/// e.g: a sheet whose background is some color other than black.
+	pub fn with_options(
+		graphics: &mut graphics::Graphics, 
+		coords: (units::Game,units::Game), // position on screen
+		offset: (units::Game,units::Game), // source_x, source_y
+		size: 	(units::Game,units::Game), // width, height
+		file_name: ~str,
+		options: graphics::ImageOptions
+	) -> GameResult<Sprite> {
 		let (w,h) = size;
 		let (x,y) = offset;
 		let (units::Pixel(wi), units::Pixel(hi)) = (w.to_pixel(), h.to_pixel());
 		let (units::Pixel(xi), units::Pixel(yi)) = (x.to_pixel(), y.to_pixel());
 	
 		let origin = rect::Rect::new(xi,yi,wi,hi);
-		let sheet = graphics.load_image(file_name, true); // request graphics subsystem cache this sprite.
+		let sheet = try!(graphics.load_image(file_name, options)); // request graphics subsystem cache this sprite.
 
 		let sprite = Sprite{
 			sprite_sheet: sheet,
 			source_rect: origin,
 			size:	size,
 			coords: coords,
+			params: graphics::PLAIN
 		};
 
-		sprite
+		Ok(sprite)
 	}
-}
 
-impl Drawable for Sprite {
-	/// Draws selfs @ coordinates provided by 
-	fn draw(&self, display: &graphics::Graphics) {
+	/// The width & height of the sprite.
+	pub fn size(&self) -> (units::Game, units::Game) { self.size }
+
+	// where the sprite lands on screen once shifted by `offset`
+	fn dest_rect(&self, offset: (units::Game, units::Game)) -> rect::Rect {
 		let (w,h) = self.size;
 		let (x,y) = self.coords;
+		let (ox,oy) = offset;
 		
 		let (units::Pixel(wi), units::Pixel(hi)) = (w.to_pixel(), h.to_pixel());
-		let (units::Pixel(xi), units::Pixel(yi)) = (x.to_pixel(), y.to_pixel());
-	
-		let dest_rect = rect::Rect::new(xi, yi, wi, hi);
+		let (units::Pixel(xi), units::Pixel(yi)) = ((x - ox).to_pixel(), (y - oy).to_pixel());
+		rect::Rect::new(xi, yi, wi, hi)
+	}
+
+	/// Crops the sprite to `width`, measured from its left edge.
+	pub fn set_width(&mut self, width: units::Game) {
+		let (_, h) = self.size;
+		let units::Pixel(wi) = width.to_pixel();
 
-		display.blit_surface(*(self.sprite_sheet.get()), &self.source_rect, &dest_rect);
+		self.size = (width, h);
+		self.source_rect.w = wi;
+	}
+
+	/// Mirrors the sprite left-to-right and/or top-to-bottom as it is drawn.
+	pub fn set_flip(&mut self, horizontal: bool, vertical: bool) {
+		self.params.flip_horizontal = horizontal;
+		self.params.flip_vertical = vertical;
+	}
+
+	/// Turns the sprite by `angle`, clockwise about its center, as it is drawn.
+	pub fn set_rotation(&mut self, angle: units::Degrees) {
+		self.params.angle = angle;
+	}
+
+	/// Multiplies the sprite's colors by `tint` & its opacity by `alpha` as it is drawn.
+	pub fn set_tint(&mut self, tint: (u8, u8, u8), alpha: u8) {
+		self.params.tint = tint;
+		self.params.alpha = alpha;
+	}
+
+	pub fn set_blend(&mut self, blend: graphics::Blend) {
+		self.params.blend = blend;
+	}
+
+	/// How the sprite is flipped, turned & colored as it is drawn.
+	pub fn params(&self) -> graphics::DrawParams { self.params }
+}
+
+impl Drawable for Sprite {
+	/// Draws selfs @ coordinates provided by 
+	fn draw(&self, display: &graphics::Graphics, offset: (units::Game, units::Game)) {
+		self.draw_with(display, offset, &self.params);
+	}
+
+	fn draw_with(&self, display: &graphics::Graphics, offset: (units::Game, units::Game), params: &graphics::DrawParams) {
+		display.blit_with(self.sprite_sheet, &self.source_rect, &self.dest_rect(offset), params);
+	}
+
+	fn draw_queued(&self, queue: &mut graphics::RenderQueue, layer: graphics::Layer, offset: (units::Game, units::Game)) {
+		self.draw_queued_with(queue, layer, offset, &self.params);
+	}
+
+	fn draw_queued_with(
+		&self,
+		queue: &mut graphics::RenderQueue, layer: graphics::Layer,
+		offset: (units::Game, units::Game), params: &graphics::DrawParams
+	) {
+		queue.submit(layer, self.sprite_sheet, &self.source_rect, &self.dest_rect(offset), params);
 	}
 }
 
@@ -105,20 +300,26 @@ impl Updatable for Sprite {
 	fn set_position(&mut self, coords: (units::Game,units::Game)) {
 		self.coords = coords;
 	}
+
+	fn clone_updatable(&self) -> ~Updatable { ~self.clone() as ~Updatable }
 }
 
 /// Represents a 32x32 2D character w/ a number of frames
 /// Frames will be selected based on time-deltas supplied through update
+#[deriving(Clone)]
 pub struct AnimatedSprite {
 	source_rect: rect::Rect,
-	sprite_sheet: Arc<~render::Texture>, 
+	sprite_sheet: graphics::Texture, 
 
 	priv coords: (units::Game, units::Game),
-	priv offset: (units::Tile, units::Tile),
-	priv size: 	 (units::Tile, units::Tile),
+	// each frame's region of the sheet, & how long it is shown for
+	priv frames: ~[(rect::Rect, units::Millis)],
 	priv current_frame: units::Frame,
-	priv num_frames: units::Frame,
-	priv fps: units::Fps,
+	priv playback: Playback,
+	// whether a ping-pong is headed back toward the first frame
+	priv backwards: bool,
+	priv complete: bool,
+	priv params: graphics::DrawParams,
 
 	priv last_update: units::Millis
 }
@@ -127,7 +328,7 @@ impl AnimatedSprite {
 	/// Loads character sprites from `assets/MyChar.bmp`
 	/// `source_rect` acts as a viewport of this sprite-sheet.
 	///
-	/// Returns an error message if sprite-sheet could not be loaded.
+	/// Returns an error if the sprite-sheet could not be loaded.
 	pub fn new(
 		graphics: &mut graphics::Graphics, 
 		sheet_path: ~str, 
@@ -135,7 +336,7 @@ impl AnimatedSprite {
 		size: 	(units::Tile, units::Tile),
 		num_frames: units::Frame,
 		fps: units::Fps
-	) -> Result<AnimatedSprite, ~str> {
+	) -> GameResult<AnimatedSprite> {
 		// attempt to load sprite-sheet from `assets/MyChar.bmp`
 		let (w,h) = size;
 		let (x,y) = offset;
@@ -143,61 +344,176 @@ impl AnimatedSprite {
 		let (units::Pixel(wi), units::Pixel(hi)) = (w.to_pixel(), h.to_pixel());	
 		let (units::Pixel(xi), units::Pixel(yi)) = (x.to_pixel(), y.to_pixel());
 		
-		let origin = rect::Rect::new(xi, yi, wi, hi);
-		
-		let sheet = graphics.load_image(sheet_path, true); // request graphics subsystem cache this sprite.
-		let sprite = AnimatedSprite{
-			offset: offset,
+		// our frames are drawn w/ a 0-idx'd window, sliding right along the sheet
+		let frame_time = units::Millis(1000 / fps as int);
+		let frames = range(0, num_frames).map(|frame| {
+			(rect::Rect::new(xi + wi * frame as i32, yi, wi, hi), frame_time)
+		}).collect();
+
+		AnimatedSprite::from_frames(graphics, sheet_path, graphics::BLACK_KEYED, frames)
+	}
+
+	/// An animation of `frames`, each a region of the sheet at `sheet_path`
+	/// shown for a duration of its own. (`frames` must not be empty.)
+	pub fn from_frames(
+		graphics: &mut graphics::Graphics, 
+		sheet_path: ~str, 
+		options: graphics::ImageOptions,
+		frames: ~[(rect::Rect, units::Millis)]
+	) -> GameResult<AnimatedSprite> {
+		let sheet = try!(graphics.load_image(sheet_path, options)); // request graphics subsystem cache this sprite.
+		let (origin, _) = frames[0];
+
+		Ok(AnimatedSprite {
 			coords: (units::Game(0.0), units::Game(0.0)),
-			size: size,
-			
-			fps: fps,
-			current_frame: 0, 
-			num_frames: num_frames, 	// our frames are drawin w/ a 0-idx'd window.
+			frames: frames,
+			current_frame: 0,
+			playback: Loop,
+			backwards: false,
+			complete: false,
+			params: graphics::PLAIN,
 			last_update: units::Millis(0),
-			
-			sprite_sheet: sheet, 	// "i made this" -- we own this side of the Arc()
+
+			sprite_sheet: sheet, 	// the sheet itself stays cached by the graphics subsystem
 			source_rect: origin
+		})
+	}
+
+	/// The animation tagged `tag` (or, w/o a tag, every frame) of a sheet
+	/// exported by Aseprite, w/ its JSON data at `data_path`.
+	pub fn from_aseprite(
+		graphics: &mut graphics::Graphics, 
+		data_path: &str,
+		tag: Option<&str>
+	) -> GameResult<AnimatedSprite> {
+		let sheet = try!(aseprite::Sheet::load(data_path));
+		let frames = try!(sheet.frames(tag));
+		// exported sheets are transparent by their alpha channel, not a color key
+		let mut sprite = try!(AnimatedSprite::from_frames(graphics, sheet.image.clone(), graphics::OPAQUE, frames));
+		sprite.set_playback(sheet.playback(tag));
+		Ok(sprite)
+	}
+
+	/// Plays the animation as `playback` describes, starting over from its
+	/// first frame (or last, in `Reverse`.)
+	pub fn set_playback(&mut self, playback: Playback) {
+		self.playback = playback;
+		self.current_frame = if playback == Reverse { self.frames.len() - 1 } else { 0 };
+		self.backwards = false;
+		self.complete = false;
+		self.last_update = units::Millis(0);
+
+		let (source_rect, _) = self.frames[self.current_frame];
+		self.source_rect = source_rect;
+	}
+
+	/// Mirrors every frame left-to-right and/or top-to-bottom as it is drawn,
+	/// e.g: so that a sheet facing west may serve for facing east as well.
+	pub fn set_flip(&mut self, horizontal: bool, vertical: bool) {
+		self.params.flip_horizontal = horizontal;
+		self.params.flip_vertical = vertical;
+	}
+
+	/// Turns every frame by `angle`, clockwise about its center, as it is drawn.
+	pub fn set_rotation(&mut self, angle: units::Degrees) {
+		self.params.angle = angle;
+	}
+
+	/// Multiplies every frame's colors by `tint` & opacity by `alpha` as it is drawn.
+	pub fn set_tint(&mut self, tint: (u8, u8, u8), alpha: u8) {
+		self.params.tint = tint;
+		self.params.alpha = alpha;
+	}
+
+	pub fn set_blend(&mut self, blend: graphics::Blend) {
+		self.params.blend = blend;
+	}
+
+	/// How every frame is flipped, turned & colored as it is drawn.
+	pub fn params(&self) -> graphics::DrawParams { self.params }
+
+	// steps to the next frame, as the animation's playback directs
+	fn advance(&mut self) {
+		let last = self.frames.len() - 1;
+		let current = self.current_frame;
+		self.current_frame = match self.playback {
+			Loop 	=> if current == last { 0 } else { current + 1 },
+			Reverse => if current == 0 { last } else { current - 1 },
+			Once 	=> {
+				if current == last { self.complete = true; }
+				cmp::min(current + 1, last)
+			}
+			PingPong => {
+				if last == 0 { return; }
+				if current == last { self.backwards = true; }
+				if current == 0 { self.backwards = false; }
+				if self.backwards { current - 1 } else { current + 1 }
+			}
 		};
+	}
 
-		return Ok(sprite);
+	// where the current frame lands on screen once shifted by `offset`
+	fn dest_rect(&self, offset: (units::Game, units::Game)) -> rect::Rect {
+		let (x,y) = self.coords;
+		let (ox,oy) = offset;
+		let (units::Pixel(xi), units::Pixel(yi)) = ((x - ox).to_pixel(), (y - oy).to_pixel());
+		rect::Rect::new(xi, yi, self.source_rect.w, self.source_rect.h)
 	}
 }
 
 impl Updatable for AnimatedSprite {
 	/// Reads current time-deltas and mutates state accordingly.
+	///
+	/// A long `elapsed_time` may step through several frames at once; any
+	/// time left over counts toward the next frame, so animations keep pace
+	/// however unevenly they are updated.
 	fn update(&mut self, elapsed_time: units::Millis) {
-		let frame_time = units::Millis(1000 / self.fps as int);	
 		self.last_update = self.last_update + elapsed_time;
 
-		// if we have missed drawing a frame
-		if self.last_update > frame_time {		
-			self.last_update = units::Millis(0);	// reset timer
-			self.current_frame += 1;				// increment frame counter
+		while !self.complete {
+			let (_, units::Millis(frame_time)) = self.frames[self.current_frame];
+			let units::Millis(waited) = self.last_update;
+			if waited < frame_time { break; }
 
-			if self.current_frame < self.num_frames {
-				self.source_rect.x += self.source_rect.w;
-			} else {
-				self.current_frame = 0;
-				self.source_rect.x -= self.source_rect.w * (self.num_frames - 1) as i32;
-			}
+			self.last_update = units::Millis(waited - frame_time);
+			self.advance();
+			// (a frame w/o any duration would otherwise be stepped past forever)
+			if frame_time <= 0 { break; }
 		}
+		if self.complete { self.last_update = units::Millis(0); }
+
+		let (source_rect, _) = self.frames[self.current_frame];
+		self.source_rect = source_rect;
 	}
 
 	fn set_position(&mut self, coords: (units::Game,units::Game)) {
 		self.coords = coords;
 	}
+
+	fn on_complete(&self) -> bool { self.complete }
+
+	fn clone_updatable(&self) -> ~Updatable { ~self.clone() as ~Updatable }
 }
 
 impl Drawable for AnimatedSprite {
 	/// Draws selfs @ coordinates provided by 
-	fn draw(&self, display: &graphics::Graphics) {
-		let (w,h) = self.size;
-		let (x,y) = self.coords;
-		let (units::Pixel(wi), units::Pixel(hi)) = (w.to_pixel(), h.to_pixel());
-		let (units::Pixel(xi), units::Pixel(yi)) = (x.to_pixel(), y.to_pixel());
+	fn draw(&self, display: &graphics::Graphics, offset: (units::Game, units::Game)) {
+		self.draw_with(display, offset, &self.params);
+	}
+
+	fn draw_with(&self, display: &graphics::Graphics, offset: (units::Game, units::Game), params: &graphics::DrawParams) {
+		display.blit_with(self.sprite_sheet, &self.source_rect, &self.dest_rect(offset), params);
+	}
+
+	fn draw_queued(&self, queue: &mut graphics::RenderQueue, layer: graphics::Layer, offset: (units::Game, units::Game)) {
+		self.draw_queued_with(queue, layer, offset, &self.params);
+	}
 
-		let dest_rect = rect::Rect::new(xi, yi, wi, hi);
-		display.blit_surface(*(self.sprite_sheet.get()), &self.source_rect, &dest_rect);
+	fn draw_queued_with(
+		&self,
+		queue: &mut graphics::RenderQueue, layer: graphics::Layer,
+		offset: (units::Game, units::Game), params: &graphics::DrawParams
+	) {
+		queue.submit(layer, self.sprite_sheet, &self.source_rect, &self.dest_rect(offset), params);
 	}
 }
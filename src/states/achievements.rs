@@ -0,0 +1,100 @@
+use game::audio;
+use game::graphics;
+use game::i18n;
+use game::input;
+use game::stats;
+
+use game::error::GameResult;
+use game::states;
+use game::states::{GameState, Transition};
+use game::states::options;
+use game::units;
+
+// the description of the achievement under the cursor sits this far above the bottom of the screen
+static DESCRIPTION_MARGIN: units::Game = units::Game(24.0);
+
+/// Lists the player's statistics, then every achievement (& whether it has
+/// been unlocked); the description of the achievement under the cursor is
+/// shown beneath the list.
+pub struct Achievements {
+	priv font: graphics::Font,
+	priv selected: uint,
+	priv stats: stats::Stats
+}
+
+impl Achievements {
+	pub fn new(display: &mut graphics::Graphics, stats: stats::Stats) -> GameResult<Achievements> {
+		Ok(Achievements {
+			font: try!(graphics::Font::load(display)),
+			selected: 0,
+			stats: stats
+		})
+	}
+
+	/// Each statistic w/ its count, then each achievement w/ whether it is unlocked.
+	fn rows(&self) -> ~[(~str, ~str)] {
+		let units::Millis(play_time) = self.stats.play_time;
+		let mut rows = ~[
+			(i18n::text("stats.jumps"), format!("{}", self.stats.jumps)),
+			(i18n::text("stats.damage_taken"), format!("{}", self.stats.damage_taken)),
+			(i18n::text("stats.enemies_defeated"), format!("{}", self.stats.enemies_defeated)),
+			(i18n::text("stats.play_time"), format!("{}:{:02d}", play_time / 3600000, (play_time / 60000) % 60))
+		];
+		for achievement in stats::ACHIEVEMENTS.iter() {
+			let status = if self.stats.is_unlocked(achievement) { "achievements.unlocked" } else { "achievements.locked" };
+			rows.push((i18n::text(format!("achievement.{}", achievement.id).as_slice()), i18n::text(status)));
+		}
+		rows
+	}
+}
+
+impl GameState for Achievements {
+	#[allow(unused_variable)]
+	fn handle_input(
+		&mut self,
+		controller: &input::Input,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		if controller.was_key_pressed(input::Pause) || controller.was_key_pressed(input::Fire) {
+			return states::Pop;
+		}
+
+		let count = self.rows().len();
+		if controller.was_key_pressed(input::LookUp) {
+			self.selected = (self.selected + count - 1) % count;
+		} else if controller.was_key_pressed(input::LookDown) {
+			self.selected = (self.selected + 1) % count;
+		}
+
+		if controller.was_key_pressed(input::Quit) { states::Quit } else { states::Stay }
+	}
+
+	#[allow(unused_variable)]
+	fn update(
+		&mut self,
+		elapsed_time: units::Millis,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		states::Stay
+	}
+
+	#[allow(unused_variable)]
+	fn draw(&self, display: &graphics::Graphics, alpha: f64) {
+		options::draw_rows(display, &self.font, i18n::text("achievements.title"), self.rows().as_slice(), self.selected);
+
+		// achievements follow the statistics
+		let first = self.rows().len() - stats::ACHIEVEMENTS.len();
+		if self.selected < first { return; }
+		let achievement = &stats::ACHIEVEMENTS[self.selected - first];
+		let description = i18n::text(format!("achievement.{}.description", achievement.id).as_slice());
+
+		let (screen_w, screen_h) = display.resolution().to_game();
+		let x = (screen_w - self.font.text_width(description.as_slice())) / units::Game(2.0);
+		let y = screen_h - DESCRIPTION_MARGIN - self.font.line_height();
+		self.font.draw_text(display, x, y, description.as_slice());
+	}
+
+	fn is_overlay(&self) -> bool { true }
+}
@@ -0,0 +1,70 @@
+use game::audio;
+use game::graphics;
+use game::input;
+use game::log;
+use game::rng;
+use game::speedrun;
+
+use game::states;
+use game::states::{GameState, Transition};
+use game::units;
+
+// time before the player may return to the title screen
+static MIN_DISPLAY_TIME: units::Millis = units::Millis(1500);
+
+/// Shown over the frozen world once the player has run out of hit points.
+/// After a moment, pressing jump returns to the title screen.
+pub struct GameOver {
+	priv display_time: units::Millis,
+	// handed on to the title screen, & so to the next game
+	priv rng: rng::Rng,
+	priv timer: speedrun::Timer
+}
+
+impl GameOver {
+	pub fn new(rng: rng::Rng, timer: speedrun::Timer) -> GameOver {
+		GameOver { display_time: units::Millis(0), rng: rng, timer: timer }
+	}
+}
+
+impl GameState for GameOver {
+	#[allow(unused_variable)]
+	fn handle_input(
+		&mut self,
+		controller: &input::Input,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		if self.display_time < MIN_DISPLAY_TIME {
+			return states::Stay;
+		}
+
+		if controller.was_key_pressed(input::Jump) {
+			audio.stop_music();
+			match states::TitleScreen::new(display, self.rng.clone(), self.timer.clone()) {
+				Ok(title) => states::Replace(~title as ~GameState),
+				Err(err) => { log::error("game_over", err.message()); states::Quit }
+			}
+		} else if controller.was_key_pressed(input::Quit) {
+			states::Quit
+		} else {
+			states::Stay
+		}
+	}
+
+	#[allow(unused_variable)]
+	fn update(
+		&mut self,
+		elapsed_time: units::Millis,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		self.display_time = self.display_time + elapsed_time;
+		states::Stay
+	}
+
+	#[allow(unused_variable)]
+	fn draw(&self, display: &graphics::Graphics, alpha: f64) {}
+
+	fn is_overlay(&self) -> bool { true }
+}
@@ -0,0 +1,168 @@
+use sdl2::pixels;
+use sdl2::rect;
+use std::cmp;
+
+use game::audio;
+use game::exploration;
+use game::graphics;
+use game::i18n;
+use game::input;
+use game::map;
+
+use game::error::GameResult;
+use game::states;
+use game::states::{GameState, Transition};
+use game::units;
+use game::units::{AsGame, AsPixel, AsTile};
+
+static DIM_ALPHA: u8 = 192;
+
+// the most game units a tile is shrunk to, & how much of the screen the map may fill
+static MAX_TILE_SIZE: f64 	= 8.0;
+static SCREEN_SHARE: f64 	= 0.8;
+static TITLE_MARGIN: units::Game = units::Game(16.0);
+
+// the player's marker blinks on & off this often
+static BLINK_TIME: units::Millis = units::Millis(250);
+
+/// A downscaled view of the current map, showing only what the player has
+/// explored & where they stand in it. The game is frozen (though still
+/// drawn, dimmed) until the map is closed again.
+pub struct MapScreen {
+	priv font: graphics::Font,
+	priv title: ~str,
+	// each tile, by row: `None` until explored, then whether it is solid
+	priv tiles: ~[~[Option<bool>]],
+	// the tile the player stands on
+	priv player: (uint, uint),
+	priv blink_time: units::Millis
+}
+
+impl MapScreen {
+	/// Charts the parts of `map` (loaded from `path`) seen in `exploration`,
+	/// w/ the player's marker at `player` (in map coordinates.)
+	pub fn new(
+		display: &mut graphics::Graphics,
+		map: &map::Map,
+		path: &str,
+		exploration: &exploration::Exploration,
+		player: (units::Game, units::Game)
+	) -> GameResult<MapScreen> {
+		let units::Tile(cols) = map.width().to_tile();
+		let units::Tile(rows) = map.height().to_tile();
+		let half_tile = units::Tile(1).to_game() / units::Game(2.0);
+
+		let tiles = range(0, rows).map(|row| {
+			range(0, cols).map(|col| {
+				if exploration.is_explored(path, (units::Tile(col), units::Tile(row))) {
+					let (x, y) = (units::Tile(col).to_game() + half_tile, units::Tile(row).to_game() + half_tile);
+					Some(map.tile_type_at(x, y).is_solid())
+				} else {
+					None
+				}
+			}).collect()
+		}).collect();
+
+		let (player_x, player_y) = player;
+		let (units::Tile(player_col), units::Tile(player_row)) = (player_x.to_tile(), player_y.to_tile());
+
+		// maps are titled by the key `map.<file>`, e.g: `map.cave` for `assets/maps/cave.map`,
+		// or after the file itself if no table holds the key
+		let stem = Path::new(path).filestem_str().unwrap_or(path).to_owned();
+		let title = i18n::lookup(format!("map.{}", stem).as_slice()).unwrap_or(stem);
+
+		Ok(MapScreen {
+			font: try!(graphics::Font::load(display)),
+			title: title,
+			tiles: tiles,
+			player: (player_col, player_row),
+			blink_time: units::Millis(0)
+		})
+	}
+
+	/// The size each tile is drawn at, & the screen-space top-left corner of
+	/// the map, so that it fits (centered) on a screen of `resolution`.
+	fn layout(&self, resolution: units::Resolution) -> (units::Game, (units::Game, units::Game)) {
+		let (units::Game(screen_w), units::Game(screen_h)) = resolution.to_game();
+		let rows = cmp::max(self.tiles.len(), 1);
+		let cols = cmp::max(self.tiles.get(0).map_or(0, |row| row.len()), 1);
+
+		let fit_w = (screen_w * SCREEN_SHARE) / cols as f64;
+		let fit_h = (screen_h * SCREEN_SHARE) / rows as f64;
+		let size = fit_w.min(fit_h).min(MAX_TILE_SIZE).floor().max(1.0);
+
+		(units::Game(size),
+		 (units::Game((screen_w - (size * cols as f64)) / 2.0),
+		  units::Game((screen_h - (size * rows as f64)) / 2.0)))
+	}
+}
+
+impl GameState for MapScreen {
+	#[allow(unused_variable)]
+	fn handle_input(
+		&mut self,
+		controller: &input::Input,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		if controller.was_key_pressed(input::ToggleMap) || controller.was_key_pressed(input::Pause) {
+			states::Pop
+		} else if controller.was_key_pressed(input::Quit) {
+			states::Quit
+		} else {
+			states::Stay
+		}
+	}
+
+	#[allow(unused_variable)]
+	fn update(
+		&mut self,
+		elapsed_time: units::Millis,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		self.blink_time = self.blink_time + elapsed_time;
+		states::Stay
+	}
+
+	/// Dims the frozen world, then queues a square for each explored tile
+	/// (walls brighter than open space) & the player's blinking marker.
+	#[allow(unused_variable)]
+	fn draw(&self, display: &graphics::Graphics, alpha: f64) {
+		display.dim_screen(DIM_ALPHA);
+
+		let (size, (left, top)) = self.layout(display.resolution());
+		let units::Pixel(side) = size.to_pixel();
+		let square = |col: uint, row: uint| {
+			let x = left + (size * units::Game(col as f64));
+			let y = top + (size * units::Game(row as f64));
+			let (units::Pixel(xi), units::Pixel(yi)) = (x.to_pixel(), y.to_pixel());
+			rect::Rect::new(xi, yi, side, side)
+		};
+
+		let mut queue = graphics::RenderQueue::new(display);
+		for (row, tiles) in self.tiles.iter().enumerate() {
+			for (col, tile) in tiles.iter().enumerate() {
+				match *tile {
+					Some(true) 	=> queue.fill(graphics::Overlay, &square(col, row), pixels::RGBA(160, 192, 224, 255)),
+					Some(false) => queue.fill(graphics::Overlay, &square(col, row), pixels::RGBA(32, 48, 96, 224)),
+					None => {}
+				}
+			}
+		}
+
+		let (units::Millis(blink), units::Millis(period)) = (self.blink_time, BLINK_TIME);
+		if (blink / period) % 2 == 0 {
+			let (col, row) = self.player;
+			queue.fill(graphics::Overlay, &square(col, row), pixels::RGBA(255, 64, 64, 255));
+		}
+
+		let (screen_w, _) = display.resolution().to_game();
+		let title_x = (screen_w - self.font.text_width(self.title.as_slice())) / units::Game(2.0);
+		let title_y = top - self.font.line_height() - TITLE_MARGIN;
+		self.font.queue_text(&mut queue, graphics::Overlay, title_x, title_y, self.title.as_slice(), &graphics::PLAIN);
+		queue.flush(display);
+	}
+
+	fn is_overlay(&self) -> bool { true }
+}
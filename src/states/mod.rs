@@ -0,0 +1,81 @@
+use game::audio;
+use game::graphics;
+use game::input;
+use game::units;
+
+// Bring states into this crate's namespace
+pub use game::states::achievements::Achievements;
+pub use game::states::game_over::GameOver;
+pub use game::states::map_screen::MapScreen;
+pub use game::states::options::{Options, ControlsMenu};
+pub use game::states::paused::Paused;
+pub use game::states::playing::Playing;
+pub use game::states::profiles::ProfileSelect;
+pub use game::states::remote::RemoteGame;
+pub use game::states::title::TitleScreen;
+
+// Load state modules
+pub mod achievements;
+pub mod game_over;
+pub mod map_screen;
+pub mod options;
+pub mod paused;
+pub mod playing;
+pub mod profiles;
+pub mod remote;
+pub mod title;
+
+/// Instructs the `Game` how its stack of states should change.
+pub enum Transition {
+	/// Remain in the current state.
+	Stay,
+	/// Suspend the current state & enter a new one on top of it.
+	Push(~GameState),
+	/// Leave the current state, resuming the one beneath it.
+	Pop,
+	/// Discard every state & start over from a new one.
+	Replace(~GameState),
+	/// Exit the game.
+	Quit
+}
+
+/// A single screen of the game (e.g: a menu, or gameplay itself.)
+///
+/// `Game` owns a stack of states; only the topmost state receives input
+/// and updates, while states beneath an overlay continue to be drawn.
+pub trait GameState {
+	/// Responds to this frame's input.
+	fn handle_input(
+		&mut self, 
+		controller: &input::Input, 
+		display: &mut graphics::Graphics, 
+		audio: &mut audio::Audio
+	) -> Transition;
+
+	/// Advances this state by a single, fixed, `elapsed_time` step.
+	fn update(
+		&mut self, 
+		elapsed_time: units::Millis, 
+		display: &mut graphics::Graphics, 
+		audio: &mut audio::Audio
+	) -> Transition;
+
+	/// Draws this state; `alpha` is how far, `[0, 1)`, we are between
+	/// the last update step & the next.
+	fn draw(&self, display: &graphics::Graphics, alpha: f64);
+
+	/// Responds true if the states beneath this one should be drawn first.
+	fn is_overlay(&self) -> bool { false }
+
+	/// Lines of text describing this state, for the debug overlay.
+	fn debug_info(&self) -> ~[~str] { ~[] }
+
+	/// Responds to the window losing focus, e.g: gameplay pauses itself.
+	#[allow(unused_variable)]
+	fn on_focus_lost(&mut self, display: &mut graphics::Graphics) -> Transition { Stay }
+
+	/// Responds to the map files at `paths` having changed on disk (w/ hot
+	/// reloading on), e.g: by rebuilding the map being played.
+	#[allow(unused_variable)]
+	fn on_maps_changed(&mut self, paths: &[~str], display: &mut graphics::Graphics) {}
+}
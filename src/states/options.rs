@@ -0,0 +1,334 @@
+use sdl2::pixels;
+use sdl2::rect;
+use std::cmp;
+
+use game::audio;
+use game::config;
+use game::graphics;
+use game::i18n;
+use game::input;
+use game::log;
+use game::profile;
+
+use game::error::GameResult;
+use game::states;
+use game::states::{GameState, Transition};
+use game::units;
+use game::units::{AsPixel};
+
+static DIM_ALPHA: u8 = 192;
+
+// menu layout (expressed as `units::Game`)
+static ROW_WIDTH: units::Game 		= units::Game(400.0);
+static ROW_HEIGHT: units::Game 		= units::Game(20.0);
+static ROW_SPACING: units::Game 	= units::Game(4.0);
+static ROW_MARGIN: units::Game 		= units::Game(8.0);
+static TITLE_MARGIN: units::Game 	= units::Game(16.0);
+
+// how much one press of left or right changes a volume
+static VOLUME_STEP: uint = 10;
+
+/// The choices offered by the options menu, from top to bottom.
+#[deriving(Eq)]
+enum Entry {
+	MasterVolume,
+	MusicVolume,
+	SfxVolume,
+	Scale,
+	Fullscreen,
+	Language,
+	Controls,
+	Back
+}
+static ENTRIES: [Entry, ..8] = [MasterVolume, MusicVolume, SfxVolume, Scale, Fullscreen, Language, Controls, Back];
+
+impl Entry {
+	/// The key of this entry's label in the string tables.
+	fn label_key(&self) -> &'static str {
+		match *self {
+			MasterVolume => "options.master_volume",
+			MusicVolume => "options.music_volume",
+			SfxVolume 	=> "options.sfx_volume",
+			Scale 		=> "options.scale",
+			Fullscreen 	=> "options.fullscreen",
+			Language 	=> "options.language",
+			Controls 	=> "options.controls",
+			Back 		=> "options.back"
+		}
+	}
+}
+
+/// Lets the player change the settings while the game runs, each change
+/// applied at once & written to the settings file so it lasts.
+///
+/// Left & right change the entry under the cursor; pausing again (or
+/// choosing "back") returns to whatever opened the menu.
+pub struct Options {
+	priv font: graphics::Font,
+	priv selected: uint,
+	priv settings: config::Settings,
+	// every language w/ a string table, in order
+	priv languages: ~[~str]
+}
+
+impl Options {
+	pub fn new(display: &mut graphics::Graphics) -> GameResult<Options> {
+		Ok(Options {
+			font: try!(graphics::Font::load(display)),
+			selected: 0,
+			settings: config::Settings::load(profile::settings_path().as_slice()),
+			languages: i18n::languages()
+		})
+	}
+
+	/// The value shown beside `entry`.
+	fn value(&self, entry: Entry) -> ~str {
+		match entry {
+			MasterVolume => format!("< {} >", self.settings.master_volume),
+			MusicVolume => format!("< {} >", self.settings.music_volume),
+			SfxVolume 	=> format!("< {} >", self.settings.sfx_volume),
+			Scale 		=> format!("< {}x >", self.settings.scale),
+			Fullscreen 	=> i18n::text(if self.settings.fullscreen { "options.on" } else { "options.off" }),
+			Language 	=> format!("< {} >", self.settings.language),
+			Controls | Back => ~""
+		}
+	}
+
+	/// Moves the setting of `entry` one `step` (`-1` or `1`) along, applies
+	/// it, & saves the settings.
+	fn adjust(&mut self, entry: Entry, step: int, display: &mut graphics::Graphics, audio: &mut audio::Audio) {
+		let nudge = |value: uint, by: uint, max: uint| {
+			if step < 0 { value - cmp::min(value, by) } else { cmp::min(value + by, max) }
+		};
+
+		match entry {
+			MasterVolume => {
+				self.settings.master_volume = nudge(self.settings.master_volume, VOLUME_STEP, audio::MAX_VOLUME);
+				audio.set_master_volume(self.settings.master_volume);
+			}
+			MusicVolume => {
+				self.settings.music_volume = nudge(self.settings.music_volume, VOLUME_STEP, audio::MAX_VOLUME);
+				audio.set_music_volume(self.settings.music_volume);
+			}
+			SfxVolume => {
+				self.settings.sfx_volume = nudge(self.settings.sfx_volume, VOLUME_STEP, audio::MAX_VOLUME);
+				audio.set_sfx_volume(self.settings.sfx_volume);
+			}
+			Scale => {
+				self.settings.scale = cmp::max(nudge(self.settings.scale, 1, config::MAX_SCALE), 1);
+				display.set_scale(self.settings.scale);
+			}
+			Fullscreen => {
+				self.settings.fullscreen = !self.settings.fullscreen;
+				if display.is_fullscreen() != self.settings.fullscreen { display.toggle_fullscreen(); }
+			}
+			Language => {
+				if self.languages.is_empty() { return; }
+				let count = self.languages.len() as int;
+				let current = self.languages.iter().position(|name| *name == self.settings.language).unwrap_or(0) as int;
+				let next = self.languages[((current + step + count) % count) as uint].clone();
+				if !i18n::set_language(next.as_slice()) { return; }
+
+				// text in the new language may need glyphs the old one did not
+				display.prepare_glyphs(i18n::characters());
+				self.settings.language = next;
+			}
+			Controls | Back => return
+		}
+		self.settings.save(profile::settings_path().as_slice());
+	}
+}
+
+impl GameState for Options {
+	fn handle_input(
+		&mut self,
+		controller: &input::Input,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		if controller.was_key_pressed(input::Pause) { return states::Pop; }
+
+		// move the cursor, wrapping around at either end
+		if controller.was_key_pressed(input::LookUp) {
+			self.selected = (self.selected + ENTRIES.len() - 1) % ENTRIES.len();
+		} else if controller.was_key_pressed(input::LookDown) {
+			self.selected = (self.selected + 1) % ENTRIES.len();
+		}
+
+		let entry = ENTRIES[self.selected];
+		if controller.was_key_pressed(input::MoveLeft) {
+			self.adjust(entry, -1, display, audio);
+		} else if controller.was_key_pressed(input::MoveRight) {
+			self.adjust(entry, 1, display, audio);
+		}
+
+		if controller.was_key_pressed(input::Jump) {
+			match entry {
+				Controls => match ControlsMenu::new(display, controller.bindings()) {
+					Ok(controls) => states::Push(~controls as ~GameState),
+					Err(err) => { log::error("options", err.message()); states::Stay }
+				},
+				Back => states::Pop,
+				Fullscreen => { self.adjust(entry, 1, display, audio); states::Stay }
+				_ => states::Stay
+			}
+		} else if controller.was_key_pressed(input::Quit) {
+			states::Quit
+		} else {
+			states::Stay
+		}
+	}
+
+	#[allow(unused_variable)]
+	fn update(
+		&mut self,
+		elapsed_time: units::Millis,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		states::Stay
+	}
+
+	/// Dims whatever is beneath and draws each entry w/ its value,
+	/// highlighting the one under the cursor.
+	#[allow(unused_variable)]
+	fn draw(&self, display: &graphics::Graphics, alpha: f64) {
+		let rows: ~[(~str, ~str)] = ENTRIES.iter()
+			.map(|entry| (i18n::text(entry.label_key()), self.value(*entry)))
+			.collect();
+		draw_rows(display, &self.font, i18n::text("options.title"), rows.as_slice(), self.selected);
+	}
+
+	fn is_overlay(&self) -> bool { true }
+}
+
+/// Lists every action w/ the keys bound to it; choosing one waits for the
+/// next key pressed & binds the action to it in place of its old keys.
+/// Each change is written to the bindings file.
+pub struct ControlsMenu {
+	priv font: graphics::Font,
+	priv selected: uint,
+	priv bindings: config::Bindings,
+	// the action about to be bound to the next key pressed
+	priv awaiting: Option<input::Action>
+}
+
+impl ControlsMenu {
+	pub fn new(display: &mut graphics::Graphics, bindings: config::Bindings) -> GameResult<ControlsMenu> {
+		Ok(ControlsMenu {
+			font: try!(graphics::Font::load(display)),
+			selected: 0,
+			bindings: bindings,
+			awaiting: None
+		})
+	}
+}
+
+impl GameState for ControlsMenu {
+	#[allow(unused_variable)]
+	fn handle_input(
+		&mut self,
+		controller: &input::Input,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		match self.awaiting {
+			Some(action) => {
+				match controller.pressed_key() {
+					// the pause key cancels, unless it is pause being rebound
+					Some(key) if self.bindings.key_action(key) == Some(input::Pause) && action != input::Pause => {
+						self.awaiting = None;
+					}
+					Some(key) => {
+						// keys the bindings file has no name for are ignored
+						if self.bindings.bind_key(key, action) {
+							controller.set_bindings(self.bindings.clone());
+							self.bindings.save(profile::bindings_path().as_slice());
+							self.awaiting = None;
+						}
+					}
+					None => {}
+				}
+				return states::Stay;
+			}
+			None => {}
+		}
+
+		if controller.was_key_pressed(input::Pause) { return states::Pop; }
+
+		let count = input::ACTIONS.len();
+		if controller.was_key_pressed(input::LookUp) {
+			self.selected = (self.selected + count - 1) % count;
+		} else if controller.was_key_pressed(input::LookDown) {
+			self.selected = (self.selected + 1) % count;
+		}
+
+		if controller.was_key_pressed(input::Jump) {
+			self.awaiting = Some(input::ACTIONS[self.selected]);
+		}
+		states::Stay
+	}
+
+	#[allow(unused_variable)]
+	fn update(
+		&mut self,
+		elapsed_time: units::Millis,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		states::Stay
+	}
+
+	#[allow(unused_variable)]
+	fn draw(&self, display: &graphics::Graphics, alpha: f64) {
+		let rows: ~[(~str, ~str)] = input::ACTIONS.iter().map(|action| {
+			let label = i18n::text(format!("action.{}", action.name()).as_slice());
+			let keys = if self.awaiting == Some(*action) {
+				i18n::text("options.press_key")
+			} else {
+				let names = self.bindings.key_names(*action);
+				if names.is_empty() { ~"-" } else { names.connect(", ") }
+			};
+			(label, keys)
+		}).collect();
+		draw_rows(display, &self.font, i18n::text("options.controls"), rows.as_slice(), self.selected);
+	}
+
+	fn is_overlay(&self) -> bool { true }
+}
+
+/// Dims the screen, then draws `title` above a column of `rows` (each a
+/// label on the left & a value on the right), highlighting `selected`.
+pub fn draw_rows(display: &graphics::Graphics, font: &graphics::Font, title: ~str, rows: &[(~str, ~str)], selected: uint) {
+	display.dim_screen(DIM_ALPHA);
+
+	let (units::Game(screen_w), units::Game(screen_h)) = display.resolution().to_game();
+	let (units::Game(w), units::Game(h), units::Game(spacing)) = (ROW_WIDTH, ROW_HEIGHT, ROW_SPACING);
+	let units::Game(text_h) = font.line_height();
+
+	let menu_h = (h * rows.len() as f64) + (spacing * (rows.len() as f64 - 1.0));
+	let (left, top) = ((screen_w - w) / 2.0, (screen_h - menu_h) / 2.0);
+
+	let units::Game(title_w) = font.text_width(title.as_slice());
+	let units::Game(title_margin) = TITLE_MARGIN;
+	font.draw_text(display, units::Game((screen_w - title_w) / 2.0), units::Game(top - text_h - title_margin), title.as_slice());
+
+	for (index, &(ref label, ref value)) in rows.iter().enumerate() {
+		let y = top + ((h + spacing) * index as f64);
+		let color = if index == selected {
+			pixels::RGBA(128, 128, 192, 224)
+		} else {
+			pixels::RGBA(64, 64, 96, 224)
+		};
+		let (units::Pixel(xi), units::Pixel(yi)) = (units::Game(left).to_pixel(), units::Game(y).to_pixel());
+		let (units::Pixel(wi), units::Pixel(hi)) = (ROW_WIDTH.to_pixel(), ROW_HEIGHT.to_pixel());
+		display.fill_rect(&rect::Rect::new(xi, yi, wi, hi), color);
+
+		// labels sit at the left of their row, values at the right
+		let units::Game(margin) = ROW_MARGIN;
+		let units::Game(value_w) = font.text_width(value.as_slice());
+		let text_y = units::Game(y + ((h - text_h) / 2.0));
+		font.draw_text(display, units::Game(left + margin), text_y, label.as_slice());
+		font.draw_text(display, units::Game(left + w - margin - value_w), text_y, value.as_slice());
+	}
+}
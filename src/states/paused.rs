@@ -0,0 +1,181 @@
+use sdl2::pixels;
+use sdl2::rect;
+
+use game::audio;
+use game::graphics;
+use game::i18n;
+use game::input;
+use game::log;
+use game::save;
+use game::stats;
+
+use game::error::GameResult;
+use game::states;
+use game::states::{GameState, Transition};
+use game::units;
+use game::units::{AsPixel};
+
+static DIM_ALPHA: u8 = 160;
+
+// menu layout (expressed as `units::Game`)
+static ENTRY_WIDTH: units::Game 	= units::Game(192.0);
+static ENTRY_HEIGHT: units::Game 	= units::Game(32.0);
+static ENTRY_SPACING: units::Game 	= units::Game(16.0);
+
+/// The choices offered by the pause menu, from top to bottom.
+#[deriving(Eq)]
+enum Entry {
+	Resume,
+	Save,
+	Options,
+	Achievements,
+	QuitGame
+}
+static ENTRIES: [Entry, ..5] = [Resume, Save, Options, Achievements, QuitGame];
+
+impl Entry {
+	/// The key of this entry's label in the string tables.
+	fn label_key(&self) -> &'static str {
+		match *self {
+			Resume 		=> "menu.resume",
+			Save 		=> "menu.save",
+			Options 	=> "menu.options",
+			Achievements => "menu.achievements",
+			QuitGame 	=> "menu.quit"
+		}
+	}
+
+	/// This entry's label, in the current language.
+	fn label(&self) -> ~str {
+		i18n::text(self.label_key())
+	}
+}
+
+/// Freezes the game in place and offers a menu until the player resumes.
+///
+/// The state beneath (i.e: gameplay) is still drawn, dimmed, but is
+/// no longer updated.
+pub struct Paused {
+	priv font: graphics::Font,
+	priv selected: uint,
+	/// The progress of the paused game, written out if the player saves.
+	priv progress: save::SaveData,
+	/// The player's statistics, as of when the game was paused.
+	priv stats: stats::Stats
+}
+
+impl Paused {
+	pub fn new(display: &mut graphics::Graphics, progress: save::SaveData, stats: stats::Stats) -> GameResult<Paused> {
+		Ok(Paused {
+			font: try!(graphics::Font::load(display)),
+			selected: 0,
+			progress: progress,
+			stats: stats
+		})
+	}
+
+	/// The screen-space top-left corner of the `index`th menu entry,
+	/// on a screen of `resolution`.
+	fn entry_origin(index: uint, resolution: units::Resolution) -> (units::Game, units::Game) {
+		let (units::Game(screen_w), units::Game(screen_h)) = resolution.to_game();
+		let (units::Game(w), units::Game(h), units::Game(spacing)) = 
+			(ENTRY_WIDTH, ENTRY_HEIGHT, ENTRY_SPACING);
+
+		let menu_h = (h * ENTRIES.len() as f64) + (spacing * (ENTRIES.len() - 1) as f64);
+		(units::Game((screen_w - w) / 2.0),
+		 units::Game(((screen_h - menu_h) / 2.0) + ((h + spacing) * index as f64)))
+	}
+
+	/// The screen-space rectangle of the `index`th menu entry.
+	fn entry_rect(index: uint, resolution: units::Resolution) -> rect::Rect {
+		let (x, y) = Paused::entry_origin(index, resolution);
+		let (units::Pixel(xi), units::Pixel(yi)) = (x.to_pixel(), y.to_pixel());
+		let (units::Pixel(wi), units::Pixel(hi)) = (ENTRY_WIDTH.to_pixel(), ENTRY_HEIGHT.to_pixel());
+		rect::Rect::new(xi, yi, wi, hi)
+	}
+}
+
+impl GameState for Paused {
+	#[allow(unused_variable)]
+	fn handle_input(
+		&mut self,
+		controller: &input::Input,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		if controller.was_key_pressed(input::Pause) {
+			return states::Pop;
+		}
+
+		// move the cursor, wrapping around at either end
+		if controller.was_key_pressed(input::LookUp) {
+			self.selected = (self.selected + ENTRIES.len() - 1) % ENTRIES.len();
+		} else if controller.was_key_pressed(input::LookDown) {
+			self.selected = (self.selected + 1) % ENTRIES.len();
+		}
+
+		if controller.was_key_pressed(input::Jump) {
+			match ENTRIES[self.selected] {
+				Resume 		=> states::Pop,
+				Save 		=> {
+					save::save_to_slot(save::QUICK_SLOT, &self.progress);
+					states::Pop
+				}
+				Options 	=> match states::Options::new(display) {
+					Ok(options) => states::Push(~options as ~GameState),
+					Err(err) => { log::error("paused", err.message()); states::Stay }
+				},
+				Achievements => match states::Achievements::new(display, self.stats.clone()) {
+					Ok(achievements) => states::Push(~achievements as ~GameState),
+					Err(err) => { log::error("paused", err.message()); states::Stay }
+				},
+				QuitGame 	=> states::Quit
+			}
+		} else if controller.was_key_pressed(input::Quit) {
+			states::Quit
+		} else {
+			states::Stay
+		}
+	}
+
+	#[allow(unused_variable)]
+	fn update(
+		&mut self,
+		elapsed_time: units::Millis,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		states::Stay
+	}
+
+	/// Dims the frozen world and draws the menu's entries over it,
+	/// highlighting the one under the cursor.
+	#[allow(unused_variable)]
+	fn draw(&self, display: &graphics::Graphics, alpha: f64) {
+		display.dim_screen(DIM_ALPHA);
+
+		for (index, entry) in ENTRIES.iter().enumerate() {
+			let color = if index == self.selected {
+				pixels::RGBA(128, 128, 192, 224)
+			} else {
+				pixels::RGBA(64, 64, 96, 224)
+			};
+			let rect = Paused::entry_rect(index, display.resolution());
+			display.fill_rect(&rect, color);
+
+			// labels are centered within their entry
+			let label = entry.label();
+			let (units::Game(w), units::Game(h)) = (ENTRY_WIDTH, ENTRY_HEIGHT);
+			let units::Game(label_w) = self.font.text_width(label.as_slice());
+			let units::Game(label_h) = self.font.line_height();
+			let (units::Game(x), units::Game(y)) = Paused::entry_origin(index, display.resolution());
+			self.font.draw_text(
+				display,
+				units::Game(x + ((w - label_w) / 2.0)), units::Game(y + ((h - label_h) / 2.0)),
+				label.as_slice()
+			);
+		}
+	}
+
+	fn is_overlay(&self) -> bool { true }
+}
@@ -0,0 +1,1099 @@
+use std::cmp;
+
+use game::audio;
+use game::boss;
+use game::camera;
+use game::config;
+use game::entity;
+use game::equipment;
+use game::exploration;
+use game::flags;
+use game::ghost;
+use game::graphics;
+use game::graphics::transitions;
+use game::hud;
+use game::input;
+use game::log;
+use game::map;
+use game::net;
+use game::npc;
+use game::player;
+use game::profile;
+use game::rng;
+use game::save;
+use game::script;
+use game::speedrun;
+use game::stats;
+use game::textbox;
+use game::weather;
+use game::world;
+
+use game::error::GameResult;
+use game::states;
+use game::states::{GameState, Transition};
+use game::units;
+use game::units::{AsGame};
+
+static FIRST_MAP: &'static str = "assets/maps/cave.map";
+
+// how deep the darkness of a dark map is
+static DARKNESS: u8 = 224;
+
+// how long the screen shakes when the player lands hard
+static HEAVY_LANDING_SHAKE: units::Millis = units::Millis(200);
+
+// how long the view takes to close in on a player who has died
+static DEATH_TRANSITION_TIME: units::Millis = units::Millis(1000);
+
+// how close the player must come to where a cutscene walks them
+static ARRIVAL_DISTANCE: units::Game = units::Game(4.0);
+
+// how near the edges of the view two players may stray from one another
+// before the second is pulled back to the first
+static STRAY_MARGIN: units::Tile = units::Tile(1);
+
+/// What the running event is waiting on before it carries on.
+enum Wait {
+	/// The player reading (& closing) a message.
+	Reading,
+	/// Time passing, w/ this much left.
+	Sleeping(units::Millis),
+	/// An actor walking to the given x-coordinate.
+	Walking(script::Actor, units::Game),
+	/// The camera finishing its pan.
+	Panning,
+	/// A transition covering (or revealing) the screen.
+	Transitioning
+}
+
+/// Everything which changes as the game is played, kept in memory so that
+/// the player may return to it over & over (in practice mode.)
+#[deriving(Clone)]
+struct SaveState {
+	world: world::Snapshot,
+	// the map the state was saved on, & its tiles (some may be destroyed)
+	map_path: ~str,
+	terrain: map::Terrain,
+	flags: flags::FlagSet,
+	items: ~[uint],
+	exploration: exploration::Exploration,
+	camera: camera::Camera,
+	rng: rng::State
+}
+
+/// Gameplay itself: the player exploring a map alongside its enemies.
+///
+/// A second player may join in at any time by jumping w/ the second set of
+/// keys (see `input::SEATS`). They share the map w/ the first player, who
+/// alone sets off its events, & drop out of the game should they fall;
+/// the game is over only once the first player dies.
+///
+/// While the game is hosted (see `config::Settings::host`) the second
+/// player is instead whoever joins from another machine; they are sent
+/// what the world looks like every few frames (see `net::Server`).
+pub struct Playing {
+	priv world: world::World,
+	priv maps: 	map::MapManager,
+	priv flags: flags::FlagSet,
+	priv items: ~[uint],
+	priv exploration: exploration::Exploration,
+	priv script: Option<script::Runner>,
+	priv wait: Option<Wait>,
+	// whether the running event is a cutscene, & whether it is being skipped
+	priv cutscene: bool,
+	priv skipping: bool,
+	// a transition played by an event, or as the player dies
+	priv transition: Option<transitions::Effect>,
+	priv dying: bool,
+	// the triggers (see `map::Map::triggers_at`) the player stood in when last checked
+	priv triggers: ~[uint],
+	priv camera: camera::Camera,
+	priv weather: Option<weather::Weather>,
+	priv hud: hud::Hud,
+	// the HUD of the second player, shown while there is one
+	priv second_hud: hud::Hud,
+	priv textbox: textbox::TextBox,
+
+	// outline hitboxes & collision tiles, for debugging
+	priv show_hitboxes: bool,
+	// whether the game may be saved & restored in memory, & the state saved
+	priv practice: bool,
+	priv savestate: Option<SaveState>,
+	// the player's run through the current map, & the best run before it (if shown)
+	priv recorder: ghost::Recorder,
+	priv ghost: Option<ghost::Ghost>,
+	priv show_ghosts: bool,
+	// counted across every game played, & saved along w/ the achievements unlocked
+	priv stats: stats::Stats,
+	// serves the second player, while the game is hosted
+	priv host: Option<net::Server>,
+	// shared w/ the world, camera & weather
+	priv rng: rng::Rng,
+	priv timer: speedrun::Timer
+}
+
+impl Playing {
+	/// Starts a new game on the first map, drawing its randomness from `rng`
+	/// & timed by `timer`.
+	pub fn new(
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio,
+		rng: rng::Rng,
+		timer: speedrun::Timer
+	) -> GameResult<Playing> {
+		Playing::on_map(display, audio, FIRST_MAP.to_owned(), None, rng, timer)
+	}
+
+	/// Starts a new game on the first map, as `new` does, hosted by `server`
+	/// whatever the settings say, e.g: for a dedicated server.
+	pub fn hosted(
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio,
+		server: net::Server,
+		rng: rng::Rng,
+		timer: speedrun::Timer
+	) -> GameResult<Playing> {
+		Playing::on_map(display, audio, FIRST_MAP.to_owned(), Some(server), rng, timer)
+	}
+
+	/// Stops hosting this game, handing back the server (if it was hosted),
+	/// e.g: to host the next game w/ once this one has ended.
+	pub fn take_host(&mut self) -> Option<net::Server> {
+		self.host.take()
+	}
+
+	/// Resumes the game recorded by `data`.
+	pub fn from_save(
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio,
+		data: &save::SaveData,
+		rng: rng::Rng,
+		timer: speedrun::Timer
+	) -> GameResult<Playing> {
+		let mut playing = try!(Playing::on_map(display, audio, data.map_path.clone(), None, rng, timer));
+		playing.world.player_mut().restore(data.position, data.hit_points);
+		playing.world.player_mut().arsenal_mut().restore(data.weapons.as_slice(), data.current_weapon);
+		playing.flags = flags::FlagSet::from_ids(data.flags.as_slice());
+		playing.items = data.items.clone();
+		playing.world.player_mut().equipment_mut().restore(data.equipment.as_slice());
+		playing.exploration.restore(data.explored.as_slice());
+		playing.populate(display);
+		// a run resumed part of the way through a map is not raced
+		playing.recorder.discard();
+		playing.ghost = None;
+
+		playing.camera = camera::Camera::new(
+			(playing.world.player().center_x(), playing.world.player().center_y()),
+			(playing.maps.current().width(), playing.maps.current().height()),
+			display.resolution(),
+			playing.rng.clone()
+		);
+		Ok(playing)
+	}
+
+	/// Captures the player's progress so that it may be saved.
+	pub fn save_data(&self) -> save::SaveData {
+		let quote = self.world.player();
+		save::SaveData {
+			map_path: self.maps.path(),
+			position: quote.position(),
+			hit_points: quote.hit_points(),
+			weapons: quote.arsenal().levels(),
+			current_weapon: quote.arsenal().index(),
+			items: self.items.clone(),
+			equipment: self.world.player().equipment().ids(),
+			flags: self.flags.ids(),
+			explored: self.exploration.entries()
+		}
+	}
+
+	/// Loads the map at `map_path`, spawning the player & its enemies, and starts its music.
+	///
+	/// The player starts at the map's `start` spawn point, or the center
+	/// of the screen if it has none. The game is hosted by `host`, if given,
+	/// or else on the port the settings name (if any.)
+	fn on_map(
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio,
+		map_path: ~str,
+		host: Option<net::Server>,
+		rng: rng::Rng,
+		timer: speedrun::Timer
+	) -> GameResult<Playing> {
+		let maps = try!(map::MapManager::new(map_path, display));
+		match maps.current().music() {
+			Some(track) => audio.play_track(track),
+			None => {}
+		}
+
+		let resolution = display.resolution();
+		let (x, y) = match maps.current().spawn_point("start") {
+			Some(position) => position,
+			None => (
+				(resolution.width / units::Tile(2)).to_game(),
+				(resolution.height / units::Tile(2)).to_game()
+			)
+		};
+		let quote = try!(player::Player::new(display, x, y));
+		let camera = camera::Camera::new(
+			(quote.center_x(), quote.center_y()),
+			(maps.current().width(), maps.current().height()),
+			resolution,
+			rng.clone()
+		);
+
+		let world = try!(world::World::new(display, quote, rng.clone()));
+
+		let weather = Playing::weather_of(maps.current(), display, &rng);
+		let settings = config::Settings::load(profile::settings_path().as_slice());
+		let recorder = ghost::Recorder::new(maps.path(), rng.seed());
+		let ghost = if settings.ghosts { ghost::Ghost::load(maps.path().as_slice(), world.player()) } else { None };
+		let host = match (host, settings.host) {
+			(Some(server), _) => Some(server),
+			(None, Some(port)) => match net::Server::host(port) {
+				Ok(server) => Some(server),
+				Err(msg) => { log::warn("playing", format!("the game could not be hosted on port {}: {}", port, msg)); None }
+			},
+			(None, None) => None
+		};
+
+		let mut playing = Playing {
+			world: 	world,
+			weather: weather,
+			maps: 	maps,
+			flags: 	flags::FlagSet::new(),
+			items: 	~[],
+			exploration: exploration::Exploration::new(),
+			script: None,
+			wait: None,
+			cutscene: false,
+			skipping: false,
+			transition: None,
+			dying: false,
+			triggers: ~[],
+			camera: camera,
+			hud: 	try!(hud::Hud::new(display)),
+			second_hud: try!(hud::Hud::for_second_player(display)),
+			textbox: try!(textbox::TextBox::new(display)),
+			show_hitboxes: false,
+			practice: settings.practice,
+			savestate: None,
+			recorder: recorder,
+			ghost: ghost,
+			show_ghosts: settings.ghosts,
+			stats: stats::Stats::load(profile::stats_path().as_slice()),
+			host: host,
+			rng: rng,
+			timer: timer
+		};
+		playing.populate(display);
+		Ok(playing)
+	}
+
+	/// Fills the world w/ the current map's enemies, npcs & boss, in place
+	/// of whatever it held before. (Those defeated for good are left out.)
+	fn populate(&mut self, display: &mut graphics::Graphics) {
+		self.world.clear();
+		for (enemy, flag) in self.maps.spawn_enemies(display, &self.flags).move_iter() {
+			self.world.add_flagged_enemy(enemy, flag);
+		}
+		self.spawn_npcs(display);
+		self.spawn_boss(display);
+	}
+
+	/// Places every npc of the current map. An npc whose sprites can not
+	/// be loaded is left out (& the error logged.)
+	fn spawn_npcs(&mut self, display: &mut graphics::Graphics) {
+		let spawns = self.maps.current().npcs().to_owned();
+		for spawn in spawns.iter() {
+			match npc::Npc::new(display, spawn) {
+				Ok(npc) => self.world.add_npc(npc),
+				Err(err) => log::error("playing", err.message())
+			}
+		}
+	}
+
+	/// Places the current map's boss, unless its flag shows it has already
+	/// been defeated. A boss which can not be loaded is left out (& the error logged.)
+	fn spawn_boss(&mut self, display: &mut graphics::Graphics) {
+		let boss = match self.maps.current().boss() {
+			Some((path, position)) => match boss::Boss::load(display, path.as_slice(), position) {
+				Ok(boss) => if self.flags.is_set(boss.flag()) { None } else { Some(boss) },
+				Err(err) => { log::error("playing", err.message()); None }
+			},
+			None => None
+		};
+		self.world.set_boss(boss);
+	}
+
+	/// Reacts to the boss being defeated: the screen shakes while it dies,
+	/// then its flag is set & its event (if any) is run.
+	fn check_boss(&mut self) {
+		let defeat = match self.world.boss_mut() {
+			Some(boss) => boss.take_defeat(),
+			None => None
+		};
+
+		match defeat {
+			Some(boss::Begun) => self.camera.shake(boss::DEFEAT_TIME),
+			Some(boss::Ended) => {
+				let (flag, event) = match self.world.boss() {
+					Some(boss) => (boss.flag(), boss.event()),
+					None => return
+				};
+				self.flags.set(flag);
+				self.stats.enemies_defeated += 1;
+				self.world.set_boss(None);
+				match event {
+					Some(id) => { self.script = self.maps.current().run_event(id); }
+					None => {}
+				}
+			}
+			None => {}
+		}
+	}
+
+	/// Runs the event of a trigger the player has just stepped into or out
+	/// of. Triggers are left be while an event already runs; should several
+	/// fire at once, only the first is run.
+	fn check_triggers(&mut self) {
+		if self.script.is_some() { return; }
+
+		let inside = self.maps.current().triggers_at(&self.world.player().damage_rectangle());
+		let mut runner = None;
+		for index in inside.iter().filter(|index| !self.triggers.contains(*index)) {
+			if runner.is_none() { runner = self.maps.current().run_trigger(*index, map::OnEnter); }
+		}
+		for index in self.triggers.iter().filter(|index| !inside.contains(*index)) {
+			if runner.is_none() { runner = self.maps.current().run_trigger(*index, map::OnExit); }
+		}
+
+		self.triggers = inside;
+		self.script = runner;
+	}
+
+	/// The weather falling on `map`, if it has any.
+	/// A map whose weather can not be loaded is left clear (& the error logged.)
+	fn weather_of(map: &map::Map, display: &mut graphics::Graphics, rng: &rng::Rng) -> Option<weather::Weather> {
+		match map.weather() {
+			Some(kind) => match weather::Weather::new(display, kind, rng.clone()) {
+				Ok(weather) => Some(weather),
+				Err(err) => { log::error("playing", err.message()); None }
+			},
+			None => None
+		}
+	}
+
+	/// Opens the map screen, unless it can not be loaded.
+	fn show_map(&self, display: &mut graphics::Graphics) -> Transition {
+		let quote = self.world.player();
+		let path = self.maps.path();
+		match states::MapScreen::new(
+			display, self.maps.current(), path.as_slice(), &self.exploration, (quote.center_x(), quote.center_y())
+		) {
+			Ok(map_screen) => states::Push(~map_screen as ~GameState),
+			Err(err) => { log::error("playing", err.message()); states::Stay }
+		}
+	}
+
+	/// Tallies what the player has done since this was last called, and
+	/// unlocks any achievements they have now earned.
+	fn track_stats(&mut self, elapsed_time: units::Millis) {
+		let (jumps, wounds) = self.world.player_mut().take_tallies();
+		self.stats.jumps += jumps;
+		self.stats.damage_taken += wounds as uint;
+		self.stats.enemies_defeated += self.world.take_kills();
+		self.stats.play_time = self.stats.play_time + elapsed_time;
+
+		for achievement in self.stats.check(&self.flags).iter() {
+			log::info("playing", format!("achievement unlocked: {}", achievement.id));
+		}
+	}
+
+	/// Pauses the game, unless the pause menu can not be loaded.
+	/// (The statistics are saved as the game is paused.)
+	fn pause(&self, display: &mut graphics::Graphics) -> Transition {
+		self.stats.save(profile::stats_path().as_slice());
+		match states::Paused::new(display, self.save_data(), self.stats.clone()) {
+			Ok(paused) => states::Push(~paused as ~GameState),
+			Err(err) => { log::error("playing", err.message()); states::Stay }
+		}
+	}
+
+	/// Carries out the commands of the running event until it either
+	/// finishes or waits on a message.
+	fn run_script(&mut self, display: &mut graphics::Graphics) {
+		loop {
+			let command = match self.script {
+				Some(ref mut runner) => runner.step(),
+				None => return
+			};
+
+			match command {
+				Some(script::Message(text)) => {
+					if !self.skipping { self.textbox.show(display, text); }
+					self.wait_on(Reading);
+				}
+				Some(script::MovePlayer(col, row)) => self.world.player_mut().teleport((col.to_game(), row.to_game())),
+				Some(script::GiveItem(id)) => if !self.items.contains(&id) { self.items.push(id) },
+				Some(script::Equip(id)) => match equipment::Item::from_id(id) {
+					Some(item) => self.world.player_mut().equipment_mut().equip(item),
+					None => log::warn("playing", format!("no equipment is numbered {}", id))
+				},
+				Some(script::Unequip(id)) => match equipment::Item::from_id(id) {
+					Some(item) => self.world.player_mut().equipment_mut().unequip(item),
+					None => log::warn("playing", format!("no equipment is numbered {}", id))
+				},
+				Some(script::SetFlag(id)) => self.flags.set(id),
+				Some(script::ClearFlag(id)) => self.flags.clear(id),
+				Some(script::ChangeMap(target_map, target_spawn, style)) => self.maps.travel(target_map, target_spawn, style),
+				Some(script::Shake(duration)) => self.camera.shake(duration),
+				Some(script::BeginCutscene) => { self.cutscene = true; }
+				Some(script::Wait(duration)) => self.wait_on(Sleeping(duration)),
+				Some(script::WalkTo(actor, col)) => self.walk_to(actor, col.to_game()),
+				Some(script::PanCamera(col, row, duration)) => {
+					let half_tile = units::Tile(1).to_game() / units::Game(2.0);
+					let bounds = (self.maps.current().width(), self.maps.current().height());
+					let duration = if self.skipping { units::Millis(0) } else { duration };
+					self.camera.pan_to((col.to_game() + half_tile, row.to_game() + half_tile), bounds, duration);
+					self.wait_on(Panning);
+				}
+				Some(script::FollowPlayer) => self.camera.follow(),
+				Some(script::FadeScreen(style, direction, duration)) => {
+					let duration = if self.skipping { units::Millis(0) } else { duration };
+					self.transition = Some(transitions::Effect::new(style, direction, duration));
+					self.wait_on(Transitioning);
+				}
+				Some(script::FinishRun) => {
+					self.timer.finish(self.maps.path().as_slice());
+					// the run through the last map ends here, rather than as it is left
+					self.recorder.save();
+					self.recorder.discard();
+				}
+				Some(script::End) => {}
+				None => break
+			}
+		}
+
+		let finished = match self.script {
+			Some(ref runner) => runner.is_finished(),
+			None => false
+		};
+		if finished {
+			// the camera & the player are handed back once the event is over
+			self.script = None;
+			self.wait = None;
+			self.cutscene = false;
+			self.skipping = false;
+			self.transition = None;
+			self.camera.follow();
+		}
+	}
+
+	/// Has the running event wait on `wait`; while skipping it carries on at once.
+	fn wait_on(&mut self, wait: Wait) {
+		if self.skipping { self.resume(); } else { self.wait = Some(wait); }
+	}
+
+	/// Lets the running event carry on past whatever it was waiting on.
+	fn resume(&mut self) {
+		self.wait = None;
+		match self.script {
+			Some(ref mut runner) => runner.resume(),
+			None => {}
+		}
+	}
+
+	/// Sends `actor` walking to `x`; while skipping they are placed there at once.
+	/// (An npc which is not on the map is not waited on.)
+	fn walk_to(&mut self, actor: script::Actor, x: units::Game) {
+		match actor {
+			script::Player => if self.skipping {
+				let (_, y) = self.world.player().position();
+				self.world.player_mut().teleport((x, y));
+			},
+			script::Npc(number) => {
+				let skipping = self.skipping;
+				let found = match self.npc_mut(number) {
+					Some(npc) => { if skipping { npc.place(x) } else { npc.walk_to(x) } true }
+					None => false
+				};
+				if !found {
+					log::warn("playing", format!("no npc {} to walk", number));
+					self.resume();
+					return;
+				}
+			}
+		}
+		self.wait_on(Walking(actor, x));
+	}
+
+	/// The npc numbered `number` (counting from 1, as in scripts.)
+	fn npc_mut<'a>(&'a mut self, number: uint) -> Option<&'a mut npc::Npc> {
+		if number == 0 { None } else { self.world.npc_mut(number - 1) }
+	}
+
+	/// Lets the running event carry on once whatever it waits on is done,
+	/// steering the player towards wherever a cutscene walks them.
+	fn check_wait(&mut self, elapsed_time: units::Millis) {
+		let wait = match self.wait.take() {
+			Some(Sleeping(left)) => Some(Sleeping(left - elapsed_time)),
+			wait => wait
+		};
+
+		let done = match wait {
+			Some(Reading) => !self.textbox.is_open(),
+			Some(Sleeping(left)) => left <= units::Millis(0),
+			Some(Walking(script::Player, x)) => {
+				let quote = self.world.player_mut();
+				let (player_x, _) = quote.position();
+				if player_x < x - ARRIVAL_DISTANCE {
+					quote.start_moving_right();
+					false
+				} else if player_x > x + ARRIVAL_DISTANCE {
+					quote.start_moving_left();
+					false
+				} else {
+					quote.stop_moving();
+					true
+				}
+			}
+			Some(Walking(script::Npc(number), _)) => self.npc_mut(number).map_or(true, |npc| npc.has_arrived()),
+			Some(Panning) => !self.camera.is_panning(),
+			Some(Transitioning) => self.transition.as_ref().map_or(true, |effect| effect.is_finished()),
+			None => false
+		};
+
+		if done { self.resume(); } else { self.wait = wait; }
+	}
+
+	/// Skips the rest of the running cutscene: whatever it waits on is
+	/// finished at once, & the rest of its commands carried out w/o delay.
+	fn skip_cutscene(&mut self) {
+		self.skipping = true;
+		self.textbox.close();
+		match self.wait.take() {
+			Some(Walking(actor, x)) => self.walk_to(actor, x),
+			_ => self.resume()
+		}
+	}
+
+	/// Moves the player as directed by `controller`.
+	fn control_player<C: input::Controls>(quote: &mut player::Player, controller: &C, audio: &audio::Audio) {
+		// Handle player movement
+		if controller.is_key_held(input::MoveLeft)
+			&& controller.is_key_held(input::MoveRight) {
+
+			quote.stop_moving();
+		} else if controller.is_key_held(input::MoveLeft) {
+			quote.start_moving_left();
+		} else if controller.is_key_held(input::MoveRight) {
+			quote.start_moving_right();
+		} else {
+			quote.stop_moving();
+		}
+
+		// Handle player looking (& climbing)
+		if controller.is_key_held(input::LookUp)
+			&& controller.is_key_held(input::LookDown) {
+
+			quote.look_horizontal();
+			quote.climb(0);
+		} else if controller.is_key_held(input::LookUp) {
+			quote.look_up();
+			quote.climb(-1);
+		} else if controller.is_key_held(input::LookDown) {
+			quote.look_down();
+			quote.climb(1);
+		} else {
+			quote.look_horizontal();
+			quote.climb(0);
+		}
+
+		// Handle player jump: jumping while holding down drops through platforms
+		if controller.was_key_pressed(input::Jump) && controller.is_key_held(input::LookDown) {
+			quote.drop_down();
+		} else if controller.was_key_pressed(input::Jump) {
+			quote.start_jump(audio, controller.press_age(input::Jump).unwrap_or(units::Millis(0)));
+		} else if controller.was_key_released(input::Jump) {
+			quote.stop_jump();
+		}
+	}
+
+	/// Lets a second player join in by jumping, then moves (& arms) them
+	/// as directed by `controller`.
+	fn control_second<C: input::Controls>(&mut self, controller: &C, display: &mut graphics::Graphics, audio: &audio::Audio) {
+		if self.world.players().len() < 2 {
+			if controller.was_key_pressed(input::Jump) { self.join(display); }
+			return;
+		}
+
+		Playing::control_player(&mut self.world.players_mut()[1], controller, audio);
+		if controller.was_key_pressed(input::Fire) {
+			self.world.fire(1, display, audio);
+		}
+		if controller.was_key_pressed(input::NextWeapon) {
+			self.world.players_mut()[1].switch_weapon(1);
+		} else if controller.was_key_pressed(input::PrevWeapon) {
+			self.world.players_mut()[1].switch_weapon(-1);
+		}
+	}
+
+	/// Sends the remote player a snapshot of the world, when one is due,
+	/// & lets their player go should they have left.
+	fn serve(&mut self, elapsed_time: units::Millis, display: &graphics::Graphics) {
+		let (due, connected) = match self.host {
+			Some(ref mut server) => (server.update(elapsed_time), server.is_connected()),
+			None => return
+		};
+		if !connected && self.world.players().len() > 1 {
+			self.world.remove_player(1);
+			log::info("playing", "the second player has left");
+		}
+		if !due { return; }
+
+		// the world is drawn whole, in map coordinates, for the client to place
+		let mut queue = graphics::RenderQueue::unculled();
+		self.world.draw(&mut queue, (units::Game(0.0), units::Game(0.0)), 1.0);
+		let (focus, status) = if self.world.players().len() > 1 {
+			let second = &self.world.players()[1];
+			let status = net::Status {
+				hit_points: second.hit_points(), max_hit_points: second.max_hit_points(),
+				level: second.weapon().experience().level(), energy: second.weapon().experience().energy(),
+				ammo: second.weapon().ammo(), fuel: second.fuel()
+			};
+			((second.center_x(), second.center_y()), Some(status))
+		} else {
+			((self.world.player().center_x(), self.world.player().center_y()), None)
+		};
+		let snapshot = net::Snapshot::capture(display, &mut queue, self.maps.path(), focus, status);
+
+		match self.host {
+			Some(ref mut server) => server.send(snapshot),
+			None => {}
+		}
+	}
+
+	/// Brings a second player into the game, beside the first.
+	fn join(&mut self, display: &mut graphics::Graphics) {
+		let (x, y) = self.world.player().position();
+		match player::Player::new(display, x, y) {
+			Ok(quote) => {
+				self.world.add_player(quote);
+				log::info("playing", "a second player has joined");
+			}
+			Err(err) => log::error("playing", err.message())
+		}
+	}
+
+	/// Stops every player in their tracks, e.g: while an event runs.
+	fn stop_players(&mut self) {
+		for quote in self.world.players_mut().mut_iter() { quote.stop_moving(); }
+	}
+
+	/// Where the camera looks: midway between every player.
+	fn focus(&self) -> (units::Game, units::Game) {
+		let players = self.world.players();
+		let count = units::Game(players.len() as f64);
+		let (mut x, mut y) = (units::Game(0.0), units::Game(0.0));
+		for quote in players.iter() {
+			x = x + quote.center_x();
+			y = y + quote.center_y();
+		}
+		(x / count, y / count)
+	}
+
+	/// Pulls any player who has strayed too far from the first to share the
+	/// view w/ them (as `focus()` keeps both in it) back to the first's side.
+	fn gather(&mut self) {
+		let (screen_w, screen_h) = self.camera.screen();
+		let margin = STRAY_MARGIN.to_game();
+		let lead = self.world.player().position();
+		let (lead_x, lead_y) = lead;
+		for quote in self.world.players_mut().mut_iter().skip(1) {
+			let (x, y) = quote.position();
+			let (dx, dy) = (cmp::max(x - lead_x, lead_x - x), cmp::max(y - lead_y, lead_y - y));
+			if dx > screen_w - margin || dy > screen_h - margin {
+				quote.teleport(lead);
+				log::info("playing", "a straggling player was pulled back into view");
+			}
+		}
+	}
+
+	/// Places the players at `position` on a newly entered map, and clears
+	/// out everything which belonged to the last one.
+	fn arrive(
+		&mut self,
+		position: (units::Game, units::Game),
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) {
+		for quote in self.world.players_mut().mut_iter() {
+			let hit_points = quote.hit_points();
+			quote.restore(position, hit_points);
+		}
+		self.populate(display);
+		self.weather = Playing::weather_of(self.maps.current(), display, &self.rng);
+		// arriving inside a trigger counts as entering it
+		self.triggers = ~[];
+
+		// the run through the last map is kept if it beat its ghost
+		self.recorder.save();
+		self.stats.save(profile::stats_path().as_slice());
+		self.recorder = ghost::Recorder::new(self.maps.path(), self.rng.seed());
+		self.ghost = if self.show_ghosts {
+			ghost::Ghost::load(self.maps.path().as_slice(), self.world.player())
+		} else {
+			None
+		};
+
+		match self.maps.current().music() {
+			Some(track) => audio.play_track(track),
+			None => audio.stop_music()
+		}
+
+		// whatever only the last map used may now be freed
+		display.unload_unused();
+		audio.unload_unused();
+
+		self.camera = camera::Camera::new(
+			(self.world.player().center_x(), self.world.player().center_y()),
+			(self.maps.current().width(), self.maps.current().height()),
+			display.resolution(),
+			self.rng.clone()
+		);
+	}
+
+	/// Responds true if the game may be saved (or restored) in memory now:
+	/// not while an event runs, nor while the screen is transitioning.
+	fn can_savestate(&self) -> bool {
+		self.script.is_none() && !self.maps.is_transitioning() && (self.dying || self.transition.is_none())
+	}
+
+	/// Saves the whole game as it is now in memory, in place of any state saved before.
+	fn save_state(&mut self) {
+		if !self.can_savestate() || self.dying {
+			log::warn("playing", "the state can not be saved right now");
+			return;
+		}
+		self.savestate = Some(SaveState {
+			world: self.world.snapshot(),
+			map_path: self.maps.path(),
+			terrain: self.maps.current().terrain(),
+			flags: self.flags.clone(),
+			items: self.items.clone(),
+			exploration: self.exploration.clone(),
+			camera: self.camera.clone(),
+			rng: self.rng.state()
+		});
+		log::info("playing", "state saved");
+	}
+
+	/// Puts the game back as it was when the state was last saved, so long
+	/// as it was saved on the current map. (A dying player is brought back.)
+	fn load_state(&mut self) {
+		if !self.can_savestate() {
+			log::warn("playing", "the state can not be loaded right now");
+			return;
+		}
+		let state = match self.savestate {
+			Some(ref state) if state.map_path == self.maps.path() => state.clone(),
+			Some(_) => { log::warn("playing", "the saved state is of another map"); return; }
+			None => { log::warn("playing", "no state has been saved"); return; }
+		};
+
+		self.world.restore(state.world);
+		self.maps.current_mut().restore_terrain(state.terrain);
+		self.flags = state.flags;
+		self.items = state.items;
+		self.exploration = state.exploration;
+		self.camera = state.camera;
+		self.rng.restore(state.rng);
+		// triggers the player stands in are entered anew
+		self.triggers = ~[];
+		self.dying = false;
+		self.transition = None;
+		// a run rewound part of the way through is no longer raced
+		self.recorder.discard();
+		self.ghost = None;
+	}
+}
+
+impl GameState for Playing {
+	fn handle_input(
+		&mut self,
+		controller: &input::Input,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		let remote = match self.host {
+			Some(ref mut server) => { server.poll(); Some(server.input()) }
+			None => None
+		};
+
+		if controller.was_key_pressed(input::ToggleHitboxes) {
+			self.show_hitboxes = !self.show_hitboxes;
+		}
+
+		// Handle pause
+		if controller.was_key_pressed(input::Pause) {
+			return self.pause(display);
+		}
+
+		// in practice mode the game may be saved & restored at will
+		if self.practice {
+			if controller.was_key_pressed(input::SaveState) {
+				self.save_state();
+			} else if controller.was_key_pressed(input::LoadState) {
+				self.load_state();
+			}
+		}
+
+		// a player who has died is out of the player's hands
+		if self.dying {
+			self.stop_players();
+			return states::Stay;
+		}
+
+		// While an event runs the player may only continue through its
+		// messages (or skip it, if it is a cutscene)
+		if self.script.is_some() {
+			if self.cutscene && controller.was_key_pressed(input::Skip) {
+				self.skip_cutscene();
+			} else if controller.was_key_pressed(input::Jump)
+				|| controller.was_key_pressed(input::Fire) {
+				self.textbox.advance();
+			}
+			self.stop_players();
+			return states::Stay;
+		}
+
+		if controller.was_key_pressed(input::ToggleMap) {
+			return self.show_map(display);
+		}
+
+		Playing::control_player(self.world.player_mut(), &controller.seat(0), audio);
+
+		// a second player joins in by jumping, then controls their own: from
+		// the second set of keys, or (while hosting) from another machine
+		match remote {
+			Some(Some(input)) => self.control_second(&input, display, audio),
+			Some(None) => {}
+			None => self.control_second(&controller.seat(1), display, audio)
+		}
+
+		// Handle interaction: talking to an npc runs its event, examining
+		// an open door leaves the map, otherwise any trigger the player stands
+		// in (or else event on their tile) is run.
+		if controller.was_key_pressed(input::LookDown) && self.world.player().is_interacting() {
+			let player_rect = self.world.player().damage_rectangle();
+			match self.world.npc_event_at(&player_rect) {
+				Some(id) => { self.script = self.maps.current().run_event(id); }
+				None => match self.maps.current().door_at(&player_rect) {
+					Some(door) => if door.is_open(&self.flags) { self.maps.enter(door) },
+					None => {
+						self.script = match self.maps.current().run_trigger_at(&player_rect) {
+							Some(runner) => Some(runner),
+							None => self.maps.current().run_event_at(&player_rect)
+						};
+					}
+				}
+			}
+		}
+
+		// Handle player fire
+		if controller.was_key_pressed(input::Fire) {
+			self.world.fire(0, display, audio);
+		}
+
+		// Handle weapon switching
+		if controller.was_key_pressed(input::NextWeapon) {
+			self.world.player_mut().switch_weapon(1);
+		} else if controller.was_key_pressed(input::PrevWeapon) {
+			self.world.player_mut().switch_weapon(-1);
+		}
+
+		states::Stay
+	}
+
+	/// Passes the current time in milliseconds to our underlying actors.
+	fn update(
+		&mut self,
+		elapsed_time: units::Millis,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		self.timer.tick(elapsed_time);
+		self.serve(elapsed_time, display);
+
+		// the world is frozen while the screen fades between maps
+		if self.maps.is_transitioning() {
+			let leaving = self.maps.path();
+			self.timer.begin_load();
+			match self.maps.update(elapsed_time, display) {
+				Some(position) => {
+					self.timer.split(leaving.as_slice());
+					self.arrive(position, display, audio);
+				}
+				None => {}
+			}
+			self.timer.end_load();
+			return states::Stay;
+		}
+
+		match self.transition {
+			Some(ref mut effect) => effect.update(elapsed_time),
+			None => {}
+		}
+		self.check_wait(elapsed_time);
+		self.run_script(display);
+
+		// the world is also frozen while a message is shown
+		if self.textbox.is_open() {
+			self.textbox.update(elapsed_time);
+			return states::Stay;
+		}
+
+		self.maps.current_mut().update(elapsed_time);
+		self.world.update(elapsed_time, display, self.maps.current_mut(), audio);
+		self.recorder.record(self.world.player());
+		match self.ghost {
+			Some(ref mut ghost) => ghost.update(elapsed_time),
+			None => {}
+		}
+		self.check_boss();
+		self.check_triggers();
+		if self.world.players().iter().any(|quote| quote.has_landed_hard()) {
+			self.camera.shake(HEAVY_LANDING_SHAKE);
+		}
+		// a second player who falls drops out, & may join in again
+		if self.world.players().len() > 1 && self.world.players()[1].is_dead() {
+			self.world.remove_player(1);
+			log::info("playing", "the second player has fallen");
+		}
+		// enemies w/ a defeated flag stay gone once beaten
+		for id in self.world.take_defeated().iter() { self.flags.set(*id); }
+		self.track_stats(elapsed_time);
+		self.gather();
+
+		let focus = self.focus();
+		let quote = self.world.player();
+		let bounds = (self.maps.current().width(), self.maps.current().height());
+		self.camera.update(elapsed_time, focus, bounds);
+		audio.set_listener(self.camera.center(), self.camera.screen());
+		self.exploration.explore(self.maps.path().as_slice(), (quote.center_x(), quote.center_y()), bounds);
+
+		match self.weather {
+			Some(ref mut weather) => weather.update(elapsed_time, self.maps.current(), self.camera.offset(1.0)),
+			None => {}
+		}
+
+		self.hud.update(
+			quote.hit_points(), quote.max_hit_points(),
+			quote.weapon().experience(), quote.weapon().ammo()
+		);
+		self.hud.update_fuel(quote.fuel());
+		self.hud.update_boss(self.world.boss().map(|boss| (boss.hit_points(), boss.max_hit_points())));
+		if self.world.players().len() > 1 {
+			let second = &self.world.players()[1];
+			self.second_hud.update(
+				second.hit_points(), second.max_hit_points(),
+				second.weapon().experience(), second.weapon().ammo()
+			);
+			self.second_hud.update_fuel(second.fuel());
+		}
+
+		if !quote.is_dead() { return states::Stay; }
+
+		// the view closes in on the player before the game is over
+		if !self.dying {
+			self.dying = true;
+			self.transition = Some(transitions::Effect::new(transitions::Iris, transitions::Out, DEATH_TRANSITION_TIME));
+		}
+		match self.transition {
+			Some(ref effect) if !effect.is_finished() => states::Stay,
+			_ => {
+				self.stats.save(profile::stats_path().as_slice());
+				states::Push(~states::GameOver::new(self.rng.clone(), self.timer.clone()) as ~GameState)
+			}
+		}
+	}
+
+	/// The player's position & velocity, and how much is in the world.
+	fn debug_info(&self) -> ~[~str] {
+		let quote = self.world.player();
+		let (units::Game(x), units::Game(y)) = quote.position();
+		let (units::Velocity(velocity_x), units::Velocity(velocity_y)) = quote.velocity();
+		~[
+			format!("pos {:.1f} {:.1f}", x, y),
+			format!("vel {:.3f} {:.3f}", velocity_x, velocity_y),
+			format!("weapon {}", quote.weapon().name()),
+			format!("enemies {}", self.world.count(entity::Enemy)),
+			format!("shots {}", self.world.count(entity::Projectile)),
+			format!("pickups {}", self.world.count(entity::Pickup)),
+			format!("npcs {}", self.world.count(entity::Npc)),
+			format!("ghost seed {}", self.ghost.as_ref().and_then(|ghost| ghost.seed())
+				.map_or(~"none", |seed| seed.to_str()))
+		]
+	}
+
+	/// Pauses the game, as if the player had pressed pause.
+	fn on_focus_lost(&mut self, display: &mut graphics::Graphics) -> Transition {
+		self.pause(display)
+	}
+
+	/// Rebuilds the current map if its file was edited; the player & the
+	/// rest of the world stay as they are.
+	fn on_maps_changed(&mut self, paths: &[~str], display: &mut graphics::Graphics) {
+		if paths.contains(&self.maps.path()) { self.maps.reload(display); }
+	}
+
+	/// Instructs our actors to draw their current state to the screen.
+	fn draw(&self, display: &graphics::Graphics, alpha: f64) {
+		let offset = self.camera.offset(alpha);
+
+		let mut queue = graphics::RenderQueue::new(display);
+		self.maps.current().draw_background(display, &mut queue, offset);
+		self.maps.current().draw_sprites(display, &mut queue, offset);
+		match self.ghost {
+			Some(ref ghost) => ghost.draw(&mut queue, offset),
+			None => {}
+		}
+		self.world.draw(&mut queue, offset, alpha);
+		self.maps.current().draw(display, &mut queue, offset);
+		queue.flush(display);
+
+		// overlays, in screen space, are drawn atop everything queued
+		if self.show_hitboxes {
+			self.world.draw_hitboxes(display, offset, self.maps.current());
+		}
+		match self.weather {
+			Some(ref weather) => weather.draw(display),
+			None => {}
+		}
+		if self.maps.current().is_dark() {
+			let (offset_x, offset_y) = offset;
+			let mut lights = self.world.lights();
+			for light in self.maps.current().lights().iter() { lights.push(*light); }
+
+			// lights are given in map coordinates, but drawn on screen
+			for light in lights.mut_iter() {
+				light.x = light.x - offset_x;
+				light.y = light.y - offset_y;
+			}
+			display.draw_darkness(DARKNESS, lights.as_slice());
+		}
+		self.hud.draw(display);
+		if self.world.players().len() > 1 { self.second_hud.draw(display); }
+		self.textbox.draw(display);
+
+		// transitions, last of all, close in on the player
+		let (offset_x, offset_y) = offset;
+		let focus = (self.world.player().center_x() - offset_x, self.world.player().center_y() - offset_y);
+		self.maps.draw_transition(display, focus);
+		match self.transition {
+			Some(ref effect) => effect.draw(display, focus),
+			None => {}
+		}
+	}
+}
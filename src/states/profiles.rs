@@ -0,0 +1,129 @@
+use game::audio;
+use game::config;
+use game::graphics;
+use game::i18n;
+use game::input;
+use game::log;
+use game::profile;
+use game::rng;
+use game::speedrun;
+
+use game::error::GameResult;
+use game::states;
+use game::states::{GameState, Transition};
+use game::states::options;
+use game::units;
+
+/// Shown before the title screen: picks the profile whose settings, key
+/// bindings, saves & statistics are used, or starts a new one.
+///
+/// The profile chosen last starts under the cursor. Choosing a profile
+/// applies its settings & bindings at once (all but the resolution, which
+/// only takes effect the next time the game is started.)
+pub struct ProfileSelect {
+	priv font: graphics::Font,
+	priv selected: uint,
+	// every existing profile, in order
+	priv names: ~[~str],
+	// handed on to the title screen
+	priv rng: rng::Rng,
+	priv timer: speedrun::Timer
+}
+
+impl ProfileSelect {
+	pub fn new(display: &mut graphics::Graphics, rng: rng::Rng, timer: speedrun::Timer) -> GameResult<ProfileSelect> {
+		let mut names = profile::list();
+		if names.is_empty() { names.push(profile::DEFAULT_PROFILE.to_owned()); }
+		let active = profile::name();
+
+		Ok(ProfileSelect {
+			font: try!(graphics::Font::load(display)),
+			selected: names.iter().position(|name| *name == active).unwrap_or(0),
+			names: names,
+			rng: rng,
+			timer: timer
+		})
+	}
+
+	/// The first name, of the form `profile<n>`, no profile has yet.
+	fn unused_name(&self) -> ~str {
+		let mut number = self.names.len() + 1;
+		loop {
+			let name = format!("profile{}", number);
+			if !self.names.contains(&name) { return name; }
+			number += 1;
+		}
+	}
+
+	/// Makes `name` the active profile, applies its settings & bindings,
+	/// & goes on to the title screen.
+	fn choose(
+		&self,
+		name: &str,
+		controller: &input::Input,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		if !profile::activate(name) { return states::Stay; }
+
+		let settings = config::Settings::load(profile::settings_path().as_slice());
+		settings.apply_volumes(audio);
+		display.set_scale(settings.scale);
+		if display.is_fullscreen() != settings.fullscreen { display.toggle_fullscreen(); }
+		if settings.language != i18n::language() && i18n::set_language(settings.language.as_slice()) {
+			display.prepare_glyphs(i18n::characters());
+		}
+		controller.set_bindings(config::Bindings::load(profile::bindings_path().as_slice()));
+
+		match states::TitleScreen::new(display, self.rng.clone(), self.timer.clone()) {
+			Ok(title) => states::Replace(~title as ~GameState),
+			Err(err) => { log::error("profiles", err.message()); states::Stay }
+		}
+	}
+}
+
+impl GameState for ProfileSelect {
+	fn handle_input(
+		&mut self,
+		controller: &input::Input,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		if controller.was_key_pressed(input::Pause) || controller.was_key_pressed(input::Quit) {
+			return states::Quit;
+		}
+
+		// every profile is listed, then the entry starting a new one
+		let count = self.names.len() + 1;
+		if controller.was_key_pressed(input::LookUp) {
+			self.selected = (self.selected + count - 1) % count;
+		} else if controller.was_key_pressed(input::LookDown) {
+			self.selected = (self.selected + 1) % count;
+		}
+
+		if !controller.was_key_pressed(input::Jump) { return states::Stay; }
+		let name = if self.selected < self.names.len() {
+			self.names[self.selected].clone()
+		} else {
+			self.unused_name()
+		};
+		self.choose(name.as_slice(), controller, display, audio)
+	}
+
+	#[allow(unused_variable)]
+	fn update(
+		&mut self,
+		elapsed_time: units::Millis,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		states::Stay
+	}
+
+	#[allow(unused_variable)]
+	fn draw(&self, display: &graphics::Graphics, alpha: f64) {
+		let mut rows: ~[(~str, ~str)] = self.names.iter().map(|name| (name.clone(), ~"")).collect();
+		rows.push((i18n::text("profiles.new"), ~""));
+		options::draw_rows(display, &self.font, i18n::text("profiles.title"), rows.as_slice(), self.selected);
+	}
+}
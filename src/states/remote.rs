@@ -0,0 +1,190 @@
+use collections::hashmap::HashMap;
+
+use sdl2::rect;
+
+use game::audio;
+use game::camera;
+use game::graphics;
+use game::hud;
+use game::input;
+use game::log;
+use game::map;
+use game::net;
+use game::rng;
+use game::weapons;
+
+use game::error::GameResult;
+use game::states;
+use game::states::{GameState, Transition};
+use game::units;
+use game::units::{AsPixel};
+
+/// Plays as the second player of a game hosted on another machine (see
+/// `net::Client`): this machine sends its inputs, & draws each snapshot
+/// the host sends back atop its own copy of the map.
+///
+/// Only what the host draws of the world is sent: sound effects, messages
+/// & tiles destroyed on the host are not seen here. Once the host closes
+/// (or the player pauses) the game quits, as it was started only to join.
+pub struct RemoteGame {
+	priv client: net::Client,
+	// the map being played, & the path it was loaded from
+	priv map: Option<(~str, map::Map)>,
+	priv camera: Option<camera::Camera>,
+	priv hud: hud::Hud,
+	// every sheet a snapshot has drawn from, by its path
+	priv sheets: HashMap<~str, graphics::Texture>,
+	// shared w/ the camera
+	priv rng: rng::Rng
+}
+
+impl RemoteGame {
+	pub fn new(display: &mut graphics::Graphics, client: net::Client, rng: rng::Rng) -> GameResult<RemoteGame> {
+		Ok(RemoteGame {
+			client: client,
+			map: None,
+			camera: None,
+			hud: try!(hud::Hud::new(display)),
+			sheets: HashMap::new(),
+			rng: rng
+		})
+	}
+
+	/// Catches up w/ the latest snapshot: loads the map it is played on (if
+	/// it is not the one loaded), & any sheet it draws from not yet loaded.
+	fn on_snapshot(&mut self, display: &mut graphics::Graphics, audio: &mut audio::Audio) {
+		let snapshot = match self.client.latest() {
+			Some(snapshot) => snapshot.clone(),
+			None => return
+		};
+
+		let is_loaded = match self.map {
+			Some((ref path, _)) => *path == snapshot.map_path,
+			None => false
+		};
+		if !is_loaded {
+			match map::Map::load_from_file(snapshot.map_path.clone(), display) {
+				Ok(map) => {
+					match self.map.take() {
+						Some((_, old)) => old.release(display),
+						None => {}
+					}
+					match map.music() {
+						Some(track) => audio.play_track(track),
+						None => audio.stop_music()
+					}
+					self.camera = Some(camera::Camera::new(snapshot.focus, (map.width(), map.height()), display.resolution(), self.rng.clone()));
+					self.map = Some((snapshot.map_path.clone(), map));
+				}
+				Err(err) => log::error("remote", err.message())
+			}
+		}
+
+		for &(ref path, options) in snapshot.sheets.iter() {
+			if self.sheets.contains_key(path) { continue; }
+			match display.load_image(path.clone(), options) {
+				Ok(texture) => { self.sheets.insert(path.clone(), texture); }
+				Err(err) => log::warn("remote", err.message())
+			}
+		}
+
+		match snapshot.status {
+			Some(ref status) => {
+				let experience = weapons::Experience::restore(status.level, status.energy);
+				self.hud.update(status.hit_points, status.max_hit_points, &experience, status.ammo);
+				self.hud.update_fuel(status.fuel);
+			}
+			None => {}
+		}
+	}
+}
+
+impl GameState for RemoteGame {
+	#[allow(unused_variable)]
+	fn handle_input(
+		&mut self,
+		controller: &input::Input,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		if controller.was_key_pressed(input::Pause) || controller.was_key_pressed(input::Quit) {
+			return states::Quit;
+		}
+
+		self.client.send_inputs(&controller.seat(0));
+		states::Stay
+	}
+
+	fn update(
+		&mut self,
+		elapsed_time: units::Millis,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		if self.client.poll() { self.on_snapshot(display, audio); }
+		self.client.update(elapsed_time);
+		match self.client.ended() {
+			Some(reason) => { log::info("remote", format!("leaving: {}", reason)); return states::Quit; }
+			None => {}
+		}
+
+		match self.map {
+			Some((_, ref mut map)) => {
+				map.update(elapsed_time);
+				match (self.client.focus(), &mut self.camera) {
+					(Some(focus), &Some(ref mut camera)) => camera.update(elapsed_time, focus, (map.width(), map.height())),
+					_ => {}
+				}
+			}
+			None => {}
+		}
+		states::Stay
+	}
+
+	fn draw(&self, display: &graphics::Graphics, alpha: f64) {
+		let (map, camera) = match (&self.map, &self.camera) {
+			(&Some((_, ref map)), &Some(ref camera)) => (map, camera),
+			// (nothing is drawn until the first snapshot arrives)
+			_ => return
+		};
+		let latest = match self.client.latest() {
+			Some(latest) => latest,
+			None => return
+		};
+		let offset = camera.offset(alpha);
+		let (offset_x, offset_y) = offset;
+		let (units::Pixel(shift_x), units::Pixel(shift_y)) = (offset_x.to_pixel(), offset_y.to_pixel());
+
+		let mut queue = graphics::RenderQueue::new(display);
+		map.draw_background(display, &mut queue, offset);
+		map.draw_sprites(display, &mut queue, offset);
+
+		// the snapshot's calls are in map coordinates
+		for call in self.client.calls().iter() {
+			match *call {
+				net::Blit(sheet, layer, src, dest, params) => match self.sheets.find_equiv(&latest.sheet_path(sheet)) {
+					Some(texture) => {
+						let dest = rect::Rect::new(dest.x - shift_x, dest.y - shift_y, dest.w, dest.h);
+						queue.submit(layer, *texture, &src, &dest, &params);
+					}
+					None => {}
+				},
+				net::Fill(layer, dest, color) => {
+					let dest = rect::Rect::new(dest.x - shift_x, dest.y - shift_y, dest.w, dest.h);
+					queue.fill(layer, &dest, color);
+				}
+			}
+		}
+
+		map.draw(display, &mut queue, offset);
+		queue.flush(display);
+		if latest.status.is_some() { self.hud.draw(display); }
+	}
+
+	fn debug_info(&self) -> ~[~str] {
+		match self.client.latest() {
+			Some(latest) => ~[format!("remote: {} calls, {} sheets", latest.calls.len(), latest.sheets.len())],
+			None => ~[~"remote: waiting on the host"]
+		}
+	}
+}
@@ -0,0 +1,103 @@
+use game::audio;
+use game::graphics;
+use game::input;
+use game::log;
+use game::rng;
+use game::save;
+use game::speedrun;
+use game::sprite;
+use game::sprite::Drawable;
+
+use game::error::GameResult;
+use game::states;
+use game::states::{GameState, Transition};
+use game::units;
+
+// the logo on the title sheet (expressed as `units::Game`)
+static LOGO_SOURCE: (units::Game, units::Game) 	= (units::Game(0.0), units::Game(0.0));
+static LOGO_SIZE: (units::Game, units::Game) 	= (units::Game(288.0), units::Game(80.0));
+static LOGO_Y: units::Game 						= units::Game(96.0);
+
+/// The first screen shown: waits for the player to start a game.
+///
+/// Jumping continues from the quick-save slot if it holds a save,
+/// while firing always starts a new game. If the game can not be loaded
+/// the error is logged, and the title screen remains.
+pub struct TitleScreen {
+	priv logo: sprite::Sprite,
+	// shared w/ every game started from here
+	priv rng: rng::Rng,
+	priv timer: speedrun::Timer
+}
+
+impl TitleScreen {
+	pub fn new(display: &mut graphics::Graphics, rng: rng::Rng, timer: speedrun::Timer) -> GameResult<TitleScreen> {
+		let (units::Game(logo_w), _) = LOGO_SIZE;
+		let (units::Game(screen_w), _) = display.resolution().to_game();
+
+		Ok(TitleScreen {
+			logo: try!(sprite::Sprite::from_region(
+				display,
+				(units::Game((screen_w - logo_w) / 2.0), LOGO_Y),
+				LOGO_SOURCE, LOGO_SIZE,
+				~"assets/base/Title.bmp"
+			)),
+			rng: rng,
+			timer: timer
+		})
+	}
+
+	// starts a new game on the first map, & a new speedrun w/ it
+	fn new_game(&self, display: &mut graphics::Graphics, audio: &mut audio::Audio) -> GameResult<states::Playing> {
+		let playing = states::Playing::new(display, audio, self.rng.clone(), self.timer.clone());
+		if playing.is_ok() { self.timer.start(); }
+		playing
+	}
+}
+
+impl GameState for TitleScreen {
+	fn handle_input(
+		&mut self,
+		controller: &input::Input,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		// only a new game starts a speedrun; continuing a save discards any run
+		let playing = if controller.was_key_pressed(input::Jump) {
+			match save::load_from_slot(save::QUICK_SLOT) {
+				Some(data) => {
+					self.timer.reset();
+					states::Playing::from_save(display, audio, &data, self.rng.clone(), self.timer.clone())
+				}
+				None => self.new_game(display, audio)
+			}
+		} else if controller.was_key_pressed(input::Fire) {
+			self.new_game(display, audio)
+		} else if controller.was_key_pressed(input::Pause)
+			|| controller.was_key_pressed(input::Quit) {
+			return states::Quit;
+		} else {
+			return states::Stay;
+		};
+
+		match playing {
+			Ok(playing) => states::Replace(~playing as ~GameState),
+			Err(err) => { log::error("title", err.message()); states::Stay }
+		}
+	}
+
+	#[allow(unused_variable)]
+	fn update(
+		&mut self,
+		elapsed_time: units::Millis,
+		display: &mut graphics::Graphics,
+		audio: &mut audio::Audio
+	) -> Transition {
+		states::Stay
+	}
+
+	#[allow(unused_variable)]
+	fn draw(&self, display: &graphics::Graphics, alpha: f64) {
+		self.logo.draw(display, (units::Game(0.0), units::Game(0.0)));
+	}
+}
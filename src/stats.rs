@@ -0,0 +1,154 @@
+use std::io;
+use std::io::File;
+use std::io::fs;
+
+use game::flags;
+use game::log;
+use game::units;
+
+/// What must happen for an achievement to be unlocked.
+pub enum Condition {
+	/// Jumping this many times.
+	Jumps(uint),
+	/// Taking this much damage, all told.
+	DamageTaken(uint),
+	/// Defeating this many enemies (bosses included.)
+	EnemiesDefeated(uint),
+	/// Playing for this long.
+	PlayTime(units::Millis),
+	/// Setting the event flag numbered so, e.g: by defeating a boss.
+	Flag(uint)
+}
+
+/// A goal for the player, named (& described) in the string tables under
+/// `achievement.<id>` & `achievement.<id>.description`.
+pub struct Achievement {
+	id: &'static str,
+	condition: Condition
+}
+
+/// Every achievement there is, in the order they are listed.
+pub static ACHIEVEMENTS: [Achievement, ..7] = [
+	Achievement { id: "hop", 			condition: Jumps(100) },
+	Achievement { id: "kangaroo", 		condition: Jumps(1000) },
+	Achievement { id: "hunter", 		condition: EnemiesDefeated(10) },
+	Achievement { id: "exterminator", 	condition: EnemiesDefeated(100) },
+	Achievement { id: "battered", 		condition: DamageTaken(100) },
+	Achievement { id: "devoted", 		condition: PlayTime(units::Millis(60 * 60 * 1000)) },
+	Achievement { id: "balrog", 		condition: Flag(100) }
+];
+
+impl Achievement {
+	/// Responds true if `stats` (& the `flags` set) meet this achievement's condition.
+	pub fn is_met(&self, stats: &Stats, flags: &flags::FlagSet) -> bool {
+		match self.condition {
+			Jumps(count) 			=> stats.jumps >= count,
+			DamageTaken(amount) 	=> stats.damage_taken >= amount,
+			EnemiesDefeated(count) 	=> stats.enemies_defeated >= count,
+			PlayTime(time) 			=> stats.play_time >= time,
+			Flag(id) 				=> flags.is_set(id)
+		}
+	}
+}
+
+/// Counts of what the player has done, across every game they have played.
+#[deriving(Clone)]
+pub struct Stats {
+	jumps: uint,
+	damage_taken: uint,
+	enemies_defeated: uint,
+	play_time: units::Millis,
+	/// Ids of every achievement unlocked, in the order they were.
+	unlocked: ~[~str]
+}
+
+impl Stats {
+	pub fn new() -> Stats {
+		Stats {
+			jumps: 0,
+			damage_taken: 0,
+			enemies_defeated: 0,
+			play_time: units::Millis(0),
+			unlocked: ~[]
+		}
+	}
+
+	pub fn is_unlocked(&self, achievement: &Achievement) -> bool {
+		self.unlocked.iter().any(|id| id.as_slice() == achievement.id)
+	}
+
+	/// Unlocks every achievement whose condition has now been met,
+	/// responding w/ those which were.
+	pub fn check(&mut self, flags: &flags::FlagSet) -> ~[&'static Achievement] {
+		let mut unlocked = ~[];
+		for achievement in ACHIEVEMENTS.iter() {
+			if !self.is_unlocked(achievement) && achievement.is_met(&*self, flags) {
+				self.unlocked.push(achievement.id.to_owned());
+				unlocked.push(achievement);
+			}
+		}
+		unlocked
+	}
+
+	/// Reads the statistics saved at `path`; if there are none yet every count starts at zero.
+	///
+	/// Each line of the file is a statistic's name followed by its count,
+	/// e.g: `jumps 120` or `play_time 360000` (in milliseconds), then an
+	/// `unlocked` line of the ids of the achievements unlocked.
+	pub fn load(path: &str) -> Stats {
+		let mut stats = Stats::new();
+		let file_path = Path::new(path);
+		if !file_path.exists() { return stats; }
+
+		let contents = match File::open(&file_path).read_to_str() {
+			Ok(contents) => contents,
+			Err(msg) => { log::warn("stats", format!("statistics could not be loaded: {}", msg)); return stats; }
+		};
+
+		for raw_line in contents.lines() {
+			let line = raw_line.trim();
+			if line.len() == 0 { continue; }
+
+			let words: ~[&str] = line.words().collect();
+			match words.as_slice() {
+				["unlocked", ..ids] => { stats.unlocked = ids.iter().map(|id| id.to_owned()).collect(); }
+				[name, count] => match from_str::<uint>(count) {
+					Some(count) => match name {
+						"jumps" 			=> { stats.jumps = count; }
+						"damage_taken" 		=> { stats.damage_taken = count; }
+						"enemies_defeated" 	=> { stats.enemies_defeated = count; }
+						"play_time" 		=> { stats.play_time = units::Millis(count as int); }
+						_ => { log::warn("stats", format!("{}: ignoring unknown statistic `{}`", path, name)); }
+					},
+					None => { log::warn("stats", format!("{}: ignoring invalid count `{}`", path, count)); }
+				},
+				_ => { log::warn("stats", format!("{}: ignoring malformed line `{}`", path, line)); }
+			}
+		}
+		stats
+	}
+
+	/// Writes these statistics to `path`, creating its directory if needed.
+	pub fn save(&self, path: &str) {
+		let file_path = Path::new(path);
+		match fs::mkdir_recursive(&file_path.dir_path(), io::UserDir) {
+			Ok(_) => {}
+			Err(msg) => { log::warn("stats", format!("could not create statistics directory: {}", msg)); return; }
+		}
+
+		let units::Millis(play_time) = self.play_time;
+		let mut contents = format!(
+			"jumps {}\ndamage_taken {}\nenemies_defeated {}\nplay_time {}\nunlocked",
+			self.jumps, self.damage_taken, self.enemies_defeated, play_time
+		);
+		for id in self.unlocked.iter() {
+			contents.push_str(format!(" {}", *id));
+		}
+		contents.push_str("\n");
+
+		match File::create(&file_path).write_str(contents) {
+			Ok(_) => {}
+			Err(msg) => { log::warn("stats", format!("statistics could not be saved: {}", msg)); }
+		}
+	}
+}
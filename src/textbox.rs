@@ -0,0 +1,216 @@
+use game::graphics;
+use game::i18n;
+use game::sprite;
+use game::sprite::Drawable;
+
+use game::error::GameResult;
+use game::units;
+
+static TEXT_BOX_PATH: &'static str = "assets/base/TextBox.bmp";
+
+// the window's frame (expressed as `units::Game`)
+static FRAME_WIDTH: units::Game 	= units::Game(488.0);
+static FRAME_ROW: units::Game 		= units::Game(16.0);
+static FRAME_TOP_SOURCE: (units::Game, units::Game) 	= (units::Game(0.0), units::Game(0.0));
+static FRAME_MIDDLE_SOURCE: (units::Game, units::Game) 	= (units::Game(0.0), units::Game(16.0));
+static FRAME_BOTTOM_SOURCE: (units::Game, units::Game) 	= (units::Game(0.0), units::Game(32.0));
+static FRAME_MIDDLE_ROWS: uint 		= 6;
+static FRAME_MARGIN: units::Game 	= units::Game(32.0);
+
+// the blinking arrow shown once a page is fully revealed
+static PROMPT_SOURCE: (units::Game, units::Game) 	= (units::Game(224.0), units::Game(32.0));
+static PROMPT_SIZE: (units::Game, units::Game) 		= (units::Game(16.0), units::Game(16.0));
+static PROMPT_BLINK: units::Millis 					= units::Millis(300);
+
+// text layout
+static TEXT_MARGIN: units::Game 	= units::Game(24.0);
+static LINE_HEIGHT: units::Game 	= units::Game(32.0);
+static LINES_PER_PAGE: uint 		= 3;
+static LINE_CHARS: uint 			= 28;
+static CHAR_TIME: units::Millis 	= units::Millis(30);
+
+/// Forces the rest of a message onto a new page.
+pub static PAGE_BREAK: char = '|';
+
+/// A bordered dialogue window along the bottom of the screen.
+///
+/// Messages are word-wrapped & split into pages, each of which is revealed
+/// one character at a time. The player advances through the pages, and
+/// the window closes after the last one.
+pub struct TextBox {
+	priv frame_top: sprite::Sprite,
+	priv frame_middle: sprite::Sprite,
+	priv frame_bottom: sprite::Sprite,
+	priv prompt: sprite::Sprite,
+	priv font: graphics::Font,
+
+	priv pages: ~[~[~str]],
+	priv page: uint,
+	priv revealed: uint,
+	priv reveal_time: units::Millis,
+	priv prompt_time: units::Millis
+}
+
+impl TextBox {
+	pub fn new(graphics: &mut graphics::Graphics) -> GameResult<TextBox> {
+		let origin = (units::Game(0.0), units::Game(0.0));
+
+		Ok(TextBox {
+			frame_top: try!(sprite::Sprite::from_region(
+				graphics, origin, FRAME_TOP_SOURCE, (FRAME_WIDTH, FRAME_ROW), TEXT_BOX_PATH.to_owned()
+			)),
+			frame_middle: try!(sprite::Sprite::from_region(
+				graphics, origin, FRAME_MIDDLE_SOURCE, (FRAME_WIDTH, FRAME_ROW), TEXT_BOX_PATH.to_owned()
+			)),
+			frame_bottom: try!(sprite::Sprite::from_region(
+				graphics, origin, FRAME_BOTTOM_SOURCE, (FRAME_WIDTH, FRAME_ROW), TEXT_BOX_PATH.to_owned()
+			)),
+			prompt: try!(sprite::Sprite::from_region(
+				graphics, origin, PROMPT_SOURCE, PROMPT_SIZE, TEXT_BOX_PATH.to_owned()
+			)),
+			font: try!(graphics::Font::load(graphics)),
+
+			pages: ~[],
+			page: 0,
+			revealed: 0,
+			reveal_time: units::Millis(0),
+			prompt_time: units::Millis(0)
+		})
+	}
+
+	/// Opens the window w/ `text`, replacing any message already shown.
+	///
+	/// `text` is wrapped to fit the window; a `PAGE_BREAK` starts a new page.
+	/// An `@key` shows the text of that key in the current language instead.
+	/// Any glyphs the text needs are rendered through `display` first.
+	pub fn show(&mut self, display: &mut graphics::Graphics, text: &str) {
+		let text = i18n::localize(text);
+		let chars: ~[char] = text.chars().collect();
+		display.prepare_glyphs(chars);
+		self.pages = ~[];
+		for section in text.as_slice().split(PAGE_BREAK) {
+			let lines = TextBox::wrap(section);
+			for page in lines.chunks(LINES_PER_PAGE) {
+				self.pages.push(page.to_owned());
+			}
+		}
+
+		self.page = 0;
+		self.revealed = 0;
+		self.reveal_time = units::Millis(0);
+		self.prompt_time = units::Millis(0);
+	}
+
+	/// Responds to the player pressing "next":
+	/// finishes revealing the current page, or moves on from it.
+	pub fn advance(&mut self) {
+		if !self.is_open() { return; }
+
+		if self.revealed < self.page_length() {
+			self.revealed = self.page_length();
+		} else {
+			self.page += 1;
+			self.revealed = 0;
+			self.reveal_time = units::Millis(0);
+		}
+	}
+
+	/// Closes the window at once, whatever is left of its message.
+	pub fn close(&mut self) {
+		self.pages = ~[];
+		self.page = 0;
+	}
+
+	/// Reveals more of the current page as time passes.
+	pub fn update(&mut self, elapsed_time: units::Millis) {
+		if !self.is_open() { return; }
+
+		self.prompt_time = self.prompt_time + elapsed_time;
+		self.reveal_time = self.reveal_time + elapsed_time;
+		while self.reveal_time >= CHAR_TIME && self.revealed < self.page_length() {
+			self.reveal_time = self.reveal_time - CHAR_TIME;
+			self.revealed += 1;
+		}
+	}
+
+	/// Responds true until the player moves on from the last page.
+	pub fn is_open(&self) -> bool {
+		self.page < self.pages.len()
+	}
+
+	/// Draws the window in screen space, if it is open.
+	pub fn draw(&self, display: &graphics::Graphics) {
+		if !self.is_open() { return; }
+
+		let (box_x, box_y) = TextBox::position(display.resolution());
+		let mut row_y = box_y;
+
+		self.draw_at(display, &self.frame_top, (box_x, row_y));
+		for _ in range(0, FRAME_MIDDLE_ROWS) {
+			row_y = row_y + FRAME_ROW;
+			self.draw_at(display, &self.frame_middle, (box_x, row_y));
+		}
+		row_y = row_y + FRAME_ROW;
+		self.draw_at(display, &self.frame_bottom, (box_x, row_y));
+
+		// only the first `revealed` characters of the page are shown
+		let mut remaining = self.revealed;
+		let mut line_y = box_y + TEXT_MARGIN;
+		for line in self.pages[self.page].iter() {
+			let shown = if remaining < line.char_len() { remaining } else { line.char_len() };
+			self.font.draw_text(display, box_x + TEXT_MARGIN, line_y, line.slice_chars(0, shown));
+
+			remaining -= shown;
+			line_y = line_y + LINE_HEIGHT;
+		}
+
+		let (units::Millis(t), units::Millis(blink)) = (self.prompt_time, PROMPT_BLINK);
+		if self.revealed == self.page_length() && (t / blink) % 2 == 0 {
+			let (prompt_w, prompt_h) = PROMPT_SIZE;
+			self.draw_at(display, &self.prompt, (
+				box_x + FRAME_WIDTH - FRAME_MARGIN - prompt_w,
+				row_y - prompt_h
+			));
+		}
+	}
+
+	/// The top-left corner of the window: centered along the bottom of a
+	/// screen of `resolution`.
+	fn position(resolution: units::Resolution) -> (units::Game, units::Game) {
+		let (screen_w, screen_h) = resolution.to_game();
+		let box_h = FRAME_ROW * units::Game((FRAME_MIDDLE_ROWS + 2) as f64);
+
+		((screen_w - FRAME_WIDTH) / units::Game(2.0), screen_h - box_h - FRAME_MARGIN)
+	}
+
+	/// The number of characters on the current page.
+	fn page_length(&self) -> uint {
+		self.pages[self.page].iter().fold(0, |total, line| total + line.char_len())
+	}
+
+	/// Draws `sprite` (which is positioned at the origin) w/ its top-left at `coords`.
+	fn draw_at(&self, display: &graphics::Graphics, sprite: &sprite::Sprite, coords: (units::Game, units::Game)) {
+		let (x, y) = coords;
+		sprite.draw(display, (units::Game(0.0) - x, units::Game(0.0) - y));
+	}
+
+	/// Breaks `text` into lines of at most `LINE_CHARS` characters,
+	/// splitting between words where possible.
+	fn wrap(text: &str) -> ~[~str] {
+		let mut lines = ~[];
+		let mut line = ~"";
+
+		for word in text.words() {
+			if line.len() > 0 && line.char_len() + 1 + word.char_len() > LINE_CHARS {
+				lines.push(line);
+				line = ~"";
+			}
+
+			if line.len() > 0 { line.push_char(' '); }
+			line.push_str(word);
+		}
+
+		if line.len() > 0 { lines.push(line); }
+		lines
+	}
+}
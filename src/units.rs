@@ -70,6 +70,13 @@ impl <T: AsGame> Div<T, Game> for Game {
 #[deriving(Eq,Ord)]
 pub struct Pixel(i32);
 
+/// A `Pixel` is scaled back up to the `Game` units it was drawn from,
+/// e.g: for measuring images which were never drawn to the tile grid.
+impl AsGame for Pixel {
+	#[inline(always)]
+	fn to_game(&self) -> Game { let Pixel(a) = *self; Game(a as f64 * SCALE) }
+}
+
 /// A `Pixel` merely dereferences itself, as it is already a `Pixel`.
 impl AsPixel for Pixel {
 	#[inline(always)]
@@ -144,6 +151,22 @@ impl<T: AsTile> Div<T, Tile> for Tile {
 	}
 }
 
+/// The size of the screen, measured in tiles: how much of a map is
+/// shown at once. Anything placed relative to the screen derives from it.
+#[deriving(Eq)]
+pub struct Resolution {
+	width: Tile,
+	height: Tile
+}
+
+impl Resolution {
+	#[inline(always)]
+	pub fn to_game(&self) -> (Game, Game) { (self.width.to_game(), self.height.to_game()) }
+
+	#[inline(always)]
+	pub fn to_pixel(&self) -> (Pixel, Pixel) { (self.width.to_pixel(), self.height.to_pixel()) }
+}
+
 /// Millis represents a length of time in milliseconds as a signed integer.
 /// (NOTE: As `Millis` supports basic arithmetic: "negative time" is possible.)
 #[deriving(Eq,Ord)]
@@ -230,7 +253,15 @@ impl Neg<Acceleration> for Acceleration {
 	}
 }
 
-#[deriving(Eq,Ord)]
+impl Add<Acceleration, Acceleration> for Acceleration {
+	#[inline(always)]
+	fn add(&self, rhs: &Acceleration) -> Acceleration {
+		let (Acceleration(a0), Acceleration(a1)) = (*self, *rhs);
+		Acceleration(a0 + a1)
+	}
+}
+
+#[deriving(Eq,Ord,Clone)]
 pub struct Degrees(f64);
 
 impl Degrees {
@@ -286,3 +317,4 @@ impl Mul<Millis, Degrees> for AngularVelocity {
 
 pub type Frame = uint;
 pub type Fps = uint;
+pub type HitPoint = int;
@@ -0,0 +1,198 @@
+use collections::hashmap::HashSet;
+
+use std::cell::RefCell;
+use std::io;
+use std::io::File;
+use std::io::fs;
+
+use game::log;
+use game::pack;
+
+/// Holds one directory per installed mod, laid out like `assets/base/`.
+pub static MODS_DIR: &'static str = "mods";
+
+/// Where files of the asset archive are unpacked to as they are needed.
+pub static EXTRACT_DIR: &'static str = "cache/assets";
+
+// asset paths are written relative to these directories
+static ASSETS_DIR: &'static str = "assets/";
+static BASE_DIR: &'static str 	= "assets/base/";
+
+struct Vfs {
+	// each mod's directory, in the order they are searched
+	mods: ~[Path],
+	archive: Option<pack::Archive>,
+	// the files of the archive unpacked during this run
+	extracted: HashSet<~str>
+}
+
+local_data_key!(VFS: RefCell<Vfs>)
+
+/// Finds every mod installed under `mods_dir` & opens the asset archive
+/// at `archive_path`, if there is one. From then on the files of those
+/// mods override the base assets (the mods being searched in order of
+/// their names), & files missing from `assets/` are read from the archive.
+///
+/// Until this is called every asset path resolves to itself.
+pub fn init(mods_dir: &str, archive_path: &str) {
+	let dir = Path::new(mods_dir);
+	let mut mods: ~[Path] = if dir.is_dir() {
+		match fs::readdir(&dir) {
+			Ok(entries) => entries.move_iter().filter(|entry| entry.is_dir()).collect(),
+			Err(msg) => { log::warn("vfs", format!("mods could not be listed: {}", msg)); ~[] }
+		}
+	} else {
+		~[]
+	};
+	mods.sort_by(|a, b| a.as_vec().cmp(&b.as_vec()));
+	for root in mods.iter() {
+		log::info("vfs", format!("using mod {}", root.display()));
+	}
+
+	let file_path = Path::new(archive_path);
+	let archive = if file_path.exists() {
+		match pack::Archive::open(&file_path) {
+			Ok(archive) => {
+				log::info("vfs", format!("using {} ({} files)", archive_path, archive.len()));
+				Some(archive)
+			}
+			Err(msg) => { log::warn("vfs", format!("{} could not be opened: {}", archive_path, msg)); None }
+		}
+	} else {
+		None
+	};
+
+	VFS.replace(Some(RefCell::new(Vfs { mods: mods, archive: archive, extracted: HashSet::new() })));
+}
+
+/// The file an asset path refers to: that of the first mod which holds
+/// it, else the loose file itself, else its copy unpacked from the archive.
+///
+/// Paths within `assets/base/` are looked for relative to each mod's
+/// directory, e.g: `mods/<mod>/MyChar.bmp` overrides `assets/base/MyChar.bmp`;
+/// other paths within `assets/` are relative to it, so `mods/<mod>/maps/cave.map`
+/// overrides `assets/maps/cave.map`. Paths outside `assets/` are never overridden.
+pub fn resolve(path: &str) -> Path {
+	if !path.starts_with(ASSETS_DIR) { return Path::new(path); }
+	let relative = relative_path(path);
+
+	match VFS.get() {
+		Some(vfs) => {
+			let mut vfs = vfs.get().borrow_mut();
+			vfs.get().resolve(path, relative)
+		}
+		None => Path::new(path)
+	}
+}
+
+/// The name of every file directly within the asset directory `dir`, e.g:
+/// `en.lang` of `assets/lang`, wherever `resolve` would look for it: in any
+/// mod, among the loose files, or in the archive. Each name is listed once,
+/// in order. (A directory outside `assets/` is simply read.)
+pub fn list(dir: &str) -> ~[~str] {
+	let mut names: ~[~str] = read_names(&Path::new(dir));
+	if dir.starts_with(ASSETS_DIR) {
+		match VFS.get() {
+			Some(vfs) => {
+				let vfs = vfs.get().borrow();
+				names.push_all_move(vfs.get().list(dir));
+			}
+			None => {}
+		}
+	}
+	names.sort();
+	names.dedup();
+	names
+}
+
+// an asset path relative to the directories of mods (see `resolve`)
+fn relative_path<'a>(path: &'a str) -> &'a str {
+	if path.starts_with(BASE_DIR) {
+		path.slice_from(BASE_DIR.len())
+	} else {
+		path.slice_from(ASSETS_DIR.len())
+	}
+}
+
+// the names of the files within `dir`, if it can be read
+fn read_names(dir: &Path) -> ~[~str] {
+	if !dir.is_dir() { return ~[]; }
+	match fs::readdir(dir) {
+		Ok(entries) => entries.iter()
+			.filter(|entry| !entry.is_dir())
+			.filter_map(|entry| entry.filename_str().map(|name| name.to_owned()))
+			.collect(),
+		Err(msg) => { log::warn("vfs", format!("{} could not be listed: {}", dir.display(), msg)); ~[] }
+	}
+}
+
+impl Vfs {
+	fn resolve(&mut self, path: &str, relative: &str) -> Path {
+		for root in self.mods.iter() {
+			let candidate = root.join(relative);
+			if candidate.exists() { return candidate; }
+		}
+
+		let loose = Path::new(path);
+		if loose.exists() { return loose; }
+
+		// SDL loads images & sounds from files, so packed files are unpacked to disk
+		match self.extract(path.slice_from(ASSETS_DIR.len())) {
+			Some(extracted) => extracted,
+			None => loose
+		}
+	}
+
+	// the names of the files within `dir` found in each mod & the archive
+	fn list(&self, dir: &str) -> ~[~str] {
+		let mut names: ~[~str] = ~[];
+		let relative = relative_path(dir);
+		for root in self.mods.iter() {
+			names.push_all_move(read_names(&root.join(relative)));
+		}
+
+		match self.archive {
+			Some(ref archive) => {
+				let prefix = format!("{}/", dir.slice_from(ASSETS_DIR.len()).trim_right_chars('/'));
+				for name in archive.names().move_iter() {
+					if !name.starts_with(prefix.as_slice()) { continue; }
+					let rest = name.slice_from(prefix.len());
+					if !rest.contains_char('/') { names.push(rest.to_owned()); }
+				}
+			}
+			None => {}
+		}
+		names
+	}
+
+	// unpacks the file packed as `name` (once per run), if the archive holds it
+	fn extract(&mut self, name: &str) -> Option<Path> {
+		let target = Path::new(EXTRACT_DIR).join(name);
+		if self.extracted.contains(&name.to_owned()) { return Some(target); }
+
+		let contents = match self.archive {
+			Some(ref archive) if archive.contains(name) => archive.read(name),
+			_ => return None
+		};
+		match contents.and_then(|contents| write_file(&target, contents)) {
+			Ok(_) => {
+				log::debug("vfs", format!("unpacked {}", name));
+				self.extracted.insert(name.to_owned());
+				Some(target)
+			}
+			Err(msg) => { log::warn("vfs", format!("{} could not be unpacked: {}", name, msg)); None }
+		}
+	}
+}
+
+// writes `contents` to `path`, creating its directory if needed
+fn write_file(path: &Path, contents: ~[u8]) -> Result<(), ~str> {
+	match fs::mkdir_recursive(&path.dir_path(), io::UserDir) {
+		Ok(_) => {}
+		Err(msg) => return Err(format!("{}", msg))
+	}
+	match File::create(path).write(contents) {
+		Ok(_) => Ok(()),
+		Err(msg) => Err(format!("{}", msg))
+	}
+}
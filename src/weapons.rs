@@ -0,0 +1,355 @@
+use std::cmp;
+
+use game::graphics;
+use game::projectiles;
+use game::sprite;
+use game::units;
+
+use game::error::GameResult;
+
+static MAX_LEVEL: uint = 3;
+
+// energy required to advance past each level
+static LEVEL_ENERGY: [uint, ..3] = [10, 20, 30];
+
+/// Tracks the experience a weapon has accumulated.
+///
+/// Energy collected from pickups fills the current level; once it is full
+/// the weapon advances to the next level. At the final level the energy
+/// is capped once full.
+#[deriving(Clone)]
+pub struct Experience {
+	priv level: uint,
+	priv energy: uint
+}
+
+impl Experience {
+	pub fn new() -> Experience {
+		Experience { level: 1, energy: 0 }
+	}
+
+	/// Rebuilds experience previously saved at `level` w/ `energy`,
+	/// clamping both into range.
+	pub fn restore(level: uint, energy: uint) -> Experience {
+		let mut experience = Experience { level: cmp::max(1, cmp::min(level, MAX_LEVEL)), energy: 0 };
+		experience.energy = cmp::min(energy, experience.max_energy());
+		experience
+	}
+
+	/// Adds `amount` energy, advancing as many levels as it fills.
+	pub fn gain(&mut self, amount: uint) {
+		self.energy += amount;
+
+		while self.energy >= self.max_energy() {
+			if self.level == MAX_LEVEL {
+				self.energy = self.max_energy();
+				break;
+			}
+
+			self.energy -= self.max_energy();
+			self.level += 1;
+		}
+	}
+
+	/// Removes `amount` energy, dropping back down a level if
+	/// the current one is emptied.
+	pub fn lose(&mut self, amount: uint) {
+		let mut remaining = amount;
+
+		while remaining > self.energy {
+			if self.level == 1 {
+				self.energy = 0;
+				return;
+			}
+
+			remaining -= self.energy + 1;
+			self.level -= 1;
+			self.energy = self.max_energy() - 1;
+		}
+
+		self.energy -= remaining;
+	}
+
+	pub fn level(&self) -> uint { self.level }
+	pub fn energy(&self) -> uint { self.energy }
+
+	/// The energy needed to fill the current level.
+	pub fn max_energy(&self) -> uint { LEVEL_ENERGY[self.level - 1] }
+
+	/// Responds true when the weapon is at its final level w/ a full bar.
+	pub fn is_max(&self) -> bool {
+		self.level == MAX_LEVEL && self.energy == self.max_energy()
+	}
+}
+
+
+/// Something the player fires, which levels up as it gains energy.
+///
+/// A weapon decides what its shots look like & how they behave (see
+/// `projectiles::Shot`); the world moves & draws them once fired.
+pub trait Weapon {
+	/// The name of this weapon, as shown while debugging.
+	fn name(&self) -> &'static str;
+
+	/// Fires from `origin` along `aim` (see `projectiles::Projectile::new`),
+	/// responding w/ the shots fired.
+	/// (None are fired while the weapon is still cooling down.)
+	fn fire(
+		&mut self,
+		graphics: &mut graphics::Graphics,
+		origin: (units::Game, units::Game),
+		aim: (sprite::Facing, sprite::Looking)
+	) -> GameResult<~[projectiles::Projectile]>;
+
+	/// Counts down the time until the weapon may fire again.
+	fn update(&mut self, elapsed_time: units::Millis);
+
+	fn experience<'a>(&'a self) -> &'a Experience;
+	fn experience_mut<'a>(&'a mut self) -> &'a mut Experience;
+
+	/// The weapon's ammo as `(current, max)`, or `None` if it is unlimited.
+	fn ammo(&self) -> Option<(uint, uint)> { None }
+
+	/// Adds `amount` ammo, up to the weapon's max. (Does nothing for
+	/// weapons w/ unlimited ammo.)
+	#[allow(unused_variable)]
+	fn refill(&mut self, amount: uint) {}
+
+	/// A copy of this weapon, as it is now, e.g: for a snapshot of the world.
+	fn clone_weapon(&self) -> ~Weapon;
+}
+
+impl Clone for ~Weapon {
+	fn clone(&self) -> ~Weapon { self.clone_weapon() }
+}
+
+// the peashooter's shot at each level
+static PEASHOOTER_SHOTS: [projectiles::Shot, ..3] = [
+	projectiles::Shot { sprite: (units::Tile(8), units::Tile(2)), speed: units::Velocity(0.6), range: units::Tile(7), damage: 1, blast: None },
+	projectiles::Shot { sprite: (units::Tile(8), units::Tile(2)), speed: units::Velocity(0.6), range: units::Tile(8), damage: 2, blast: None },
+	projectiles::Shot { sprite: (units::Tile(8), units::Tile(2)), speed: units::Velocity(0.6), range: units::Tile(9), damage: 4, blast: None }
+];
+static PEASHOOTER_COOLDOWN: units::Millis = units::Millis(100);
+
+/// Fires a single shot straight ahead, which strikes harder &
+/// reaches further as the weapon levels up.
+#[deriving(Clone)]
+pub struct Peashooter {
+	priv experience: Experience,
+	priv cooldown: units::Millis
+}
+
+impl Peashooter {
+	pub fn new() -> Peashooter {
+		Peashooter { experience: Experience::new(), cooldown: units::Millis(0) }
+	}
+}
+
+impl Weapon for Peashooter {
+	fn name(&self) -> &'static str { "peashooter" }
+
+	fn clone_weapon(&self) -> ~Weapon { ~self.clone() as ~Weapon }
+
+	fn fire(
+		&mut self,
+		graphics: &mut graphics::Graphics,
+		origin: (units::Game, units::Game),
+		aim: (sprite::Facing, sprite::Looking)
+	) -> GameResult<~[projectiles::Projectile]> {
+		if self.cooldown > units::Millis(0) { return Ok(~[]); }
+
+		let shot = &PEASHOOTER_SHOTS[self.experience.level() - 1];
+		let projectile = try!(projectiles::Projectile::new(graphics, origin, aim, units::Velocity(0.0), shot));
+		self.cooldown = PEASHOOTER_COOLDOWN;
+		Ok(~[projectile])
+	}
+
+	fn update(&mut self, elapsed_time: units::Millis) {
+		self.cooldown = cmp::max(units::Millis(0), self.cooldown - elapsed_time);
+	}
+
+	fn experience<'a>(&'a self) -> &'a Experience { &self.experience }
+	fn experience_mut<'a>(&'a mut self) -> &'a mut Experience { &mut self.experience }
+}
+
+static SPREAD_SHOT: projectiles::Shot = projectiles::Shot {
+	sprite: (units::Tile(8), units::Tile(2)), speed: units::Velocity(0.5), range: units::Tile(4), damage: 1, blast: None
+};
+static SPREAD_COOLDOWN: units::Millis = units::Millis(300);
+// how fast each shot of the fan drifts away from the one inside it
+static SPREAD_STEP: units::Velocity = units::Velocity(0.08);
+
+/// Fires a fan of short-ranged shots: three at the first level, & two
+/// more for each level after it.
+#[deriving(Clone)]
+pub struct SpreadShot {
+	priv experience: Experience,
+	priv cooldown: units::Millis
+}
+
+impl SpreadShot {
+	pub fn new() -> SpreadShot {
+		SpreadShot { experience: Experience::new(), cooldown: units::Millis(0) }
+	}
+}
+
+impl Weapon for SpreadShot {
+	fn name(&self) -> &'static str { "spread shot" }
+
+	fn clone_weapon(&self) -> ~Weapon { ~self.clone() as ~Weapon }
+
+	fn fire(
+		&mut self,
+		graphics: &mut graphics::Graphics,
+		origin: (units::Game, units::Game),
+		aim: (sprite::Facing, sprite::Looking)
+	) -> GameResult<~[projectiles::Projectile]> {
+		if self.cooldown > units::Millis(0) { return Ok(~[]); }
+
+		let units::Velocity(step) = SPREAD_STEP;
+		let reach = self.experience.level() as int;
+		let mut shots = ~[];
+		for spread in range(-reach, reach + 1) {
+			let drift = units::Velocity(step * spread as f64);
+			shots.push(try!(projectiles::Projectile::new(graphics, origin, aim, drift, &SPREAD_SHOT)));
+		}
+		self.cooldown = SPREAD_COOLDOWN;
+		Ok(shots)
+	}
+
+	fn update(&mut self, elapsed_time: units::Millis) {
+		self.cooldown = cmp::max(units::Millis(0), self.cooldown - elapsed_time);
+	}
+
+	fn experience<'a>(&'a self) -> &'a Experience { &self.experience }
+	fn experience_mut<'a>(&'a mut self) -> &'a mut Experience { &mut self.experience }
+}
+
+// the missile at each level: its blast grows, rather than its damage
+static MISSILE_SHOTS: [projectiles::Shot, ..3] = [
+	projectiles::Shot { sprite: (units::Tile(0), units::Tile(3)), speed: units::Velocity(0.4), range: units::Tile(10), damage: 2, blast: Some(units::Game(24.0)) },
+	projectiles::Shot { sprite: (units::Tile(0), units::Tile(3)), speed: units::Velocity(0.4), range: units::Tile(10), damage: 2, blast: Some(units::Game(32.0)) },
+	projectiles::Shot { sprite: (units::Tile(0), units::Tile(3)), speed: units::Velocity(0.4), range: units::Tile(10), damage: 3, blast: Some(units::Game(40.0)) }
+];
+static MISSILE_COOLDOWN: units::Millis = units::Millis(400);
+static MAX_MISSILES: uint = 10;
+
+/// Fires missiles which explode on impact, damaging every enemy (& any
+/// breakable tile) caught in the blast. Each missile uses up one of a
+/// limited supply of ammo, which is restocked by ammo pickups.
+#[deriving(Clone)]
+pub struct MissileLauncher {
+	priv experience: Experience,
+	priv cooldown: units::Millis,
+	priv ammo: uint
+}
+
+impl MissileLauncher {
+	pub fn new() -> MissileLauncher {
+		MissileLauncher { experience: Experience::new(), cooldown: units::Millis(0), ammo: MAX_MISSILES }
+	}
+}
+
+impl Weapon for MissileLauncher {
+	fn name(&self) -> &'static str { "missile launcher" }
+
+	fn clone_weapon(&self) -> ~Weapon { ~self.clone() as ~Weapon }
+
+	fn fire(
+		&mut self,
+		graphics: &mut graphics::Graphics,
+		origin: (units::Game, units::Game),
+		aim: (sprite::Facing, sprite::Looking)
+	) -> GameResult<~[projectiles::Projectile]> {
+		if self.cooldown > units::Millis(0) || self.ammo == 0 { return Ok(~[]); }
+
+		let shot = &MISSILE_SHOTS[self.experience.level() - 1];
+		let projectile = try!(projectiles::Projectile::new(graphics, origin, aim, units::Velocity(0.0), shot));
+		self.cooldown = MISSILE_COOLDOWN;
+		self.ammo -= 1;
+		Ok(~[projectile])
+	}
+
+	fn update(&mut self, elapsed_time: units::Millis) {
+		self.cooldown = cmp::max(units::Millis(0), self.cooldown - elapsed_time);
+	}
+
+	fn experience<'a>(&'a self) -> &'a Experience { &self.experience }
+	fn experience_mut<'a>(&'a mut self) -> &'a mut Experience { &mut self.experience }
+
+	fn ammo(&self) -> Option<(uint, uint)> { Some((self.ammo, MAX_MISSILES)) }
+
+	fn refill(&mut self, amount: uint) {
+		self.ammo = cmp::min(self.ammo + amount, MAX_MISSILES);
+	}
+}
+
+/// The weapons the player carries, one of which is at hand.
+#[deriving(Clone)]
+pub struct Arsenal {
+	priv weapons: ~[~Weapon],
+	priv current: uint
+}
+
+impl Arsenal {
+	/// Every weapon, starting w/ the peashooter at hand.
+	pub fn new() -> Arsenal {
+		Arsenal {
+			weapons: ~[
+				~Peashooter::new() as ~Weapon,
+				~SpreadShot::new() as ~Weapon,
+				~MissileLauncher::new() as ~Weapon
+			],
+			current: 0
+		}
+	}
+
+	/// The weapon at hand.
+	pub fn current<'a>(&'a self) -> &'a Weapon { &*self.weapons[self.current] }
+	pub fn current_mut<'a>(&'a mut self) -> &'a mut Weapon { &mut *self.weapons[self.current] }
+
+	/// Which of the weapons is at hand.
+	pub fn index(&self) -> uint { self.current }
+
+	/// Switches to the weapon after (or, for a negative `step`, before) the one at hand.
+	pub fn switch(&mut self, step: int) {
+		let len = self.weapons.len() as int;
+		self.current = (((self.current as int + step) % len + len) % len) as uint;
+	}
+
+	/// Cools down every weapon, not just the one at hand.
+	pub fn update(&mut self, elapsed_time: units::Millis) {
+		for weapon in self.weapons.mut_iter() {
+			weapon.update(elapsed_time);
+		}
+	}
+
+	/// Restocks every weapon which uses ammo w/ `amount` more.
+	pub fn refill(&mut self, amount: uint) {
+		for weapon in self.weapons.mut_iter() {
+			weapon.refill(amount);
+		}
+	}
+
+	/// Responds true if any weapon which uses ammo is short of its max.
+	pub fn needs_ammo(&self) -> bool {
+		self.weapons.iter().any(|weapon| match weapon.ammo() {
+			Some((current, max)) => current < max,
+			None => false
+		})
+	}
+
+	/// The level & energy of each weapon, in order.
+	pub fn levels(&self) -> ~[(uint, uint)] {
+		self.weapons.iter().map(|weapon| (weapon.experience().level(), weapon.experience().energy())).collect()
+	}
+
+	/// Restores the `levels` previously saved, w/ weapon `current` at hand.
+	/// (Weapons w/o a saved level are left as they are.)
+	pub fn restore(&mut self, levels: &[(uint, uint)], current: uint) {
+		for (weapon, &(level, energy)) in self.weapons.mut_iter().zip(levels.iter()) {
+			*weapon.experience_mut() = Experience::restore(level, energy);
+		}
+		if current < self.weapons.len() { self.current = current; }
+	}
+}
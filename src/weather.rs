@@ -0,0 +1,159 @@
+use std::cmp;
+use std::rand::Rng;
+
+use game::graphics;
+use game::map;
+use game::rng;
+use game::sprite;
+use game::sprite::Drawable;
+
+use game::collisions::Rectangle;
+use game::error::GameResult;
+use game::units;
+
+static WEATHER_ATLAS: &'static str = "assets/atlases/caret.atlas";
+
+// how far wind (i.e: force regions) can push falling drops sideways
+static MAX_DRIFT: units::Velocity = units::Velocity(0.2);
+
+// rain: many fast, straight streaks
+static RAIN_COUNT: uint 						= 96;
+static RAIN_SPEED: (f64, f64) 					= (0.4, 0.6);
+
+// snow: fewer, slower flakes which wander from side to side
+static SNOW_COUNT: uint 						= 48;
+static SNOW_SPEED: (f64, f64) 					= (0.03, 0.08);
+static SNOW_WANDER: f64 						= 0.03;
+
+/// The sorts of weather a map may have.
+#[deriving(Eq)]
+pub enum Kind {
+	Rain,
+	Snow
+}
+
+impl Kind {
+	/// The kind of weather written as `name` in map files.
+	pub fn from_name(name: &str) -> Option<Kind> {
+		match name {
+			"rain" => Some(Rain),
+			"snow" => Some(Snow),
+			_ => None
+		}
+	}
+}
+
+// a single raindrop or snowflake, positioned in screen space
+struct Drop {
+	x: units::Game,
+	y: units::Game,
+	fall_speed: units::Velocity,
+	// the drop's own sideways motion, before any wind
+	wander: units::Velocity,
+	drift: units::Velocity
+}
+
+/// Rain or snow falling across the whole screen.
+///
+/// Drops live in screen space & wrap around its edges, so a small,
+/// fixed number of them covers a map of any size. Wind is not a separate
+/// setting: drops drift w/ any force region of the map they fall past.
+pub struct Weather {
+	priv kind: Kind,
+	priv drops: ~[Drop],
+	priv sprite: sprite::Sprite,
+	// the width & height of the screen
+	priv screen: (f64, f64),
+	priv rng: rng::Rng
+}
+
+impl Weather {
+	pub fn new(graphics: &mut graphics::Graphics, kind: Kind, rng: rng::Rng) -> GameResult<Weather> {
+		let (region, count) = match kind {
+			Rain => ("rain", RAIN_COUNT),
+			Snow => ("snow", SNOW_COUNT)
+		};
+		let atlas = try!(graphics::Atlas::load(WEATHER_ATLAS.to_owned()));
+		let (units::Game(screen_w), units::Game(screen_h)) = graphics.resolution().to_game();
+
+		let mut weather = Weather {
+			kind: kind,
+			drops: ~[],
+			sprite: try!(sprite::Sprite::from_atlas(graphics, (units::Game(0.0), units::Game(0.0)), &atlas, region)),
+			screen: (screen_w, screen_h),
+			rng: rng
+		};
+
+		// the screen starts out already full of drops
+		let mut rng = weather.rng.clone();
+		for _ in range(0, count) {
+			let y = units::Game(rng.gen_range(0.0, screen_h));
+			let drop = weather.new_drop(y);
+			weather.drops.push(drop);
+		}
+		Ok(weather)
+	}
+
+	/// Moves each drop, pushed by any force regions beneath it; `offset`
+	/// is the camera's, which places the screen within `map`.
+	pub fn update(&mut self, elapsed_time: units::Millis, map: &map::Map, offset: (units::Game, units::Game)) {
+		let (offset_x, offset_y) = offset;
+		let (screen_w, screen_h) = self.screen;
+
+		for i in range(0, self.drops.len()) {
+			let fallen = {
+				let drop = &mut self.drops[i];
+				let (force_x, _) = map.force_at(&Rectangle {
+					x: drop.x + offset_x, y: drop.y + offset_y,
+					width: units::Game(1.0), height: units::Game(1.0)
+				});
+
+				drop.drift = cmp::max(-MAX_DRIFT, cmp::min(drop.drift + (force_x * elapsed_time), MAX_DRIFT));
+				drop.x = drop.x + ((drop.wander + drop.drift) * elapsed_time);
+				drop.y = drop.y + (drop.fall_speed * elapsed_time);
+
+				// wrap around the sides of the screen
+				let units::Game(x) = drop.x;
+				if x < 0.0 { drop.x = units::Game(x + screen_w); }
+				else if x >= screen_w { drop.x = units::Game(x - screen_w); }
+
+				drop.y > units::Game(screen_h)
+			};
+
+			// drops which fall off the bottom start again from the top
+			if fallen {
+				let drop = self.new_drop(units::Game(0.0));
+				self.drops[i] = drop;
+			}
+		}
+	}
+
+	/// Draws the drops over the world (& under the HUD.)
+	pub fn draw(&self, display: &graphics::Graphics) {
+		for drop in self.drops.iter() {
+			self.sprite.draw(display, (units::Game(0.0) - drop.x, units::Game(0.0) - drop.y));
+		}
+	}
+
+	// a drop at a random position along the height `y`
+	fn new_drop(&self, y: units::Game) -> Drop {
+		let mut rng = self.rng.clone();
+		let (screen_w, _) = self.screen;
+		let (slowest, fastest) = match self.kind {
+			Rain => RAIN_SPEED,
+			Snow => SNOW_SPEED
+		};
+		let wander = match self.kind {
+			Rain => 0.0,
+			Snow => rng.gen_range(-SNOW_WANDER, SNOW_WANDER)
+		};
+
+		Drop {
+			x: units::Game(rng.gen_range(0.0, screen_w)),
+			y: y,
+			fall_speed: units::Velocity(rng.gen_range(slowest, fastest)),
+			wander: units::Velocity(wander),
+			drift: units::Velocity(0.0)
+		}
+	}
+}
@@ -0,0 +1,537 @@
+use std::f64;
+use std::mem;
+use std::rand::Rng;
+
+use sdl2::pixels;
+
+use game::audio;
+use game::boss;
+use game::collisions;
+use game::damage_numbers;
+use game::entity;
+use game::graphics;
+use game::log;
+use game::map;
+use game::npc;
+use game::particles;
+use game::pickups;
+use game::player;
+use game::projectiles;
+use game::rng;
+
+use game::collisions::Rectangle;
+use game::enemies;
+use game::enemies::Enemy;
+use game::entity::Entity;
+use game::error::GameResult;
+use game::units;
+use game::units::{AsGame};
+
+// how far the light of the player & their shots reaches in the dark
+static PLAYER_LIGHT: units::Tile = units::Tile(3);
+static SHOT_LIGHT: units::Tile = units::Tile(1);
+
+// a dying boss bursts into an explosion one frame in every so many
+static BOSS_EXPLOSION_ODDS: uint = 6;
+
+/// Everything which lives on the current map: the players, and the
+/// enemies (& any boss), projectiles, pickups & NPCs around them.
+///
+/// The players are updated first, so that everything else reacts to where
+/// they now stand; the world then resolves collisions between entities
+/// and removes those which have died. Enemies (& NPCs) act upon whichever
+/// living player is nearest them.
+pub struct World {
+	// the first player is always present; any other shares their map
+	priv players: ~[player::Player],
+	priv enemies: ~[~Enemy],
+	// the flag set once each enemy is defeated (if any), in step w/ `enemies`
+	priv enemy_flags: ~[Option<uint>],
+	// flags of enemies defeated since they were last taken, & how many were
+	priv defeated: ~[uint],
+	priv kills: uint,
+	priv boss: Option<boss::Boss>,
+	priv projectiles: ~[projectiles::Projectile],
+	priv pickups: ~[pickups::Pickup],
+	priv npcs: ~[npc::Npc],
+	priv particles: particles::ParticleSystem,
+	priv damage_numbers: damage_numbers::DamageNumbers,
+
+	// enemies bucketed by tile, so that shots only test those nearby
+	priv grid: collisions::SpatialGrid,
+	// draws loot & where a dying boss explodes
+	priv rng: rng::Rng
+}
+
+/// The entities of a `World` at some point, e.g: for a savestate.
+#[deriving(Clone)]
+pub struct Snapshot {
+	priv players: ~[player::Player],
+	priv enemies: ~[~Enemy],
+	priv enemy_flags: ~[Option<uint>],
+	priv defeated: ~[uint],
+	priv boss: Option<boss::Boss>,
+	priv projectiles: ~[projectiles::Projectile],
+	priv pickups: ~[pickups::Pickup],
+	priv npcs: ~[npc::Npc]
+}
+
+impl World {
+	/// Creates a world holding nothing but `player`, whose randomness is drawn from `rng`.
+	pub fn new(display: &mut graphics::Graphics, player: player::Player, rng: rng::Rng) -> GameResult<World> {
+		Ok(World {
+			players: ~[player],
+			enemies: ~[],
+			enemy_flags: ~[],
+			defeated: ~[],
+			kills: 0,
+			boss: None,
+			projectiles: ~[],
+			pickups: ~[],
+			npcs: ~[],
+			particles: try!(particles::ParticleSystem::new(display, rng.clone())),
+			damage_numbers: try!(damage_numbers::DamageNumbers::new(display)),
+			grid: collisions::SpatialGrid::new(),
+			rng: rng
+		})
+	}
+
+	/// The first player, whom events, doors & saves concern.
+	pub fn player<'a>(&'a self) -> &'a player::Player { &self.players[0] }
+	pub fn player_mut<'a>(&'a mut self) -> &'a mut player::Player { &mut self.players[0] }
+
+	/// Every player, the first player first.
+	pub fn players<'a>(&'a self) -> &'a [player::Player] { self.players.as_slice() }
+	pub fn players_mut<'a>(&'a mut self) -> &'a mut [player::Player] { self.players.as_mut_slice() }
+
+	/// Adds another player, e.g: one joining in w/ the second set of keys.
+	pub fn add_player(&mut self, player: player::Player) { self.players.push(player); }
+
+	/// Removes the player numbered `index` (the first player may not be removed.)
+	pub fn remove_player(&mut self, index: uint) {
+		if index > 0 && index < self.players.len() { self.players.remove(index); }
+	}
+
+	/// How many entities of `kind` are in the world.
+	pub fn count(&self, kind: entity::Kind) -> uint {
+		match kind {
+			entity::Enemy 		=> self.enemies.len(),
+			entity::Projectile 	=> self.projectiles.len(),
+			entity::Pickup 		=> self.pickups.len(),
+			entity::Npc 		=> self.npcs.len()
+		}
+	}
+
+	pub fn add_enemy(&mut self, enemy: ~Enemy) { self.add_flagged_enemy(enemy, None); }
+
+	/// Adds `enemy`, which sets `flag` (if any) once it is defeated.
+	pub fn add_flagged_enemy(&mut self, enemy: ~Enemy, flag: Option<uint>) {
+		self.enemies.push(enemy);
+		self.enemy_flags.push(flag);
+	}
+
+	/// The flags of every enemy defeated since this was last called.
+	pub fn take_defeated(&mut self) -> ~[uint] {
+		mem::replace(&mut self.defeated, ~[])
+	}
+
+	/// How many enemies have been defeated since this was last called.
+	pub fn take_kills(&mut self) -> uint {
+		mem::replace(&mut self.kills, 0)
+	}
+	pub fn add_npc(&mut self, npc: npc::Npc) { self.npcs.push(npc); }
+
+	/// The npc numbered `index` (from 0) in the order they were added, if there is one.
+	pub fn npc_mut<'a>(&'a mut self, index: uint) -> Option<&'a mut npc::Npc> {
+		self.npcs.mut_iter().nth(index)
+	}
+
+	/// The event of the npc the player would talk to from `rectangle`, if any.
+	pub fn npc_event_at(&self, rectangle: &Rectangle) -> Option<uint> {
+		self.npcs.iter().find(|npc| npc.hitbox().collides_with(rectangle)).map(|npc| npc.event())
+	}
+
+	/// The boss being fought, if any. (A boss stays in the world after it
+	/// has finished dying, until it is replaced or removed.)
+	pub fn boss<'a>(&'a self) -> Option<&'a boss::Boss> { self.boss.as_ref() }
+	pub fn boss_mut<'a>(&'a mut self) -> Option<&'a mut boss::Boss> { self.boss.as_mut() }
+	pub fn set_boss(&mut self, boss: Option<boss::Boss>) { self.boss = boss; }
+
+	/// Has the player numbered `index` fire their weapon.
+	/// (Nothing is fired if the projectiles' sprites can not be loaded.)
+	pub fn fire(&mut self, index: uint, display: &mut graphics::Graphics, audio: &audio::Audio) {
+		match self.players[index].fire(display, audio) {
+			Ok(projectiles) => self.projectiles.push_all_move(projectiles),
+			Err(err) => log::error("world", err.message())
+		}
+	}
+
+	/// Removes everything but the players, e.g: when they leave the map.
+	pub fn clear(&mut self) {
+		self.enemies = ~[];
+		self.enemy_flags = ~[];
+		self.boss = None;
+		self.projectiles = ~[];
+		self.pickups = ~[];
+		self.npcs = ~[];
+		self.particles.clear();
+		self.damage_numbers.clear();
+	}
+
+	/// A copy of every entity as it is now, which `restore` puts back.
+	pub fn snapshot(&self) -> Snapshot {
+		Snapshot {
+			players: self.players.clone(),
+			enemies: self.enemies.clone(),
+			enemy_flags: self.enemy_flags.clone(),
+			defeated: self.defeated.clone(),
+			boss: self.boss.clone(),
+			projectiles: self.projectiles.clone(),
+			pickups: self.pickups.clone(),
+			npcs: self.npcs.clone()
+		}
+	}
+
+	/// Puts back every entity as it was in `snapshot`, clearing away any
+	/// particles & damage numbers (which are only for show.)
+	pub fn restore(&mut self, snapshot: Snapshot) {
+		let Snapshot { players, enemies, enemy_flags, defeated, boss, projectiles, pickups, npcs } = snapshot;
+		self.players = players;
+		self.enemies = enemies;
+		self.enemy_flags = enemy_flags;
+		self.defeated = defeated;
+		self.boss = boss;
+		self.projectiles = projectiles;
+		self.pickups = pickups;
+		self.npcs = npcs;
+		self.particles.clear();
+		self.damage_numbers.clear();
+	}
+
+	/// Advances every entity by `elapsed_time`, then lets them act upon
+	/// each other: enemies hurt the players, shots hurt enemies (& wear
+	/// down the walls they strike), and the players collect pickups.
+	pub fn update(
+		&mut self,
+		elapsed_time: units::Millis,
+		display: &mut graphics::Graphics,
+		map: &mut map::Map,
+		audio: &audio::Audio
+	) {
+		let hit_points: ~[units::HitPoint] = self.players.iter().map(|quote| quote.hit_points()).collect();
+		for quote in self.players.mut_iter() {
+			quote.update(elapsed_time, &*map, audio);
+			if quote.has_landed() {
+				let feet = quote.damage_rectangle();
+				if quote.has_landed_hard() {
+					self.particles.heavy_landing((quote.center_x(), feet.bottom()));
+				} else {
+					self.particles.landing_dust((quote.center_x(), feet.bottom()));
+				}
+			}
+			match quote.boosting() {
+				Some(thrust) => {
+					let angle = match thrust {
+						player::Up 		=> f64::consts::PI / 2.0,
+						player::Left 	=> 0.0,
+						player::Right 	=> f64::consts::PI
+					};
+					self.particles.exhaust((quote.center_x(), quote.center_y()), angle);
+				}
+				None => {}
+			}
+
+			match map.spike_at(&quote.damage_rectangle()) {
+				Some(spikes) => quote.take_damage(map::SPIKE_DAMAGE, &spikes),
+				None => {}
+			}
+		}
+
+		for npc in self.npcs.mut_iter() {
+			let player_center = nearest_player(self.players.as_slice(), &npc.hitbox());
+			npc.update(elapsed_time, player_center, &*map);
+		}
+
+		for enemy in self.enemies.mut_iter() {
+			let player_center = nearest_player(self.players.as_slice(), &enemy.hitbox());
+			enemy.update(elapsed_time, player_center, &*map);
+
+			let damage_rect = enemy.hitbox();
+			for quote in self.players.mut_iter() {
+				if !quote.is_dead() && damage_rect.collides_with(&quote.damage_rectangle()) {
+					quote.take_damage(enemy.contact_damage(), &damage_rect);
+				}
+			}
+		}
+
+		match self.boss {
+			Some(ref mut boss) => if boss.is_alive() {
+				let player_center = nearest_player(self.players.as_slice(), &boss.hitbox());
+				boss.update(elapsed_time, player_center, &*map);
+
+				let damage_rect = boss.hitbox();
+				for quote in self.players.mut_iter() {
+					if !boss.is_dying() && !quote.is_dead() && damage_rect.collides_with(&quote.damage_rectangle()) {
+						quote.take_damage(boss.contact_damage(), &damage_rect);
+					}
+				}
+				if boss.is_dying() && self.rng.gen_range(0, BOSS_EXPLOSION_ODDS) == 0 {
+					let rng = &mut self.rng;
+					let units::Game(w) = damage_rect.width();
+					let units::Game(h) = damage_rect.height();
+					self.particles.explosion((
+						damage_rect.left() + units::Game(rng.gen_range(0.0, w)),
+						damage_rect.top() + units::Game(rng.gen_range(0.0, h))
+					));
+				}
+			},
+			None => {}
+		}
+
+		for (quote, &before) in self.players.iter().zip(hit_points.iter()) {
+			if quote.hit_points() < before {
+				self.damage_numbers.hurt((quote.center_x(), quote.center_y()), before - quote.hit_points());
+			}
+		}
+
+		self.grid.clear();
+		for (id, enemy) in self.enemies.iter().enumerate() {
+			self.grid.insert(id, &enemy.hitbox());
+		}
+
+		for projectile in self.projectiles.mut_iter() {
+			let player_center = nearest_player(self.players.as_slice(), &projectile.hitbox());
+			projectile.update(elapsed_time, player_center, &*map);
+
+			let hitbox = projectile.hitbox();
+			let center = World::center_of(&hitbox);
+
+			// explosive shots only do damage once they explode (below)
+			if projectile.struck_wall() && !projectile.is_explosive() {
+				self.particles.impact(center);
+
+				let destroyed = map.damage_tiles(&hitbox, projectile.damage());
+				for &(row, col) in destroyed.iter() {
+					self.particles.tile_destroyed(row, col);
+				}
+				if destroyed.len() > 0 {
+					audio.play_sfx_at(audio::BreakBlock, center);
+				}
+			}
+
+			for &id in self.grid.query(&hitbox).iter() {
+				let enemy = &mut self.enemies[id];
+				if projectile.is_alive() && hitbox.collides_with(&enemy.hitbox()) {
+					if !projectile.is_explosive() {
+						enemy.on_hit(projectile.damage());
+						self.damage_numbers.hit(center, projectile.damage());
+					}
+					projectile.kill();
+					self.particles.impact(center);
+				}
+			}
+
+			match self.boss {
+				Some(ref mut boss) => {
+					if projectile.is_alive() && boss.is_alive() && !boss.is_dying() && hitbox.collides_with(&boss.hitbox()) {
+						if !projectile.is_explosive() && !boss.is_invulnerable() {
+							self.damage_numbers.hit(center, projectile.damage());
+						}
+						if !projectile.is_explosive() { boss.on_hit(projectile.damage()); }
+						projectile.kill();
+						self.particles.impact(center);
+					}
+				}
+				None => {}
+			}
+
+			match projectile.explosion() {
+				Some(blast) => {
+					self.particles.explosion(center);
+					for &id in self.grid.query(&blast).iter() {
+						let enemy = &mut self.enemies[id];
+						if enemy.is_alive() && blast.collides_with(&enemy.hitbox()) {
+							enemy.on_hit(projectile.damage());
+							self.damage_numbers.hit(World::center_of(&enemy.hitbox()), projectile.damage());
+						}
+					}
+					match self.boss {
+						Some(ref mut boss) => if boss.is_alive() && blast.collides_with(&boss.hitbox()) {
+							if !boss.is_invulnerable() && !boss.is_dying() {
+								self.damage_numbers.hit(World::center_of(&boss.hitbox()), projectile.damage());
+							}
+							boss.on_hit(projectile.damage());
+						},
+						None => {}
+					}
+
+					let destroyed = map.damage_tiles(&blast, projectile.damage());
+					for &(row, col) in destroyed.iter() {
+						self.particles.tile_destroyed(row, col);
+					}
+					if destroyed.len() > 0 {
+						audio.play_sfx_at(audio::BreakBlock, center);
+					}
+				}
+				None => {}
+			}
+		}
+
+		// defeated enemies leave their loot behind
+		for (enemy, flag) in self.enemies.iter().zip(self.enemy_flags.iter()) {
+			if !enemy.is_alive() {
+				self.kills += 1;
+				match *flag {
+					Some(id) => self.defeated.push(id),
+					None => {}
+				}
+
+				let center = World::center_of(&enemy.hitbox());
+				// (missiles are only dropped while the player is short of them,
+				// & are otherwise swapped for experience.)
+				let pickup = match enemies::roll(enemy.loot(), &mut self.rng) {
+					enemies::Nothing => continue,
+					enemies::Missiles(amount) if self.players.iter().any(|quote| quote.arsenal().needs_ammo()) =>
+						pickups::Pickup::ammo(display, center, amount),
+					enemies::Heart(hit_points) => pickups::Pickup::heart(display, center, hit_points),
+					enemies::Energy | enemies::Missiles(_) =>
+						pickups::Pickup::experience(display, center, enemy.experience())
+				};
+				match pickup {
+					Ok(pickup) => self.pickups.push(pickup),
+					Err(err) => log::error("world", err.message())
+				}
+			}
+		}
+
+		for pickup in self.pickups.mut_iter() {
+			let player_center = nearest_player(self.players.as_slice(), &pickup.hitbox());
+			pickup.update(elapsed_time, player_center, &*map);
+
+			// (whoever reaches a pickup first takes it)
+			for quote in self.players.mut_iter() {
+				if pickup.is_alive() && !quote.is_dead() && pickup.hitbox().collides_with(&quote.damage_rectangle()) {
+					match pickup.collect() {
+						pickups::Experience(energy) => quote.gain_experience(energy),
+						pickups::Ammo(amount) => quote.arsenal_mut().refill(amount),
+						pickups::Health(hit_points) => quote.heal(hit_points)
+					}
+				}
+			}
+		}
+
+		let (enemies, flags) = (mem::replace(&mut self.enemies, ~[]), mem::replace(&mut self.enemy_flags, ~[]));
+		for (enemy, flag) in enemies.move_iter().zip(flags.move_iter()) {
+			if enemy.is_alive() { self.add_flagged_enemy(enemy, flag); }
+		}
+		self.projectiles.retain(|projectile| projectile.is_alive());
+		self.pickups.retain(|pickup| pickup.is_alive());
+		self.npcs.retain(|npc| npc.is_alive());
+
+		self.particles.update(elapsed_time, &*map);
+		self.damage_numbers.update(elapsed_time);
+	}
+
+	fn center_of(rectangle: &Rectangle) -> (units::Game, units::Game) {
+		(rectangle.left() + (rectangle.width() / units::Game(2.0)),
+		 rectangle.top() + (rectangle.height() / units::Game(2.0)))
+	}
+
+	/// The light given off by the players & their shots, in map coordinates.
+	pub fn lights(&self) -> ~[graphics::Light] {
+		let mut lights: ~[graphics::Light] = self.players.iter().map(|quote| graphics::Light {
+			x: quote.center_x(), y: quote.center_y(), radius: PLAYER_LIGHT.to_game()
+		}).collect();
+		for projectile in self.projectiles.iter() {
+			let (x, y) = World::center_of(&projectile.hitbox());
+			lights.push(graphics::Light { x: x, y: y, radius: SHOT_LIGHT.to_game() });
+		}
+		lights
+	}
+
+	/// Outlines the players' collision boxes (& the tiles beneath them)
+	/// and every entity's hitbox, to make collisions visible while debugging.
+	///
+	/// Solid tiles are outlined more brightly than those which are not.
+	pub fn draw_hitboxes(&self, display: &graphics::Graphics, offset: (units::Game, units::Game), map: &map::Map) {
+		for quote in self.players.iter() {
+			let (x_box, y_box) = quote.collision_boxes();
+			for hitbox in [x_box, y_box].iter() {
+				for tile in map.get_colliding_tiles(hitbox).iter() {
+					let color = if tile.tile_type.is_solid() {
+						pixels::RGBA(64, 128, 255, 255)
+					} else {
+						pixels::RGBA(64, 128, 255, 96)
+					};
+					display.draw_outline(&Rectangle {
+						x: tile.col.to_game(), y: tile.row.to_game(),
+						width: units::Tile(1).to_game(), height: units::Tile(1).to_game()
+					}, offset, color);
+				}
+			}
+			display.draw_outline(&x_box, offset, pixels::RGBA(255, 255, 255, 255));
+			display.draw_outline(&y_box, offset, pixels::RGBA(255, 0, 255, 255));
+		}
+
+		for enemy in self.enemies.iter() {
+			display.draw_outline(&enemy.hitbox(), offset, pixels::RGBA(255, 64, 64, 255));
+		}
+		match self.boss {
+			Some(ref boss) if boss.is_alive() => display.draw_outline(&boss.hitbox(), offset, pixels::RGBA(255, 64, 64, 255)),
+			_ => {}
+		}
+		for projectile in self.projectiles.iter() {
+			display.draw_outline(&projectile.hitbox(), offset, pixels::RGBA(255, 224, 64, 255));
+		}
+		for pickup in self.pickups.iter() {
+			display.draw_outline(&pickup.hitbox(), offset, pixels::RGBA(64, 255, 64, 255));
+		}
+		for npc in self.npcs.iter() {
+			display.draw_outline(&npc.hitbox(), offset, pixels::RGBA(64, 255, 255, 255));
+		}
+	}
+
+	/// Queues every entity to be drawn, shifted by the camera's `offset`,
+	/// `alpha` of the way from where it stood before the last update to
+	/// where it stands now. (The queue decides which overlaps which, not the
+	/// order they are queued in.)
+	pub fn draw(&self, queue: &mut graphics::RenderQueue, offset: (units::Game, units::Game), alpha: f64) {
+		for npc in self.npcs.iter() {
+			npc.draw(queue, offset, alpha);
+		}
+		for quote in self.players.iter() {
+			quote.draw(queue, offset, alpha);
+		}
+		for enemy in self.enemies.iter() {
+			enemy.draw(queue, offset, alpha);
+		}
+		match self.boss {
+			Some(ref boss) if boss.is_alive() => boss.draw(queue, offset, alpha),
+			_ => {}
+		}
+		for projectile in self.projectiles.iter() {
+			projectile.draw(queue, offset, alpha);
+		}
+		for pickup in self.pickups.iter() {
+			pickup.draw(queue, offset, alpha);
+		}
+		self.particles.draw(queue, offset);
+		self.damage_numbers.draw(queue, offset);
+	}
+}
+
+/// The center of whichever living player is nearest `rectangle`, or of
+/// the first player should none be alive.
+fn nearest_player(players: &[player::Player], rectangle: &Rectangle) -> (units::Game, units::Game) {
+	let (units::Game(x), units::Game(y)) = World::center_of(rectangle);
+	let distance = |quote: &player::Player| {
+		let (units::Game(px), units::Game(py)) = (quote.center_x(), quote.center_y());
+		(px - x) * (px - x) + (py - y) * (py - y)
+	};
+
+	let mut nearest = &players[0];
+	for quote in players.iter().filter(|quote| !quote.is_dead()) {
+		if nearest.is_dead() || distance(quote) < distance(nearest) { nearest = quote; }
+	}
+	(nearest.center_x(), nearest.center_y())
+}